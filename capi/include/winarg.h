@@ -0,0 +1,89 @@
+#ifndef WINARG_H
+#define WINARG_H
+
+// This file is generated by cbindgen from src/lib.rs. Don't edit it.
+
+#include <stddef.h>
+#include <stdint.h>
+#include <wchar.h>
+
+// The function succeeded.
+#define WINARG_OK 0
+
+// A pointer argument was null.
+#define WINARG_NULL_POINTER 1
+
+// The buffer is too small. The length needed has been written.
+#define WINARG_BUFFER_TOO_SMALL 2
+
+// The index is past the end of the list.
+#define WINARG_OUT_OF_RANGE 3
+
+// An internal error. This is a bug in winarg.
+#define WINARG_INTERNAL_ERROR 4
+
+// A list of arguments returned by `winarg_split`.
+//
+// Free it with `winarg_list_free`.
+typedef struct winarg_list winarg_list;
+
+// Split a command line into arguments.
+//
+// The first argument is the program name. The command line is parsed the same
+// way as the C runtime parses the command line of the current process.
+//
+// On success `*out` is set to a new list. Otherwise it's set to null.
+//
+// # Safety
+//
+// `cmdline` must be null or a null terminated string. `out` must be null or
+// valid for writes.
+int32_t winarg_split(const wchar_t *cmdline, struct winarg_list **out);
+
+// The number of arguments in a list, or zero if `list` is null.
+//
+// # Safety
+//
+// `list` must be null or a list returned by `winarg_split` that hasn't been
+// freed.
+size_t winarg_list_count(const struct winarg_list *list);
+
+// Get an argument from a list.
+//
+// On success `*arg` points to the argument, which is null terminated, and
+// `*len` is its length not including the null. The argument is valid until
+// the list is freed.
+//
+// # Safety
+//
+// `list` must be null or a list returned by `winarg_split` that hasn't been
+// freed. `arg` and `len` must be null or valid for writes.
+int32_t winarg_list_get(const struct winarg_list *list,
+                        size_t index,
+                        const wchar_t **arg,
+                        size_t *len);
+
+// Free a list returned by `winarg_split`. Does nothing if `list` is null.
+//
+// # Safety
+//
+// `list` must be null or a list returned by `winarg_split` that hasn't
+// already been freed.
+void winarg_list_free(struct winarg_list *list);
+
+// Quote an argument so that it's parsed back unchanged.
+//
+// The argument is quoted if it needs to be, for use anywhere but the program
+// name. `*len` is the size of `buf` in code units, including room for the
+// null terminator. It's set to the length of the quoted argument, not
+// including the null. If `buf` is null or too small, nothing is written and
+// `WINARG_BUFFER_TOO_SMALL` is returned, so the length can be found first.
+//
+// # Safety
+//
+// `arg` must be null or a null terminated string. `len` must be null or valid
+// for reads and writes. `buf` must be null or valid for writes of `*len` code
+// units.
+int32_t winarg_quote(const wchar_t *arg, wchar_t *buf, size_t *len);
+
+#endif  /* WINARG_H */
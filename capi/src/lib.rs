@@ -0,0 +1,186 @@
+//! A C API for winarg.
+//!
+//! This lets C and C++ code use the same parser as Rust code. Strings are
+//! null terminated UTF-16, which is `wchar_t` on Windows. Every function
+//! returns a status code instead of panicking. See `include/winarg.h` for the
+//! header, which is generated from this file by cbindgen.
+
+// Like winarg, this only works on Windows.
+#![cfg(windows)]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{ptr, slice};
+use winarg::SpanKind;
+
+/// A UTF-16 code unit.
+pub type WChar = u16;
+
+/// The function succeeded.
+pub const WINARG_OK: i32 = 0;
+/// A pointer argument was null.
+pub const WINARG_NULL_POINTER: i32 = 1;
+/// The buffer is too small. The length needed has been written.
+pub const WINARG_BUFFER_TOO_SMALL: i32 = 2;
+/// The index is past the end of the list.
+pub const WINARG_OUT_OF_RANGE: i32 = 3;
+/// An internal error. This is a bug in winarg.
+pub const WINARG_INTERNAL_ERROR: i32 = 4;
+
+/// A list of arguments returned by `winarg_split`.
+///
+/// Free it with `winarg_list_free`.
+pub struct WinargList {
+	// Each argument has a null terminator.
+	args: Vec<Vec<WChar>>,
+}
+
+/// Split a command line into arguments.
+///
+/// The first argument is the program name. The command line is parsed the same
+/// way as the C runtime parses the command line of the current process.
+///
+/// On success `*out` is set to a new list. Otherwise it's set to null.
+///
+/// # Safety
+///
+/// `cmdline` must be null or a null terminated string. `out` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn winarg_split(cmdline: *const WChar, out: *mut *mut WinargList) -> i32 {
+	if out.is_null() {
+		return WINARG_NULL_POINTER;
+	}
+	*out = ptr::null_mut();
+	if cmdline.is_null() {
+		return WINARG_NULL_POINTER;
+	}
+	let cmdline = wide_str(cmdline);
+	guard(|| {
+		let list = WinargList {
+			args: split(cmdline),
+		};
+		*out = Box::into_raw(Box::new(list));
+		WINARG_OK
+	})
+}
+
+/// The number of arguments in a list, or zero if `list` is null.
+///
+/// # Safety
+///
+/// `list` must be null or a list returned by `winarg_split` that hasn't been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn winarg_list_count(list: *const WinargList) -> usize {
+	match list.as_ref() {
+		Some(list) => list.args.len(),
+		None => 0,
+	}
+}
+
+/// Get an argument from a list.
+///
+/// On success `*arg` points to the argument, which is null terminated, and
+/// `*len` is its length not including the null. The argument is valid until
+/// the list is freed.
+///
+/// # Safety
+///
+/// `list` must be null or a list returned by `winarg_split` that hasn't been
+/// freed. `arg` and `len` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn winarg_list_get(
+	list: *const WinargList,
+	index: usize,
+	arg: *mut *const WChar,
+	len: *mut usize,
+) -> i32 {
+	let list = match list.as_ref() {
+		Some(list) if !arg.is_null() && !len.is_null() => list,
+		_ => return WINARG_NULL_POINTER,
+	};
+	match list.args.get(index) {
+		Some(units) => {
+			*arg = units.as_ptr();
+			*len = units.len() - 1;
+			WINARG_OK
+		}
+		None => WINARG_OUT_OF_RANGE,
+	}
+}
+
+/// Free a list returned by `winarg_split`. Does nothing if `list` is null.
+///
+/// # Safety
+///
+/// `list` must be null or a list returned by `winarg_split` that hasn't
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn winarg_list_free(list: *mut WinargList) {
+	if !list.is_null() {
+		drop(Box::from_raw(list));
+	}
+}
+
+/// Quote an argument so that it's parsed back unchanged.
+///
+/// The argument is quoted if it needs to be, for use anywhere but the program
+/// name. `*len` is the size of `buf` in code units, including room for the
+/// null terminator. It's set to the length of the quoted argument, not
+/// including the null. If `buf` is null or too small, nothing is written and
+/// `WINARG_BUFFER_TOO_SMALL` is returned, so the length can be found first.
+///
+/// # Safety
+///
+/// `arg` must be null or a null terminated string. `len` must be null or valid
+/// for reads and writes. `buf` must be null or valid for writes of `*len` code
+/// units.
+#[no_mangle]
+pub unsafe extern "C" fn winarg_quote(arg: *const WChar, buf: *mut WChar, len: *mut usize) -> i32 {
+	if arg.is_null() || len.is_null() {
+		return WINARG_NULL_POINTER;
+	}
+	let arg = wide_str(arg);
+	guard(|| {
+		let mut quoted = Vec::new();
+		winarg::quote_one(arg, false, |u| quoted.push(u));
+		let capacity = *len;
+		*len = quoted.len();
+		if buf.is_null() || capacity <= quoted.len() {
+			return WINARG_BUFFER_TOO_SMALL;
+		}
+		quoted.push(0);
+		ptr::copy_nonoverlapping(quoted.as_ptr(), buf, quoted.len());
+		WINARG_OK
+	})
+}
+
+// Split a command line using the same parser as the current process's
+// arguments.
+fn split(cmdline: &[WChar]) -> Vec<Vec<WChar>> {
+	let mut args = Vec::new();
+	winarg::analyze(cmdline, |span| {
+		if span.kind == SpanKind::Argument {
+			let is_program = args.is_empty();
+			let (arg, _) = winarg::unquote_one(&cmdline[span.range], is_program);
+			let mut arg: Vec<WChar> = arg.collect();
+			arg.push(0);
+			args.push(arg);
+		}
+	});
+	args
+}
+
+// Don't let a panic unwind into C.
+fn guard<F: FnOnce() -> i32>(f: F) -> i32 {
+	catch_unwind(AssertUnwindSafe(f)).unwrap_or(WINARG_INTERNAL_ERROR)
+}
+
+// SAFETY: `s` must be a null terminated string that outlives the result.
+unsafe fn wide_str<'a>(s: *const WChar) -> &'a [WChar] {
+	let mut len = 0;
+	while *s.add(len) != 0 {
+		len += 1;
+	}
+	slice::from_raw_parts(s, len)
+}
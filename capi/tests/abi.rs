@@ -0,0 +1,147 @@
+// Calls the C API through its exported symbols, the same way C code would.
+#![cfg(windows)]
+
+// Link the library so its symbols can be found.
+use winarg_capi as _;
+
+use std::ptr;
+
+#[repr(C)]
+struct List {
+	_private: [u8; 0],
+}
+
+extern "C" {
+	fn winarg_split(cmdline: *const u16, out: *mut *mut List) -> i32;
+	fn winarg_list_count(list: *const List) -> usize;
+	fn winarg_list_get(
+		list: *const List,
+		index: usize,
+		arg: *mut *const u16,
+		len: *mut usize,
+	) -> i32;
+	fn winarg_list_free(list: *mut List);
+	fn winarg_quote(arg: *const u16, buf: *mut u16, len: *mut usize) -> i32;
+}
+
+const OK: i32 = 0;
+const NULL_POINTER: i32 = 1;
+const BUFFER_TOO_SMALL: i32 = 2;
+const OUT_OF_RANGE: i32 = 3;
+
+fn wide(s: &str) -> Vec<u16> {
+	s.encode_utf16().chain(Some(0)).collect()
+}
+
+fn split(cmdline: &str) -> Vec<String> {
+	let cmdline = wide(cmdline);
+	unsafe {
+		let mut list = ptr::null_mut();
+		assert_eq!(winarg_split(cmdline.as_ptr(), &mut list), OK);
+		let mut args = Vec::new();
+		for index in 0..winarg_list_count(list) {
+			let (mut arg, mut len) = (ptr::null(), 0);
+			assert_eq!(winarg_list_get(list, index, &mut arg, &mut len), OK);
+			assert_eq!(*arg.add(len), 0);
+			let arg = std::slice::from_raw_parts(arg, len);
+			args.push(String::from_utf16(arg).unwrap());
+		}
+		winarg_list_free(list);
+		args
+	}
+}
+
+fn quote(arg: &str) -> String {
+	let arg = wide(arg);
+	unsafe {
+		let mut len = 0;
+		assert_eq!(
+			winarg_quote(arg.as_ptr(), ptr::null_mut(), &mut len),
+			BUFFER_TOO_SMALL
+		);
+		// The buffer needs room for the null.
+		let mut buf = vec![0xffff; len + 1];
+		let mut short = len;
+		assert_eq!(
+			winarg_quote(arg.as_ptr(), buf.as_mut_ptr(), &mut short),
+			BUFFER_TOO_SMALL
+		);
+		assert_eq!((short, buf[0]), (len, 0xffff));
+		let mut capacity = buf.len();
+		assert_eq!(
+			winarg_quote(arg.as_ptr(), buf.as_mut_ptr(), &mut capacity),
+			OK
+		);
+		assert_eq!(capacity, len);
+		assert_eq!(buf[len], 0);
+		String::from_utf16(&buf[..len]).unwrap()
+	}
+}
+
+#[test]
+fn split_official_examples() {
+	assert_eq!(split(r#"EXE "abc" d e"#), ["EXE", "abc", "d", "e"]);
+	assert_eq!(
+		split(r#"EXE a\\\b d"e f"g h"#),
+		["EXE", r"a\\\b", "de fg", "h"]
+	);
+	assert_eq!(split(r#"EXE a\\\"b c d"#), ["EXE", r#"a\"b"#, "c", "d"]);
+	assert_eq!(split(r#"EXE a\\\\"b c" d e"#), ["EXE", r"a\\b c", "d", "e"]);
+	// The program name has different rules.
+	assert_eq!(split(r#""C:\dir\" a"#), [r"C:\dir\", "a"]);
+	assert_eq!(split(" a"), ["", "a"]);
+	assert!(split("").is_empty());
+}
+
+#[test]
+fn quote_round_trips() {
+	for &arg in &["a", "", "a b", r#"say "hi""#, r"a \", r"C:\dir\"] {
+		let quoted = quote(arg);
+		assert_eq!(split(&format!("EXE {}", quoted)), ["EXE", arg]);
+	}
+	assert_eq!(quote("a b"), r#""a b""#);
+	assert_eq!(quote(r#"\""#), r#"\\\""#);
+}
+
+#[test]
+fn errors() {
+	let cmdline = wide("EXE a");
+	unsafe {
+		let mut list = ptr::NonNull::<List>::dangling().as_ptr();
+		assert_eq!(winarg_split(ptr::null(), &mut list), NULL_POINTER);
+		assert!(list.is_null());
+		assert_eq!(
+			winarg_split(cmdline.as_ptr(), ptr::null_mut()),
+			NULL_POINTER
+		);
+
+		assert_eq!(winarg_split(cmdline.as_ptr(), &mut list), OK);
+		let (mut arg, mut len) = (ptr::null(), 0);
+		assert_eq!(winarg_list_get(list, 2, &mut arg, &mut len), OUT_OF_RANGE);
+		assert_eq!(
+			winarg_list_get(list, 0, ptr::null_mut(), &mut len),
+			NULL_POINTER
+		);
+		assert_eq!(
+			winarg_list_get(list, 0, &mut arg, ptr::null_mut()),
+			NULL_POINTER
+		);
+		winarg_list_free(list);
+
+		assert_eq!(
+			winarg_list_get(ptr::null(), 0, &mut arg, &mut len),
+			NULL_POINTER
+		);
+		assert_eq!(winarg_list_count(ptr::null()), 0);
+		winarg_list_free(ptr::null_mut());
+
+		assert_eq!(
+			winarg_quote(ptr::null(), ptr::null_mut(), &mut len),
+			NULL_POINTER
+		);
+		assert_eq!(
+			winarg_quote(cmdline.as_ptr(), ptr::null_mut(), ptr::null_mut()),
+			NULL_POINTER
+		);
+	}
+}
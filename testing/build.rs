@@ -0,0 +1,67 @@
+// Builds the programs used to generate test cases:
+//
+// * `args.exe` reports how the C runtime splits the command line into `argv`.
+// * `winmain.exe` is a GUI subsystem program that reports the `lpCmdLine`
+//   given to `wWinMain`.
+//
+// They have to be compiled with MSVC because the whole point is to compare
+// against the MSVC C runtime. If they can't be built then the reason is passed
+// on so the generator can report it. The exhaustive tests don't need them so
+// this doesn't fail the build.
+
+use std::{env, path::PathBuf};
+
+fn main() {
+	println!("cargo:rerun-if-changed=src/args.cpp");
+	println!("cargo:rerun-if-changed=src/winmain.cpp");
+	println!("cargo:rerun-if-changed=src/manifest.xml");
+
+	let oracles = [
+		(
+			"ARGS_ORACLE",
+			"args",
+			&["/MANIFEST:EMBED", "/MANIFESTINPUT:src/manifest.xml"][..],
+		),
+		("WINMAIN_ORACLE", "winmain", &["/SUBSYSTEM:WINDOWS"][..]),
+	];
+	for (var, name, link_args) in oracles {
+		match build_oracle(name, link_args) {
+			Ok(path) => println!("cargo:rustc-env={}={}", var, path.display()),
+			Err(e) => println!("cargo:rustc-env={}_ERROR={}", var, e),
+		}
+	}
+}
+
+// Compile `src/{name}.cpp` to `{name}.exe` in the output directory.
+fn build_oracle(name: &str, link_args: &[&str]) -> Result<PathBuf, String> {
+	let target = env::var("TARGET").unwrap();
+	if !target.ends_with("-windows-msvc") {
+		return Err(format!(
+			"{}.exe must be built with MSVC but the target is `{}`",
+			name, target
+		));
+	}
+	let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+	let exe = out_dir.join(format!("{}.exe", name));
+	let source = format!("src/{}.cpp", name);
+
+	let compiler = cc::Build::new()
+		.cpp(true)
+		.opt_level(2)
+		.cargo_metadata(false)
+		.try_get_compiler()
+		.map_err(|e| format!("couldn't find the MSVC compiler: {}", e))?;
+	let status = compiler
+		.to_command()
+		.arg(&source)
+		.arg(format!("/Fe{}", exe.display()))
+		.arg(format!("/Fo{}\\", out_dir.display()))
+		.arg("/link")
+		.args(link_args)
+		.status()
+		.map_err(|e| format!("couldn't run `{}`: {}", compiler.path().display(), e))?;
+	if !status.success() {
+		return Err(format!("compiling {} failed ({})", source, status));
+	}
+	Ok(exe)
+}
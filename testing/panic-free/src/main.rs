@@ -0,0 +1,254 @@
+//! A minimal program that uses every `no_std` function in winarg.
+//!
+//! The panic handler calls a function that doesn't exist, so this only links
+//! if every panic has been optimized out. See `tests/panic_free.rs`.
+//!
+//! Each group of functions is checked in its own function so that, if the
+//! link fails, the disassembly shows which group can panic.
+
+#![no_std]
+#![no_main]
+
+use core::fmt::{self, Write};
+use core::hint::black_box;
+use core::panic::PanicInfo;
+
+#[panic_handler]
+fn panic(_: &PanicInfo) -> ! {
+	extern "C" {
+		// If the linker can't find this, something in winarg can panic.
+		fn winarg_can_panic() -> !;
+	}
+	unsafe { winarg_can_panic() }
+}
+
+// The C runtime calls `main` and provides `memset` and similar functions.
+// `GetCommandLineW` is in kernel32.
+#[link(name = "msvcrt")]
+#[link(name = "vcruntime")]
+#[link(name = "ucrt")]
+#[link(name = "kernel32")]
+extern "C" {}
+
+#[no_mangle]
+pub extern "C" fn main(_argc: i32, _argv: *const *const u8) -> i32 {
+	let mut units = [0u16; 256];
+	let mut len = 0;
+	for (slot, unit) in units.iter_mut().zip(winarg::null_separated_list_wide()) {
+		*slot = unit;
+		len += 1;
+	}
+	// The input to the functions that take a slice.
+	let input = black_box(&units[..len.min(units.len())]);
+
+	args(input);
+	parser();
+	unquote(input);
+	analyze(input);
+	validate(input);
+	quoting(input);
+	0
+}
+
+#[inline(never)]
+fn args(input: &[u16]) {
+	for arg in winarg::args_native() {
+		black_box(arg.scalars().count());
+		black_box(arg.code_points().count());
+		black_box(arg.scalars_with(None).count());
+		black_box((&arg).into_iter().count());
+		black_box(arg.strip_prefix_ignore_case("/out:").map(Iterator::count));
+		black_box(arg.to_u64(winarg::Radix::Hexadecimal).ok());
+		let trimmed = arg.trim();
+		black_box((
+			trimmed == "-",
+			trimmed.scalars().count(),
+			trimmed.is_empty(),
+		));
+		black_box(arg.trim_matches(black_box('-')).len());
+		for field in arg.split(black_box(';')) {
+			black_box(field.count());
+		}
+		for line in arg.split_terminator('\n') {
+			black_box(line.count());
+		}
+		let _ = black_box(arg.to_u64_auto().map_err(winarg::ParseIntError::offset));
+		if let Err(e) = arg.to_i64(black_box(winarg::Radix::Decimal)) {
+			let _ = write!(Sink, "{}", e);
+		}
+		let _ = arg.write_lossy(&mut Sink);
+		let _ = write!(Sink, "{}", arg);
+		black_box(arg.raw_arg());
+		black_box(arg.as_raw_verbatim());
+		black_box(arg.cmp_ignore_case("--help"));
+		black_box(arg.wtf8_units().count());
+		black_box(arg.stats());
+		black_box(arg.is_roundtrip_safe());
+		black_box(arg.classify(black_box(winarg::ClassifyStyle {
+			dash: true,
+			slash: true,
+		})));
+		black_box(arg.ascii_lowercase().eq(arg.ascii_uppercase()));
+		black_box(arg == "--");
+		black_box(arg == input);
+		black_box(arg == b"--help");
+		if let Some((name, value)) = arg.as_slash_option() {
+			black_box((name.count(), value.map(Iterator::count)));
+		}
+	}
+	black_box(winarg::program_name().utf16_units().count());
+	let mut args = winarg::args_native();
+	black_box(args.skip_until_eq(black_box("--target")));
+	black_box(args.skip_until(|arg| arg.is_roundtrip_safe()));
+	let (args, rest) = winarg::split_at_double_dash();
+	black_box((args.count(), rest));
+	let (program, rest) = winarg::split_program();
+	black_box((program.utf16_units().count(), rest));
+	black_box(winarg::null_separated_list().count());
+	let _ = winarg::write_args(&mut Sink, " ");
+	let _ = winarg::to_json(&mut Sink);
+	let _ = winarg::explain(&mut Sink);
+	black_box(winarg::arg_spans().map(|span| span.len()).sum::<usize>());
+	black_box(winarg::null_separated_list_with(Some('?')).count());
+	black_box(winarg::command_line_len());
+	black_box(winarg::raw_command_line());
+	black_box(winarg::command_line_stats());
+	black_box(winarg::find_flag(&["-h", "--help"]).is_some());
+	black_box(winarg::has_flag(&["--"]));
+	black_box(winarg::cached::has_flag(&["-v", "--verbose"]));
+	black_box(winarg::cached::find_flag(&["-q"]).map(|arg| arg.utf16_units().count()));
+	black_box(winarg::cached::args().count());
+	winarg::arg_match!(winarg::args_native().skip(1), {
+		"-h" | "--help" => { black_box(0); }
+		"--out" = value => { black_box(value.map(|value| value.utf16_units().count())); }
+		positional(arg) => { black_box(arg.utf16_units().count()); }
+		rest(raw) => { black_box(raw); }
+	});
+	static ARGS: winarg::cached::ArgCache<4> = winarg::cached::ArgCache::new();
+	black_box((ARGS.is_complete(), ARGS.args().count()));
+	black_box(winarg::first_match(&["-h", "/?"]).map(|(index, _)| index));
+	black_box(winarg::contains_any(&["--version"]));
+	if let Some(value) = winarg::value_of("--out") {
+		black_box((value.scalars().count(), value.is_separate()));
+		black_box(value == "-");
+		black_box(value.to_u64(black_box(winarg::Radix::Decimal)).ok());
+		black_box(value.to_i64(winarg::Radix::Hexadecimal).ok());
+		black_box(value.to_u64_auto().ok());
+		for field in value.split(black_box(';')) {
+			black_box(field.count());
+		}
+		black_box(value.trim().len());
+		black_box(value.trim_matches(black_box('"')).is_empty());
+	}
+	if let Some(value) = winarg::slash_value_of("out", true) {
+		black_box(value.utf16_units().count());
+	}
+	let options = winarg::MatchOptions {
+		case: winarg::CaseSensitivity::OrdinalInsensitive,
+		prefixes: winarg::FlagStyle::SlashOrDash,
+	};
+	black_box(winarg::has_flag_with(&["nologo"], options));
+	if let Some(value) = winarg::value_of_with("out", black_box(options)) {
+		black_box(value.utf16_units().count());
+	}
+	if let Some(value) = winarg::slash_value_of_with("out", black_box(options)) {
+		black_box(value.utf16_units().count());
+	}
+	for item in winarg::split_short_flags(winarg::args_native().skip(1), &['o']) {
+		black_box(item);
+	}
+}
+
+#[inline(never)]
+fn parser() {
+	let mut parser = winarg::Parser();
+	while let Some(token) = parser.next() {
+		black_box((token.as_u16(), token.is_next_arg(), token.as_char()));
+		black_box((parser.in_quotes(), parser.pending_backslashes()));
+		black_box(parser.current_arg_index());
+	}
+	for event in winarg::Parser().events() {
+		black_box(matches!(event, winarg::ParserEvent::End { .. }));
+	}
+	for token in winarg::Parser().grouped() {
+		black_box(token);
+	}
+	for token in winarg::Parser().with_rules(winarg::ParserRules::Pre2008) {
+		black_box(token);
+	}
+	let leading = black_box(winarg::LeadingWhitespace::SkipToFirstToken);
+	for token in winarg::Parser().with_leading_whitespace(leading) {
+		black_box(token);
+	}
+	let mut buffer = [0u16; 256];
+	if let Some(args) = winarg::parse_into_buffer(|_| black_box(&mut buffer[..])) {
+		black_box(args.as_slice());
+		for arg in args {
+			black_box(arg);
+		}
+	}
+}
+
+#[inline(never)]
+fn unquote(input: &[u16]) {
+	let (arg, used) = winarg::unquote_one(input, false);
+	black_box((arg.count(), used));
+	let (arg, used) = winarg::unquote_one_with(input, false, winarg::ParserRules::Pre2008);
+	black_box((arg.count(), used));
+	let (program, rest) = winarg::split_program_of(input);
+	black_box((program.count(), rest));
+}
+
+#[inline(never)]
+fn analyze(input: &[u16]) {
+	winarg::analyze(input, |span| {
+		black_box(span);
+	});
+	winarg::analyze_lenient(input, &[0xA0, 0x3000], |span| {
+		black_box(span);
+	});
+	for span in winarg::arg_spans_lenient(input, black_box(&[0xA0, 0x3000])) {
+		black_box(span);
+	}
+	let leading = black_box(winarg::LeadingWhitespace::SkipToFirstToken);
+	for span in winarg::arg_spans_of(input).with_leading_whitespace(leading) {
+		black_box(span);
+	}
+	let case = black_box(winarg::CaseSensitivity::AsciiInsensitive);
+	black_box(winarg::args_equivalent_with(
+		input,
+		black_box(input.get(1..).unwrap_or_default()),
+		case,
+	));
+	black_box(winarg::args_equivalent_to(input));
+}
+
+#[inline(never)]
+fn validate(input: &[u16]) {
+	for diagnostic in winarg::validate(input) {
+		black_box(diagnostic);
+	}
+}
+
+#[inline(never)]
+fn quoting(input: &[u16]) {
+	let args = [input, input];
+	black_box(winarg::quoted_len(&args));
+	winarg::quote_one(input, true, |unit| {
+		black_box(unit);
+	});
+	if let Err(e) = winarg::is_representable_with(&args, winarg::QuotingRules::Legacy) {
+		black_box((e.index(), e.position(), e.unit()));
+		let _ = write!(Sink, "{}", e);
+		let _ = write!(Sink, "{}", winarg::Error::from(e));
+	}
+}
+
+// Formats into nothing.
+struct Sink;
+impl Write for Sink {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		black_box(s);
+		Ok(())
+	}
+}
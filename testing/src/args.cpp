@@ -1,18 +1,31 @@
-// To build:
-// cl src\args.cpp /nologo /O2 /link /MANIFEST:EMBED /MANIFESTINPUT:src\manifest.xml
+// This is built automatically by `build.rs`.
 //
-// TODO: Use `cc` in a build script to build this automatically.
+// Writes a single test case to stdout in the format described in `corpus.rs`.
 #include <Windows.h>
+#include <fcntl.h>
+#include <io.h>
 #include <stdio.h>
+#include <wchar.h>
 
-int main(int argc, char *argv[]) {
-	char num_args[10];
-	_itoa_s(argc, num_args, sizeof(num_args), 10);
+static void write_u16(size_t n) {
+	unsigned char bytes[2] = { (unsigned char)(n & 0xFF), (unsigned char)((n >> 8) & 0xFF) };
+	fwrite(bytes, 1, sizeof(bytes), stdout);
+}
 
-	puts(GetCommandLineA());
-	puts(num_args);
+// The length in code units then the UTF-16LE code units.
+static void write_record(const wchar_t *s) {
+	size_t len = wcslen(s);
+	write_u16(len);
+	fwrite(s, sizeof(wchar_t), len, stdout);
+}
+
+int wmain(int argc, wchar_t *argv[]) {
+	_setmode(_fileno(stdout), _O_BINARY);
+
+	write_record(GetCommandLineW());
+	write_u16(argc);
 	for (int i = 0; i < argc; i++) {
-		puts(argv[i]);
+		write_record(argv[i]);
 	}
 	return 0;
-}
\ No newline at end of file
+}
@@ -0,0 +1,54 @@
+// Converts a corpus from the old text format into the binary format.
+//
+// The text format had a line for the command line, a line for argc and then a
+// line for each argument. As such it can't contain newlines.
+//
+// Usage: cargo run --bin convert -- output.txt corpus.bin
+
+use std::{
+	env,
+	fs::File,
+	io::{self, BufRead, BufReader, BufWriter, Write},
+};
+use testing::corpus::{self, Case};
+
+fn main() -> io::Result<()> {
+	let mut paths = env::args().skip(1);
+	let (input, output) = match (paths.next(), paths.next()) {
+		(Some(input), Some(output)) => (input, output),
+		_ => {
+			eprintln!("usage: convert <input.txt> <output.bin>");
+			std::process::exit(1);
+		}
+	};
+	let mut lines = BufReader::new(File::open(input)?).lines();
+	// The header needs the number of cases so read them all first.
+	let mut cases = Vec::new();
+	let mut next_line = || -> io::Result<Option<Vec<u16>>> {
+		Ok(lines
+			.next()
+			.transpose()?
+			.map(|line| line.encode_utf16().collect()))
+	};
+	while let Some(cmdline) = next_line()? {
+		let truncated = || io::Error::new(io::ErrorKind::UnexpectedEof, "truncated case");
+		let argc = next_line()?.ok_or_else(truncated)?;
+		let argc: usize = String::from_utf16_lossy(&argc)
+			.parse()
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+		let mut args = Vec::with_capacity(argc);
+		for _ in 0..argc {
+			args.push(next_line()?.ok_or_else(truncated)?);
+		}
+		cases.push(Case { cmdline, args });
+	}
+
+	let mut writer = BufWriter::new(File::create(output)?);
+	corpus::write_header(&mut writer, cases.len())?;
+	for case in &cases {
+		corpus::write_case(&mut writer, case)?;
+	}
+	writer.flush()?;
+	println!("converted {} cases", cases.len());
+	Ok(())
+}
@@ -0,0 +1,180 @@
+// Reading and writing the test corpus.
+//
+// The corpus is a binary file so that command lines and arguments can contain
+// any UTF-16 code unit, including newlines and isolated surrogates.
+//
+// Format (all integers are little endian):
+//
+//     header: the bytes `WINARGCP`, the format version as a `u16`, then the
+//             number of cases as a `u32`
+//     case:   command line record, argc as a `u16`, then `argc` argument records
+//     record: the length in code units as a `u16`, then the UTF-16 code units
+//
+// The file is the header followed by the cases. `u16` is enough for
+// lengths because Windows limits command lines to 32767 code units.
+//
+// `args.cpp` writes cases in this format directly.
+
+use std::{
+	convert::TryFrom,
+	io::{self, Read, Write},
+};
+
+pub const MAGIC: &[u8; 8] = b"WINARGCP";
+pub const VERSION: u16 = 2;
+
+// A command line and the arguments the C runtime parsed from it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Case {
+	pub cmdline: Vec<u16>,
+	pub args: Vec<Vec<u16>>,
+}
+
+pub fn write_header<W: Write>(w: &mut W, cases: usize) -> io::Result<()> {
+	let cases = u32::try_from(cases)
+		.map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many cases"))?;
+	w.write_all(MAGIC)?;
+	w.write_all(&VERSION.to_le_bytes())?;
+	w.write_all(&cases.to_le_bytes())
+}
+
+pub fn write_case<W: Write>(w: &mut W, case: &Case) -> io::Result<()> {
+	write_record(w, &case.cmdline)?;
+	write_u16(w, case.args.len())?;
+	for arg in &case.args {
+		write_record(w, arg)?;
+	}
+	Ok(())
+}
+
+fn write_record<W: Write>(w: &mut W, units: &[u16]) -> io::Result<()> {
+	write_u16(w, units.len())?;
+	let bytes: Vec<u8> = units.iter().flat_map(|w| w.to_le_bytes()).collect();
+	w.write_all(&bytes)
+}
+
+fn write_u16<W: Write>(w: &mut W, n: usize) -> io::Result<()> {
+	let n =
+		u16::try_from(n).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too long"))?;
+	w.write_all(&n.to_le_bytes())
+}
+
+// Iterates the cases in a corpus.
+pub struct Reader<R> {
+	inner: R,
+	cases: u32,
+}
+impl<R: Read> Reader<R> {
+	// Reads and checks the header.
+	pub fn new(mut inner: R) -> io::Result<Self> {
+		let mut magic = [0; 8];
+		inner.read_exact(&mut magic)?;
+		if &magic != MAGIC {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"not a corpus file",
+			));
+		}
+		let version = read_u16(&mut inner)?;
+		if version != VERSION {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("unsupported corpus version {}", version),
+			));
+		}
+		let mut cases = [0; 4];
+		inner.read_exact(&mut cases)?;
+		Ok(Self {
+			inner,
+			cases: u32::from_le_bytes(cases),
+		})
+	}
+
+	// The number of cases the header says the file contains.
+	pub fn cases(&self) -> usize {
+		self.cases as usize
+	}
+}
+impl<R: Read> Iterator for Reader<R> {
+	type Item = io::Result<Case>;
+	fn next(&mut self) -> Option<Self::Item> {
+		read_case(&mut self.inner).transpose()
+	}
+}
+
+// Reads a single case, such as the output of `args.exe`.
+// Returns `None` at the end of the file. A partial case is an error.
+pub fn read_case<R: Read>(r: &mut R) -> io::Result<Option<Case>> {
+	let mut len = [0; 2];
+	match r.read(&mut len[..1])? {
+		0 => return Ok(None),
+		_ => r.read_exact(&mut len[1..])?,
+	}
+	let cmdline = read_units(r, u16::from_le_bytes(len))?;
+	let argc = read_u16(r)?;
+	let mut args = Vec::with_capacity(argc as usize);
+	for _ in 0..argc {
+		let len = read_u16(r)?;
+		args.push(read_units(r, len)?);
+	}
+	Ok(Some(Case { cmdline, args }))
+}
+
+fn read_u16<R: Read>(r: &mut R) -> io::Result<u16> {
+	let mut bytes = [0; 2];
+	r.read_exact(&mut bytes)?;
+	Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_units<R: Read>(r: &mut R, len: u16) -> io::Result<Vec<u16>> {
+	let mut bytes = vec![0; len as usize * 2];
+	r.read_exact(&mut bytes)?;
+	Ok(bytes
+		.chunks_exact(2)
+		.map(|b| u16::from_le_bytes([b[0], b[1]]))
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let cases = [
+			Case {
+				cmdline: "a \"b\nc\"".encode_utf16().collect(),
+				args: vec![
+					"a".encode_utf16().collect(),
+					"b\nc".encode_utf16().collect(),
+				],
+			},
+			Case {
+				cmdline: vec![0xD800],
+				args: vec![vec![0xD800]],
+			},
+			Case {
+				cmdline: vec![],
+				args: vec![vec![]],
+			},
+		];
+		let mut buffer = Vec::new();
+		write_header(&mut buffer, cases.len()).unwrap();
+		for case in &cases {
+			write_case(&mut buffer, case).unwrap();
+		}
+		assert_eq!(Reader::new(&buffer[..]).unwrap().cases(), cases.len());
+		let read: Vec<Case> = Reader::new(&buffer[..])
+			.unwrap()
+			.map(Result::unwrap)
+			.collect();
+		assert_eq!(read, cases);
+
+		// A truncated case is an error.
+		buffer.pop();
+		let mut reader = Reader::new(&buffer[..]).unwrap();
+		assert!(reader.next().unwrap().is_ok());
+		assert!(reader.next().unwrap().is_ok());
+		assert!(reader.next().unwrap().is_err());
+	}
+}
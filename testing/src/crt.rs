@@ -0,0 +1,158 @@
+// A simple reimplementation of how the C runtime (2008 and later) splits the
+// command line into `argv`.
+//
+// This is written from the documented rules, deliberately without reference
+// to winarg, so that the two can be checked against each other on any
+// platform. It favours being obviously correct over being fast.
+//
+// The program name (`argv[0]`):
+//
+// * Quotes are removed and switch between being inside and outside quotes.
+// * Outside quotes, a space or tab ends the program name.
+// * Backslashes have no special meaning.
+//
+// Every other argument:
+//
+// * Arguments are separated by spaces or tabs outside quotes.
+// * A quote switches between being inside and outside quotes.
+// * Inside quotes, two quotes together are a literal quote.
+// * Backslashes are literal unless they're followed by a quote.
+// * `2n` backslashes followed by a quote are `n` backslashes then the quote
+//   is treated as above.
+// * `2n + 1` backslashes followed by a quote are `n` backslashes and a literal
+//   quote.
+//
+// `split_pre_2008` uses the rule from before 2008 where two quotes together
+// inside quotes are removed, as if the first closed the quotes and the second
+// opened them again.
+
+const SPACE: u16 = b' ' as u16;
+const TAB: u16 = b'\t' as u16;
+const QUOTE: u16 = b'"' as u16;
+const BACKSLASH: u16 = b'\\' as u16;
+
+// Split the command line using the C runtime's rules.
+// The command line ends at the first null, if any.
+pub fn split(cmdline: &[u16]) -> Vec<Vec<u16>> {
+	split_with(cmdline, false)
+}
+
+// Split the command line using the rules from before 2008.
+pub fn split_pre_2008(cmdline: &[u16]) -> Vec<Vec<u16>> {
+	split_with(cmdline, true)
+}
+
+fn split_with(cmdline: &[u16], pre_2008: bool) -> Vec<Vec<u16>> {
+	let end = cmdline
+		.iter()
+		.position(|&u| u == 0)
+		.unwrap_or(cmdline.len());
+	let cmdline = &cmdline[..end];
+	let mut args = Vec::new();
+
+	// The program name.
+	let mut program = Vec::new();
+	let mut in_quotes = false;
+	let mut i = 0;
+	while i < cmdline.len() {
+		let unit = cmdline[i];
+		i += 1;
+		if unit == QUOTE {
+			in_quotes = !in_quotes;
+			continue;
+		}
+		if !in_quotes && is_whitespace(unit) {
+			break;
+		}
+		program.push(unit);
+	}
+	args.push(program);
+
+	// The remaining arguments.
+	loop {
+		while i < cmdline.len() && is_whitespace(cmdline[i]) {
+			i += 1;
+		}
+		if i == cmdline.len() {
+			break;
+		}
+		let mut arg = Vec::new();
+		let mut in_quotes = false;
+		while i < cmdline.len() {
+			let mut backslashes = 0;
+			while i < cmdline.len() && cmdline[i] == BACKSLASH {
+				backslashes += 1;
+				i += 1;
+			}
+			if i < cmdline.len() && cmdline[i] == QUOTE {
+				arg.extend((0..backslashes / 2).map(|_| BACKSLASH));
+				if backslashes % 2 == 1 {
+					arg.push(QUOTE);
+				} else if in_quotes && cmdline.get(i + 1) == Some(&QUOTE) {
+					if !pre_2008 {
+						arg.push(QUOTE);
+					}
+					i += 1;
+				} else {
+					in_quotes = !in_quotes;
+				}
+				i += 1;
+				continue;
+			}
+			arg.extend((0..backslashes).map(|_| BACKSLASH));
+			if i == cmdline.len() || (!in_quotes && is_whitespace(cmdline[i])) {
+				break;
+			}
+			arg.push(cmdline[i]);
+			i += 1;
+		}
+		args.push(arg);
+	}
+	args
+}
+
+fn is_whitespace(unit: u16) -> bool {
+	unit == SPACE || unit == TAB
+}
+
+#[cfg(test)]
+mod tests {
+	use super::split;
+
+	fn check(cmdline: &str, expected: &[&str]) {
+		let cmdline: Vec<u16> = cmdline.encode_utf16().collect();
+		let args: Vec<String> = split(&cmdline)
+			.iter()
+			.map(|arg| String::from_utf16(arg).unwrap())
+			.collect();
+		assert_eq!(args, expected);
+	}
+
+	// The examples from Microsoft's "Parsing C++ command-line arguments".
+	#[test]
+	fn documented_examples() {
+		check(r#"EXE "abc" d e"#, &["EXE", "abc", "d", "e"]);
+		check(r#"EXE a\\b d"e f"g h"#, &["EXE", r"a\\b", "de fg", "h"]);
+		check(r#"EXE a\\\"b c d"#, &["EXE", r#"a\"b"#, "c", "d"]);
+		check(r#"EXE a\\\\"b c" d e"#, &["EXE", r"a\\b c", "d", "e"]);
+		check(r#"EXE a"b"" c d"#, &["EXE", r#"ab" c d"#]);
+	}
+
+	#[test]
+	fn pre_2008() {
+		let cmdline: Vec<u16> = r#"EXE a"b"" c d "e""f""#.encode_utf16().collect();
+		let args: Vec<String> = super::split_pre_2008(&cmdline)
+			.iter()
+			.map(|arg| String::from_utf16(arg).unwrap())
+			.collect();
+		assert_eq!(args, ["EXE", "ab c d ef"]);
+	}
+
+	#[test]
+	fn program_name() {
+		check("", &[""]);
+		check(" a", &["", "a"]);
+		check(r#""C:\a b\"c d"#, &[r"C:\a b\c", "d"]);
+		check(r#"a\"b c"#, &[r"a\b c"]);
+	}
+}
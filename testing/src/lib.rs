@@ -0,0 +1,5 @@
+// Shared code for the test generators and the exhaustive tests.
+
+pub mod corpus;
+pub mod crt;
+pub mod oracle;
@@ -1,34 +1,240 @@
 // Generates exhaustive test cases.
 // This was thrown together quite quickly to ensure the parser was correct.
 // It could definitely be improved.
+//
+// Usage:
+//     testing                 Generate `corpus.bin`.
+//     testing --verify        Check `golden.bin` against the current OS.
+//     testing --verify FILE   Check FILE against the current OS.
+//     testing --program-names Generate `program_name.rs`.
 
-// Note that this could be made multi-threaded for a big speed up.
-// Though it'll ideally only need to be generated once.
+// The work is split between threads. Each thread handles a contiguous range of
+// command lines and writes to its own file. The files are then joined in order
+// so the output is the same as if it had been generated by a single thread.
 
-use std::{ffi::c_void, fs::File, os::windows::io::AsRawHandle, ptr::null_mut as null};
+use std::{
+	env,
+	fs::{self, File},
+	io::{self, BufWriter, Write},
+	process, thread,
+};
+use testing::{
+	corpus::{self, Case},
+	oracle::{self, run_oracle},
+};
 
 fn main() {
+	let args: Vec<String> = env::args().skip(1).collect();
+	let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+	match args[..] {
+		[] => generate(),
+		["--verify"] => verify("golden.bin"),
+		["--verify", path] => verify(path),
+		["--program-names"] => program_names(),
+		_ => {
+			eprintln!("usage: testing [--verify [FILE] | --program-names]");
+			process::exit(1);
+		}
+	}
+}
+
+fn generate() {
+	check_oracle();
 	println!("Generating permutations (this may take awhile)...");
-	let mut buffer = Io::new("output.txt");
-	
+
 	// For the most part it should be sufficient to test a limited number of characters.
-	let input: Vec<u16> = "\\a\" \t".encode_utf16().collect();
+	// A newline and an isolated surrogate are included to make sure they aren't treated specially.
+	let input: Vec<u16> = "\\a\" \t\n".encode_utf16().chain(Some(0xD800)).collect();
 
 	// Uncomment this code if you don't mind waiting awhile.
-	// Sample space: All ASCII characters (except `\0` and `\n`) and the characters `£` and `�`.
-	//let input: Vec<u16> = "£�".encode_utf16().chain(1..=9).chain(0xB..=0x7f).collect();
-	
+	// Sample space: All ASCII characters (except `\0`) and the characters `£` and `�`.
+	//let input: Vec<u16> = "£�".encode_utf16().chain(1..=0x7f).collect();
+
 	// Run `args.exe` with all the different combinations of characters as the
 	// command line.
 	// Adjust max_len as needed. Remember that the time taken increases exponentially. So adding
 	// even one to the max_len can greatly increase the time taken.
-	perms(&input, 6, move |perm| {
-		run_args(perm, &mut buffer);
-	});
+	let max_len = 6;
+
+	let total = perms_count(&input, max_len);
+	run_parallel(total, "corpus.bin", |start, count, buffer| {
+		perms(&input, max_len, start, count, |perm| {
+			run_oracle(perm, buffer)
+		})
+	})
+	.unwrap();
 
 	println!("Done.")
 }
 
+// Run `args.exe` with every command line in an existing corpus and check the
+// results are the same.
+fn verify(path: &str) {
+	check_oracle();
+	println!("Verifying {}...", path);
+
+	let expected = read_corpus(path).unwrap_or_else(|e| {
+		eprintln!("error: couldn't read {}: {}", path, e);
+		process::exit(1);
+	});
+	let output = "corpus.verify.bin";
+	run_parallel(expected.len(), output, |start, count, buffer| {
+		for case in expected.iter().skip(start).take(count) {
+			run_oracle(&case.cmdline, buffer)?;
+		}
+		Ok(())
+	})
+	.unwrap();
+	let actual = read_corpus(output).unwrap();
+	fs::remove_file(output).unwrap();
+
+	let mut mismatches = 0_usize;
+	for (expected, actual) in expected.iter().zip(&actual) {
+		if expected != actual {
+			mismatches += 1;
+			if mismatches <= 10 {
+				let lossy = |args: &[Vec<u16>]| -> Vec<String> {
+					args.iter().map(|a| String::from_utf16_lossy(a)).collect()
+				};
+				println!("{:?}", String::from_utf16_lossy(&expected.cmdline));
+				println!("  corpus: {:?}", lossy(&expected.args));
+				println!("  now:    {:?}", lossy(&actual.args));
+			}
+		}
+	}
+	if mismatches > 0 || expected.len() != actual.len() {
+		eprintln!(
+			"error: {} of {} cases no longer match the OS",
+			mismatches,
+			expected.len()
+		);
+		process::exit(1);
+	}
+	println!("All {} cases match.", expected.len());
+}
+
+// The arguments put after each program name. The quoted argument shows up
+// whether a program name wrongly leaves the parser inside or outside quotes.
+const PROGRAM_ARGS: &str = r#" "x y" z"#;
+
+// Write `chk` calls for `winarg/src/tests/program_name.rs`.
+//
+// The program name (`argv[0]`) has its own parsing rules. So each command line
+// is a permutation followed by `PROGRAM_ARGS`, which puts the permutation in
+// the program name.
+fn program_names() {
+	check_oracle();
+	println!("Generating program names...");
+
+	let input: Vec<u16> = "\\\" a".encode_utf16().collect();
+	let max_len = 4;
+
+	let mut output = BufWriter::new(File::create("program_name.rs").unwrap());
+	writeln!(
+		output,
+		"// Program names made from `\\`, `\"`, `a` and spaces, followed by `{}`.
+//
+// Generated by `cargo run --bin testing -- --program-names` in the testing
+// crate, which runs each command line through `args.exe`. Don't edit by hand.
+
+use super::chk;
+
+#[test]
+fn program_names() {{",
+		PROGRAM_ARGS
+	)
+	.unwrap();
+	perms(&input, max_len, 0, perms_count(&input, max_len), |perm| {
+		let cmdline: Vec<u16> = perm
+			.iter()
+			.copied()
+			.chain(PROGRAM_ARGS.encode_utf16())
+			.collect();
+		let mut buffer = Vec::new();
+		run_oracle(&cmdline, &mut buffer)?;
+		let case = corpus::read_case(&mut &buffer[..])?
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "args.exe wrote nothing"))?;
+		let args: Vec<String> = case.args.iter().map(|arg| rust_str(arg)).collect();
+		writeln!(
+			output,
+			"\tchk({}, &[{}]);",
+			rust_str(&case.cmdline),
+			args.join(", ")
+		)
+	})
+	.unwrap();
+	writeln!(output, "}}").unwrap();
+	output.flush().unwrap();
+
+	println!("Done. Copy program_name.rs to winarg/src/tests/.")
+}
+
+// A Rust string literal. Raw strings are used so that quotes and backslashes
+// are readable.
+fn rust_str(units: &[u16]) -> String {
+	let s = String::from_utf16(units).unwrap();
+	if s.contains('"') {
+		format!("r#\"{}\"#", s)
+	} else if s.contains('\\') {
+		format!("r\"{}\"", s)
+	} else {
+		format!("\"{}\"", s)
+	}
+}
+
+fn read_corpus(path: &str) -> io::Result<Vec<Case>> {
+	let reader = io::BufReader::new(File::open(path)?);
+	corpus::Reader::new(reader)?.collect()
+}
+
+// Call `f(start, count, buffer)` on each thread to write `count` cases,
+// starting from the `start`th, to `buffer`. The output is saved to `name`.
+fn run_parallel<F>(total: usize, name: &str, f: F) -> io::Result<()>
+where
+	F: Fn(usize, usize, &mut BufWriter<File>) -> io::Result<()> + Sync,
+{
+	let threads = thread::available_parallelism().map_or(1, |n| n.get());
+	let per_thread = total.div_ceil(threads);
+	let parts: Vec<String> = (0..threads).map(|n| format!("{}.{}", name, n)).collect();
+	thread::scope(|scope| {
+		let handles: Vec<_> = parts
+			.iter()
+			.enumerate()
+			.map(|(n, part)| {
+				let f = &f;
+				scope.spawn(move || {
+					let mut buffer = BufWriter::new(File::create(part)?);
+					f(n * per_thread, per_thread, &mut buffer)?;
+					buffer.flush()
+				})
+			})
+			.collect();
+		handles.into_iter().try_for_each(|h| h.join().unwrap())
+	})?;
+	merge(&parts, name, total)
+}
+
+// Exit with an error if `args.exe` couldn't be built.
+fn check_oracle() {
+	if let Err(reason) = oracle::oracle_path() {
+		eprintln!("error: args.exe could not be built: {}", reason);
+		eprintln!("The generator needs the MSVC toolchain to build src/args.cpp.");
+		process::exit(1);
+	}
+}
+
+// Join the output of each thread, in order.
+// `args.exe` only writes the cases so the header is added here.
+fn merge(parts: &[String], name: &str, cases: usize) -> io::Result<()> {
+	let mut output = File::create(name)?;
+	corpus::write_header(&mut output, cases)?;
+	for part in parts {
+		io::copy(&mut File::open(part)?, &mut output)?;
+		fs::remove_file(part)?;
+	}
+	Ok(())
+}
+
 // Enumerate all permutations with repetitions and for all output lengths from 1 to `max_len`.
 // Don't ask me how this works, I typed it out in a single stream of consciousness.
 fn perms_iter<'a, T: Copy>(
@@ -45,158 +251,23 @@ fn perms_iter<'a, T: Copy>(
 			})
 		})
 }
-fn perms<F: FnMut(&mut [u16])>(input: &[u16], max_len: u16, mut f: F) {
-	let mut buffer = Vec::with_capacity((max_len + 1) as _);
+// The number of items `perms_iter` will produce.
+fn perms_count<T>(input: &[T], max_len: u16) -> usize {
+	(1..=max_len as u32).map(|len| input.len().pow(len)).sum()
+}
+// Call `f` with `count` permutations, starting from the `start`th permutation.
+fn perms<F>(input: &[u16], max_len: u16, start: usize, count: usize, mut f: F) -> io::Result<()>
+where
+	F: FnMut(&[u16]) -> io::Result<()>,
+{
+	let mut buffer = Vec::with_capacity(max_len as _);
 
-	for args in perms_iter(input, max_len as _) {
+	for args in perms_iter(input, max_len as _).skip(start).take(count) {
 		buffer.clear();
 		for unit in args {
 			buffer.push(unit);
 		}
-		buffer.push(0);
-		f(&mut buffer);
-	}
-}
-
-// Call `CreateProcessW` with the command line and write the output to `buffer`.
-// We can't use std::process::Command because it doesn't (yet) allow setting the
-// zeroth argument. Nightly does support raw_args for setting the others.
-fn run_args(args: &mut [u16], buffer: &mut Io) {
-	// args.exe
-	static NAME: &[u16] = &[
-		b'a' as _, b'r' as _, b'g' as _, b's' as _, b'.' as _, b'e' as _, b'x' as _, b'e' as _, 0,
-	];
-	unsafe {
-		let mut startup = STARTUPINFOW::new();
-		startup.dwFlags = 0x100;
-		startup.hStdOutput = buffer.write;
-
-		let mut info = PROCESS_INFORMATION::new();
-		let result = CreateProcessW(
-			NAME.as_ptr(),
-			args.as_mut_ptr(),
-			null(),
-			null(),
-			1,
-			0,
-			null(),
-			null(),
-			&startup,
-			&mut info,
-		);
-		assert_ne!(result, 0);
-		// Wait for the process to exit to avoid overlapping writes.
-		let result = WaitForSingleObject(info.hProcess, u32::MAX);
-		assert_ne!(result, u32::MAX);
-
-		let mut exit_code = 0;
-		let result = GetExitCodeProcess(info.hProcess, &mut exit_code);
-		assert_ne!(result, 0);
-		assert_eq!(exit_code, 0);
-
-		CloseHandle(info.hProcess);
-		CloseHandle(info.hThread);
+		f(&buffer)?;
 	}
-}
-
-// Some thing to write the output to. Could be a pipe but in this case I'm saving directly to a file so it can be reused.
-struct Io {
-	write: usize,
-}
-impl Io {
-	fn new(name: &str) -> Self {
-		let f = File::create(name).unwrap();
-		let handle = f.as_raw_handle();
-		let mut output = 0;
-		unsafe {
-			DuplicateHandle(
-				GetCurrentProcess(),
-				handle as _,
-				GetCurrentProcess(),
-				&mut output,
-				0,
-				1,
-				2,
-			);
-		}
-		Self { write: output }
-	}
-}
-impl Drop for Io {
-	fn drop(&mut self) {
-		unsafe {
-			CloseHandle(self.write);
-		}
-	}
-}
-
-#[repr(C)]
-#[allow(nonstandard_style)]
-struct PROCESS_INFORMATION {
-	hProcess: usize,
-	hThread: usize,
-	dwProcessId: u32,
-	dwThreadId: u32,
-}
-impl PROCESS_INFORMATION {
-	fn new() -> Self {
-		unsafe { return std::mem::zeroed() }
-	}
-}
-#[repr(C)]
-#[allow(nonstandard_style)]
-struct STARTUPINFOW {
-	cb: u32,
-	lpReserved: *mut u16,
-	lpDesktop: *mut u16,
-	lpTitle: *mut u16,
-	dwX: u32,
-	dwY: u32,
-	dwXSize: u32,
-	dwYSize: u32,
-	dwXCountChars: u32,
-	dwYCountChars: u32,
-	dwFillAttribute: u32,
-	dwFlags: u32,
-	wShowWindow: u16,
-	cbReserved2: u16,
-	lpReserved2: *mut u8,
-	hStdInput: usize,
-	hStdOutput: usize,
-	hStdError: usize,
-}
-impl STARTUPINFOW {
-	fn new() -> Self {
-		let mut new: STARTUPINFOW = unsafe { std::mem::zeroed() };
-		new.cb = std::mem::size_of::<Self>() as _;
-		new
-	}
-}
-
-extern "system" {
-	fn CreateProcessW(
-		lpApplicationName: *const u16,
-		lpCommandLine: *mut u16,
-		lpProcessAttributes: *const c_void,
-		lpThreadAttributes: *const c_void,
-		bInheritHandles: i32,
-		dwCreationFlags: u32,
-		lpEnvironment: *const u16,
-		lpCurrentDirectory: *const u16,
-		lpStartupInfo: *const STARTUPINFOW,
-		lpProcessInformation: *mut PROCESS_INFORMATION,
-	) -> i32;
-	fn WaitForSingleObject(hHandle: usize, dwMilliseconds: u32) -> u32;
-	fn CloseHandle(hObject: usize) -> i32;
-	fn DuplicateHandle(
-		hSourceProcessHandle: usize,
-		hSourceHandle: usize,
-		hTargetProcessHandle: usize,
-		lpTargetHandle: *mut usize,
-		dwDesiredAccess: u32,
-		bInheritHandle: i32,
-		dwOptions: u32,
-	) -> i32;
-	fn GetCurrentProcess() -> usize;
-	fn GetExitCodeProcess(hProcess: usize, lpExitCode: *mut u32) -> i32;
+	Ok(())
 }
@@ -0,0 +1,357 @@
+// Running `args.exe` (the oracle) and other programs.
+//
+// `std::process::Command` can't be used because it doesn't allow setting the
+// whole command line, including the zeroth argument. So this calls
+// `CreateProcessW` directly. On other platforms the oracles can't be built
+// and `run` always fails.
+
+use std::{
+	io::{self, Write},
+	path::Path,
+	time::Duration,
+};
+
+// How long `run_oracle` and `run_winmain_oracle` wait before giving up.
+pub const ORACLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The path to `args.exe`. It's built by `build.rs`, which passes on the reason
+// if that failed.
+pub fn oracle_path() -> Result<&'static Path, &'static str> {
+	match option_env!("ARGS_ORACLE") {
+		Some(path) => Ok(Path::new(path)),
+		None => Err(option_env!("ARGS_ORACLE_ERROR").unwrap_or("unknown error")),
+	}
+}
+
+// Run `args.exe` with the given command line and write its output (a single
+// corpus case) to `sink`.
+pub fn run_oracle<W: Write + ?Sized>(cmdline: &[u16], sink: &mut W) -> io::Result<()> {
+	let oracle = oracle_path().map_err(|reason| {
+		io::Error::new(
+			io::ErrorKind::NotFound,
+			format!("args.exe could not be built: {}", reason),
+		)
+	})?;
+	run(oracle, cmdline, ORACLE_TIMEOUT, sink)
+}
+
+// The path to `winmain.exe`. It's built by `build.rs`, which passes on the
+// reason if that failed.
+pub fn winmain_oracle_path() -> Result<&'static Path, &'static str> {
+	match option_env!("WINMAIN_ORACLE") {
+		Some(path) => Ok(Path::new(path)),
+		None => Err(option_env!("WINMAIN_ORACLE_ERROR").unwrap_or("unknown error")),
+	}
+}
+
+// Run `winmain.exe` with the given command line and write its output to
+// `sink`. This is a single corpus case whose only argument is the `lpCmdLine`
+// passed to `wWinMain`.
+pub fn run_winmain_oracle<W: Write + ?Sized>(cmdline: &[u16], sink: &mut W) -> io::Result<()> {
+	let oracle = winmain_oracle_path().map_err(|reason| {
+		io::Error::new(
+			io::ErrorKind::NotFound,
+			format!("winmain.exe could not be built: {}", reason),
+		)
+	})?;
+	run(oracle, cmdline, ORACLE_TIMEOUT, sink)
+}
+
+// Run `program` with the given command line and write its stdout to `sink`.
+//
+// It's an error if the program can't be started, doesn't exit within
+// `timeout` or exits with a non-zero exit code. A program that times out is
+// terminated.
+pub fn run<W: Write + ?Sized>(
+	program: &Path,
+	cmdline: &[u16],
+	timeout: Duration,
+	sink: &mut W,
+) -> io::Result<()> {
+	#[cfg(windows)]
+	return windows::run(program, cmdline, timeout, sink);
+	#[cfg(not(windows))]
+	{
+		let _ = (program, cmdline, timeout, sink);
+		Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"running a program with a raw command line needs Windows",
+		))
+	}
+}
+
+#[cfg(windows)]
+mod windows {
+	use std::{
+		convert::TryFrom,
+		ffi::c_void,
+		fs::File,
+		io::{self, Read, Write},
+		os::windows::{
+			ffi::OsStrExt,
+			io::{AsRawHandle, FromRawHandle, OwnedHandle},
+		},
+		path::Path,
+		ptr::null_mut as null,
+		sync::Mutex,
+		time::Duration,
+	};
+
+	// The output is only read once the process has exited so it must fit in the pipe.
+	const PIPE_SIZE: u32 = 1 << 20;
+
+	pub(super) fn run<W: Write + ?Sized>(
+		program: &Path,
+		cmdline: &[u16],
+		timeout: Duration,
+		sink: &mut W,
+	) -> io::Result<()> {
+		let program: Vec<u16> = program.as_os_str().encode_wide().chain(Some(0)).collect();
+		// `CreateProcessW` may modify the command line so it needs its own copy.
+		let mut cmdline: Vec<u16> = cmdline.iter().copied().chain(Some(0)).collect();
+
+		let (mut read, write) = pipe()?;
+		let process = spawn(&program, &mut cmdline, &write)?;
+		// Close our copy of the write end so reading stops when the process exits.
+		drop(write);
+		wait(&process, timeout)?;
+
+		let mut exit_code = 0;
+		cvt(unsafe { GetExitCodeProcess(raw(&process), &mut exit_code) })?;
+		if exit_code != 0 {
+			return Err(io::Error::other(format!(
+				"process exited with code {}",
+				exit_code as i32
+			)));
+		}
+
+		let mut output = Vec::new();
+		read.read_to_end(&mut output)?;
+		sink.write_all(&output)
+	}
+
+	// Returns the read and write ends of an anonymous pipe.
+	// Neither end is inheritable.
+	fn pipe() -> io::Result<(File, OwnedHandle)> {
+		let (mut read, mut write) = (0, 0);
+		cvt(unsafe { CreatePipe(&mut read, &mut write, null(), PIPE_SIZE) })?;
+		unsafe {
+			Ok((
+				File::from_raw_handle(read as _),
+				OwnedHandle::from_raw_handle(write as _),
+			))
+		}
+	}
+
+	// Start a process with `stdout` as its standard output.
+	fn spawn(
+		program: &[u16],
+		cmdline: &mut [u16],
+		stdout: &OwnedHandle,
+	) -> io::Result<OwnedHandle> {
+		// All inheritable handles are inherited. If another thread were spawning a
+		// process at the same time then its child could inherit our pipe and keep
+		// it open. So the inheritable copy only exists while the lock is held.
+		static SPAWN_LOCK: Mutex<()> = Mutex::new(());
+		let _lock = SPAWN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+		let stdout = inheritable(stdout)?;
+		let mut startup = STARTUPINFOW::new();
+		startup.dwFlags = STARTF_USESTDHANDLES;
+		startup.hStdOutput = raw(&stdout);
+
+		let mut info = PROCESS_INFORMATION::new();
+		cvt(unsafe {
+			CreateProcessW(
+				program.as_ptr(),
+				cmdline.as_mut_ptr(),
+				null(),
+				null(),
+				1,
+				0,
+				null(),
+				null(),
+				&startup,
+				&mut info,
+			)
+		})?;
+		unsafe {
+			drop(OwnedHandle::from_raw_handle(info.hThread as _));
+			Ok(OwnedHandle::from_raw_handle(info.hProcess as _))
+		}
+	}
+
+	fn inheritable(handle: &OwnedHandle) -> io::Result<OwnedHandle> {
+		let mut copy = 0;
+		cvt(unsafe {
+			DuplicateHandle(
+				GetCurrentProcess(),
+				raw(handle),
+				GetCurrentProcess(),
+				&mut copy,
+				0,
+				1,
+				DUPLICATE_SAME_ACCESS,
+			)
+		})?;
+		unsafe { Ok(OwnedHandle::from_raw_handle(copy as _)) }
+	}
+
+	fn wait(process: &OwnedHandle, timeout: Duration) -> io::Result<()> {
+		let millis = u32::try_from(timeout.as_millis()).unwrap_or(INFINITE - 1);
+		match unsafe { WaitForSingleObject(raw(process), millis) } {
+			WAIT_OBJECT_0 => Ok(()),
+			WAIT_TIMEOUT => {
+				unsafe {
+					TerminateProcess(raw(process), 1);
+					WaitForSingleObject(raw(process), INFINITE);
+				}
+				Err(io::Error::new(
+					io::ErrorKind::TimedOut,
+					format!("process didn't exit within {:?}", timeout),
+				))
+			}
+			_ => Err(io::Error::last_os_error()),
+		}
+	}
+
+	fn raw(handle: &OwnedHandle) -> usize {
+		handle.as_raw_handle() as usize
+	}
+
+	fn cvt(result: i32) -> io::Result<()> {
+		if result == 0 {
+			Err(io::Error::last_os_error())
+		} else {
+			Ok(())
+		}
+	}
+
+	const STARTF_USESTDHANDLES: u32 = 0x100;
+	const DUPLICATE_SAME_ACCESS: u32 = 2;
+	const INFINITE: u32 = u32::MAX;
+	const WAIT_OBJECT_0: u32 = 0;
+	const WAIT_TIMEOUT: u32 = 0x102;
+
+	#[repr(C)]
+	#[allow(nonstandard_style)]
+	struct PROCESS_INFORMATION {
+		hProcess: usize,
+		hThread: usize,
+		dwProcessId: u32,
+		dwThreadId: u32,
+	}
+	impl PROCESS_INFORMATION {
+		fn new() -> Self {
+			unsafe { std::mem::zeroed() }
+		}
+	}
+	#[repr(C)]
+	#[allow(nonstandard_style, clippy::upper_case_acronyms)]
+	struct STARTUPINFOW {
+		cb: u32,
+		lpReserved: *mut u16,
+		lpDesktop: *mut u16,
+		lpTitle: *mut u16,
+		dwX: u32,
+		dwY: u32,
+		dwXSize: u32,
+		dwYSize: u32,
+		dwXCountChars: u32,
+		dwYCountChars: u32,
+		dwFillAttribute: u32,
+		dwFlags: u32,
+		wShowWindow: u16,
+		cbReserved2: u16,
+		lpReserved2: *mut u8,
+		hStdInput: usize,
+		hStdOutput: usize,
+		hStdError: usize,
+	}
+	impl STARTUPINFOW {
+		fn new() -> Self {
+			let mut new: STARTUPINFOW = unsafe { std::mem::zeroed() };
+			new.cb = std::mem::size_of::<Self>() as _;
+			new
+		}
+	}
+
+	extern "system" {
+		fn CreateProcessW(
+			lpApplicationName: *const u16,
+			lpCommandLine: *mut u16,
+			lpProcessAttributes: *const c_void,
+			lpThreadAttributes: *const c_void,
+			bInheritHandles: i32,
+			dwCreationFlags: u32,
+			lpEnvironment: *const u16,
+			lpCurrentDirectory: *const u16,
+			lpStartupInfo: *const STARTUPINFOW,
+			lpProcessInformation: *mut PROCESS_INFORMATION,
+		) -> i32;
+		fn CreatePipe(
+			hReadPipe: *mut usize,
+			hWritePipe: *mut usize,
+			lpPipeAttributes: *const c_void,
+			nSize: u32,
+		) -> i32;
+		fn WaitForSingleObject(hHandle: usize, dwMilliseconds: u32) -> u32;
+		fn TerminateProcess(hProcess: usize, uExitCode: u32) -> i32;
+		fn DuplicateHandle(
+			hSourceProcessHandle: usize,
+			hSourceHandle: usize,
+			hTargetProcessHandle: usize,
+			lpTargetHandle: *mut usize,
+			dwDesiredAccess: u32,
+			bInheritHandle: i32,
+			dwOptions: u32,
+		) -> i32;
+		fn GetCurrentProcess() -> usize;
+		fn GetExitCodeProcess(hProcess: usize, lpExitCode: *mut u32) -> i32;
+	}
+}
+
+#[cfg(all(test, windows))]
+mod tests {
+	use super::*;
+	use std::{env, path::PathBuf};
+
+	fn cmd() -> PathBuf {
+		env::var_os("ComSpec").map_or_else(|| r"C:\Windows\System32\cmd.exe".into(), PathBuf::from)
+	}
+	fn run_cmd(cmdline: &str, timeout: Duration) -> io::Result<Vec<u8>> {
+		let cmdline: Vec<u16> = cmdline.encode_utf16().collect();
+		let mut output = Vec::new();
+		run(&cmd(), &cmdline, timeout, &mut output)?;
+		Ok(output)
+	}
+
+	#[test]
+	fn success() {
+		assert_eq!(run_cmd("cmd /c exit 0", ORACLE_TIMEOUT).unwrap(), b"");
+		assert_eq!(
+			run_cmd("cmd /c echo hello", ORACLE_TIMEOUT).unwrap(),
+			b"hello\r\n"
+		);
+	}
+
+	#[test]
+	fn exit_code() {
+		let error = run_cmd("cmd /c exit 3", ORACLE_TIMEOUT).unwrap_err();
+		assert_eq!(error.to_string(), "process exited with code 3");
+	}
+
+	#[test]
+	fn not_found() {
+		let mut output = Vec::new();
+		let program = Path::new(r"C:\this\does\not\exist.exe");
+		let error = run(program, &[], ORACLE_TIMEOUT, &mut output).unwrap_err();
+		assert_eq!(error.kind(), io::ErrorKind::NotFound);
+	}
+
+	#[test]
+	fn timeout() {
+		let error = run_cmd("cmd /c ping -n 5 127.0.0.1", Duration::from_millis(100)).unwrap_err();
+		assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+	}
+}
@@ -0,0 +1,37 @@
+// This is built automatically by `build.rs`.
+//
+// A GUI subsystem program that writes a single test case to stdout in the
+// format described in `corpus.rs`. The command line is `GetCommandLineW()` and
+// the only argument is the `lpCmdLine` passed to `wWinMain`.
+//
+// GUI programs don't necessarily have the C runtime's stdout set up so this
+// writes to the standard output handle directly.
+#include <Windows.h>
+#include <wchar.h>
+
+static HANDLE out;
+
+static void write_bytes(const void *bytes, size_t len) {
+	DWORD written;
+	WriteFile(out, bytes, (DWORD)len, &written, NULL);
+}
+
+static void write_u16(size_t n) {
+	unsigned char bytes[2] = { (unsigned char)(n & 0xFF), (unsigned char)((n >> 8) & 0xFF) };
+	write_bytes(bytes, sizeof(bytes));
+}
+
+// The length in code units then the UTF-16LE code units.
+static void write_record(const wchar_t *s) {
+	size_t len = wcslen(s);
+	write_u16(len);
+	write_bytes(s, len * sizeof(wchar_t));
+}
+
+int WINAPI wWinMain(HINSTANCE instance, HINSTANCE prev_instance, PWSTR cmd_line, int show) {
+	out = GetStdHandle(STD_OUTPUT_HANDLE);
+	write_record(GetCommandLineW());
+	write_u16(1);
+	write_record(cmd_line);
+	return 0;
+}
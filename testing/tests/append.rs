@@ -0,0 +1,85 @@
+// Checks that `with_appended` and `with_program_and_appended` make command
+// lines that a child process reads as the original arguments with the new ones
+// added.
+//
+// Each result is split by `crt::split` and, if `args.exe` could be built, also
+// passed to it so the real C runtime decides. Otherwise, such as when not on
+// Windows, only `crt::split` is used.
+
+use std::io;
+use testing::{corpus, crt, oracle};
+
+// Command lines that are awkward to add to, with the arguments after the
+// program name.
+const CASES: &[(&str, &[&str])] = &[
+	("app.exe", &[]),
+	("app.exe   ", &[]),
+	(" ", &[]),
+	("", &[]),
+	(r#"app.exe "a b"  c\"d "#, &["a b", r#"c"d"#]),
+	(r#"app.exe a\\ "b\\" \\\"c"#, &[r"a\\", r"b\", r#"\"c"#]),
+	(r#"app.exe "a b\"#, &[r"a b\"]),
+	("\"C:\\Program Files\\app.exe\"\t\"\"\t", &[""]),
+	(r#""C:\app "#, &[]),
+];
+
+const EXTRA: &[&str] = &["--extra1", "a b", "", r#"c"d\"#, r"e f\"];
+
+#[test]
+fn append() -> io::Result<()> {
+	let args_exe = match oracle::oracle_path() {
+		Ok(path) => Some(path.to_str().expect("args.exe has a Unicode path")),
+		Err(reason) => {
+			eprintln!(
+				"only using crt::split: args.exe could not be built: {}",
+				reason
+			);
+			None
+		}
+	};
+	for &(cmdline, args) in CASES {
+		let units: Vec<u16> = cmdline.encode_utf16().collect();
+		let program = crt::split(&units).into_iter().next().unwrap_or_default();
+		let program = String::from_utf16(&program).unwrap();
+		let _guard = winarg::testing::set_command_line(units.leak());
+		let expected: Vec<&str> = args.iter().chain(EXTRA).copied().collect();
+
+		let appended = winarg::with_appended(EXTRA);
+		check(cmdline, &appended, &program, &expected, args_exe.is_some())?;
+		let program = args_exe.unwrap_or(r"C:\Program Files\tool.exe");
+		let appended = winarg::with_program_and_appended(program, EXTRA);
+		check(cmdline, &appended, program, &expected, args_exe.is_some())?;
+	}
+	Ok(())
+}
+
+// Check that `appended` is read as `program` followed by `expected`.
+fn check(
+	original: &str,
+	appended: &[u16],
+	program: &str,
+	expected: &[&str],
+	args_exe: bool,
+) -> io::Result<()> {
+	assert_eq!(appended.last(), Some(&0), "{:?}", original);
+	let appended = &appended[..appended.len() - 1];
+	let expected: Vec<Vec<u16>> = Some(program)
+		.iter()
+		.chain(expected)
+		.map(|arg| arg.encode_utf16().collect())
+		.collect();
+	let message = format!(
+		"{:?} became {:?}",
+		original,
+		String::from_utf16_lossy(appended)
+	);
+	assert_eq!(crt::split(appended), expected, "{}", message);
+	if args_exe {
+		let mut output = Vec::new();
+		oracle::run_oracle(appended, &mut output)?;
+		let case = corpus::read_case(&mut &output[..])?
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no output"))?;
+		assert_eq!(case.args, expected, "{}", message);
+	}
+	Ok(())
+}
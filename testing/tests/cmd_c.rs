@@ -0,0 +1,54 @@
+// Checks that `nest_for_cmd_c` makes text that, after `cmd.exe /c`, runs
+// `args.exe` with exactly the arguments it was given.
+//
+// This needs Windows and `args.exe`, so it's skipped without them.
+
+use std::{env, io, path::PathBuf};
+use testing::{corpus, oracle};
+
+// Arguments that are awkward for `cmd`, the C runtime or both.
+const CASES: &[&[&str]] = &[
+	&[],
+	&["a"],
+	&["", "a b", "\t", " "],
+	&["a&b", "a & b", "a|b", "a>b", "a<b", "(a)", "^", "^^", "!a!"],
+	&["100%", "%PATH%", "\"%PATH%\"", "a%b%c", "%%", "%"],
+	&[r#"say "hi""#, "\"", "\"\"", "\\\"", r#"\\"&\\""#],
+	&[r"a\", r"a b\", r"\\server\share\", r"C:\dir\ "],
+	&["\"&\" & \"|\"", "a \"b & c\" d", "^\"^", "\"a\" \"b\""],
+	&["é", "日本 語", "🦀&🦀"],
+	&["/c", "exit", "1"],
+];
+
+#[test]
+fn cmd_c() -> io::Result<()> {
+	let args_exe = match oracle::oracle_path() {
+		Ok(path) => path.to_str().expect("args.exe has a Unicode path"),
+		Err(reason) => {
+			eprintln!("skipped: args.exe could not be built: {}", reason);
+			return Ok(());
+		}
+	};
+	let cmd = env::var_os("ComSpec")
+		.map(PathBuf::from)
+		.unwrap_or_else(|| r"C:\Windows\System32\cmd.exe".into());
+	for args in CASES {
+		let inner: Vec<Vec<u16>> = Some(args_exe)
+			.iter()
+			.chain(args.iter())
+			.map(|arg| arg.encode_utf16().collect())
+			.collect();
+		let nested = winarg::nest_for_cmd_c(&inner);
+		// `/d` skips any AutoRun commands.
+		let mut cmdline: Vec<u16> = "cmd.exe /d /c ".encode_utf16().collect();
+		cmdline.extend_from_slice(&nested);
+		let message = String::from_utf16_lossy(&cmdline);
+
+		let mut output = Vec::new();
+		oracle::run(&cmd, &cmdline, oracle::ORACLE_TIMEOUT, &mut output)?;
+		let case = corpus::read_case(&mut &output[..])?
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no output"))?;
+		assert_eq!(case.args.get(1..), inner.get(1..), "{}", message);
+	}
+	Ok(())
+}
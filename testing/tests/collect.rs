@@ -0,0 +1,134 @@
+// Checks `collect_wide`, `collect_args_wide`, `cow_args_of`,
+// `Argument::as_raw_verbatim` and `canonicalize` against the corpus, and
+// compares their speed with collecting the parser's output.
+//
+// The benchmarks are ignored by default. Run them with:
+//
+//     cargo test --release --test collect -- --ignored --nocapture
+
+use std::{borrow::Cow, env, fs::File, hint::black_box, io, time::Instant};
+use testing::corpus;
+
+#[test]
+fn exact_capacity() -> io::Result<()> {
+	let path = env::var_os("WINARG_CORPUS")
+		.unwrap_or_else(|| concat!(env!("CARGO_MANIFEST_DIR"), "/golden.bin").into());
+	let reader = corpus::Reader::new(io::BufReader::new(File::open(path)?))?;
+	for (index, case) in reader.enumerate() {
+		let case = case.unwrap_or_else(|e| panic!("corpus case {} is malformed: {}", index, e));
+		let cmdline = String::from_utf16_lossy(&case.cmdline);
+		let _guard = winarg::testing::set_command_line(case.cmdline.clone().leak());
+
+		let args = winarg::collect_args_wide();
+		assert_eq!(args, case.args, "{:?}", cmdline);
+		assert_eq!(args.capacity(), args.len(), "{:?}", cmdline);
+		for arg in &args {
+			assert_eq!(arg.capacity(), arg.len(), "{:?}", cmdline);
+		}
+
+		let list = winarg::collect_wide();
+		assert_eq!(list, case.args.join(&0), "{:?}", cmdline);
+		assert_eq!(list.capacity(), list.len(), "{:?}", cmdline);
+
+		// Whether an argument is borrowed or not, its content is the same.
+		let cow: Vec<Cow<[u16]>> = winarg::cow_args_of(&case.cmdline).collect();
+		assert_eq!(cow, case.args, "{:?}", cmdline);
+		let range = case.cmdline.as_ptr_range();
+		for arg in &cow {
+			if let Cow::Borrowed(arg) = arg {
+				assert!(range.start <= arg.as_ptr(), "{:?}", cmdline);
+				assert!(arg.as_ptr_range().end <= range.end, "{:?}", cmdline);
+			}
+		}
+		for (arg, expected) in winarg::args_native().zip(&case.args) {
+			if let Some(verbatim) = arg.as_raw_verbatim() {
+				assert_eq!(verbatim, &expected[..], "{:?}", cmdline);
+			}
+		}
+
+		// The canonical command line has the same arguments and is already
+		// canonical.
+		let canonical = winarg::canonicalize(&case.cmdline)
+			.unwrap_or_else(|e| panic!("{:?} can't be canonicalized: {}", cmdline, e));
+		assert_eq!(canonical.capacity(), canonical.len(), "{:?}", cmdline);
+		let reparsed: Vec<Cow<[u16]>> = winarg::cow_args_of(&canonical).collect();
+		assert_eq!(reparsed, case.args, "{:?}", cmdline);
+		assert_eq!(
+			winarg::canonicalize(&canonical),
+			Ok(canonical.clone()),
+			"{:?}",
+			cmdline
+		);
+	}
+	Ok(())
+}
+
+fn time(name: &str, f: &dyn Fn() -> usize) {
+	const RUNS: u32 = 200;
+	let start = Instant::now();
+	for _ in 0..RUNS {
+		black_box(f());
+	}
+	println!("{:>40}: {:?}", name, start.elapsed() / RUNS);
+}
+
+// Repeat `args` after the program name, until the command line is about as
+// long as it can be.
+fn long_command_line(args: &[&str]) -> &'static [u16] {
+	let mut cmdline: Vec<u16> = "app.exe".encode_utf16().collect();
+	for arg in args.iter().cycle() {
+		if cmdline.len() + 1 + arg.len() > winarg::MAX_COMMAND_LINE {
+			break;
+		}
+		cmdline.push(b' ' as u16);
+		cmdline.extend(arg.encode_utf16());
+	}
+	cmdline.leak()
+}
+
+#[test]
+#[ignore]
+fn benchmark() {
+	// A mix of plain and quoted arguments.
+	let _guard = winarg::testing::set_command_line(long_command_line(&[
+		r"C:\dir\file.txt",
+		r#""a b c""#,
+		r#"a\\\"b"#,
+		"--flag=value",
+	]));
+	time("null_separated_list_wide().collect()", &|| {
+		winarg::null_separated_list_wide()
+			.collect::<Vec<u16>>()
+			.len()
+	});
+	time("collect_wide()", &|| winarg::collect_wide().len());
+	time("args_native() collected", &|| {
+		winarg::args_native()
+			.map(|arg| arg.utf16_units().collect::<Vec<u16>>())
+			.collect::<Vec<_>>()
+			.len()
+	});
+	time("collect_args_wide()", &|| winarg::collect_args_wide().len());
+}
+
+#[test]
+#[ignore]
+fn benchmark_paths() {
+	// Paths, which rarely need quotes or escapes removed.
+	let _guard = winarg::testing::set_command_line(long_command_line(&[
+		r"C:\Windows\System32\drivers\etc\hosts",
+		r"D:\src\project\target\release\build.rs",
+		r#""C:\Program Files\Common Files\app.dll""#,
+		r"\\server\share\folder\report.docx",
+	]));
+	time("args_native() collected", &|| {
+		winarg::args_native()
+			.map(|arg| arg.utf16_units().collect::<Vec<u16>>())
+			.collect::<Vec<_>>()
+			.len()
+	});
+	time("collect_args_wide()", &|| winarg::collect_args_wide().len());
+	time("cow_args().collect()", &|| {
+		winarg::cow_args().collect::<Vec<_>>().len()
+	});
+}
@@ -1,55 +1,88 @@
-use std::{
-	fs::File,
-	io::{self, BufRead},
-	sync::atomic::{AtomicPtr, Ordering},
-};
+use std::{env, fs::File, io};
+#[cfg(windows)]
+use std::{io::Write, slice};
+use testing::{corpus, crt};
 
 // Again, this isn't very good and should probably be rewritten.
 // It does the job though (mostly).
 
+// Uses the committed `golden.bin` unless `WINARG_CORPUS` is set to the path of
+// another corpus, such as a larger one made by the generator.
 #[test]
 fn exhaustive() -> io::Result<()> {
-	let f = File::open("output.txt")?;
-	let reader = io::BufReader::new(f);
-	let mut lines = reader.lines();
-	let mut buffer = Vec::new();
+	let path = env::var_os("WINARG_CORPUS")
+		.unwrap_or_else(|| concat!(env!("CARGO_MANIFEST_DIR"), "/golden.bin").into());
+	let f = File::open(path)?;
+	let reader = corpus::Reader::new(io::BufReader::new(f))?;
+	let expected = reader.cases();
+	// Where `CommandLineToArgvW` disagrees with the CRT.
+	#[cfg(windows)]
+	let mut report = io::BufWriter::new(File::create("shell32_divergences.txt")?);
+	#[cfg(windows)]
+	let mut divergences = 0_usize;
 	let mut counter = 0_usize;
-	loop {
-		let cmdline = match lines.next() {
-			Some(line) => line?,
-			None => break,
-		};
-		if cmdline.len() > 10 {
-			break;
-		}
+	for (index, case) in reader.enumerate() {
+		let case = case.unwrap_or_else(|e| panic!("corpus case {} is malformed: {}", index, e));
 		counter += 1;
-		let argc = match lines.next() {
-			Some(line) => line?.parse::<u16>().unwrap(),
-			None => break,
-		};
-		buffer.clear();
-		for _ in 0..argc {
-			let arg = match lines.next() {
-				Some(line) => line?,
-				None => break,
-			};
-			buffer.push(arg);
-		}
+		let cmdline = String::from_utf16_lossy(&case.cmdline);
+		let buffer = &case.args;
 		//
-		let cmd: Vec<u16> = cmdline.encode_utf16().chain(Some(0)).collect();
-		CMD.store(cmd.as_ptr() as _, std::sync::atomic::Ordering::Relaxed);
+		// Arguments are 'static so the command line has to be leaked.
+		let cmd: &'static [u16] = case
+			.cmdline
+			.iter()
+			.copied()
+			.chain(Some(0))
+			.collect::<Vec<_>>()
+			.leak();
+		let _guard = winarg::testing::set_command_line(cmd);
+
+		// The pure Rust oracle should agree with the real CRT.
+		assert_eq!(crt::split(cmd), *buffer, "crt::split: {:?}", cmdline);
+
+		// Differences are expected so record them rather than failing.
+		#[cfg(windows)]
+		{
+			let shell32 = shell32_args(cmd);
+			if shell32.as_ref() != Some(buffer) {
+				divergences += 1;
+				writeln!(report, "{:?}", cmdline)?;
+				writeln!(report, "  crt:     {:?}", lossy(buffer))?;
+				match shell32 {
+					Some(args) => writeln!(report, "  shell32: {:?}", lossy(&args))?,
+					None => writeln!(report, "  shell32: error {}", io::Error::last_os_error())?,
+				}
+			}
+		}
 
 		let mut counter = 0;
 		for arg in winarg::ArgsNative::from_env() {
-			assert_eq!(arg, buffer[counter].as_str());
+			let arg: Vec<u16> = arg.utf16_units().collect();
+			assert_eq!(arg, buffer[counter], "{:?}", cmdline);
 			counter += 1;
 		}
 		assert_eq!(counter, buffer.len());
 
+		// `arg_spans` finds each argument without parsing it. Parsing the span
+		// on its own gives the same argument.
+		let spans: Vec<_> = winarg::arg_spans_of(&case.cmdline).collect();
+		assert_eq!(spans.len(), buffer.len(), "{:?}", cmdline);
+		for (index, span) in spans.into_iter().enumerate() {
+			let (arg, used) = winarg::unquote_one(&case.cmdline[span.clone()], index == 0);
+			assert_eq!(arg.collect::<Vec<u16>>(), buffer[index], "{:?}", cmdline);
+			assert_eq!(used, span.len(), "{:?}", cmdline);
+		}
+
+		// `null_separated_list` is lossy so compare it against the lossy version.
 		let mut counter = 0;
 		let args: String = winarg::null_separated_list().collect();
 		for arg in args.split('\0') {
-			assert_eq!(arg, buffer[counter], "{:?}", cmdline);
+			assert_eq!(
+				arg,
+				String::from_utf16_lossy(&buffer[counter]),
+				"{:?}",
+				cmdline
+			);
 			counter += 1;
 		}
 		assert_eq!(counter, buffer.len());
@@ -69,21 +102,64 @@ fn exhaustive() -> io::Result<()> {
 			if index + 1 == buffer.len() {
 				assert!(arg.is_empty());
 			} else {
-				let arg = String::from_utf16_lossy(arg);
-				assert_eq!(arg, buffer[index + 1], "{:?}", cmdline);
+				assert_eq!(arg, &buffer[index + 1][..], "{:?}", cmdline);
 			}
 		}
 	}
-	println!("{}", counter);
+	println!("ran {} cases", counter);
+	assert!(
+		counter >= expected,
+		"only {} cases were run but the corpus header says it has {}",
+		counter,
+		expected
+	);
+	#[cfg(windows)]
+	{
+		println!(
+			"{} command lines are parsed differently by CommandLineToArgvW (see shell32_divergences.txt)",
+			divergences
+		);
+		report.flush()?;
+	}
 	Ok(())
 }
 
-// Replace `GetCommandLineW` with our own version.
-// WARNING: Never do this in real code.
-// Replacing system functions is massively unsafe and a recipe for disaster.
-// Especially so with a library that assumes the memory is 'static.
-static CMD: AtomicPtr<u16> = AtomicPtr::new(std::ptr::null_mut());
-#[no_mangle]
-extern "C" fn GetCommandLineW() -> *const u16 {
-	CMD.load(Ordering::Relaxed)
+#[cfg(windows)]
+fn lossy(args: &[Vec<u16>]) -> Vec<String> {
+	args.iter()
+		.map(|arg| String::from_utf16_lossy(arg))
+		.collect()
+}
+
+// Parse a null terminated command line using `CommandLineToArgvW`.
+// Returns `None` if it fails.
+#[cfg(windows)]
+fn shell32_args(cmdline: &[u16]) -> Option<Vec<Vec<u16>>> {
+	unsafe {
+		let mut argc = 0;
+		let argv = CommandLineToArgvW(cmdline.as_ptr(), &mut argc);
+		if argv.is_null() {
+			return None;
+		}
+		let args = (0..argc as usize)
+			.map(|i| {
+				let arg = *argv.add(i);
+				let len = (0..).take_while(|&n| *arg.add(n) != 0).count();
+				slice::from_raw_parts(arg, len).to_vec()
+			})
+			.collect();
+		// The array and the strings are a single allocation.
+		LocalFree(argv as usize);
+		Some(args)
+	}
+}
+
+#[cfg(windows)]
+#[link(name = "shell32")]
+extern "system" {
+	fn CommandLineToArgvW(lpCmdLine: *const u16, pNumArgs: *mut i32) -> *mut *mut u16;
+}
+#[cfg(windows)]
+extern "system" {
+	fn LocalFree(hMem: usize) -> usize;
 }
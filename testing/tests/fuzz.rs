@@ -0,0 +1,187 @@
+// Compares winarg against the C runtime using random command lines.
+//
+// The exhaustive test covers every short command line. This covers longer
+// command lines, up to a few hundred code units, by sampling them at random.
+//
+// Each command line is parsed by winarg and by `crt::split`, which should
+// always agree. If `args.exe` could be built then the real C runtime is also
+// checked, which decides who's right when they don't agree. Otherwise, such
+// as when not on Windows, only the two Rust implementations are compared.
+//
+// Set `WINARG_FUZZ_SEED` to rerun with a particular seed. The seed is printed
+// when a test fails. `WINARG_FUZZ_CASES` sets the number of command lines to
+// try (the default is 200).
+
+use std::{
+	env, io,
+	time::{SystemTime, UNIX_EPOCH},
+};
+use testing::{corpus, crt, oracle};
+
+const MAX_LEN: usize = 300;
+
+#[test]
+fn fuzz() {
+	let args_exe = match oracle::oracle_path() {
+		Ok(_) => true,
+		Err(reason) => {
+			eprintln!(
+				"only using crt::split: args.exe could not be built: {}",
+				reason
+			);
+			false
+		}
+	};
+	let seed = env_var("WINARG_FUZZ_SEED").unwrap_or_else(|| {
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+		now.as_nanos() as u64
+	});
+	let cases = env_var("WINARG_FUZZ_CASES").unwrap_or(200);
+
+	let mut rng = Rng(seed);
+	for _ in 0..cases {
+		let len = rng.below(MAX_LEN as u64) as usize + 1;
+		let cmdline: Vec<u16> = (0..len).flat_map(|_| rng.unit()).collect();
+		if check(&cmdline, args_exe).is_err() {
+			let cmdline = shrink(cmdline, args_exe);
+			let error = check(&cmdline, args_exe).unwrap_err();
+			panic!(
+				"the command line was parsed differently (seed {})\n  readable: {}\n  escaped:  {}\n{}",
+				seed,
+				String::from_utf16_lossy(&cmdline),
+				escape(&cmdline),
+				error
+			);
+		}
+	}
+}
+
+fn env_var(name: &str) -> Option<u64> {
+	let value = env::var(name).ok()?;
+	match value.parse() {
+		Ok(n) => Some(n),
+		Err(_) => panic!("{} must be a number, not {:?}", name, value),
+	}
+}
+
+// Check winarg and `crt::split` parse the command line the same way and, if
+// `args_exe` is true, that they agree with `args.exe`.
+fn check(cmdline: &[u16], args_exe: bool) -> Result<(), String> {
+	let winarg = winarg_args(cmdline);
+	let split = crt::split(cmdline);
+	let real = if args_exe {
+		let mut output = Vec::new();
+		oracle::run_oracle(cmdline, &mut output)
+			.map_err(|e| format!("couldn't run args.exe: {}", e))?;
+		let case = corpus::read_case(&mut io::Cursor::new(output))
+			.ok()
+			.flatten()
+			.ok_or("args.exe returned an invalid case")?;
+		Some(case.args)
+	} else {
+		None
+	};
+	let agree = match &real {
+		Some(real) => winarg == *real && split == *real,
+		None => winarg == split,
+	};
+	if agree {
+		return Ok(());
+	}
+	let mut error = format!("  command line: {}\n", escape(cmdline));
+	if let Some(real) = &real {
+		error += &format!("  args.exe:   {:?}\n", lossy(real));
+	}
+	error += &format!("  winarg:     {:?}\n", lossy(&winarg));
+	error += &format!("  crt::split: {:?}", lossy(&split));
+	Err(error)
+}
+
+fn winarg_args(cmdline: &[u16]) -> Vec<Vec<u16>> {
+	// Arguments are 'static so the command line has to be leaked. It has no
+	// null and ends where its allocation does, so AddressSanitizer catches any
+	// read past the end.
+	let cmdline: &'static [u16] = cmdline.to_vec().leak();
+	let _guard = winarg::testing::set_command_line(cmdline);
+	winarg::ArgsNative::from_env()
+		.map(|arg| arg.utf16_units().collect())
+		.collect()
+}
+
+// Find a smaller command line that still fails.
+// Removes ever smaller chunks then tries simplifying the remaining units.
+fn shrink(mut cmdline: Vec<u16>, args_exe: bool) -> Vec<u16> {
+	let mut chunk = cmdline.len() / 2;
+	while chunk > 0 {
+		let mut start = 0;
+		while start + chunk <= cmdline.len() {
+			let mut smaller = cmdline.clone();
+			smaller.drain(start..start + chunk);
+			if check(&smaller, args_exe).is_err() {
+				cmdline = smaller;
+			} else {
+				start += chunk;
+			}
+		}
+		chunk /= 2;
+	}
+	for i in 0..cmdline.len() {
+		if cmdline[i] != u16::from(b'a') {
+			let mut simpler = cmdline.clone();
+			simpler[i] = u16::from(b'a');
+			if check(&simpler, args_exe).is_err() {
+				cmdline = simpler;
+			}
+		}
+	}
+	cmdline
+}
+
+// Printable ASCII is shown as is. Everything else is escaped.
+fn escape(units: &[u16]) -> String {
+	units
+		.iter()
+		.map(|&u| match u {
+			0x20..=0x7E => char::from(u as u8).to_string(),
+			_ => format!("\\u{{{:04X}}}", u),
+		})
+		.collect()
+}
+
+fn lossy(args: &[Vec<u16>]) -> Vec<String> {
+	args.iter()
+		.map(|arg| String::from_utf16_lossy(arg))
+		.collect()
+}
+
+// A small PRNG (splitmix64) so runs are reproducible from the seed.
+struct Rng(u64);
+impl Rng {
+	fn next(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+		z ^ (z >> 31)
+	}
+	fn below(&mut self, n: u64) -> u64 {
+		self.next() % n
+	}
+	// A random character, weighted toward the ones that affect parsing.
+	fn unit(&mut self) -> Vec<u16> {
+		let s = match self.below(100) {
+			0..=29 => "\\",
+			30..=59 => "\"",
+			60..=74 => " ",
+			75..=79 => "\t",
+			80..=94 => "a",
+			95 => "\n",
+			96 => "é",
+			97 => "😅",
+			// Isolated surrogates.
+			98 => return vec![0xD800],
+			_ => return vec![0xDC00],
+		};
+		s.encode_utf16().collect()
+	}
+}
@@ -0,0 +1,32 @@
+// Checks that winarg's `no_std` functions can't panic.
+//
+// `panic-free` is a minimal `no_std` program that uses all of them. Its panic
+// handler calls a function that doesn't exist so it only links if the
+// optimizer removed every panic. A failure here means a change to winarg has
+// added a panic that can't be proven unreachable, such as a slice index.
+//
+// The program links against the MSVC C runtime so this only runs with the
+// MSVC toolchain.
+#![cfg(all(windows, target_env = "msvc"))]
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn panic_free() {
+	let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("panic-free");
+	let output = Command::new(env!("CARGO"))
+		.args(["build", "--release", "--target-dir"])
+		.arg(dir.join("target"))
+		.current_dir(&dir)
+		.output()
+		.expect("failed to run cargo");
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(
+		!stderr.contains("winarg_can_panic"),
+		"something in winarg can panic. Run `cargo rustc --release -- --emit=obj` in \
+		panic-free and disassemble the object to see which function calls a panic.\n{}",
+		stderr
+	);
+	assert!(output.status.success(), "{}", stderr);
+}
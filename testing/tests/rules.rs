@@ -0,0 +1,93 @@
+// Checks `ParserRules::Pre2008` against the corpus.
+//
+// The corpus is parsed with the modern rules so it can't say how the old rules
+// parse a command line. Instead, each command line is checked against
+// `crt::split_pre_2008` and the command lines that change are pinned down:
+// they're exactly the ones where the modern rules read `""` in quotes as a
+// literal quote. Every command line that changes is written to
+// `pre_2008_divergences.txt`.
+
+use std::{
+	env,
+	fs::File,
+	io::{self, Write},
+};
+use testing::{corpus, crt};
+use winarg::{ParserRules, Token};
+
+const QUOTE: u16 = b'"' as u16;
+
+#[test]
+fn pre_2008() -> io::Result<()> {
+	let path = env::var_os("WINARG_CORPUS")
+		.unwrap_or_else(|| concat!(env!("CARGO_MANIFEST_DIR"), "/golden.bin").into());
+	let reader = corpus::Reader::new(io::BufReader::new(File::open(path)?))?;
+	let mut report = io::BufWriter::new(File::create("pre_2008_divergences.txt")?);
+	let mut cases = 0_usize;
+	let mut divergences = 0_usize;
+	for (index, case) in reader.enumerate() {
+		let case = case.unwrap_or_else(|e| panic!("corpus case {} is malformed: {}", index, e));
+		cases += 1;
+		let cmdline = String::from_utf16_lossy(&case.cmdline);
+		let cmd: &'static [u16] = case
+			.cmdline
+			.iter()
+			.copied()
+			.chain(Some(0))
+			.collect::<Vec<_>>()
+			.leak();
+		let _guard = winarg::testing::set_command_line(cmd);
+
+		let pre_2008 = args(winarg::Parser().with_rules(ParserRules::Pre2008));
+		assert_eq!(pre_2008, crt::split_pre_2008(cmd), "{:?}", cmdline);
+		// The arguments start and end in the same places.
+		assert_eq!(winarg::arg_spans().count(), pre_2008.len(), "{:?}", cmdline);
+
+		// Removing the quotes that came from `""` in quotes gives the old rules.
+		let mut expected = vec![Vec::new()];
+		for spanned in winarg::Parser().spanned() {
+			match spanned.token {
+				Token::NextArg => expected.push(Vec::new()),
+				// A quote from `""` comes from the second quote.
+				Token::Unit(unit)
+					if unit.get() == QUOTE
+						&& spanned.offset > 0
+						&& cmd[spanned.offset - 1] == QUOTE => {}
+				token => expected.last_mut().unwrap().push(token.as_u16()),
+			}
+		}
+		assert_eq!(pre_2008, expected, "{:?}", cmdline);
+
+		if pre_2008 != case.args {
+			divergences += 1;
+			writeln!(report, "{:?}", cmdline)?;
+			writeln!(report, "  modern:   {:?}", lossy(&case.args))?;
+			writeln!(report, "  pre-2008: {:?}", lossy(&pre_2008))?;
+		}
+	}
+	report.flush()?;
+	println!(
+		"{} of {} command lines are parsed differently by the pre-2008 rules (see pre_2008_divergences.txt)",
+		divergences, cases
+	);
+	assert!(divergences > 0);
+	Ok(())
+}
+
+// Collect the arguments from a parser.
+fn args(parser: winarg::Parser) -> Vec<Vec<u16>> {
+	let mut args = vec![Vec::new()];
+	for token in parser {
+		match token {
+			Token::NextArg => args.push(Vec::new()),
+			token => args.last_mut().unwrap().push(token.as_u16()),
+		}
+	}
+	args
+}
+
+fn lossy(args: &[Vec<u16>]) -> Vec<String> {
+	args.iter()
+		.map(|arg| String::from_utf16_lossy(arg))
+		.collect()
+}
@@ -0,0 +1,59 @@
+// Checks `utf8::parse_str` against the corpus, argument by argument.
+//
+// Each command line is parsed as it is, and again with every `a` replaced by
+// characters that take two, three and four bytes in UTF-8, which is compared
+// with the UTF-16 parser's arguments for the same replacement.
+
+use std::{borrow::Cow, env, fs::File, io};
+use testing::corpus;
+
+#[test]
+fn same_as_wide() -> io::Result<()> {
+	let path = env::var_os("WINARG_CORPUS")
+		.unwrap_or_else(|| concat!(env!("CARGO_MANIFEST_DIR"), "/golden.bin").into());
+	let reader = corpus::Reader::new(io::BufReader::new(File::open(path)?))?;
+	let mut checked = 0;
+	for (index, case) in reader.enumerate() {
+		let case = case.unwrap_or_else(|e| panic!("corpus case {} is malformed: {}", index, e));
+		// Isolated surrogates can't be written in UTF-8.
+		let cmdline = match String::from_utf16(&case.cmdline) {
+			Ok(cmdline) => cmdline,
+			Err(_) => continue,
+		};
+		let expected: Vec<String> = case
+			.args
+			.iter()
+			.map(|arg| String::from_utf16(arg).unwrap())
+			.collect();
+		check(&cmdline, &expected);
+
+		for replacement in ["é", "語", "🦀"] {
+			let cmdline = cmdline.replace('a', replacement);
+			let wide: Vec<u16> = cmdline.encode_utf16().collect();
+			let expected: Vec<String> = winarg::cow_args_of(&wide)
+				.map(|arg| String::from_utf16(&arg).unwrap())
+				.collect();
+			check(&cmdline, &expected);
+		}
+		checked += 1;
+	}
+	assert!(checked > 0, "no command lines were checked");
+	Ok(())
+}
+
+fn check(cmdline: &str, expected: &[String]) {
+	let args: Vec<Cow<str>> = winarg::utf8::parse_str(cmdline).collect();
+	assert_eq!(args, expected, "{:?}", cmdline);
+	// Borrowed arguments are part of the command line.
+	let range = cmdline.as_bytes().as_ptr_range();
+	for arg in &args {
+		if let Cow::Borrowed(arg) = arg {
+			assert!(range.start <= arg.as_ptr(), "{:?}", cmdline);
+			assert!(
+				arg.as_bytes().as_ptr_range().end <= range.end,
+				"{:?}",
+				cmdline
+			);
+		}
+	}
+}
@@ -0,0 +1,95 @@
+// Checks the `lpCmdLine` passed to `wWinMain` by running `winmain.exe`.
+//
+// The C runtime makes `lpCmdLine` by skipping the program name and the
+// whitespace after it. The program name is skipped using only quotes, the same
+// as `argv[0]`. So `lpCmdLine` should start exactly where winarg's first
+// argument after the program name starts. `CommandLineToArgvW` based code
+// doesn't strip the program name this way so this pins down the behaviour.
+//
+// Running a process per command line is slow so only short command lines
+// from the golden corpus are used.
+
+use std::{fs::File, io};
+use testing::{corpus, oracle};
+
+const MAX_LEN: usize = 5;
+const SPACE: u16 = b' ' as u16;
+const QUOTE: u16 = b'"' as u16;
+
+#[test]
+fn winmain() -> io::Result<()> {
+	if let Err(reason) = oracle::winmain_oracle_path() {
+		eprintln!("skipping: winmain.exe could not be built: {}", reason);
+		return Ok(());
+	}
+	let f = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/golden.bin"))?;
+	let reader = corpus::Reader::new(io::BufReader::new(f))?;
+	let mut counter = 0_usize;
+	for case in reader {
+		let case = case?;
+		if case.cmdline.len() > MAX_LEN {
+			continue;
+		}
+		counter += 1;
+		let mut output = Vec::new();
+		oracle::run_winmain_oracle(&case.cmdline, &mut output)?;
+		let winmain = corpus::read_case(&mut &output[..])?
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no output"))?;
+		let cmdline = String::from_utf16_lossy(&case.cmdline);
+		assert_eq!(winmain.cmdline, case.cmdline, "{:?}", cmdline);
+		let lp_cmd_line = &winmain.args[0][..];
+
+		// The C runtime's documented rules.
+		assert_eq!(
+			lp_cmd_line,
+			skip_program_name(&case.cmdline),
+			"{:?}",
+			cmdline
+		);
+
+		// `lpCmdLine` is the rest of the command line from winarg's first argument.
+		let (args, rest) = winarg_args(case.cmdline.clone());
+		assert_eq!(lp_cmd_line, rest, "{:?}", cmdline);
+
+		// Parsing `lpCmdLine` after a program name gives the same arguments as
+		// parsing the whole command line.
+		let with_program: Vec<u16> = "EXE "
+			.encode_utf16()
+			.chain(lp_cmd_line.iter().copied())
+			.collect();
+		let (lp_args, _) = winarg_args(with_program);
+		assert_eq!(lp_args[1..], args[1..], "{:?}", cmdline);
+	}
+	println!("checked {} command lines", counter);
+	Ok(())
+}
+
+// Returns the arguments and the unparsed command line after the program name.
+fn winarg_args(cmdline: Vec<u16>) -> (Vec<Vec<u16>>, &'static [u16]) {
+	// Arguments are 'static so the command line has to be leaked.
+	let _guard = winarg::testing::set_command_line(cmdline.leak());
+	let args = winarg::ArgsNative::from_env()
+		.map(|arg| arg.utf16_units().collect())
+		.collect();
+	let rest = winarg::ArgsNative::from_env()
+		.nth(1)
+		.map_or(&[][..], |arg| arg.raw_arg());
+	(args, rest)
+}
+
+// How the C runtime finds `lpCmdLine`. Anything up to and including a space
+// counts as whitespace here.
+fn skip_program_name(cmdline: &[u16]) -> &[u16] {
+	let mut in_quotes = false;
+	let mut i = 0;
+	while i < cmdline.len() && (cmdline[i] > SPACE || in_quotes) {
+		if cmdline[i] == QUOTE {
+			in_quotes = !in_quotes;
+		}
+		i += 1;
+	}
+	while i < cmdline.len() && cmdline[i] <= SPACE {
+		i += 1;
+	}
+	&cmdline[i..]
+}
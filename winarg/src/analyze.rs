@@ -0,0 +1,146 @@
+//! Describing how a command line is parsed, for highlighting.
+
+use crate::{ParseArgs, WideIter};
+use core::ops::Range;
+
+/// Split a command line into spans that describe how it's parsed.
+///
+/// `visit` is called with each span in the order they appear in `cmdline`.
+/// Each argument is reported as a [`SpanKind::Argument`] followed by the spans
+/// within it, which together cover the whole argument. The whitespace between
+/// arguments isn't reported. The command line ends at the first null, if any.
+///
+/// The spans come from the same parser used for the arguments so they always
+/// agree with how the command line is split.
+///
+/// ```
+/// use winarg::{analyze, SpanKind};
+///
+/// let cmdline: Vec<u16> = r#"app.exe "a b" c\"d"#.encode_utf16().collect();
+/// let mut spans = Vec::new();
+/// analyze(&cmdline, |span| spans.push((span.kind, span.range)));
+/// assert_eq!(
+///     spans,
+///     [
+///         (SpanKind::Argument, 0..7),
+///         (SpanKind::Literal, 0..7),
+///         (SpanKind::Argument, 8..13),
+///         (SpanKind::Quote, 8..9),
+///         (SpanKind::Literal, 9..12),
+///         (SpanKind::Quote, 12..13),
+///         (SpanKind::Argument, 14..18),
+///         (SpanKind::Literal, 14..15),
+///         (SpanKind::Escape, 15..16),
+///         (SpanKind::Literal, 16..18),
+///     ]
+/// );
+/// ```
+pub fn analyze<F: FnMut(Span)>(cmdline: &[u16], visit: F) {
+	analyze_with(cmdline, &[], visit)
+}
+
+/// Like [`analyze`] but the code units in `separators` also separate arguments
+/// when they're not in quotes.
+///
+/// **This doesn't match how Windows parses command lines.** See
+/// [`arg_spans_lenient`](crate::arg_spans_lenient) for when it's useful.
+/// `separators` must be sorted.
+///
+/// ```
+/// use winarg::{analyze_lenient, SpanKind};
+///
+/// let cmdline: Vec<u16> = "app.exe a\u{3000}b".encode_utf16().collect();
+/// let mut args = Vec::new();
+/// analyze_lenient(&cmdline, &[0x3000], |span| {
+///     if span.kind == SpanKind::Argument {
+///         args.push(span.range);
+///     }
+/// });
+/// assert_eq!(args, [0..7, 8..9, 10..11]);
+/// ```
+pub fn analyze_lenient<F: FnMut(Span)>(cmdline: &[u16], separators: &[u16], visit: F) {
+	analyze_with(cmdline, separators, visit)
+}
+
+fn analyze_with<F: FnMut(Span)>(cmdline: &[u16], separators: &[u16], visit: F) {
+	let end = cmdline
+		.iter()
+		.position(|&u| u == 0)
+		.unwrap_or(cmdline.len());
+	let mut parser = ParseArgs::new(WideIter::new(&cmdline[..end]), true);
+	parser.separators = separators;
+	let mut spans = Spans {
+		visit,
+		literal: None,
+	};
+	while parser.cursor.peek().is_some() {
+		// Find the end of the argument before reporting what's in it.
+		let start = parser.offset();
+		let mut rest = parser.clone();
+		while rest.next().is_some() {}
+		spans.push(SpanKind::Argument, start..rest.offset());
+
+		while parser
+			.next_visit(&mut |kind, range| spans.push(kind, range))
+			.is_some()
+		{}
+		spans.flush();
+		parser.move_to_next_arg();
+	}
+}
+
+/// A part of a command line found by [`analyze`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+	/// What the parser did with it.
+	pub kind: SpanKind,
+	/// The code units, as indexes into the command line.
+	pub range: Range<usize>,
+}
+
+/// The kinds of [`Span`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SpanKind {
+	/// A whole argument, including any quotes.
+	Argument,
+	/// Text that's part of the argument as it is. Adjacent literal code units
+	/// are reported as one span.
+	Literal,
+	/// A quote that starts or ends quoted text. It's removed from the argument.
+	Quote,
+	/// Backslashes before a quote. Half of them, rounded down, are kept as
+	/// backslashes. If there's an odd number, the quote after them is a
+	/// literal quote instead of a [`SpanKind::Quote`].
+	Escape,
+}
+
+// Passes spans on to the visitor, joining up literals.
+struct Spans<F> {
+	visit: F,
+	literal: Option<Range<usize>>,
+}
+impl<F: FnMut(Span)> Spans<F> {
+	fn push(&mut self, kind: SpanKind, range: Range<usize>) {
+		if kind == SpanKind::Literal {
+			match &mut self.literal {
+				Some(literal) if literal.end == range.start => literal.end = range.end,
+				_ => {
+					self.flush();
+					self.literal = Some(range);
+				}
+			}
+		} else {
+			self.flush();
+			(self.visit)(Span { kind, range });
+		}
+	}
+	fn flush(&mut self) {
+		if let Some(range) = self.literal.take() {
+			(self.visit)(Span {
+				kind: SpanKind::Literal,
+				range,
+			});
+		}
+	}
+}
@@ -0,0 +1,224 @@
+//! The `arg_match!` macro, for deciding what to do with each argument.
+
+use crate::{Argument, FlagValue, MatchOptions, EQUALS};
+
+/// Run the first arm that matches each argument.
+///
+/// This replaces a loop over the arguments with a long chain of `if`s. It's
+/// much smaller than a full argument parser: it only decides which block of
+/// code runs for each argument, in the order they're given. Nothing is
+/// allocated so it works without `alloc`.
+///
+/// The first part is the arguments, which can be anything that iterates
+/// [`Argument`]s, such as [`args_native`](crate::args_native). Skip the
+/// program name if it shouldn't be matched. The arms come after it in braces,
+/// optionally separated by commas. Each arm's body is a block. The arms are:
+///
+/// * `"--help" | "-h" => { ... }` runs if the argument is one of the names.
+/// * `"--out" | "-o" = value => { ... }` runs for an option with a value,
+///   written as `--out=file` or as `--out file`. `value` is an
+///   `Option<`[`FlagValue`]`>`, which is `None` if the option is the last
+///   argument so it has no value. As in [`value_of`](crate::value_of), an
+///   argument used as a value is never matched itself.
+/// * `positional(arg) => { ... }` runs for any argument that no other arm
+///   matches, including unknown options. Without it, those arguments are
+///   ignored.
+/// * `rest(raw) => { ... }` runs when a standalone `--` is found, and then no
+///   more arguments are matched. `raw` is the rest of the command line after
+///   the `--` and the whitespace following it, exactly as it was written, as a
+///   `&'static [u16]`. Without this arm, `--` is matched like any other
+///   argument.
+///
+/// Names are compared with the argument after it's parsed, so a quoted
+/// `"--help"` matches `--help`. Name and value arms are tried in the order
+/// they're written. The macro is a loop, so `break` and `continue` in an arm
+/// apply to it.
+///
+/// ```
+/// let mut verbose = false;
+/// let mut out = None;
+/// winarg::arg_match!(winarg::args_native().skip(1), {
+///     "--help" | "-h" | "/?" => {
+///         return println!("usage: app [--verbose] [--out FILE] INPUT... [-- ARGS]");
+///     }
+///     "--verbose" | "-v" => { verbose = true }
+///     "--out" | "-o" = value => {
+///         match value {
+///             Some(value) => out = Some(value.scalars().collect::<String>()),
+///             None => return eprintln!("--out needs a file name"),
+///         }
+///     }
+///     positional(arg) => {
+///         if arg.utf16_units().next() == Some(u16::from(b'-')) {
+///             return eprintln!("unknown option {}", arg);
+///         }
+///         println!("reading {}", arg);
+///     }
+///     rest(raw) => { println!("passing on {}", String::from_utf16_lossy(raw)) }
+/// });
+/// ```
+///
+/// An arm that isn't one of these, or a `positional` or `rest` arm given more
+/// than once, is a compile error.
+#[macro_export]
+macro_rules! arg_match {
+	// Sort the arms into name and value arms, which are kept in order, and the
+	// `positional` and `rest` arms.
+	(@parse $args:tt $checks:tt $pos:tt $rest:tt , $($tail:tt)*) => {
+		$crate::arg_match!(@parse $args $checks $pos $rest $($tail)*)
+	};
+	(@parse $args:tt $checks:tt [] $rest:tt positional ($arg:ident) => $body:block $($tail:tt)*) => {
+		$crate::arg_match!(@parse $args $checks [$arg $body] $rest $($tail)*)
+	};
+	(@parse $args:tt $checks:tt $pos:tt $rest:tt positional $($tail:tt)*) => {
+		::core::compile_error!("`positional` must be written as `positional(arg) => { ... }`, and only once")
+	};
+	(@parse $args:tt $checks:tt $pos:tt [] rest ($raw:ident) => $body:block $($tail:tt)*) => {
+		$crate::arg_match!(@parse $args $checks $pos [$raw $body] $($tail)*)
+	};
+	(@parse $args:tt $checks:tt $pos:tt $rest:tt rest $($tail:tt)*) => {
+		::core::compile_error!("`rest` must be written as `rest(raw) => { ... }`, and only once")
+	};
+	(@parse $args:tt [$($checks:tt)*] $pos:tt $rest:tt $($name:literal)|+ => $body:block $($tail:tt)*) => {
+		$crate::arg_match!(@parse $args [$($checks)* [[$($name),+] $body]] $pos $rest $($tail)*)
+	};
+	(@parse $args:tt [$($checks:tt)*] $pos:tt $rest:tt $($name:literal)|+ = $value:ident => $body:block $($tail:tt)*) => {
+		$crate::arg_match!(@parse $args [$($checks)* [[$($name),+] $value $body]] $pos $rest $($tail)*)
+	};
+	(@parse $args:tt $checks:tt $pos:tt $rest:tt $bad:tt $($tail:tt)*) => {
+		::core::compile_error!(::core::concat!(
+			"expected an arm such as `\"--name\" => { ... }`, `\"--name\" = value => { ... }`, ",
+			"`positional(arg) => { ... }` or `rest(raw) => { ... }`, found `",
+			::core::stringify!($bad $($tail)*),
+			"`"
+		))
+	};
+
+	// All the arms have been sorted, so write the loop.
+	(@parse [$args:expr] [$($checks:tt)*] $pos:tt $rest:tt) => {{
+		let mut args = ::core::iter::IntoIterator::into_iter($args);
+		while let ::core::option::Option::Some(arg) = ::core::iter::Iterator::next(&mut args) {
+			let arg: $crate::Argument = arg;
+			$crate::arg_match!(@rest arg args $rest);
+			$crate::arg_match!(@checks arg args $pos $($checks)*);
+		}
+	}};
+
+	(@rest $arg:ident $args:ident []) => {};
+	(@rest $arg:ident $args:ident [$raw:ident $body:block]) => {
+		if $arg == "--" {
+			let $raw: &'static [u16] = match ::core::iter::Iterator::next(&mut $args) {
+				::core::option::Option::Some(next) => next.raw_arg(),
+				::core::option::Option::None => &[],
+			};
+			$body;
+			break;
+		}
+	};
+
+	(@checks $arg:ident $args:ident $pos:tt [[$($name:literal),+] $body:block] $($checks:tt)*) => {
+		if $($arg == $name)||+ {
+			$body;
+		} else {
+			$crate::arg_match!(@checks $arg $args $pos $($checks)*);
+		}
+	};
+	(@checks $arg:ident $args:ident $pos:tt [[$($name:literal),+] $value:ident $body:block] $($checks:tt)*) => {
+		if let ::core::option::Option::Some($value) = $crate::__arg_match_value(&$arg, &[$($name),+], &mut $args) {
+			$body;
+		} else {
+			$crate::arg_match!(@checks $arg $args $pos $($checks)*);
+		}
+	};
+	(@checks $arg:ident $args:ident []) => {
+		let _ = $arg;
+	};
+	(@checks $arg:ident $args:ident [$pos:ident $body:block]) => {
+		let $pos = $arg;
+		$body;
+	};
+
+	($args:expr, { $($arms:tt)* }) => {
+		$crate::arg_match!(@parse [$args] [] [] [] $($arms)*)
+	};
+}
+
+// Used by `arg_match!` for value arms. Returns `None` if `arg` isn't one of
+// `names`, otherwise its value, taking the next argument if it's separate.
+#[doc(hidden)]
+pub fn __arg_match_value<I: Iterator<Item = Argument>>(
+	arg: &Argument,
+	names: &[&str],
+	args: &mut I,
+) -> Option<Option<FlagValue>> {
+	let options = MatchOptions::default();
+	for name in names {
+		if options.matches(arg, name) {
+			return Some(args.next().map(|arg| FlagValue { arg, start: 0 }));
+		} else if let Some(start) = options.value_start(arg, name, EQUALS) {
+			let arg = arg.clone();
+			return Some(Some(FlagValue { arg, start }));
+		}
+	}
+	None
+}
+
+/// Arms that don't compile.
+///
+/// ```compile_fail
+/// winarg::arg_match!(winarg::args_native(), {
+///     positional(a) => {}
+///     positional(b) => {}
+/// });
+/// ```
+///
+/// ```compile_fail
+/// winarg::arg_match!(winarg::args_native(), {
+///     rest(a) => {}
+///     rest(b) => {}
+/// });
+/// ```
+///
+/// ```compile_fail
+/// // The body must be a block.
+/// winarg::arg_match!(winarg::args_native(), { "--a" => () });
+/// ```
+///
+/// ```compile_fail
+/// // Names must be string literals.
+/// let name = "--a";
+/// winarg::arg_match!(winarg::args_native(), { name => {} });
+/// ```
+///
+/// ```compile_fail
+/// winarg::arg_match!(winarg::args_native(), { 1 => {} });
+/// ```
+///
+/// ```compile_fail
+/// // A value needs a name to bind it to.
+/// winarg::arg_match!(winarg::args_native(), { "--out" = => {} });
+/// ```
+///
+/// ```compile_fail
+/// winarg::arg_match!(winarg::args_native(), { positional => {} });
+/// ```
+///
+/// ```compile_fail
+/// winarg::arg_match!(winarg::args_native(), { _ => {} });
+/// ```
+///
+/// ```compile_fail
+/// // Only `Argument`s can be matched.
+/// winarg::arg_match!(["--a"], { "--a" => {} });
+/// ```
+///
+/// The bindings are only in scope in their own arm.
+///
+/// ```compile_fail
+/// winarg::arg_match!(winarg::args_native(), {
+///     "--out" = value => {}
+///     positional(arg) => { drop(value) }
+/// });
+/// ```
+#[cfg(doctest)]
+pub struct CompileFail;
@@ -0,0 +1,91 @@
+//! Parsing the arguments into a buffer provided by the caller.
+
+use crate::{arg_spans_of, command_line, null_separated_list_wide};
+use core::iter::FusedIterator;
+
+/// Parse every argument into one buffer, without allocating.
+///
+/// The length needed is worked out first and passed to `get_buffer`, which
+/// returns a buffer of at least that many code units. It could come from an
+/// arena, a bump allocator or a static array. The arguments are written to the
+/// start of the buffer separated by nulls, the same as
+/// [`null_separated_list_wide`], and the returned iterator gives each argument
+/// as a slice of the buffer. Nothing else is written to it.
+///
+/// `None` is returned if the buffer is too short, in which case nothing has
+/// been written to it.
+///
+/// ```
+/// // Keep the arguments for the rest of the program, using one allocation.
+/// let args: Vec<&'static [u16]> =
+///     winarg::parse_into_buffer(|len| Box::leak(vec![0; len].into_boxed_slice()))
+///         .unwrap()
+///         .collect();
+/// for arg in args {
+///     println!("{}", String::from_utf16_lossy(arg));
+/// }
+/// ```
+pub fn parse_into_buffer<'a, F>(get_buffer: F) -> Option<BufferArgs<'a>>
+where
+	F: FnOnce(usize) -> &'a mut [u16],
+{
+	let mut spans = arg_spans_of(command_line().as_slice());
+	let (mut len, mut count) = (0, 0_usize);
+	while let Some((_, arg_len)) = spans.next_with_len() {
+		len += arg_len;
+		count += 1;
+	}
+	// The arguments are separated by nulls.
+	let len = len + count.saturating_sub(1);
+	let buffer = get_buffer(len).get_mut(..len)?;
+	for (slot, unit) in buffer.iter_mut().zip(null_separated_list_wide()) {
+		*slot = unit;
+	}
+	Some(BufferArgs {
+		rest: buffer,
+		count,
+	})
+}
+
+/// An iterator over the arguments written by [`parse_into_buffer`].
+///
+/// Each argument is a slice of the caller's buffer.
+#[derive(Clone, Debug)]
+pub struct BufferArgs<'a> {
+	rest: &'a [u16],
+	count: usize,
+}
+impl<'a> BufferArgs<'a> {
+	/// Every argument, separated by nulls.
+	///
+	/// This is the part of the buffer that was written to. It's empty if
+	/// there are no arguments left.
+	pub fn as_slice(&self) -> &'a [u16] {
+		self.rest
+	}
+}
+impl<'a> Iterator for BufferArgs<'a> {
+	type Item = &'a [u16];
+	fn next(&mut self) -> Option<Self::Item> {
+		// An empty buffer can hold one empty argument or none at all so the
+		// number left is kept separately.
+		self.count = self.count.checked_sub(1)?;
+		let rest = self.rest;
+		match rest.iter().position(|&u| u == 0) {
+			Some(end) => {
+				let (arg, after) = rest.split_at(end);
+				self.rest = after.get(1..).unwrap_or_default();
+				Some(arg)
+			}
+			None => {
+				self.rest = &[];
+				Some(rest)
+			}
+		}
+	}
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.count, Some(self.count))
+	}
+}
+impl ExactSizeIterator for BufferArgs<'_> {}
+impl FusedIterator for BufferArgs<'_> {}
@@ -0,0 +1,452 @@
+//! Building a command line one argument at a time.
+
+use crate::quote::{quote_one, representable, QuotingRules, WhyNot};
+use crate::{scalars, validate, Diagnostic, DiagnosticKind, QUOTE, SPACE};
+#[cfg(all(windows, feature = "std"))]
+use crate::{verbatim::strip_verbatim_prefix, FORWARD_SLASH, SLASH};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt::{self, Write};
+#[cfg(all(windows, feature = "std"))]
+use std::ffi::OsString;
+#[cfg(all(windows, feature = "std"))]
+use std::os::windows::ffi::OsStrExt;
+#[cfg(all(windows, feature = "std"))]
+use std::os::windows::ffi::OsStringExt;
+#[cfg(all(windows, feature = "std"))]
+use std::path::Path;
+#[cfg(all(windows, feature = "windows"))]
+use windows_strings::HSTRING;
+
+/// Builds a command line one argument at a time.
+///
+/// Each argument is quoted as it's added, in the same way as
+/// [`WideBuf::join`](crate::WideBuf::join), so that it's parsed back
+/// unchanged. It's checked first in the same way as
+/// [`is_representable`](crate::is_representable), so a program name with a
+/// quote or an argument with a null is an error instead of a command line
+/// that's read back differently.
+///
+/// ```
+/// use winarg::CommandLineBuilder;
+///
+/// let units = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+/// let mut builder = CommandLineBuilder::new(units("app.exe"))?;
+/// builder.arg(units("a b"))?.arg(units(r#"say "hi""#))?;
+/// assert_eq!(
+///     String::from_utf16_lossy(builder.as_slice()),
+///     r#"app.exe "a b" "say \"hi\"""#
+/// );
+/// let command_line = builder.build_for_create_process();
+/// assert_eq!(command_line.last(), Some(&0));
+/// # Ok::<(), winarg::WhyNot>(())
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CommandLineBuilder {
+	pub(crate) units: Vec<u16>,
+	// The length of the quoted program name.
+	program_len: usize,
+	// The number of arguments after the program name.
+	args: usize,
+}
+impl CommandLineBuilder {
+	/// Start a command line with the program name.
+	///
+	/// The program name is written using its own rules, where nothing can be
+	/// escaped. It's an error if it contains a quote, which would end the
+	/// program name early, or a null.
+	///
+	/// ```
+	/// use winarg::CommandLineBuilder;
+	///
+	/// let program: Vec<u16> = r#"a" b"#.encode_utf16().collect();
+	/// let error = CommandLineBuilder::new(program).unwrap_err();
+	/// assert_eq!((error.index(), error.position()), (0, 1));
+	/// ```
+	pub fn new<S: AsRef<[u16]>>(program: S) -> Result<Self, WhyNot> {
+		let program = program.as_ref();
+		representable(program, 0, QuotingRules::Modern)?;
+		let mut units = Vec::new();
+		quote_one(program, true, |u| units.push(u));
+		Ok(Self {
+			program_len: units.len(),
+			units,
+			args: 0,
+		})
+	}
+
+	/// Add an argument.
+	///
+	/// It's an error if the argument contains a null, which would end the
+	/// command line. Nothing is added in that case. The error's
+	/// [`index`](WhyNot::index) counts the program name and each argument
+	/// added before this one.
+	pub fn arg<S: AsRef<[u16]>>(&mut self, arg: S) -> Result<&mut Self, WhyNot> {
+		let arg = arg.as_ref();
+		representable(arg, self.args + 1, QuotingRules::Modern)?;
+		self.units.push(SPACE);
+		quote_one(arg, false, |u| self.units.push(u));
+		self.args += 1;
+		Ok(self)
+	}
+
+	/// Add text to the command line as it is, after checking it.
+	///
+	/// This is for text that's already quoted, such as a whole command line
+	/// for a child to pass on. It's checked with [`validate`](crate::validate)
+	/// as part of the command line. If it contains a null or leaves a quote
+	/// open, nothing is added and the error says where in `raw` the problem
+	/// is. An open quote would otherwise be closed by the next quoted
+	/// argument, joining them together.
+	///
+	/// Otherwise `raw` is added, counting as one argument, and any other
+	/// diagnostics that start in it are returned as warnings. Their spans are indexes into the whole command
+	/// line. As with any argument, `raw` is separated from what comes before
+	/// and after it by a space, so backslashes at its end can't escape a quote
+	/// that starts the next argument.
+	///
+	/// ```
+	/// use winarg::{CommandLineBuilder, DiagnosticKind};
+	///
+	/// let units = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+	/// let mut builder = CommandLineBuilder::new(units("cmd.exe")).unwrap();
+	/// builder.arg(units("/c")).unwrap();
+	/// let warnings = builder
+	///     .push_verbatim_checked(&units(r#""app.exe "a b" c""#))
+	///     .unwrap();
+	/// assert!(warnings.is_empty());
+	///
+	/// let error = builder
+	///     .push_verbatim_checked(&units(r#""C:\My Files\""#))
+	///     .unwrap_err();
+	/// assert_eq!(error.kind(), DiagnosticKind::UnterminatedQuote);
+	/// assert_eq!(error.position(), 0);
+	/// ```
+	pub fn push_verbatim_checked(&mut self, raw: &[u16]) -> Result<Vec<Diagnostic>, VerbatimError> {
+		if let Some(position) = raw.iter().position(|&u| u == 0) {
+			return Err(VerbatimError {
+				kind: DiagnosticKind::EmbeddedNul,
+				position,
+			});
+		}
+		let len = self.units.len();
+		self.units.push(SPACE);
+		self.units.extend_from_slice(raw);
+		// Everything before `raw` was added with its quotes closed so only
+		// diagnostics that start in `raw` are new.
+		let start = len + 1;
+		let mut warnings = Vec::new();
+		for diagnostic in validate(&self.units) {
+			if diagnostic.span.start < start {
+				continue;
+			}
+			if diagnostic.kind == DiagnosticKind::UnterminatedQuote {
+				self.units.truncate(len);
+				return Err(VerbatimError {
+					kind: diagnostic.kind,
+					position: diagnostic.span.start - start,
+				});
+			}
+			warnings.push(diagnostic);
+		}
+		self.args += 1;
+		Ok(warnings)
+	}
+
+	/// Add each of the arguments in turn.
+	///
+	/// As with [`arg`](Self::arg), it's an error if an argument contains a
+	/// null. None of the arguments are added in that case.
+	pub fn args<I>(&mut self, args: I) -> Result<&mut Self, WhyNot>
+	where
+		I: IntoIterator,
+		I::Item: AsRef<[u16]>,
+	{
+		let (len, count) = (self.units.len(), self.args);
+		for arg in args {
+			if let Err(e) = self.arg(arg) {
+				self.units.truncate(len);
+				self.args = count;
+				return Err(e);
+			}
+		}
+		Ok(self)
+	}
+
+	/// Add a path, quoting it so that it's parsed back unchanged.
+	///
+	/// This is the same as [`arg_path_with`](Self::arg_path_with) using the
+	/// default options, so the path is written as it is.
+	///
+	/// ```
+	/// use std::path::Path;
+	/// use winarg::CommandLineBuilder;
+	///
+	/// let mut builder = CommandLineBuilder::new("app.exe".encode_utf16().collect::<Vec<u16>>())?;
+	/// builder.arg_path(Path::new(r"\\server\share\file name.txt"))?;
+	/// assert_eq!(
+	///     String::from_utf16_lossy(builder.as_slice()),
+	///     r#"app.exe "\\server\share\file name.txt""#
+	/// );
+	/// # Ok::<(), winarg::WhyNot>(())
+	/// ```
+	#[cfg(all(windows, feature = "std"))]
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	pub fn arg_path(&mut self, path: &Path) -> Result<&mut Self, WhyNot> {
+		self.arg_path_with(path, PathOptions::default())
+	}
+
+	/// Add a path, changing it as set by `options` first.
+	///
+	/// Backslashes at the start of a UNC path, such as `\\server\share`, are
+	/// never changed. Nor are forward slashes. As with any argument, the
+	/// backslashes at the end of a quoted path are doubled so they don't
+	/// escape the closing quote, and it's an error if the path contains a
+	/// null.
+	///
+	/// ```
+	/// use std::path::Path;
+	/// use winarg::{CommandLineBuilder, PathOptions};
+	///
+	/// let options = PathOptions {
+	///     strip_verbatim_prefix: true,
+	///     ensure_trailing_separator: true,
+	/// };
+	/// let mut builder = CommandLineBuilder::new("app.exe".encode_utf16().collect::<Vec<u16>>())?;
+	/// builder.arg_path_with(Path::new(r"\\?\C:\My Files"), options)?;
+	/// assert_eq!(
+	///     String::from_utf16_lossy(builder.as_slice()),
+	///     r#"app.exe "C:\My Files\\""#
+	/// );
+	/// # Ok::<(), winarg::WhyNot>(())
+	/// ```
+	#[cfg(all(windows, feature = "std"))]
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	pub fn arg_path_with(
+		&mut self,
+		path: &Path,
+		options: PathOptions,
+	) -> Result<&mut Self, WhyNot> {
+		let mut units: Vec<u16> = path.as_os_str().encode_wide().collect();
+		if options.strip_verbatim_prefix {
+			strip_verbatim_prefix(&mut units);
+		}
+		if options.ensure_trailing_separator
+			&& !matches!(units.last(), Some(&SLASH) | Some(&FORWARD_SLASH))
+		{
+			units.push(SLASH);
+		}
+		self.arg(units)
+	}
+
+	/// The command line so far, without a null terminator.
+	pub fn as_slice(&self) -> &[u16] {
+		&self.units
+	}
+
+	/// The length of the command line so far, in UTF-16 code units.
+	///
+	/// This doesn't include a null terminator so it can be compared to
+	/// [`MAX_COMMAND_LINE`](crate::MAX_COMMAND_LINE).
+	#[allow(clippy::len_without_is_empty)]
+	pub fn len(&self) -> usize {
+		self.units.len()
+	}
+
+	/// Remove the arguments, leaving only the program name.
+	///
+	/// The builder keeps its buffer so building another command line for the
+	/// same program doesn't allocate unless it's longer than any before.
+	///
+	/// ```
+	/// use winarg::CommandLineBuilder;
+	///
+	/// let units = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+	/// let mut builder = CommandLineBuilder::new(units("app.exe")).unwrap();
+	/// let mut command_line = Vec::new();
+	/// for file in ["a.txt", "b c.txt"] {
+	///     builder.reset();
+	///     builder.arg(units(file)).unwrap();
+	///     builder.build_into(&mut command_line);
+	///     // Pass `command_line` to `CreateProcessW`.
+	/// }
+	/// assert_eq!(String::from_utf16_lossy(&command_line), "app.exe \"b c.txt\"\0");
+	/// ```
+	pub fn reset(&mut self) -> &mut Self {
+		self.units.truncate(self.program_len);
+		self.args = 0;
+		self
+	}
+
+	/// A null terminated copy of the command line.
+	///
+	/// `CreateProcessW` may write to the command line it's given so it needs
+	/// its own buffer.
+	pub fn build_for_create_process(&self) -> Vec<u16> {
+		let mut units = Vec::new();
+		self.build_into(&mut units);
+		units
+	}
+
+	/// A null terminated copy of the command line, without any spare
+	/// capacity.
+	pub fn build_boxed(&self) -> Box<[u16]> {
+		self.build_for_create_process().into_boxed_slice()
+	}
+
+	/// Replace the contents of `buffer` with a null terminated copy of the
+	/// command line.
+	///
+	/// This reserves the space needed all at once so reusing a buffer only
+	/// allocates when the command line is longer than it's been before.
+	pub fn build_into(&self, buffer: &mut Vec<u16>) {
+		buffer.clear();
+		buffer.reserve_exact(self.units.len() + 1);
+		buffer.extend_from_slice(&self.units);
+		buffer.push(0);
+	}
+
+	/// The command line as an `OsString`, without a null terminator.
+	///
+	/// This is lossless so it can be logged, stored or passed on to
+	/// `CommandExt::raw_arg`.
+	///
+	/// ```
+	/// use winarg::CommandLineBuilder;
+	///
+	/// let mut builder = CommandLineBuilder::new("app.exe".encode_utf16().collect::<Vec<u16>>())?;
+	/// builder.arg("a b".encode_utf16().collect::<Vec<u16>>())?;
+	/// assert_eq!(builder.build_os_string(), r#"app.exe "a b""#);
+	/// # Ok::<(), winarg::WhyNot>(())
+	/// ```
+	#[cfg(all(windows, feature = "std"))]
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	pub fn build_os_string(&self) -> OsString {
+		OsString::from_wide(&self.units)
+	}
+
+	/// The command line as an `HSTRING`, for passing to functions from the
+	/// `windows` crate that read a command line.
+	///
+	/// An `HSTRING` can't be modified, so use
+	/// [`build_for_create_process`](Self::build_for_create_process) for
+	/// `CreateProcessW`.
+	///
+	/// ```
+	/// use windows::Win32::Foundation::{LocalFree, HLOCAL};
+	/// use windows::Win32::UI::Shell::CommandLineToArgvW;
+	/// use winarg::CommandLineBuilder;
+	///
+	/// let mut builder = CommandLineBuilder::new("app.exe".encode_utf16().collect::<Vec<u16>>()).unwrap();
+	/// builder.arg("a b".encode_utf16().collect::<Vec<u16>>()).unwrap();
+	/// let command_line = builder.build_hstring();
+	/// assert_eq!(command_line, r#"app.exe "a b""#);
+	///
+	/// let mut count = 0;
+	/// // SAFETY: The `HSTRING` is null terminated and outlives the call. The
+	/// // array is freed with `LocalFree` as `CommandLineToArgvW` requires.
+	/// unsafe {
+	///     let argv = CommandLineToArgvW(&command_line, &mut count);
+	///     assert!(!argv.is_null());
+	///     assert_eq!((*argv.add(1)).to_string().unwrap(), "a b");
+	///     LocalFree(Some(HLOCAL(argv.cast())));
+	/// }
+	/// assert_eq!(count, 2);
+	/// ```
+	#[cfg(all(windows, feature = "windows"))]
+	#[cfg_attr(docsrs, doc(cfg(feature = "windows")))]
+	pub fn build_hstring(&self) -> HSTRING {
+		HSTRING::from_wide(&self.units)
+	}
+
+	/// The command line as a `String`, for display.
+	///
+	/// Isolated surrogates are replaced with `�` so this can't always be used
+	/// to run the program. The command line can also be displayed without
+	/// allocating a `String` by using the builder's `Display` implementation.
+	///
+	/// ```
+	/// use winarg::CommandLineBuilder;
+	///
+	/// let mut builder = CommandLineBuilder::new("app.exe".encode_utf16().collect::<Vec<u16>>())?;
+	/// builder.arg(vec![0xD800])?;
+	/// assert_eq!(builder.build_string_lossy(), "app.exe �");
+	/// assert_eq!(format!("spawning: {}", builder), "spawning: app.exe �");
+	/// # Ok::<(), winarg::WhyNot>(())
+	/// ```
+	pub fn build_string_lossy(&self) -> String {
+		scalars(self.units.iter().copied()).collect()
+	}
+}
+/// Isolated surrogates are replaced with `�`. See
+/// [`CommandLineBuilder::build_string_lossy`].
+impl fmt::Display for CommandLineBuilder {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		scalars(self.units.iter().copied()).try_for_each(|c| f.write_char(c))
+	}
+}
+
+/// The reason [`CommandLineBuilder::push_verbatim_checked`] refused to add
+/// text.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerbatimError {
+	kind: DiagnosticKind,
+	position: usize,
+}
+impl VerbatimError {
+	/// What was wrong: either [`DiagnosticKind::EmbeddedNul`] or
+	/// [`DiagnosticKind::UnterminatedQuote`].
+	pub fn kind(self) -> DiagnosticKind {
+		self.kind
+	}
+	/// The position of the null or the quote that's never closed, within the
+	/// text.
+	pub fn position(self) -> usize {
+		self.position
+	}
+	/// The code unit that's the problem.
+	pub fn unit(self) -> u16 {
+		match self.kind {
+			DiagnosticKind::EmbeddedNul => 0,
+			_ => QUOTE,
+		}
+	}
+}
+impl fmt::Display for VerbatimError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let reason = match self.kind {
+			DiagnosticKind::EmbeddedNul => "contains a null",
+			_ => "has a quote that's never closed",
+		};
+		write!(
+			f,
+			"the verbatim text {} at position {}",
+			reason, self.position
+		)
+	}
+}
+#[cfg(feature = "std")]
+impl std::error::Error for VerbatimError {}
+
+/// How [`CommandLineBuilder::arg_path_with`] changes a path before adding it.
+///
+/// The default doesn't change the path.
+#[cfg(all(windows, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PathOptions {
+	/// Remove the `\\?\` prefix from a verbatim path, which many programs
+	/// don't understand. `\\?\C:\dir` becomes `C:\dir` and
+	/// `\\?\UNC\server\share` becomes `\\server\share`. Other verbatim paths,
+	/// such as `\\?\Volume{...}\`, are left alone because they have no other
+	/// form.
+	///
+	/// Without the prefix, a path longer than `MAX_PATH` or with components
+	/// that Windows normalizes, such as `..` or a trailing `.`, may refer to a
+	/// different file.
+	pub strip_verbatim_prefix: bool,
+	/// Add a `\` to the end of the path if it doesn't already end with a `\`
+	/// or `/`. Some programs need this to treat the path as a directory.
+	pub ensure_trailing_separator: bool,
+}
@@ -0,0 +1,150 @@
+//! Parsing a command line stored as UTF-16 bytes.
+
+use crate::{cow_args_of, debug_units, CommandLine, CowArgs};
+use alloc::{borrow::Cow, vec::Vec};
+use core::fmt;
+
+/// Parse a command line stored as little endian UTF-16 bytes, such as one
+/// read from a file, a minidump or a pipe.
+///
+/// This is [`parse_utf16_bytes`] with [`Endianness::Little`], the byte order
+/// Windows uses.
+///
+/// ```
+/// let bytes = b"a\0p\0p\0 \0\"\0b\0 \0c\0\"\0";
+/// let cmdline = winarg::parse_utf16le_bytes(bytes).unwrap();
+/// for arg in cmdline.args() {
+///     println!("{}", String::from_utf16_lossy(&arg));
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn parse_utf16le_bytes(bytes: &[u8]) -> Result<DecodedCommandLine<'_>, OddLengthError> {
+	parse_utf16_bytes(bytes, Endianness::Little)
+}
+
+/// Parse a command line stored as UTF-16 bytes in the given byte order.
+///
+/// A byte order mark for `endianness` at the start is skipped. The bytes can
+/// be at any alignment. They're used in place if they're suitably aligned for
+/// a `u16` and `endianness` is the byte order of the computer this is running
+/// on, and otherwise copied once. The command line ends at the first null, if
+/// any.
+///
+/// An error is returned if there are an odd number of bytes, including the
+/// byte order mark.
+///
+/// ```
+/// use winarg::Endianness;
+///
+/// let bytes = b"\xFE\xFF\0a\0p\0p\0 \0b";
+/// let cmdline = winarg::parse_utf16_bytes(bytes, Endianness::Big).unwrap();
+/// assert_eq!(String::from_utf16_lossy(cmdline.as_slice()), "app b");
+/// let error = winarg::parse_utf16_bytes(&bytes[1..], Endianness::Big).unwrap_err();
+/// assert_eq!(error.len(), 11);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn parse_utf16_bytes(
+	bytes: &[u8],
+	endianness: Endianness,
+) -> Result<DecodedCommandLine<'_>, OddLengthError> {
+	let contents = match (endianness, bytes) {
+		(Endianness::Little, [0xFF, 0xFE, rest @ ..]) => rest,
+		(Endianness::Big, [0xFE, 0xFF, rest @ ..]) => rest,
+		_ => bytes,
+	};
+	if contents.len() % 2 != 0 {
+		return Err(OddLengthError { len: bytes.len() });
+	}
+	// SAFETY: Any two bytes are a valid `u16`.
+	let (before, units, after) = unsafe { contents.align_to::<u16>() };
+	let units = if before.is_empty() && after.is_empty() && endianness == Endianness::NATIVE {
+		let len = units.iter().position(|&u| u == 0).unwrap_or(units.len());
+		Cow::Borrowed(&units[..len])
+	} else {
+		let from_bytes = match endianness {
+			Endianness::Little => u16::from_le_bytes,
+			Endianness::Big => u16::from_be_bytes,
+		};
+		Cow::Owned(
+			contents
+				.chunks_exact(2)
+				.map(|b| from_bytes([b[0], b[1]]))
+				.take_while(|&u| u != 0)
+				.collect::<Vec<u16>>(),
+		)
+	};
+	Ok(DecodedCommandLine { units })
+}
+
+/// The order of the bytes in each UTF-16 code unit.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Endianness {
+	/// The least significant byte comes first. This is what Windows uses.
+	#[default]
+	Little,
+	/// The most significant byte comes first.
+	Big,
+}
+impl Endianness {
+	#[cfg(target_endian = "little")]
+	const NATIVE: Self = Self::Little;
+	#[cfg(target_endian = "big")]
+	const NATIVE: Self = Self::Big;
+}
+
+/// A command line decoded from bytes by [`parse_utf16le_bytes`] or
+/// [`parse_utf16_bytes`].
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone)]
+pub struct DecodedCommandLine<'a> {
+	units: Cow<'a, [u16]>,
+}
+impl DecodedCommandLine<'_> {
+	/// The command line's code units, without the byte order mark and without
+	/// the first null and anything after it.
+	pub fn as_slice(&self) -> &[u16] {
+		&self.units
+	}
+
+	/// The arguments in the command line, borrowed from it where possible.
+	///
+	/// See [`cow_args_of`].
+	pub fn args(&self) -> CowArgs<'_> {
+		cow_args_of(&self.units)
+	}
+}
+impl fmt::Debug for DecodedCommandLine<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		debug_units(f, &self.units)
+	}
+}
+/// The code units are only copied if they're borrowed from the bytes.
+impl From<DecodedCommandLine<'_>> for CommandLine {
+	fn from(cmdline: DecodedCommandLine<'_>) -> Self {
+		Self::from_wide(cmdline.units.into_owned())
+	}
+}
+
+/// The error returned when UTF-16 bytes have an odd length.
+///
+/// Returned by [`parse_utf16le_bytes`] and [`parse_utf16_bytes`].
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OddLengthError {
+	len: usize,
+}
+impl OddLengthError {
+	/// The number of bytes, including any byte order mark.
+	#[allow(clippy::len_without_is_empty)]
+	pub fn len(self) -> usize {
+		self.len
+	}
+}
+impl fmt::Display for OddLengthError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "odd number of bytes ({})", self.len)
+	}
+}
+#[cfg(feature = "std")]
+impl std::error::Error for OddLengthError {}
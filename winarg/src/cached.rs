@@ -0,0 +1,259 @@
+//! Finding where the arguments are once and keeping them for later.
+//!
+//! The functions at the crate root read the command line again each time
+//! they're called. That's cheap, but code that asks the same questions often,
+//! such as checking for `--verbose` before every log message, can use this
+//! module to only find the arguments once. The first call finds where each
+//! argument starts and keeps that in a static. Later calls only parse the
+//! arguments they look at.
+//!
+//! Nothing is allocated so this works without `alloc`. The arguments are kept
+//! in an [`ArgCache`], which has a fixed capacity. The functions in this module
+//! use one that holds [`DEFAULT_CAPACITY`] arguments. A program that needs
+//! more can declare its own:
+//!
+//! ```
+//! use winarg::cached::ArgCache;
+//!
+//! static ARGS: ArgCache<256> = ArgCache::new();
+//!
+//! if ARGS.has_flag(&["-v", "--verbose"]) {
+//!     println!("verbose");
+//! }
+//! ```
+//!
+//! If the command line has more arguments than the capacity, none of them are
+//! kept and every call parses the command line again, as the functions at the
+//! crate root do. The results are the same either way.
+//!
+//! The command line is only read once, so overriding it with
+//! `testing::set_command_line` after that has no effect.
+//!
+//! This module is only available with the `cached` feature.
+
+use crate::{arg_spans_of, command_line, ArgSpans, Argument, MatchOptions, WideIter};
+use core::{
+	cell::UnsafeCell,
+	fmt, hint, slice,
+	sync::atomic::{AtomicU8, Ordering},
+};
+
+/// The number of arguments kept by the functions in this module.
+pub const DEFAULT_CAPACITY: usize = 64;
+
+static ARGS: ArgCache<DEFAULT_CAPACITY> = ArgCache::new();
+
+/// The arguments, starting with the program name.
+///
+/// This is the same as [`args_native`](crate::args_native) but only parses
+/// the command line once. See [`ArgCache::args`].
+///
+/// ```
+/// for arg in winarg::cached::args() {
+///     println!("{}", arg);
+/// }
+/// ```
+pub fn args() -> CachedArgs<'static> {
+	ARGS.args()
+}
+
+/// The first argument after the program name that's equal to any of `names`.
+///
+/// This is the same as [`winarg::find_flag`](crate::find_flag) but only
+/// parses the command line once, so it also stops at a standalone `--`. See
+/// [`ArgCache::find_flag`].
+///
+/// ```
+/// if let Some(flag) = winarg::cached::find_flag(&["-v", "--verbose"]) {
+///     println!("{} was passed", flag);
+/// }
+/// ```
+pub fn find_flag(names: &[&str]) -> Option<Argument> {
+	ARGS.find_flag(names)
+}
+
+/// Returns `true` if any argument after the program name, and before a
+/// standalone `--`, is equal to any of `names`.
+///
+/// This is the same as [`winarg::has_flag`](crate::has_flag) but only parses
+/// the command line once. See [`ArgCache::has_flag`].
+///
+/// ```
+/// if winarg::cached::has_flag(&["--verbose"]) {
+///     println!("verbose");
+/// }
+/// ```
+pub fn has_flag(names: &[&str]) -> bool {
+	ARGS.has_flag(names)
+}
+
+// The states of an `ArgCache`.
+const EMPTY: u8 = 0;
+const FILLING: u8 = 1;
+const FULL: u8 = 2;
+
+/// Where each argument of the command line is, found the first time it's
+/// needed and kept for `N` arguments.
+///
+/// This can be used in a `static`. The command line is read the first time
+/// any method is called and never again after that. If several threads call
+/// it at the same time, one of them reads the command line and the others
+/// wait for it to finish. No locks are used.
+///
+/// If the command line has more than `N` arguments they aren't kept and every
+/// method parses the command line again, so the results are always the same
+/// as the functions at the crate root. Use [`is_complete`](Self::is_complete)
+/// to check whether this happened. The program name counts as an argument.
+pub struct ArgCache<const N: usize> {
+	state: AtomicU8,
+	args: UnsafeCell<Starts<N>>,
+}
+// SAFETY: `args` is only written by the thread that changes `state` from
+// `EMPTY` to `FILLING`, and only read once `state` is `FULL`.
+unsafe impl<const N: usize> Sync for ArgCache<N> {}
+
+// Where each argument of `cmdline` starts. An argument ends where parsing it
+// stops, so that's all that's needed.
+struct Starts<const N: usize> {
+	cmdline: &'static [u16],
+	starts: [usize; N],
+	// More than `N` if they didn't fit.
+	len: usize,
+}
+
+impl<const N: usize> ArgCache<N> {
+	/// Creates an empty cache. Nothing is read until it's used.
+	pub const fn new() -> Self {
+		Self {
+			state: AtomicU8::new(EMPTY),
+			args: UnsafeCell::new(Starts {
+				cmdline: &[],
+				starts: [0; N],
+				len: 0,
+			}),
+		}
+	}
+
+	/// The arguments, starting with the program name.
+	///
+	/// These are the same as the arguments from
+	/// [`args_native`](crate::args_native).
+	pub fn args(&self) -> CachedArgs<'_> {
+		let args = self.get();
+		let inner = match args.starts.get(..args.len) {
+			Some(starts) => Inner::Cached(starts.iter()),
+			None => Inner::Parsed(arg_spans_of(args.cmdline)),
+		};
+		CachedArgs {
+			cmdline: args.cmdline,
+			inner,
+			is_arg0: true,
+		}
+	}
+
+	/// The first argument after the program name that's equal to any of
+	/// `names`.
+	///
+	/// The search stops at a standalone `--`. See
+	/// [`winarg::find_flag`](crate::find_flag) for how arguments are compared.
+	pub fn find_flag(&self, names: &[&str]) -> Option<Argument> {
+		let options = MatchOptions::default();
+		self.args()
+			.skip(1)
+			.take_while(|arg| *arg != "--")
+			.find(|arg| names.iter().any(|name| options.matches(arg, name)))
+	}
+
+	/// Returns `true` if any argument after the program name, and before a
+	/// standalone `--`, is equal to any of `names`.
+	pub fn has_flag(&self, names: &[&str]) -> bool {
+		self.find_flag(names).is_some()
+	}
+
+	/// Returns `false` if the command line has more than `N` arguments, so
+	/// they're parsed again each time they're used.
+	pub fn is_complete(&self) -> bool {
+		self.get().len <= N
+	}
+
+	fn get(&self) -> &Starts<N> {
+		loop {
+			match self
+				.state
+				.compare_exchange(EMPTY, FILLING, Ordering::Acquire, Ordering::Acquire)
+			{
+				Ok(_) => {
+					// SAFETY: Only this thread has access until `state` is `FULL`.
+					unsafe { (*self.args.get()).fill() };
+					self.state.store(FULL, Ordering::Release);
+					break;
+				}
+				Err(FULL) => break,
+				Err(_) => hint::spin_loop(),
+			}
+		}
+		// SAFETY: `state` is `FULL` so nothing writes to `args` again.
+		unsafe { &*self.args.get() }
+	}
+}
+impl<const N: usize> Default for ArgCache<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl<const N: usize> fmt::Debug for ArgCache<N> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ArgCache")
+			.field("capacity", &N)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<const N: usize> Starts<N> {
+	// Find the arguments. This can't panic so the cache is never left
+	// `FILLING`.
+	fn fill(&mut self) {
+		self.cmdline = command_line().as_slice();
+		for span in arg_spans_of(self.cmdline) {
+			match self.starts.get_mut(self.len) {
+				Some(start) => *start = span.start,
+				// There are more arguments than fit.
+				None => {
+					self.len = N + 1;
+					return;
+				}
+			}
+			self.len += 1;
+		}
+	}
+}
+
+/// An iterator over the arguments kept by an [`ArgCache`].
+///
+/// Created by [`args`] or [`ArgCache::args`].
+#[derive(Clone, Debug)]
+pub struct CachedArgs<'a> {
+	cmdline: &'static [u16],
+	inner: Inner<'a>,
+	is_arg0: bool,
+}
+#[derive(Clone, Debug)]
+enum Inner<'a> {
+	Cached(slice::Iter<'a, usize>),
+	Parsed(ArgSpans<'static>),
+}
+impl Iterator for CachedArgs<'_> {
+	type Item = Argument;
+	fn next(&mut self) -> Option<Argument> {
+		let start = match &mut self.inner {
+			Inner::Cached(starts) => *starts.next()?,
+			Inner::Parsed(spans) => spans.next()?.start,
+		};
+		let arg = Argument {
+			arg: WideIter::new(self.cmdline.get(start..).unwrap_or_default()),
+			is_arg0: self.is_arg0,
+		};
+		self.is_arg0 = false;
+		Some(arg)
+	}
+}
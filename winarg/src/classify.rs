@@ -0,0 +1,99 @@
+//! Telling flags apart from other arguments.
+
+use crate::{Argument, COLON, DASH, EQUALS, FORWARD_SLASH};
+
+impl Argument {
+	/// Whether the argument looks like a flag, a flag with a value, the `--`
+	/// that ends flags, or anything else.
+	///
+	/// `style` chooses which prefixes start a flag. See [`ClassifyStyle`].
+	///
+	/// A flag has a value if its name is followed by `=` or `:`, as in
+	/// `--out=file` or `/out:file`. A prefix on its own, such as `-` for
+	/// standard input, is positional, as is an empty argument. `--` is only
+	/// [`ArgClass::DoubleDash`] if `style.dash` is set.
+	///
+	/// This only looks at the argument itself. A `/` also starts MSYS and
+	/// Cygwin style paths, and `-` starts negative numbers, so they're
+	/// classified as flags if those prefixes are accepted.
+	///
+	/// ```
+	/// use winarg::{ArgClass, ClassifyStyle};
+	///
+	/// let style = ClassifyStyle {
+	///     dash: true,
+	///     slash: true,
+	/// };
+	/// let mut positional = Vec::new();
+	/// for arg in winarg::args_native().skip(1) {
+	///     match arg.classify(style) {
+	///         ArgClass::DoubleDash => break,
+	///         ArgClass::Positional => positional.push(arg),
+	///         _ => println!("flag {}", arg),
+	///     }
+	/// }
+	/// ```
+	pub fn classify(&self, style: ClassifyStyle) -> ArgClass {
+		let mut units = self.utf16_units();
+		match units.next() {
+			Some(FORWARD_SLASH) if style.slash => {}
+			Some(DASH) if style.dash => {
+				let mut rest = units.clone();
+				if rest.next() == Some(DASH) {
+					if rest.next().is_none() {
+						return ArgClass::DoubleDash;
+					}
+					units.next();
+				}
+			}
+			_ => return ArgClass::Positional,
+		}
+		match units.next() {
+			None => ArgClass::Positional,
+			Some(_) if units.any(|unit| unit == EQUALS || unit == COLON) => ArgClass::FlagWithValue,
+			Some(_) => ArgClass::Flag,
+		}
+	}
+}
+
+/// Which prefixes start a flag for [`Argument::classify`].
+///
+/// This is separate from [`FlagStyle`](crate::FlagStyle) because there are no
+/// names to match, only prefixes to look for.
+///
+/// ```
+/// use winarg::ClassifyStyle;
+///
+/// // Unix style flags. Paths such as `/usr/bin/x` aren't flags.
+/// let style = ClassifyStyle {
+///     dash: true,
+///     slash: false,
+/// };
+/// for arg in winarg::args_native().skip(1) {
+///     println!("{:?}", arg.classify(style));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClassifyStyle {
+	/// Whether flags can start with `-` or `--`. This is also needed for `--`
+	/// on its own to be [`ArgClass::DoubleDash`].
+	pub dash: bool,
+	/// Whether flags can start with `/`.
+	pub slash: bool,
+}
+
+/// What kind of argument [`Argument::classify`] found.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgClass {
+	/// A flag without a value, such as `-v`, `--verbose` or `/nologo`.
+	Flag,
+	/// A flag with `=` or `:` after its name, such as `--out=file` or
+	/// `/out:file`.
+	FlagWithValue,
+	/// Anything that isn't a flag.
+	Positional,
+	/// `--` on its own, which usually means the arguments after it aren't
+	/// flags.
+	DoubleDash,
+}
@@ -0,0 +1,89 @@
+//! Quoting a command line so it can be run by `cmd.exe /c`.
+
+use crate::{quote::quote_one, QUOTE, SPACE};
+use alloc::vec::Vec;
+
+const CARET: u16 = b'^' as _;
+
+/// Make the text that follows `/c` in a `cmd.exe` command line, so that `cmd`
+/// runs a program with exactly `inner_args`.
+///
+/// The first argument is the program name. Running a program through `cmd`
+/// needs two layers of quoting. The program reads its arguments with the C
+/// runtime's rules, but first `cmd` reads the whole line and treats
+/// characters such as `&`, `|`, `>` and `%` as its own. This quotes each
+/// argument for the C runtime, as [`quote_one`](crate::quote_one) does, then
+/// puts a `^` before every character that's special to `cmd`, including the
+/// quotes, so `cmd` passes them on unchanged. The program name is quoted with
+/// plain quotes instead, which `cmd` needs to find where it ends.
+///
+/// `cmd` removes the first and last quote of the text after `/c` unless it
+/// has exactly two quotes around the name of a program, which it then runs.
+/// That depends on which files exist, so when there's a program name the
+/// result has more than two quotes, and starts and ends with one, so they're
+/// always removed. With no arguments at all the result is just `""`, which
+/// `cmd` reduces to an empty command.
+///
+/// The result isn't null terminated. Add it to the `cmd` command line as it
+/// is, without quoting it again, such as with `CommandExt::raw_arg`:
+///
+/// ```no_run
+/// use std::os::windows::process::CommandExt;
+/// use std::process::Command;
+///
+/// let args = ["findstr.exe", "a & b", "100%", "file.txt"];
+/// let args: Vec<Vec<u16>> = args.iter().map(|s| s.encode_utf16().collect()).collect();
+/// let nested = String::from_utf16(&winarg::nest_for_cmd_c(&args)).unwrap();
+/// assert_eq!(nested, r#"""findstr.exe" ^"a ^& b^" 100^% file.txt""#);
+/// Command::new("cmd.exe").arg("/c").raw_arg(&nested).status()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// Some arguments can't be passed through `cmd`:
+///
+/// * A line break ends the command, and `cmd` removes carriage returns.
+/// * `%` in the program name may be expanded as an environment variable.
+///   Quotes stop `cmd` from reading `^` as an escape so there's no way to
+///   prevent this.
+/// * With delayed expansion on, such as with `cmd /v:on /c`, `!` is still
+///   special. Delayed expansion is off by default.
+///
+/// Use [`is_representable`](crate::is_representable) to check the arguments
+/// can be written to a command line at all.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn nest_for_cmd_c<I>(inner_args: I) -> Vec<u16>
+where
+	I: IntoIterator,
+	I::Item: AsRef<[u16]>,
+{
+	let mut nested = Vec::new();
+	nested.push(QUOTE);
+	for (index, arg) in inner_args.into_iter().enumerate() {
+		let arg = arg.as_ref();
+		if index == 0 {
+			// `cmd` ignores anything special in quotes, apart from `%`.
+			nested.push(QUOTE);
+			nested.extend_from_slice(arg);
+			nested.push(QUOTE);
+		} else {
+			nested.push(SPACE);
+			quote_one(arg, false, |unit| {
+				if is_special(unit) {
+					nested.push(CARET);
+				}
+				nested.push(unit);
+			});
+		}
+	}
+	nested.push(QUOTE);
+	nested
+}
+
+// The characters `cmd` treats specially outside of quotes.
+fn is_special(unit: u16) -> bool {
+	unit < 0x80
+		&& matches!(
+			unit as u8,
+			b'(' | b')' | b'%' | b'!' | b'^' | b'"' | b'<' | b'>' | b'&' | b'|'
+		)
+}
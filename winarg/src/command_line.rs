@@ -0,0 +1,182 @@
+//! An owned command line that can be parsed in the same ways as the current
+//! one.
+
+use crate::{
+	command_line, cow_args_of, debug_units, validate, CowArgs, Diagnostics, ParseArgs, Token,
+	WideIter,
+};
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::num::NonZeroU16;
+use core::ops::Deref;
+
+/// An owned, null terminated command line.
+///
+/// This holds a command line that came from somewhere other than the current
+/// process, such as a file, another process or a test, so its arguments can be
+/// parsed. The command line ends at the first null so there are no others
+/// before the terminator.
+///
+/// It derefs to the code units without the null terminator, so it can be
+/// passed to functions such as [`arg_spans_of`](crate::arg_spans_of) or
+/// [`analyze`](crate::analyze).
+///
+/// ```
+/// use winarg::CommandLine;
+///
+/// let cmdline = CommandLine::from_str(r#"app.exe "a b" c"#);
+/// let args: Vec<String> = cmdline
+///     .args()
+///     .map(|arg| String::from_utf16_lossy(&arg))
+///     .collect();
+/// assert_eq!(args, ["app.exe", "a b", "c"]);
+/// assert_eq!(cmdline.validate().count(), 0);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CommandLine {
+	units: Vec<u16>,
+}
+impl CommandLine {
+	/// Take ownership of a UTF-16 command line.
+	///
+	/// The command line ends at the first null, if any. Anything after it is
+	/// removed. The buffer is reused so it's only reallocated if a null
+	/// terminator needs to be added and there's no spare capacity.
+	///
+	/// ```
+	/// let units: Vec<u16> = "app.exe a\0b".encode_utf16().collect();
+	/// let cmdline = winarg::CommandLine::from_wide(units);
+	/// assert_eq!(String::from_utf16_lossy(&cmdline), "app.exe a");
+	/// ```
+	pub fn from_wide(mut units: Vec<u16>) -> Self {
+		let len = units.iter().position(|&u| u == 0).unwrap_or(units.len());
+		units.truncate(len);
+		units.push(0);
+		Self { units }
+	}
+
+	/// Copy a command line from a string.
+	///
+	/// As with [`from_wide`](Self::from_wide), it ends at the first null.
+	#[allow(clippy::should_implement_trait)]
+	pub fn from_str(cmdline: &str) -> Self {
+		Self::from_wide(cmdline.encode_utf16().collect())
+	}
+
+	/// Copy the command line of the current process.
+	///
+	/// ```
+	/// let cmdline = winarg::CommandLine::from_current_process();
+	/// assert_eq!(&cmdline[..], winarg::raw_command_line());
+	/// ```
+	pub fn from_current_process() -> Self {
+		Self::from_wide(command_line().as_slice().to_vec())
+	}
+
+	/// The arguments, starting with the program name, borrowed from the
+	/// command line where possible.
+	///
+	/// See [`cow_args_of`](crate::cow_args_of).
+	pub fn args(&self) -> CowArgs<'_> {
+		cow_args_of(self)
+	}
+
+	/// The code units of each argument, with [`Token::NextArg`] between
+	/// arguments, the same as [`Parser`](struct@crate::Parser) gives for the
+	/// current command line.
+	///
+	/// ```
+	/// use winarg::Token;
+	///
+	/// let cmdline = winarg::CommandLine::from_str(r#"app.exe "a b""#);
+	/// let args = cmdline.tokens().filter(|t| t.is_next_arg()).count();
+	/// assert_eq!(args, 1);
+	/// ```
+	pub fn tokens(&self) -> Tokens<'_> {
+		Tokens {
+			iter: ParseArgs::new(WideIter::new(self), true),
+		}
+	}
+
+	/// Look for anything in the command line that might not be read as
+	/// intended.
+	///
+	/// See [`validate`](crate::validate).
+	pub fn validate(&self) -> Diagnostics<'_> {
+		validate(self)
+	}
+
+	/// The code units, including the null terminator.
+	///
+	/// A pointer to this can be passed to Windows functions that read a
+	/// command line.
+	pub fn as_slice_with_nul(&self) -> &[u16] {
+		&self.units
+	}
+
+	/// Convert into a `Vec` that includes the null terminator.
+	pub fn into_vec_with_nul(self) -> Vec<u16> {
+		self.units
+	}
+}
+impl Deref for CommandLine {
+	type Target = [u16];
+	fn deref(&self) -> &[u16] {
+		self.units.split_last().map_or(&[], |(_, units)| units)
+	}
+}
+impl fmt::Debug for CommandLine {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		debug_units(f, self)
+	}
+}
+
+/// An iterator over the tokens of a [`CommandLine`].
+///
+/// Created by [`CommandLine::tokens`].
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Debug)]
+pub struct Tokens<'a> {
+	iter: ParseArgs<'a>,
+}
+impl Iterator for Tokens<'_> {
+	type Item = Token;
+	fn next(&mut self) -> Option<Token> {
+		if let Some(unit) = self.iter.next() {
+			// SAFETY: `ParseArgs` never returns zero as `WideIter` stops at the
+			// first null.
+			return Some(Token::Unit(unsafe { NonZeroU16::new_unchecked(unit) }));
+		}
+		self.iter.move_to_next_arg();
+		self.iter.cursor.peek().map(|_| Token::NextArg)
+	}
+}
+impl FusedIterator for Tokens<'_> {}
+
+/// Uses that don't compile.
+///
+/// The iterators borrow the command line, so it can't be dropped while
+/// they're in use.
+///
+/// ```compile_fail
+/// let cmdline = winarg::CommandLine::from_str("app.exe a");
+/// let args = cmdline.args();
+/// drop(cmdline);
+/// args.count();
+/// ```
+///
+/// ```compile_fail
+/// let tokens = winarg::CommandLine::from_str("app.exe a").tokens();
+/// tokens.count();
+/// ```
+///
+/// ```compile_fail
+/// let cmdline = winarg::CommandLine::from_str("app.exe a");
+/// let diagnostics = cmdline.validate();
+/// drop(cmdline);
+/// diagnostics.count();
+/// ```
+#[cfg(doctest)]
+pub struct CompileFail;
@@ -0,0 +1,116 @@
+//! The files given to a program by dropping them on it in Explorer.
+
+use crate::{ArgsNative, Argument, COLON, FORWARD_SLASH, SLASH};
+use alloc::vec::Vec;
+use core::iter::{FusedIterator, Skip};
+use std::os::windows::ffi::OsStringExt;
+use std::{ffi::OsString, path::PathBuf};
+
+/// The arguments after the program name, as paths.
+///
+/// When files are dropped on a program in Explorer, or it's opened through a
+/// file association, its arguments are the path of each file, quoted if they
+/// need to be. Each argument is converted losslessly, so long paths such as
+/// `\\?\C:\...` and UNC paths such as `\\server\share\file` are returned
+/// exactly as they were written.
+///
+/// Every argument is returned, whether or not it's a path. See
+/// [`dropped_files_checked`] to find arguments that aren't absolute paths.
+///
+/// ```
+/// for path in winarg::dropped_files() {
+///     println!("opening {}", path.display());
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn dropped_files() -> DroppedFiles {
+	DroppedFiles {
+		args: ArgsNative::from_env().skip(1),
+	}
+}
+
+/// The arguments after the program name, as paths if they're absolute.
+///
+/// This is the same as [`dropped_files`] but an argument that isn't an
+/// absolute path is returned as an error, so something other than a file,
+/// such as a stray `--flag`, can be reported or skipped.
+///
+/// An argument is an absolute path if it starts with a drive letter followed
+/// by `:\` or `:/`, or with two separators, as UNC paths and `\\?\` and `\\.\`
+/// paths do. These are the paths that don't depend on the current directory
+/// or drive. The argument is only checked, not opened, so the file may not
+/// exist.
+///
+/// ```
+/// for arg in winarg::dropped_files_checked() {
+///     match arg {
+///         Ok(path) => println!("opening {}", path.display()),
+///         Err(arg) => eprintln!("{} isn't a file", arg),
+///     }
+/// }
+///
+/// // Or skip anything that isn't a file.
+/// let paths: Vec<_> = winarg::dropped_files_checked().filter_map(Result::ok).collect();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn dropped_files_checked() -> DroppedFilesChecked {
+	DroppedFilesChecked {
+		args: ArgsNative::from_env().skip(1),
+	}
+}
+
+/// An iterator over the arguments after the program name, as paths.
+///
+/// Created by [`dropped_files`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Clone, Debug)]
+pub struct DroppedFiles {
+	args: Skip<ArgsNative>,
+}
+impl Iterator for DroppedFiles {
+	type Item = PathBuf;
+	fn next(&mut self) -> Option<PathBuf> {
+		self.args.next().map(|arg| to_path(&arg))
+	}
+}
+impl FusedIterator for DroppedFiles {}
+
+/// An iterator over the arguments after the program name, as paths if they're
+/// absolute.
+///
+/// Created by [`dropped_files_checked`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Clone, Debug)]
+pub struct DroppedFilesChecked {
+	args: Skip<ArgsNative>,
+}
+impl Iterator for DroppedFilesChecked {
+	type Item = Result<PathBuf, Argument>;
+	fn next(&mut self) -> Option<Self::Item> {
+		let arg = self.args.next()?;
+		Some(if is_absolute(&arg) {
+			Ok(to_path(&arg))
+		} else {
+			Err(arg)
+		})
+	}
+}
+impl FusedIterator for DroppedFilesChecked {}
+
+fn to_path(arg: &Argument) -> PathBuf {
+	let units: Vec<u16> = arg.utf16_units().collect();
+	OsString::from_wide(&units).into()
+}
+
+// Returns `true` for `C:\`, `C:/` and anything starting with two separators.
+fn is_absolute(arg: &Argument) -> bool {
+	let is_separator = |unit| unit == SLASH || unit == FORWARD_SLASH;
+	let mut units = arg.utf16_units();
+	match (units.next(), units.next(), units.next()) {
+		(Some(drive), Some(COLON), Some(sep)) => {
+			drive < 0x80 && (drive as u8).is_ascii_alphabetic() && is_separator(sep)
+		}
+		(Some(a), Some(b), _) => is_separator(a) && is_separator(b),
+		_ => false,
+	}
+}
@@ -0,0 +1,84 @@
+//! Comparing command lines by their arguments.
+
+use crate::{arg_spans_of, raw_command_line, unquote_one, CaseSensitivity};
+
+/// Whether two command lines have the same arguments.
+///
+/// The command lines are parsed and compared one argument at a time, so they
+/// can be quoted differently. `"a b" c` and `a" "b "c"` are equivalent, but
+/// `a b` and `"a b"` are not. Nothing is allocated and the comparison stops at
+/// the first argument that's different. Each command line ends at its first
+/// null, if any.
+///
+/// ```
+/// let a: Vec<u16> = r#""C:\app.exe" "--name=a b""#.encode_utf16().collect();
+/// let b: Vec<u16> = r#"C:\app.exe --name="a b""#.encode_utf16().collect();
+/// assert!(winarg::args_equivalent(&a, &b));
+/// ```
+pub fn args_equivalent(a: &[u16], b: &[u16]) -> bool {
+	args_equivalent_with(a, b, CaseSensitivity::Sensitive)
+}
+
+/// Like [`args_equivalent`] but compares the program names using
+/// `program_case`.
+///
+/// Windows finds programs the same way it finds any other file, so
+/// `C:\App.exe` and `c:\app.exe` run the same program. The other arguments
+/// are always compared exactly because what they mean is up to the program.
+///
+/// ```
+/// use winarg::CaseSensitivity;
+///
+/// let a: Vec<u16> = r"C:\App.exe /Run".encode_utf16().collect();
+/// let b: Vec<u16> = r"c:\app.exe /Run".encode_utf16().collect();
+/// assert!(!winarg::args_equivalent(&a, &b));
+/// assert!(winarg::args_equivalent_with(&a, &b, CaseSensitivity::AsciiInsensitive));
+/// ```
+pub fn args_equivalent_with(a: &[u16], b: &[u16], program_case: CaseSensitivity) -> bool {
+	let (mut spans_a, mut spans_b) = (arg_spans_of(a), arg_spans_of(b));
+	let mut is_program = true;
+	loop {
+		let (raw_a, raw_b) = match (spans_a.next(), spans_b.next()) {
+			(None, None) => return true,
+			(Some(span_a), Some(span_b)) => (
+				a.get(span_a).unwrap_or_default(),
+				b.get(span_b).unwrap_or_default(),
+			),
+			_ => return false,
+		};
+		let case = if is_program {
+			program_case
+		} else {
+			CaseSensitivity::Sensitive
+		};
+		// The same text always gives the same argument.
+		if raw_a != raw_b {
+			let (mut arg_a, _) = unquote_one(raw_a, is_program);
+			let (mut arg_b, _) = unquote_one(raw_b, is_program);
+			loop {
+				match (arg_a.next(), arg_b.next()) {
+					(None, None) => break,
+					(Some(unit_a), Some(unit_b)) if case.eq(unit_a, unit_b) => {}
+					_ => return false,
+				}
+			}
+		}
+		is_program = false;
+	}
+}
+
+/// Whether the command line that started the process has the same arguments
+/// as `other`.
+///
+/// This is [`args_equivalent`] with [`raw_command_line`] on one side. Use
+/// [`args_equivalent_with`] to ignore the case of the program name.
+///
+/// ```
+/// let other: Vec<u16> = "app.exe --service".encode_utf16().collect();
+/// if winarg::args_equivalent_to(&other) {
+///     println!("already running with the same arguments");
+/// }
+/// ```
+pub fn args_equivalent_to(other: &[u16]) -> bool {
+	args_equivalent(raw_command_line(), other)
+}
@@ -0,0 +1,157 @@
+//! A single error type for everything that can fail.
+
+#[cfg(all(windows, feature = "std"))]
+use crate::ResponseFileError;
+use crate::{InvalidUnicodeError, ParseIntError, WhyNot};
+#[cfg(feature = "alloc")]
+use crate::{OddLengthError, VerbatimError};
+use core::fmt;
+
+/// An error from any of winarg's fallible functions.
+///
+/// Each function returns its own, more specific, error type. They can all be
+/// converted into this so that code using several of them only has to handle
+/// one error type.
+///
+/// ```
+/// fn forward(args: &[Vec<u16>]) -> Result<(), winarg::Error> {
+///     winarg::is_representable(args)?;
+///     // ...
+///     Ok(())
+/// }
+///
+/// let error = forward(&[vec![b'"' as u16]]).unwrap_err();
+/// assert_eq!(error.index(), Some(0));
+/// assert_eq!(error.to_string(), "the program name contains a quote at position 0");
+/// ```
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Error {
+	/// An argument is not valid Unicode.
+	InvalidUnicode(InvalidUnicodeError),
+	/// Arguments can't be written to a command line.
+	Unrepresentable(WhyNot),
+	/// An argument isn't an integer.
+	ParseInt(ParseIntError),
+	/// A response file couldn't be expanded.
+	#[cfg(all(windows, feature = "std"))]
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	ResponseFile(ResponseFileError),
+	/// Text couldn't be added to a command line as it is.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+	Verbatim(VerbatimError),
+	/// UTF-16 bytes have an odd length.
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+	OddLength(OddLengthError),
+}
+impl Error {
+	/// The index of the argument that caused the error, if known. The program
+	/// name is `0`.
+	pub fn index(&self) -> Option<usize> {
+		match self {
+			Self::InvalidUnicode(e) => e.arg_index(),
+			Self::Unrepresentable(e) => Some(e.index()),
+			Self::ParseInt(_) => None,
+			#[cfg(all(windows, feature = "std"))]
+			Self::ResponseFile(_) => None,
+			#[cfg(feature = "alloc")]
+			Self::Verbatim(_) => None,
+			#[cfg(feature = "alloc")]
+			Self::OddLength(_) => None,
+		}
+	}
+	/// Where the error is, if known.
+	///
+	/// For an argument this is the position of the code unit within the
+	/// argument. For a response file it's the position of the byte within the
+	/// file. For verbatim text it's the position within the text.
+	pub fn offset(&self) -> Option<usize> {
+		match self {
+			Self::InvalidUnicode(e) => Some(e.offset()),
+			Self::Unrepresentable(e) => Some(e.position()),
+			Self::ParseInt(e) => Some(e.offset()),
+			#[cfg(all(windows, feature = "std"))]
+			Self::ResponseFile(e) => e.offset(),
+			#[cfg(feature = "alloc")]
+			Self::Verbatim(e) => Some(e.position()),
+			#[cfg(feature = "alloc")]
+			Self::OddLength(_) => None,
+		}
+	}
+	/// The code unit that caused the error, if any.
+	pub fn unit(&self) -> Option<u16> {
+		match self {
+			Self::InvalidUnicode(e) => Some(e.unpaired_surrogate()),
+			Self::Unrepresentable(e) => Some(e.unit()),
+			Self::ParseInt(ParseIntError::InvalidDigit { unit, .. }) => Some(*unit),
+			Self::ParseInt(_) => None,
+			#[cfg(all(windows, feature = "std"))]
+			Self::ResponseFile(_) => None,
+			#[cfg(feature = "alloc")]
+			Self::Verbatim(e) => Some(e.unit()),
+			#[cfg(feature = "alloc")]
+			Self::OddLength(_) => None,
+		}
+	}
+}
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::InvalidUnicode(e) => e.fmt(f),
+			Self::Unrepresentable(e) => e.fmt(f),
+			Self::ParseInt(e) => e.fmt(f),
+			#[cfg(all(windows, feature = "std"))]
+			Self::ResponseFile(e) => e.fmt(f),
+			#[cfg(feature = "alloc")]
+			Self::Verbatim(e) => e.fmt(f),
+			#[cfg(feature = "alloc")]
+			Self::OddLength(e) => e.fmt(f),
+		}
+	}
+}
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			#[cfg(windows)]
+			Self::ResponseFile(e) => std::error::Error::source(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<InvalidUnicodeError> for Error {
+	fn from(e: InvalidUnicodeError) -> Self {
+		Self::InvalidUnicode(e)
+	}
+}
+impl From<WhyNot> for Error {
+	fn from(e: WhyNot) -> Self {
+		Self::Unrepresentable(e)
+	}
+}
+impl From<ParseIntError> for Error {
+	fn from(e: ParseIntError) -> Self {
+		Self::ParseInt(e)
+	}
+}
+#[cfg(feature = "alloc")]
+impl From<VerbatimError> for Error {
+	fn from(e: VerbatimError) -> Self {
+		Self::Verbatim(e)
+	}
+}
+#[cfg(feature = "alloc")]
+impl From<OddLengthError> for Error {
+	fn from(e: OddLengthError) -> Self {
+		Self::OddLength(e)
+	}
+}
+#[cfg(all(windows, feature = "std"))]
+impl From<ResponseFileError> for Error {
+	fn from(e: ResponseFileError) -> Self {
+		Self::ResponseFile(e)
+	}
+}
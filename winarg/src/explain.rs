@@ -0,0 +1,149 @@
+//! Explaining how each argument was parsed, for debugging.
+
+use crate::{write_json_string, ParseArgs, SpanKind, QUOTE};
+use core::fmt;
+use core::ops::Range;
+
+/// Write a description of how the command line was split into arguments.
+///
+/// This is meant for a `--debug-args` option, so users can see why their
+/// arguments were split the way they were. Each argument has a line with its
+/// index, the range of code units it came from in the command line, the text
+/// of that range and the argument after quotes and escapes were removed. The
+/// text is written as JSON strings. It's followed by one indented line for
+/// each change the parser made, starting with the offset in the command line:
+///
+/// * `open quote` and `close quote` for a quote that starts or ends quoted
+///   text. The quote is removed.
+/// * `backslashes 4 -> 2` for backslashes before a quote, which are halved.
+///   An odd number ends with `+ literal quote` because the last backslash
+///   escapes the quote.
+/// * `"" -> literal quote` for two quotes in quoted text, which become one
+///   literal quote.
+///
+/// The explanation comes from the same parser used for the arguments so it
+/// always agrees with them. The format won't change within a major version.
+///
+/// For example, `app.exe "a\\"b"" c\\\"d` is explained as:
+///
+/// ```text
+/// arg 0 0..7 "app.exe" -> "app.exe"
+/// arg 1 8..16 "\"a\\\\\"b\"\"" -> "a\\b"
+///   8 open quote
+///   10 backslashes 2 -> 1
+///   12 close quote
+///   14 open quote
+///   15 close quote
+/// arg 2 17..23 "c\\\\\\\"d" -> "c\\\"d"
+///   18 backslashes 3 -> 1 + literal quote
+/// ```
+///
+/// ```
+/// if winarg::has_flag(&["--debug-args"]) {
+///     let mut explanation = String::new();
+///     winarg::explain(&mut explanation).unwrap();
+///     eprint!("{}", explanation);
+/// }
+/// ```
+pub fn explain<W: fmt::Write + ?Sized>(w: &mut W) -> fmt::Result {
+	let mut parser = ParseArgs::from_env();
+	let mut index = 0;
+	while parser.cursor.peek().is_some() {
+		// Find the end of the argument before explaining it.
+		let start = parser.offset();
+		let mut rest = parser.clone();
+		while rest.next().is_some() {}
+		let end = rest.offset();
+		write!(w, "arg {} {}..{} ", index, start, end)?;
+		let raw = parser
+			.input
+			.iter()
+			.copied()
+			.skip(start)
+			.take(end.saturating_sub(start));
+		write_json_string(w, raw)?;
+		w.write_str(" -> ")?;
+		write_json_string(w, parser.clone())?;
+		w.write_char('\n')?;
+
+		let mut steps = Steps {
+			w: &mut *w,
+			input: parser.input,
+			quoted: parser.quote_mode,
+			quote: None,
+			result: Ok(()),
+		};
+		while parser
+			.next_visit(&mut |kind, range| steps.push(kind, range))
+			.is_some()
+		{}
+		steps.flush();
+		steps.result?;
+		parser.move_to_next_arg();
+		index += 1;
+	}
+	Ok(())
+}
+
+// Writes a line for each change the parser made, from the spans it visits.
+struct Steps<'a, W: ?Sized> {
+	w: &'a mut W,
+	input: &'a [u16],
+	// Whether the parser is in quoted text.
+	quoted: bool,
+	// A quote that's either the first of `""` or opens or closes quoted text,
+	// which isn't known until the next span.
+	quote: Option<usize>,
+	// The first error from `w`.
+	result: fmt::Result,
+}
+impl<W: fmt::Write + ?Sized> Steps<'_, W> {
+	fn push(&mut self, kind: SpanKind, range: Range<usize>) {
+		// A literal quote straight after a quote means they were `""`.
+		if let Some(offset) = self.quote {
+			if kind == SpanKind::Literal && self.unit(range.start) == Some(QUOTE) {
+				self.quote = None;
+				return self.write(format_args!("  {} \"\" -> literal quote\n", offset));
+			}
+		}
+		self.flush();
+		match kind {
+			SpanKind::Quote => self.quote = Some(range.start),
+			SpanKind::Escape => {
+				let count = range.len();
+				let quote = if count % 2 == 1 {
+					" + literal quote"
+				} else {
+					""
+				};
+				self.write(format_args!(
+					"  {} backslashes {} -> {}{}\n",
+					range.start,
+					count,
+					count / 2,
+					quote
+				));
+			}
+			_ => {}
+		}
+	}
+
+	// Write the quote waiting to be explained, if any.
+	fn flush(&mut self) {
+		if let Some(offset) = self.quote.take() {
+			self.quoted = !self.quoted;
+			let action = if self.quoted { "open" } else { "close" };
+			self.write(format_args!("  {} {} quote\n", offset, action));
+		}
+	}
+
+	fn unit(&self, offset: usize) -> Option<u16> {
+		self.input.get(offset).copied()
+	}
+
+	fn write(&mut self, args: fmt::Arguments<'_>) {
+		if self.result.is_ok() {
+			self.result = self.w.write_fmt(args);
+		}
+	}
+}
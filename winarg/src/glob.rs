@@ -0,0 +1,205 @@
+//! Wildcard expansion using `FindFirstFileW`.
+
+use crate::{Argument, SLASH};
+use alloc::vec::Vec;
+use core::{fmt, mem::MaybeUninit};
+
+const STAR: u16 = b'*' as _;
+const QUESTION: u16 = b'?' as _;
+const DOT: u16 = b'.' as _;
+const FORWARD_SLASH: u16 = b'/' as _;
+const COLON: u16 = b':' as _;
+
+/// Options for [`glob_one_with`].
+#[cfg_attr(docsrs, doc(cfg(feature = "glob")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlobOptions {
+	/// Include files that have the hidden attribute. Defaults to `true`.
+	pub hidden: bool,
+	/// Include files that have the system attribute. Defaults to `true`.
+	pub system: bool,
+	/// Include the `.` and `..` directory entries if they match the pattern.
+	/// Defaults to `false`.
+	pub dot_entries: bool,
+}
+impl Default for GlobOptions {
+	fn default() -> Self {
+		Self {
+			hidden: true,
+			system: true,
+			dot_entries: false,
+		}
+	}
+}
+impl GlobOptions {
+	fn allows(&self, data: &WIN32_FIND_DATAW) -> bool {
+		let name = data.file_name();
+		if !self.dot_entries && (name == [DOT] || name == [DOT, DOT]) {
+			return false;
+		}
+		let attributes = data.dwFileAttributes;
+		(self.hidden || attributes & FILE_ATTRIBUTE_HIDDEN == 0)
+			&& (self.system || attributes & FILE_ATTRIBUTE_SYSTEM == 0)
+	}
+}
+
+/// Expand a wildcard pattern into the names of matching files.
+///
+/// `*` matches any number of characters and `?` matches a single character.
+/// Like the C runtime, only the last path component may contain wildcards.
+/// Each name is returned with the pattern's directory prepended so `src\*.rs`
+/// may produce `src\lib.rs`.
+///
+/// If nothing matches, or the pattern is invalid, then the iterator is empty.
+/// A pattern without wildcards will only match itself.
+///
+/// This uses the default [`GlobOptions`], which includes hidden and system
+/// files but skips the `.` and `..` directory entries.
+///
+/// ```
+/// use std::ffi::OsString;
+/// use std::os::windows::ffi::OsStringExt;
+///
+/// for arg in winarg::args_native().skip(1) {
+///     for path in winarg::glob_one(&arg) {
+///         println!("{:?}", OsString::from_wide(&path));
+///     }
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "glob")))]
+pub fn glob_one(pattern: &Argument) -> GlobIter {
+	glob_one_with(pattern, GlobOptions::default())
+}
+
+/// Expand a wildcard pattern using the given options.
+///
+/// See [`glob_one`] for details.
+///
+/// ```
+/// use winarg::GlobOptions;
+///
+/// let options = GlobOptions { hidden: false, ..GlobOptions::default() };
+/// for arg in winarg::args_native().skip(1) {
+///     let count = winarg::glob_one_with(&arg, options).count();
+///     println!("{} visible files", count);
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "glob")))]
+pub fn glob_one_with(pattern: &Argument, options: GlobOptions) -> GlobIter {
+	let mut pattern: Vec<u16> = pattern.utf16_units().collect();
+	// The directory is everything up to and including the last separator.
+	let dir_len = pattern
+		.iter()
+		.rposition(|&w| matches!(w, SLASH | FORWARD_SLASH | COLON))
+		.map_or(0, |i| i + 1);
+	let dir = pattern[..dir_len].to_vec();
+
+	let mut iter = GlobIter {
+		handle: INVALID_HANDLE_VALUE,
+		dir,
+		next: None,
+		options,
+	};
+	if iter.dir.iter().any(|&w| w == STAR || w == QUESTION) {
+		return iter;
+	}
+	pattern.push(0);
+	let mut data = MaybeUninit::uninit();
+	// SAFETY: `pattern` is null terminated and `data` is only read on success.
+	unsafe {
+		iter.handle = FindFirstFileW(pattern.as_ptr(), data.as_mut_ptr());
+		if iter.handle != INVALID_HANDLE_VALUE {
+			iter.next = Some(data.assume_init());
+		}
+	}
+	iter
+}
+
+/// An iterator over the files matching a pattern.
+///
+/// Created by [`glob_one`]. Each item is a UTF-16 encoded path, without a
+/// terminating null.
+#[cfg_attr(docsrs, doc(cfg(feature = "glob")))]
+pub struct GlobIter {
+	handle: isize,
+	dir: Vec<u16>,
+	// The result of the last call to `FindFirstFileW` or `FindNextFileW`.
+	next: Option<WIN32_FIND_DATAW>,
+	options: GlobOptions,
+}
+impl Iterator for GlobIter {
+	type Item = Vec<u16>;
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let data = self.next.take()?;
+			let mut next = MaybeUninit::uninit();
+			// SAFETY: `handle` is a valid find handle because `next` was set.
+			// `next` is only read on success.
+			unsafe {
+				if FindNextFileW(self.handle, next.as_mut_ptr()) != 0 {
+					self.next = Some(next.assume_init());
+				}
+			}
+			if self.options.allows(&data) {
+				let name = data.file_name();
+				let mut path = Vec::with_capacity(self.dir.len() + name.len());
+				path.extend_from_slice(&self.dir);
+				path.extend_from_slice(name);
+				return Some(path);
+			}
+		}
+	}
+}
+impl Drop for GlobIter {
+	fn drop(&mut self) {
+		if self.handle != INVALID_HANDLE_VALUE {
+			// SAFETY: The handle was returned by `FindFirstFileW` and is only closed once.
+			unsafe {
+				FindClose(self.handle);
+			}
+		}
+	}
+}
+impl fmt::Debug for GlobIter {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("GlobIter")
+			.field("handle", &self.handle)
+			.field("options", &self.options)
+			.finish()
+	}
+}
+
+const INVALID_HANDLE_VALUE: isize = -1;
+const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+
+#[repr(C)]
+#[allow(nonstandard_style)]
+struct WIN32_FIND_DATAW {
+	dwFileAttributes: u32,
+	ftCreationTime: [u32; 2],
+	ftLastAccessTime: [u32; 2],
+	ftLastWriteTime: [u32; 2],
+	nFileSizeHigh: u32,
+	nFileSizeLow: u32,
+	dwReserved0: u32,
+	dwReserved1: u32,
+	cFileName: [u16; 260],
+	cAlternateFileName: [u16; 14],
+}
+impl WIN32_FIND_DATAW {
+	fn file_name(&self) -> &[u16] {
+		let len = self
+			.cFileName
+			.iter()
+			.position(|&w| w == 0)
+			.unwrap_or(self.cFileName.len());
+		&self.cFileName[..len]
+	}
+}
+
+extern "system" {
+	fn FindFirstFileW(lpFileName: *const u16, lpFindFileData: *mut WIN32_FIND_DATAW) -> isize;
+	fn FindNextFileW(hFindFile: isize, lpFindFileData: *mut WIN32_FIND_DATAW) -> i32;
+	fn FindClose(hFindFile: isize) -> i32;
+}
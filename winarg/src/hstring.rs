@@ -0,0 +1,105 @@
+//! Conversions to and from the string types of the `windows` crate.
+//!
+//! `HSTRING` and `PCWSTR` come from the `windows-strings` crate, which
+//! `windows` re-exports as `windows::core`, so these work with either.
+
+use crate::{ArgsNative, Argument, ParseArgs, Parser, WideIter};
+use alloc::vec::Vec;
+use windows_strings::{HSTRING, PCWSTR};
+
+impl ArgsNative {
+	/// Parse the arguments of a command line other than the current one.
+	///
+	/// A null pointer is treated as an empty command line.
+	///
+	/// # Safety
+	///
+	/// `cmdline` must either be null or point to a null terminated string that
+	/// is valid and unmodified for the rest of the program. The string returned
+	/// by `GetCommandLineW` meets these requirements.
+	///
+	/// ```
+	/// use windows::Win32::System::Environment::GetCommandLineW;
+	///
+	/// // SAFETY: The command line is never freed or modified.
+	/// let args = unsafe { winarg::ArgsNative::from_windows_pcwstr(GetCommandLineW()) };
+	/// assert_eq!(args.count(), winarg::args_native().count());
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "windows")))]
+	pub unsafe fn from_windows_pcwstr(cmdline: PCWSTR) -> Self {
+		Self {
+			next: ParseArgs::new(wide_iter(cmdline), true),
+		}
+	}
+}
+
+impl Parser {
+	/// Parse a command line other than the current one.
+	///
+	/// A null pointer is treated as an empty command line.
+	///
+	/// # Safety
+	///
+	/// This has the same requirements as
+	/// [`ArgsNative::from_windows_pcwstr`].
+	///
+	/// ```
+	/// use windows::Win32::System::Environment::GetCommandLineW;
+	///
+	/// // SAFETY: The command line is never freed or modified.
+	/// let parser = unsafe { winarg::Parser::from_windows_pcwstr(GetCommandLineW()) };
+	/// assert!(parser.eq(winarg::Parser()));
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "windows")))]
+	pub unsafe fn from_windows_pcwstr(cmdline: PCWSTR) -> Self {
+		Parser {
+			iter: ParseArgs::new(wide_iter(cmdline), true),
+			arg_index: 0,
+		}
+	}
+}
+
+// SAFETY: See `ArgsNative::from_windows_pcwstr`.
+unsafe fn wide_iter(cmdline: PCWSTR) -> WideIter<'static> {
+	if cmdline.is_null() {
+		WideIter::new(&[])
+	} else {
+		WideIter::from_ptr(cmdline.0)
+	}
+}
+
+impl Argument {
+	/// The argument as an `HSTRING`, for passing to functions from the
+	/// `windows` crate.
+	///
+	/// This is lossless, including for isolated surrogates.
+	///
+	/// ```
+	/// use windows::Win32::Storage::FileSystem::{GetFileAttributesW, INVALID_FILE_ATTRIBUTES};
+	///
+	/// for arg in winarg::args_native().skip(1) {
+	///     // SAFETY: The `HSTRING` is null terminated and outlives the call.
+	///     let attributes = unsafe { GetFileAttributesW(&arg.to_hstring()) };
+	///     if attributes != INVALID_FILE_ATTRIBUTES {
+	///         println!("{} exists", arg);
+	///     }
+	/// }
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "windows")))]
+	pub fn to_hstring(&self) -> HSTRING {
+		HSTRING::from_wide(&self.utf16_units().collect::<Vec<u16>>())
+	}
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "windows")))]
+impl PartialEq<HSTRING> for Argument {
+	fn eq(&self, other: &HSTRING) -> bool {
+		self.eq(other.iter().copied())
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "windows")))]
+impl PartialEq<Argument> for HSTRING {
+	fn eq(&self, other: &Argument) -> bool {
+		other.eq(self.iter().copied())
+	}
+}
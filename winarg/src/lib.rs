@@ -1,5 +1,6 @@
 #![no_std]
-#![cfg(windows)]
+// Other platforms are only supported for testing the parser. See `test-override`.
+#![cfg(any(windows, feature = "test-override"))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 //! The Windows command line is passed to applications as a string. To get an
 //! array of arguments it's necessary to parse this string, which is what this
@@ -29,13 +30,22 @@
 //! }
 //! ```
 //!
+//! Checking for a flag without allocation using [`has_flag`]:
+//!
+//! ```
+//! if winarg::has_flag(&["-h", "--help"]) {
+//!     println!("help me!");
+//! }
+//! ```
+//!
 //! Iterating arguments without allocation using [`args_native`]:
 //!
 //! ```
 //! for arg in winarg::args_native().skip(1) {
-//!     if arg == "--help" {
-//!         println!("help me!");
+//!     if arg == "--" {
+//!         break;
 //!     }
+//!     println!("{}", arg.scalars().collect::<String>());
 //! }
 //! ```
 //!
@@ -50,6 +60,51 @@
 //! // Collect the rest into a UTF-16 encoded vector.
 //! let args: Vec<u16> = parser.map(|t| t.as_u16() ).collect();
 //! ```
+//!
+//! # Features
+//!
+//! The crate is `no_std` by default. Optional features add functions that
+//! return owned types:
+//!
+//! * `alloc` enables functions that return `String` or `Vec`, and the [`utf8`]
+//!   module for parsing command lines stored as UTF-8.
+//! * `std` enables functions that return `OsString` and [`ResponseFileArgs`]
+//!   for expanding `@file` arguments. Implies `alloc`.
+//! * `glob` enables [`glob_one`] for expanding wildcards. Implies `alloc`.
+//! * `cached` enables the [`cached`] module, which only finds the arguments
+//!   once and keeps them in a static. It doesn't need `alloc`.
+//! * `ordinal-case` enables [`CaseSensitivity::OrdinalInsensitive`] for
+//!   matching flag names the way the file system compares names.
+//! * `program-path` enables [`program_path`] for finding the path of the
+//!   executable. Implies `alloc` and `ordinal-case`.
+//! * `windows-sys` adds conversions to and from the `PCWSTR` and `PWSTR` types
+//!   of the [windows-sys] crate, such as [`WideBuf`]. Implies `alloc`.
+//! * `windows` adds [`Argument::to_hstring`], comparisons with `HSTRING` and
+//!   constructors that take a `PCWSTR`, for use with the [windows] crate.
+//!   Implies `alloc`.
+//! * `widestring` adds [`Argument::to_u16cstring`] and comparisons with the
+//!   `U16Str` and `U16CStr` types of the [widestring] crate. Implies `alloc`.
+//! * `test-override` enables the [`testing`] module for overriding the command
+//!   line in tests. Implies `std`. Only use this for dev-dependencies. It also
+//!   allows the crate to be built on other platforms so the parser can be
+//!   tested anywhere. Functions that need Windows, such as [`glob_one`], are
+//!   left out and the command line is empty unless it's overridden.
+//!
+//! [windows-sys]: https://crates.io/crates/windows-sys
+//! [windows]: https://crates.io/crates/windows
+//! [widestring]: https://crates.io/crates/widestring
+//!
+//! # Panics
+//!
+//! The command line is often read at the very start of a program, where a
+//! panic may abort without a message. Functions that don't allocate never
+//! panic, and neither do the iterators and `Display` implementations they
+//! return. This is checked by linking a `no_std` program that uses them with a
+//! panic handler that doesn't exist. See the `panic_free` test in the testing
+//! crate.
+//!
+//! Functions that allocate follow the same rule but aren't checked in this
+//! way. Running out of memory is handled by the allocator's error handler.
 
 /*
 Implementation note: The public interface and the private implementation were
@@ -57,20 +112,165 @@ basically created separately then glued together. I've started joining them up
 more but there's still a lot artificial separation and indirection.
 */
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod analyze;
+mod arg_match;
+mod buffer;
+#[cfg(feature = "alloc")]
+mod builder;
+#[cfg(feature = "alloc")]
+mod bytes;
+#[cfg(feature = "cached")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cached")))]
+pub mod cached;
+mod classify;
+#[cfg(feature = "alloc")]
+mod cmd;
+#[cfg(feature = "alloc")]
+mod command_line;
+#[cfg(all(windows, feature = "std"))]
+mod dropped;
+mod equivalent;
+mod error;
+mod explain;
+#[cfg(all(windows, feature = "glob"))]
+mod glob;
+#[cfg(all(windows, feature = "windows"))]
+mod hstring;
+mod matching;
+#[cfg(feature = "alloc")]
+mod multi;
+mod number;
+#[cfg(feature = "alloc")]
+mod owned;
+#[cfg(all(windows, feature = "windows-sys"))]
+mod pcwstr;
+#[cfg(feature = "alloc")]
+mod portability;
+#[cfg(all(windows, feature = "program-path"))]
+mod program_path;
+mod quote;
+#[cfg(feature = "alloc")]
+mod rebuild;
+#[cfg(all(windows, feature = "std"))]
+mod response_file;
+mod rules;
+#[cfg(feature = "alloc")]
+mod service;
+mod short_flags;
+mod spans;
+mod split;
+mod stats;
+#[cfg(any(test, feature = "test-override"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-override")))]
+pub mod testing;
 #[cfg(test)]
 mod tests;
+mod trim;
+#[cfg(feature = "widestring")]
+mod u16str;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod utf8;
+mod validate;
+#[cfg(all(windows, any(feature = "std", feature = "program-path")))]
+mod verbatim;
+mod wtf8;
+
+pub use analyze::{analyze, analyze_lenient, Span, SpanKind};
+#[doc(hidden)]
+pub use arg_match::__arg_match_value;
+pub use buffer::{parse_into_buffer, BufferArgs};
+#[cfg(all(windows, feature = "std"))]
+pub use builder::PathOptions;
+#[cfg(feature = "alloc")]
+pub use builder::{CommandLineBuilder, VerbatimError};
+#[cfg(feature = "alloc")]
+pub use bytes::{
+	parse_utf16_bytes, parse_utf16le_bytes, DecodedCommandLine, Endianness, OddLengthError,
+};
+pub use classify::{ArgClass, ClassifyStyle};
+#[cfg(feature = "alloc")]
+pub use cmd::nest_for_cmd_c;
+#[cfg(feature = "alloc")]
+pub use command_line::{CommandLine, Tokens};
+#[cfg(all(windows, feature = "std"))]
+pub use dropped::{dropped_files, dropped_files_checked, DroppedFiles, DroppedFilesChecked};
+pub use equivalent::{args_equivalent, args_equivalent_to, args_equivalent_with};
+pub use error::Error;
+pub use explain::explain;
+#[cfg(all(windows, feature = "glob"))]
+pub use glob::{glob_one, glob_one_with, GlobIter, GlobOptions};
+pub use matching::{CaseSensitivity, FlagStyle, MatchOptions};
+#[cfg(feature = "alloc")]
+pub use multi::{parse_multi, EmptyRecords, MultiArgs};
+pub use number::{ParseIntError, Radix};
+#[cfg(feature = "std")]
+pub use owned::args_cached_lossy;
+#[cfg(all(windows, feature = "std"))]
+pub use owned::{args_cached, os_args_after_program};
+#[cfg(feature = "alloc")]
+pub use owned::{
+	collect_args_wide, collect_wide, command_line_lossy, cow_args, cow_args_of,
+	extend_null_separated, program_name_lossy, string_args_after_program, to_json_string,
+	try_command_line, values_of, values_of_with, CowArgs,
+};
+#[cfg(all(windows, feature = "windows-sys"))]
+pub use pcwstr::WideBuf;
+#[cfg(feature = "alloc")]
+pub use portability::{check_portability, PortabilityWarning};
+#[cfg(all(windows, feature = "program-path", feature = "std"))]
+pub use program_path::program_path_buf;
+#[cfg(all(windows, feature = "program-path"))]
+pub use program_path::{program_name_or_module, program_path, program_path_matches_arg0};
+pub use quote::{
+	is_representable, is_representable_with, quote_one, quoted_len, QuotingRules, WhyNot,
+	MAX_CMD_EXE_LINE, MAX_COMMAND_LINE,
+};
+#[cfg(feature = "alloc")]
+pub use rebuild::{
+	canonicalize, canonicalize_with, rebuild, splice, with_appended, with_program_and_appended,
+	Edit,
+};
+#[cfg(all(windows, feature = "std"))]
+pub use response_file::{
+	FallbackEncoding, ResponseFileArgs, ResponseFileEncoding, ResponseFileError, ResponseFileRules,
+};
+pub use rules::{LeadingWhitespace, ParserRules};
+#[cfg(feature = "alloc")]
+pub use service::check_image_path;
+#[cfg(all(windows, feature = "std"))]
+pub use service::service_image_path;
+pub use short_flags::{split_short_flags, ShortFlag, ShortFlags};
+pub use spans::{arg_spans, arg_spans_lenient, arg_spans_of, ArgSpans};
+pub use stats::{command_line_stats, ArgStats};
+pub use trim::Trimmed;
+pub use validate::{validate, Diagnostic, DiagnosticKind, Diagnostics};
+#[cfg(feature = "alloc")]
+pub use wtf8::from_wtf8;
 
+#[cfg(windows)]
+use core::sync::atomic::{AtomicUsize, Ordering};
 use core::{
 	char::{decode_utf16, REPLACEMENT_CHARACTER},
-	fmt,
+	cmp, fmt,
+	iter::FusedIterator,
 	num::NonZeroU16,
-	slice,
+	ops::Range,
 };
 
 const SPACE: u16 = b' ' as _;
 const TAB: u16 = b'\t' as _;
 const QUOTE: u16 = b'"' as _;
 const SLASH: u16 = b'\\' as _;
+const EQUALS: u16 = b'=' as _;
+const FORWARD_SLASH: u16 = b'/' as _;
+const DASH: u16 = b'-' as _;
+const COLON: u16 = b':' as _;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Token {
@@ -94,6 +294,100 @@ impl Token {
 	pub fn is_next_arg(self) -> bool {
 		self == Token::NextArg
 	}
+	/// Helper to test for [`Token::Unit`].
+	///
+	/// ```
+	/// use winarg::Token;
+	///
+	/// assert!(Token::Unit(core::num::NonZeroU16::new(b'a'.into()).unwrap()).is_unit());
+	/// assert!(!Token::NextArg.is_unit());
+	/// ```
+	#[inline]
+	pub fn is_unit(self) -> bool {
+		matches!(self, Token::Unit(_))
+	}
+	/// The code unit, if this is a [`Token::Unit`].
+	///
+	/// Unlike [`as_u16`](Self::as_u16), this can tell [`Token::NextArg`]
+	/// apart from a code unit.
+	///
+	/// ```
+	/// use core::num::NonZeroU16;
+	/// use winarg::Token;
+	///
+	/// let a = NonZeroU16::new(b'a'.into()).unwrap();
+	/// assert_eq!(Token::Unit(a).unit(), Some(a));
+	/// assert_eq!(Token::NextArg.unit(), None);
+	/// ```
+	#[inline]
+	pub fn unit(self) -> Option<NonZeroU16> {
+		match self {
+			Token::Unit(u) => Some(u),
+			Token::NextArg => None,
+		}
+	}
+	/// The code unit as a `char`, if this is a [`Token::Unit`] that's a
+	/// character by itself.
+	///
+	/// Each token is a single UTF-16 code unit so this only works for
+	/// characters in the Basic Multilingual Plane. Surrogates return `None`,
+	/// even if they're half of a valid surrogate pair. Use
+	/// [`char::decode_utf16`] with the code units to decode pairs.
+	///
+	/// ```
+	/// use core::num::NonZeroU16;
+	/// use winarg::Token;
+	///
+	/// let unit = |u| Token::Unit(NonZeroU16::new(u).unwrap());
+	/// assert_eq!(unit(b'a'.into()).as_char(), Some('a'));
+	/// assert_eq!(unit(0x00E9).as_char(), Some('é'));
+	/// // 😅 is a surrogate pair, which are two separate tokens.
+	/// let pair: Vec<Token> = "😅".encode_utf16().map(unit).collect();
+	/// assert_eq!(pair[0].as_char(), None);
+	/// assert_eq!(pair[1].as_char(), None);
+	/// assert_eq!(Token::NextArg.as_char(), None);
+	/// ```
+	#[inline]
+	pub fn as_char(self) -> Option<char> {
+		self.unit().and_then(|u| char::from_u32(u32::from(u.get())))
+	}
+}
+/// A [`Token::Unit`] is equal to its code unit. Other tokens aren't equal to
+/// any `u16`, including `0`.
+///
+/// ```
+/// let tokens: Vec<winarg::Token> = winarg::Parser().collect();
+/// let slashes = tokens.iter().filter(|&&t| t == u16::from(b'\\')).count();
+/// ```
+impl PartialEq<u16> for Token {
+	fn eq(&self, other: &u16) -> bool {
+		self.unit().map(NonZeroU16::get) == Some(*other)
+	}
+}
+impl PartialEq<Token> for u16 {
+	fn eq(&self, other: &Token) -> bool {
+		other == self
+	}
+}
+/// A [`Token::Unit`] is equal to a `char` that's encoded as that single code
+/// unit. See [`Token::as_char`].
+///
+/// ```
+/// for t in winarg::Parser() {
+///     if t == '"' {
+///         // ...
+///     }
+/// }
+/// ```
+impl PartialEq<char> for Token {
+	fn eq(&self, other: &char) -> bool {
+		self.as_char() == Some(*other)
+	}
+}
+impl PartialEq<Token> for char {
+	fn eq(&self, other: &Token) -> bool {
+		other == self
+	}
 }
 /// A parsing iterator that produces [`Token`]s.
 ///
@@ -105,49 +399,310 @@ impl Token {
 /// ```
 #[derive(Debug, Clone)]
 pub struct Parser {
-	iter: ParseArgs,
+	iter: ParseArgs<'static>,
+	// The number of `Token::NextArg` returned.
+	arg_index: usize,
 }
 impl Parser {
 	pub fn from_env() -> Self {
 		Parser()
 	}
-	#[cfg(test)]
-	// For testing only.
-	// SAFETY: `lpwstr` must point to valid null terminated wide string.
-	// It must live at least as long as this struct and must not be modified.
-	unsafe fn from_ptr(lpwstr: *const u16) -> Self {
-		Self {
-			iter: ParseArgs::from_ptr(lpwstr),
+	/// Whether the parser is inside quotes.
+	///
+	/// Like the other state queries, this is the state after the last token
+	/// returned. Once the parser has returned `None` it's the state at the end
+	/// of the command line, so this says whether a quote was left open.
+	///
+	/// ```
+	/// let mut parser = winarg::Parser();
+	/// while parser.next().is_some() {}
+	/// if parser.in_quotes() {
+	///     println!("the command line has an unclosed quote");
+	/// }
+	/// ```
+	pub fn in_quotes(&self) -> bool {
+		self.iter.quote_mode
+	}
+	/// The number of backslashes that would escape a quote if one came next.
+	///
+	/// These are the backslashes just before the parser's position in the
+	/// command line. A run of backslashes is read all at once, so they're
+	/// counted as soon as the first of them is returned. Backslashes in the
+	/// program name never escape quotes so this is always `0` there.
+	pub fn pending_backslashes(&self) -> u16 {
+		if self.arg_index == 0 {
+			return 0;
 		}
+		self.iter.pending_slashes().min(usize::from(u16::MAX)) as u16
 	}
-}
-impl Iterator for Parser {
-	type Item = Token;
-	fn next(&mut self) -> Option<Self::Item> {
+	/// The index of the argument the last token was in. The program name is
+	/// `0`.
+	///
+	/// This is the number of [`Token::NextArg`]s that have been returned.
+	pub fn current_arg_index(&self) -> usize {
+		self.arg_index
+	}
+	/// Parse the command line using `rules` instead of the modern rules.
+	///
+	/// This should be used before any tokens are taken from the parser.
+	///
+	/// ```
+	/// use winarg::ParserRules;
+	///
+	/// // Match a child process built with an old toolchain.
+	/// let parser = winarg::Parser().with_rules(ParserRules::Pre2008);
+	/// let args: Vec<u16> = parser.map(|t| t.as_u16()).collect();
+	/// ```
+	pub fn with_rules(mut self, rules: ParserRules) -> Self {
+		self.iter.rules = rules;
+		self
+	}
+	/// Choose what happens to whitespace at the start of the command line.
+	///
+	/// By default it ends an empty program name, as it does for the C runtime.
+	/// See [`LeadingWhitespace`]. This has no effect once tokens have been
+	/// taken from the parser.
+	///
+	/// ```
+	/// use winarg::LeadingWhitespace;
+	///
+	/// let parser = winarg::Parser().with_leading_whitespace(LeadingWhitespace::SkipToFirstToken);
+	/// let args: Vec<u16> = parser.map(|t| t.as_u16()).collect();
+	/// ```
+	pub fn with_leading_whitespace(mut self, leading: LeadingWhitespace) -> Self {
+		if leading == LeadingWhitespace::SkipToFirstToken && self.iter.offset() == 0 {
+			self.iter.cursor.skip_whitespace(self.iter.separators);
+		}
+		self
+	}
+	/// Also return where in the command line each token came from.
+	///
+	/// See [`SpannedToken`] for how tokens are mapped to offsets.
+	///
+	/// ```
+	/// for winarg::SpannedToken { token, offset } in winarg::Parser().spanned() {
+	///     println!("{:?} from code unit {}", token, offset);
+	/// }
+	/// ```
+	pub fn spanned(self) -> SpannedParser {
+		SpannedParser { parser: self }
+	}
+	/// Mark the start of every argument, including the program name.
+	///
+	/// Instead of [`Token::NextArg`] between arguments, a
+	/// [`GroupedToken::ArgStart`] comes before each argument, even if the
+	/// argument is empty. There's one `ArgStart` for each argument returned by
+	/// [`args_native`]. This should be used before any tokens are taken from
+	/// the parser.
+	///
+	/// ```
+	/// use winarg::GroupedToken;
+	///
+	/// for token in winarg::Parser().grouped() {
+	///     match token {
+	///         GroupedToken::ArgStart { index, is_program } => {
+	///             println!("argument {} (program name: {})", index, is_program)
+	///         }
+	///         GroupedToken::Unit(unit) => println!("  {:#06x}", unit),
+	///     }
+	/// }
+	/// ```
+	pub fn grouped(self) -> GroupedParser {
+		GroupedParser {
+			parser: self,
+			index: None,
+		}
+	}
+	/// Return each token with where it came from, as [`spanned`](Self::spanned)
+	/// does, then [`ParserEvent::End`] after the last token.
+	///
+	/// `End` is returned exactly once, even if the command line is empty.
+	/// After that the parser only returns `None`.
+	///
+	/// ```
+	/// use winarg::ParserEvent;
+	///
+	/// for event in winarg::Parser().events() {
+	///     match event {
+	///         ParserEvent::Token(token) => println!("{:?}", token),
+	///         ParserEvent::End { offset } => println!("end at code unit {}", offset),
+	///         _ => {}
+	///     }
+	/// }
+	/// ```
+	pub fn events(self) -> EventParser {
+		EventParser {
+			parser: self,
+			ended: false,
+		}
+	}
+	fn next_spanned(&mut self) -> Option<SpannedToken> {
 		self.iter
-			.next()
-			// SAFETY: ParseArgs will never return zero as it terminates the command line.
-			.map(|w| unsafe { Token::Unit(NonZeroU16::new_unchecked(w)) })
+			.next_spanned()
+			// SAFETY: ParseArgs will never return zero as `WideIter` stops at the first null.
+			.map(|(w, offset)| SpannedToken {
+				token: unsafe { Token::Unit(NonZeroU16::new_unchecked(w)) },
+				offset,
+			})
 			.or_else(|| {
 				self.iter.move_to_next_arg();
-				if self.iter.cursor.peek().is_none() {
-					None
-				} else {
-					Some(Token::NextArg)
-				}
+				self.iter.cursor.peek()?;
+				self.arg_index += 1;
+				Some(SpannedToken {
+					token: Token::NextArg,
+					offset: self.iter.offset(),
+				})
 			})
 	}
-	/// Calculate the maximum possible size by scanning for the terminating null.
+}
+impl Iterator for Parser {
+	type Item = Token;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.next_spanned().map(|t| t.token)
+	}
+	/// The maximum possible size is the number of remaining code units.
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(0, Some(self.iter.cursor.max_len()))
+	}
+}
+impl FusedIterator for Parser {}
+
+/// A [`Token`] and the position in the command line it came from.
+///
+/// The offset is the index of the code unit in the command line that produced
+/// the token:
+///
+/// * Most code units come from themselves.
+/// * Every slash produced by a run of slashes comes from the first slash in
+///   the run.
+/// * The literal quote produced by an escape such as `\"` comes from the
+///   quote. So does the quote produced by `""` within quotes.
+/// * [`Token::NextArg`] comes from the first code unit of the next argument,
+///   after any whitespace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpannedToken {
+	/// The token.
+	pub token: Token,
+	/// The index of the code unit in the command line that produced it.
+	pub offset: usize,
+}
+
+/// A parsing iterator that produces [`SpannedToken`]s.
+///
+/// Created by [`Parser::spanned`].
+#[derive(Debug, Clone)]
+pub struct SpannedParser {
+	parser: Parser,
+}
+impl Iterator for SpannedParser {
+	type Item = SpannedToken;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.parser.next_spanned()
+	}
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.parser.size_hint()
+	}
+}
+impl FusedIterator for SpannedParser {}
+
+/// A token from [`Parser::grouped`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupedToken {
+	/// The start of an argument. This comes before any of its code units.
+	ArgStart {
+		/// The index of the argument. The program name is `0`.
+		index: usize,
+		/// Whether this is the program name, i.e. `index` is `0`.
+		is_program: bool,
+	},
+	/// A UTF-16 code unit in the current argument.
+	Unit(NonZeroU16),
+}
+
+/// A parsing iterator that produces [`GroupedToken`]s.
+///
+/// Created by [`Parser::grouped`].
+#[derive(Debug, Clone)]
+pub struct GroupedParser {
+	parser: Parser,
+	// The index of the current argument, if one has been started.
+	index: Option<usize>,
+}
+impl Iterator for GroupedParser {
+	type Item = GroupedToken;
+	fn next(&mut self) -> Option<Self::Item> {
+		let index = match self.index {
+			Some(index) => match self.parser.next()? {
+				Token::Unit(unit) => return Some(GroupedToken::Unit(unit)),
+				Token::NextArg => index + 1,
+			},
+			// The program name starts the command line, unless it's empty.
+			None => {
+				self.parser.iter.cursor.peek()?;
+				0
+			}
+		};
+		self.index = Some(index);
+		Some(GroupedToken::ArgStart {
+			index,
+			is_program: index == 0,
+		})
+	}
+}
+impl FusedIterator for GroupedParser {}
+
+/// An item from [`Parser::events`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParserEvent {
+	/// A token and where it came from. See [`SpannedToken`].
+	Token(SpannedToken),
+	/// The end of the command line. This comes after every token.
+	End {
+		/// The length of the command line, up to any null.
+		offset: usize,
+	},
+}
+
+/// A parsing iterator that produces [`ParserEvent`]s.
+///
+/// Created by [`Parser::events`].
+#[derive(Debug, Clone)]
+pub struct EventParser {
+	parser: Parser,
+	// Whether `ParserEvent::End` has been returned.
+	ended: bool,
+}
+impl Iterator for EventParser {
+	type Item = ParserEvent;
+	fn next(&mut self) -> Option<Self::Item> {
+		if let Some(token) = self.parser.next_spanned() {
+			return Some(ParserEvent::Token(token));
+		}
+		if self.ended {
+			return None;
+		}
+		self.ended = true;
+		Some(ParserEvent::End {
+			offset: self.parser.iter.offset(),
+		})
+	}
+	/// The maximum possible size is the number of remaining code units, plus
+	/// one for [`ParserEvent::End`].
 	fn size_hint(&self) -> (usize, Option<usize>) {
-		(0, Some(self.iter.cursor.max_len() as _))
+		let end = usize::from(!self.ended);
+		let (_, upper) = self.parser.size_hint();
+		(end, upper.map(|upper| upper + end))
 	}
 }
+impl FusedIterator for EventParser {}
 
 #[allow(nonstandard_style)]
 #[doc(hidden)]
 pub fn Parser() -> Parser {
 	Parser {
 		iter: ParseArgs::from_env(),
+		arg_index: 0,
 	}
 }
 
@@ -161,6 +716,94 @@ pub fn Parser() -> Parser {
 pub fn null_separated_list() -> impl Iterator<Item = char> + fmt::Debug + Clone {
 	scalars(null_separated_list_wide())
 }
+/// A list of arguments separated by a `\0` character, with isolated
+/// surrogates replaced by `replacement`.
+///
+/// If `replacement` is `None`, isolated surrogates are left out. See
+/// [`Argument::scalars_with`].
+/// ```
+/// let args: String = winarg::null_separated_list_with(Some('\u{2370}')).collect();
+/// for arg in args.split('\0') {
+///     println!("{}", arg);
+/// }
+/// ```
+pub fn null_separated_list_with(
+	replacement: Option<char>,
+) -> impl Iterator<Item = char> + fmt::Debug + Clone {
+	scalars_with(null_separated_list_wide(), replacement)
+}
+/// Write every argument to `w`, with `separator` between them.
+///
+/// Isolated surrogates are replaced with `�`, as in [`Argument::write_lossy`].
+/// Nothing is allocated. Stops at the first error from `w` and returns it.
+///
+/// ```
+/// let mut line = String::new();
+/// winarg::write_args(&mut line, " ").unwrap();
+/// println!("{}", line);
+/// ```
+pub fn write_args<W: fmt::Write + ?Sized>(w: &mut W, separator: &str) -> fmt::Result {
+	for (index, arg) in ArgsNative::from_env().enumerate() {
+		if index > 0 {
+			w.write_str(separator)?;
+		}
+		arg.write_lossy(w)?;
+	}
+	Ok(())
+}
+/// Write every argument to `w` as a JSON array of strings.
+///
+/// This is useful for logging how a program was run. The program name is the
+/// first string. `"` and `\` are escaped with a backslash and control
+/// characters are written as `\u` escapes, such as `\u0009` for a tab.
+/// Everything else is written as it is. Isolated surrogates can't be written
+/// to valid JSON so they're replaced with `�`, which is `\uFFFD` when decoded.
+///
+/// There's no whitespace between strings and nothing is allocated.
+///
+/// ```
+/// // ["app.exe","a b","say \"hi\""]
+/// let mut json = String::new();
+/// winarg::to_json(&mut json).unwrap();
+/// eprintln!("started with {}", json);
+/// ```
+pub fn to_json<W: fmt::Write + ?Sized>(w: &mut W) -> fmt::Result {
+	w.write_char('[')?;
+	for (index, arg) in ArgsNative::from_env().enumerate() {
+		if index > 0 {
+			w.write_char(',')?;
+		}
+		write_json_string(w, arg.utf16_units())?;
+	}
+	w.write_char(']')
+}
+// Write `units` as a quoted JSON string, replacing isolated surrogates.
+fn write_json_string<W, I>(w: &mut W, units: I) -> fmt::Result
+where
+	W: fmt::Write + ?Sized,
+	I: Iterator<Item = u16>,
+{
+	w.write_char('"')?;
+	for c in decode_utf16(units) {
+		match c.unwrap_or(REPLACEMENT_CHARACTER) {
+			'"' => w.write_str("\\\"")?,
+			'\\' => w.write_str("\\\\")?,
+			c @ '\0'..='\u{1F}' => write!(w, "\\u{:04x}", u32::from(c))?,
+			c => w.write_char(c)?,
+		}
+	}
+	w.write_char('"')
+}
+// Write `units` as a quoted Rust string for `Debug`, replacing isolated
+// surrogates.
+#[cfg(feature = "alloc")]
+fn debug_units(f: &mut fmt::Formatter<'_>, units: &[u16]) -> fmt::Result {
+	f.write_str("\"")?;
+	for c in scalars(units.iter().copied()) {
+		write!(f, "{}", c.escape_debug())?;
+	}
+	f.write_str("\"")
+}
 /// A list of UTF-16 encoded arguments, separated by a NULL.
 /// ```
 /// let args: Vec<u16> = winarg::null_separated_list_wide().collect();
@@ -177,238 +820,1265 @@ pub fn null_separated_list_wide() -> impl Iterator<Item = u16> + fmt::Debug + Cl
 /// Can be encoded as scalars, code points or UTF-16 code units.
 /// Arguments can also be compared to `&str` or `&[u16]` slices.
 ///
+/// Windows command lines may contain isolated surrogates, which are not valid
+/// Unicode. Each method documents how it handles them. An argument containing
+/// an isolated surrogate is never equal to any `&str` but can be equal to a
+/// `&[u16]`, which compares the UTF-16 code units exactly.
+///
+/// ```
+/// if let Some(arg) = winarg::ArgsNative::from_env().next() {
+///     if arg == "binname.exe" {
+///         println!("Program was called as `binname.exe`");
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Argument {
+	arg: WideIter<'static>,
+	is_arg0: bool,
+}
+impl Argument {
+	/// Iterates scalar values. Isolated surrogates will be replaced with
+	/// the replacement character (`�`).
+	///
+	/// Each isolated surrogate is replaced by exactly one replacement character
+	/// and valid surrogate pairs are combined into a single `char`.
+	///
+	/// ```
+	/// for arg in winarg::args_native() {
+	///     let arg: String = arg.scalars().collect();
+	///     println!("{}", arg);
+	/// }
+	/// ```
+	pub fn scalars(&self) -> impl Iterator<Item = char> + fmt::Debug + Clone {
+		scalars(self.utf16_units())
+	}
+	/// Iterates scalar values, replacing isolated surrogates with
+	/// `replacement`.
+	///
+	/// This is useful if `�` already means something else in your output. If
+	/// `replacement` is `None`, isolated surrogates are left out entirely.
+	///
+	/// ```
+	/// for arg in winarg::args_native() {
+	///     let arg: String = arg.scalars_with(Some('\u{2370}')).collect();
+	///     println!("{}", arg);
+	/// }
+	/// ```
+	pub fn scalars_with(
+		&self,
+		replacement: Option<char>,
+	) -> impl Iterator<Item = char> + fmt::Debug + Clone {
+		scalars_with(self.utf16_units(), replacement)
+	}
+	/// Iterates code points. These are similar to scalar values except that
+	/// they may contain isolated surrogates.
+	///
+	/// Valid surrogate pairs are combined into a single code point. Isolated
+	/// surrogates are returned unchanged as values in the range
+	/// `0xD800..=0xDFFF`.
+	///
+	/// ```
+	/// use std::char;
+	///
+	/// for arg in winarg::args_native() {
+	///     let arg: String = arg.code_points()
+	///     .map(|cp| char::from_u32(cp)
+	///     .unwrap_or(char::REPLACEMENT_CHARACTER))
+	///     .collect();
+	///     println!("{}", arg);
+	/// }
+	/// ```
+	pub fn code_points(&self) -> impl Iterator<Item = u32> + fmt::Debug + Clone {
+		code_points(self.utf16_units())
+	}
+
+	/// Iterates UTF-16 code units. May contain isolated surrogates, which means it's invalid Unicode.
+	///
+	/// This is lossless. The code units are exactly those of the parsed argument.
+	///
+	/// ```
+	/// use std::os::windows::ffi::OsStringExt;
+	/// use std::ffi::OsString;
+	///
+	/// for arg in winarg::args_native() {
+	///     let arg: Vec<u16> = arg.utf16_units().collect();
+	///     let arg = OsString::from_wide(&arg);
+	///     println!("{:?}", arg);
+	/// }
+	/// ```
+	pub fn utf16_units(&self) -> impl Iterator<Item = u16> + fmt::Debug + Clone {
+		ParseArgs::new(self.arg, self.is_arg0)
+	}
+
+	/// Write the argument to `w`, replacing isolated surrogates with `�`.
+	///
+	/// This streams the argument's scalar values so nothing is allocated,
+	/// which is useful when `w` writes to a fixed size buffer or a device.
+	/// Stops at the first error from `w` and returns it. The `Display`
+	/// implementation uses this.
+	///
+	/// ```
+	/// use std::fmt::Write;
+	///
+	/// let mut out = String::new();
+	/// for arg in winarg::args_native().skip(1) {
+	///     arg.write_lossy(&mut out).unwrap();
+	///     out.push('\n');
+	/// }
+	/// ```
+	pub fn write_lossy<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+		self.scalars().try_for_each(|c| w.write_char(c))
+	}
+
+	/// Get the rest of the command line as a single, unparsed, argument. This
+	/// may contain quotes and escape characters.
+	///
+	/// While this is rarely used, it can be useful for passing on arguments to
+	/// other programs or doing non-standard parsing.
+	///
+	/// ```
+	/// let mut args = winarg::args_native();
+	/// let mut raw_args = None;
+	/// while let Some(arg) = args.next() {
+	///     if arg == "--" {
+	///          // Gets the next argument and call `raw_arg`.
+	///          raw_args = args.next().map(|arg| arg.raw_arg());
+	///          // Stop parsing the arguments.
+	///          break;
+	///     }
+	/// }
+	/// ```
+	pub fn raw_arg(&self) -> &'static [u16] {
+		self.arg.as_slice()
+	}
+
+	/// The argument as a slice of the command line, if parsing it doesn't
+	/// change anything.
+	///
+	/// This is true of any argument without quotes, because backslashes only
+	/// escape quotes. Most arguments are like this, so slice operations such
+	/// as comparing or hashing can often be used instead of iterating. `None`
+	/// is returned if the argument has a quote anywhere, even if the quotes
+	/// only surround it. Finding out needs a scan of the argument but nothing
+	/// is decoded.
+	///
+	/// ```
+	/// for arg in winarg::args_native() {
+	///     let units: Vec<u16> = match arg.as_raw_verbatim() {
+	///         Some(units) => units.to_vec(),
+	///         None => arg.utf16_units().collect(),
+	///     };
+	///     println!("{}", String::from_utf16_lossy(&units));
+	/// }
+	/// ```
+	pub fn as_raw_verbatim(&self) -> Option<&'static [u16]> {
+		let raw = self.arg.as_slice();
+		let len = raw
+			.iter()
+			.position(|&unit| matches!(unit, QUOTE | SPACE | TAB))
+			.unwrap_or(raw.len());
+		match raw.get(len) {
+			Some(&QUOTE) => None,
+			_ => raw.get(..len),
+		}
+	}
+
+	/// Returns `true` if this argument can be written to a command line and
+	/// read back unchanged, wherever it's placed.
+	///
+	/// Putting an argument back in the same position is always safe. But an
+	/// argument that's forwarded to another program may become its program
+	/// name, which can't contain quotes or, under [`QuotingRules::Legacy`],
+	/// control characters. See [`is_representable_with`].
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     if !arg.is_roundtrip_safe() {
+	///         eprintln!("{:?} can't be used as a program name", arg.raw_arg());
+	///     }
+	/// }
+	/// ```
+	pub fn is_roundtrip_safe(&self) -> bool {
+		quote::unrepresentable(self.utf16_units(), true, QuotingRules::Legacy).is_none()
+	}
+
+	/// Iterates UTF-16 code units with the ASCII letters `A` to `Z` changed to
+	/// lowercase.
+	///
+	/// This is not Unicode case folding. Every other code unit, including
+	/// non-ASCII letters and isolated surrogates, is returned unchanged so
+	/// `É` stays `É`. The length is always the same as
+	/// [`utf16_units`](Self::utf16_units). This is useful for making lookup
+	/// keys from arguments that are mostly ASCII, such as option names.
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     let key: Vec<u16> = arg.ascii_lowercase().collect();
+	///     let text: String = char::decode_utf16(arg.ascii_lowercase())
+	///         .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+	///         .collect();
+	///     println!("{} ({} code units)", text, key.len());
+	/// }
+	/// ```
+	pub fn ascii_lowercase(&self) -> impl Iterator<Item = u16> + fmt::Debug + Clone {
+		self.utf16_units().map(to_ascii_lowercase as fn(u16) -> u16)
+	}
+
+	/// Iterates UTF-16 code units with the ASCII letters `a` to `z` changed to
+	/// uppercase.
+	///
+	/// Like [`ascii_lowercase`](Self::ascii_lowercase), this is not Unicode
+	/// case folding and every other code unit is returned unchanged.
+	pub fn ascii_uppercase(&self) -> impl Iterator<Item = u16> + fmt::Debug + Clone {
+		self.utf16_units().map(to_ascii_uppercase as fn(u16) -> u16)
+	}
+
+	/// Returns `true` if the argument starts with `prefix`, ignoring the case
+	/// of ASCII letters.
+	///
+	/// Other characters, including non-ASCII letters, must match exactly. See
+	/// [`strip_prefix_with`](Self::strip_prefix_with) for other ways of
+	/// comparing.
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     if arg.starts_with_ignore_case("/out:") {
+	///         println!("an output option");
+	///     }
+	/// }
+	/// ```
+	pub fn starts_with_ignore_case(&self, prefix: &str) -> bool {
+		self.strip_prefix_ignore_case(prefix).is_some()
+	}
+
+	/// The code units after `prefix`, if the argument starts with `prefix`
+	/// ignoring the case of ASCII letters.
+	///
+	/// Only the prefix is compared without case. The rest of the argument is
+	/// returned as it was written.
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     if let Some(path) = arg.strip_prefix_ignore_case("/out:") {
+	///         let path: String = char::decode_utf16(path)
+	///             .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+	///             .collect();
+	///         println!("writing to {}", path);
+	///     }
+	/// }
+	/// ```
+	pub fn strip_prefix_ignore_case(&self, prefix: &str) -> Option<Utf16Units> {
+		self.strip_prefix_with(prefix, CaseSensitivity::AsciiInsensitive)
+	}
+
+	/// Compare the argument with `other`, ignoring the case of ASCII letters.
+	///
+	/// This is the same order as comparing both with their ASCII letters
+	/// changed to lowercase, so a table sorted by
+	/// [`str::to_ascii_lowercase`] can be searched with
+	/// [`binary_search_by`](slice::binary_search_by). The result is only
+	/// [`Equal`](cmp::Ordering::Equal) if the argument equals `other` ignoring
+	/// ASCII case, as [`str::eq_ignore_ascii_case`] would decide. An argument
+	/// that's a prefix of `other` is less than it. Isolated surrogates are
+	/// compared as their code point. Nothing is allocated.
+	///
+	/// ```
+	/// // Sorted by their lowercase names.
+	/// const COMMANDS: &[(&str, fn())] = &[("build", build), ("Run", run), ("TEST", test)];
+	/// # fn build() {}
+	/// # fn run() {}
+	/// # fn test() {}
+	///
+	/// if let Some(arg) = winarg::args_native().nth(1) {
+	///     match COMMANDS.binary_search_by(|&(name, _)| arg.cmp_ignore_case(name).reverse()) {
+	///         Ok(index) => (COMMANDS[index].1)(),
+	///         Err(_) => eprintln!("unknown command"),
+	///     }
+	/// }
+	/// ```
+	pub fn cmp_ignore_case(&self, other: &str) -> cmp::Ordering {
+		let other = other.chars().map(|c| u32::from(c.to_ascii_lowercase()));
+		code_points(self.ascii_lowercase()).cmp(other)
+	}
+
+	/// The code units after `prefix`, if the argument starts with `prefix`
+	/// when compared using `case`.
+	///
+	/// The rest of the argument is returned as it was written. With the
+	/// `ordinal-case` feature, [`CaseSensitivity::OrdinalInsensitive`] compares
+	/// letters the way the file system does.
+	///
+	/// ```
+	/// use winarg::CaseSensitivity;
+	///
+	/// for arg in winarg::args_native().skip(1) {
+	///     if let Some(rest) = arg.strip_prefix_with("--", CaseSensitivity::Sensitive) {
+	///         println!("a long option {} code units long", rest.count());
+	///     }
+	/// }
+	/// ```
+	pub fn strip_prefix_with(&self, prefix: &str, case: CaseSensitivity) -> Option<Utf16Units> {
+		let options = MatchOptions {
+			case,
+			prefixes: FlagStyle::Verbatim,
+		};
+		let mut units = self.into_iter();
+		options.match_name(&mut units, prefix)?;
+		Some(units)
+	}
+
+	/// Split a Windows style option, such as `/out:file.txt`, into its name
+	/// and value.
+	///
+	/// Returns `None` if the argument doesn't start with `/`. Otherwise the
+	/// name is everything after the `/` up to the first `:`. The value is
+	/// everything after the `:`, or `None` if there isn't one. Quotes have
+	/// already been removed so `"/out:a b"` and `/out:"a b"` both have the
+	/// value `a b`.
+	///
+	/// An absolute path such as `/home/file` also starts with `/`, so it's
+	/// split into the name `home/file` and no value. Use [`slash_value_of`] to
+	/// only find options with a particular name.
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     if let Some((name, value)) = arg.as_slash_option() {
+	///         let name = String::from_utf16_lossy(&name.collect::<Vec<u16>>());
+	///         let value = value.map(|v| String::from_utf16_lossy(&v.collect::<Vec<u16>>()));
+	///         println!("option {} = {:?}", name, value);
+	///     }
+	/// }
+	/// ```
+	#[allow(clippy::type_complexity)]
+	pub fn as_slash_option(
+		&self,
+	) -> Option<(
+		impl Iterator<Item = u16> + fmt::Debug + Clone,
+		Option<impl Iterator<Item = u16> + fmt::Debug + Clone>,
+	)> {
+		let (name_len, has_value) = self.slash_option_len()?;
+		let name = self.utf16_units().skip(1).take(name_len);
+		let value = if has_value {
+			Some(self.utf16_units().skip(name_len + 2))
+		} else {
+			None
+		};
+		Some((name, value))
+	}
+
+	// The length of the name of a slash option and whether it has a value.
+	fn slash_option_len(&self) -> Option<(usize, bool)> {
+		let mut units = self.utf16_units();
+		if units.next() != Some(FORWARD_SLASH) {
+			return None;
+		}
+		let mut len = 0;
+		for unit in units {
+			if unit == COLON {
+				return Some((len, true));
+			}
+			len += 1;
+		}
+		Some((len, false))
+	}
+
+	fn eq<I: Iterator<Item = u16>>(&self, other: I) -> bool {
+		self.utf16_units().eq(other)
+	}
+}
+impl fmt::Debug for Argument {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Argument")
+			.field("arg", &self.arg.as_ptr())
+			.field("is_arg0", &self.is_arg0)
+			.finish()
+	}
+}
+/// Isolated surrogates are replaced with `�`. See [`Argument::write_lossy`].
+impl fmt::Display for Argument {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.write_lossy(f)
+	}
+}
+impl Eq for Argument {}
+impl PartialEq<Argument> for Argument {
+	fn eq(&self, other: &Argument) -> bool {
+		self.eq(other.utf16_units())
+	}
+}
+impl PartialEq<Argument> for &str {
+	fn eq(&self, other: &Argument) -> bool {
+		other.eq(self.encode_utf16())
+	}
+}
+impl PartialEq<&str> for Argument {
+	fn eq(&self, other: &&str) -> bool {
+		self.eq(other.encode_utf16())
+	}
+}
+impl PartialEq<&[u16]> for Argument {
+	fn eq(&self, other: &&[u16]) -> bool {
+		self.eq(other.iter().copied())
+	}
+}
+impl PartialEq<Argument> for &[u16] {
+	fn eq(&self, other: &Argument) -> bool {
+		other.eq(self.iter().copied())
+	}
+}
+/// Compares with ASCII bytes, such as `b"--help"`.
+///
+/// Each byte is compared with a code unit. A byte of `0x80` or more is never
+/// equal to anything, so it makes the whole comparison false. Bytes aren't
+/// treated as Latin-1 or any other encoding.
+///
+/// ```
+/// for arg in winarg::args_native().skip(1) {
+///     if arg == b"--help" {
+///         println!("help me!");
+///     }
+/// }
+/// ```
+impl PartialEq<&[u8]> for Argument {
+	fn eq(&self, other: &&[u8]) -> bool {
+		let ascii = other
+			.iter()
+			.map(|&b| if b < 0x80 { Some(u16::from(b)) } else { None });
+		self.utf16_units().map(Some).eq(ascii)
+	}
+}
+impl PartialEq<Argument> for &[u8] {
+	fn eq(&self, other: &Argument) -> bool {
+		other == self
+	}
+}
+/// Compares with ASCII bytes. See the comparison with `&[u8]`.
+impl<const N: usize> PartialEq<&[u8; N]> for Argument {
+	fn eq(&self, other: &&[u8; N]) -> bool {
+		*self == &other[..]
+	}
+}
+impl<const N: usize> PartialEq<Argument> for &[u8; N] {
+	fn eq(&self, other: &Argument) -> bool {
+		*other == &self[..]
+	}
+}
+/// Iterates UTF-16 code units, the same as [`Argument::utf16_units`].
+///
+/// Each call starts from the beginning of the argument so a borrowed
+/// argument can be iterated any number of times.
+///
+/// ```
+/// fn count_spaces<I: IntoIterator<Item = u16>>(units: I) -> usize {
+///     units.into_iter().filter(|&u| u == u16::from(b' ')).count()
+/// }
+///
+/// for arg in winarg::args_native() {
+///     println!("{} spaces", count_spaces(&arg));
+///     for unit in &arg {
+///         print!("{:04x} ", unit);
+///     }
+/// }
+/// ```
+impl IntoIterator for &Argument {
+	type Item = u16;
+	type IntoIter = Utf16Units;
+	fn into_iter(self) -> Utf16Units {
+		Utf16Units(ParseArgs::new(self.arg, self.is_arg0))
+	}
+}
+/// Iterates UTF-16 code units, the same as [`Argument::utf16_units`].
+impl IntoIterator for Argument {
+	type Item = u16;
+	type IntoIter = Utf16Units;
+	fn into_iter(self) -> Utf16Units {
+		(&self).into_iter()
+	}
+}
+
+/// An iterator over the UTF-16 code units of an [`Argument`].
+///
+/// This is returned by iterating `Argument` or `&Argument`.
+#[derive(Clone, Debug)]
+pub struct Utf16Units(ParseArgs<'static>);
+impl Iterator for Utf16Units {
+	type Item = u16;
+	fn next(&mut self) -> Option<u16> {
+		self.0.next()
+	}
+}
+
+/// An iterator over native command line [`Argument`]s.
+///
+/// Cloning creates an independent iterator that starts from the same position.
+#[derive(Clone)]
+pub struct ArgsNative {
+	next: ParseArgs<'static>,
+}
+impl ArgsNative {
+	/// Get the command line arguments from the environment.
+	///
+	/// ```
+	/// use winarg::ArgsNative;
+	///
+	/// for arg in ArgsNative::from_env() {
+	///     let arg: String = arg.scalars().collect();
+	///     println!("{}", arg);
+	/// }
+	/// ```
+	pub fn from_env() -> Self {
+		let arg = ParseArgs::from_env();
+		Self { next: arg }
+	}
+
+	/// Skip arguments until `pred` returns `true`, and return the argument it
+	/// matched.
+	///
+	/// The iterator is left after the matching argument, so the rest of the
+	/// arguments follow. If nothing matches, `None` is returned and the
+	/// iterator is empty. The program name is tested too, so skip it first if
+	/// it shouldn't be.
+	///
+	/// Each skipped argument is only decoded as far as `pred` reads it.
+	///
+	/// ```
+	/// // Everything after `--target <name>` is passed on to the target.
+	/// let mut args = winarg::args_native();
+	/// if args.skip_until(|arg| *arg == "--target").is_some() {
+	///     let target = args.next();
+	///     let rest: Vec<String> = args.map(|arg| arg.to_string()).collect();
+	///     println!("{:?} {:?}", target.map(|arg| arg.to_string()), rest);
+	/// }
+	/// ```
+	pub fn skip_until<F>(&mut self, mut pred: F) -> Option<Argument>
+	where
+		F: FnMut(&Argument) -> bool,
+	{
+		self.find(|arg| pred(arg))
+	}
+
+	/// Skip arguments until one is equal to `arg`, and return it.
+	///
+	/// See [`skip_until`](Self::skip_until).
+	///
+	/// ```
+	/// let mut args = winarg::args_native();
+	/// if args.skip_until_eq("--").is_some() {
+	///     for arg in args {
+	///         println!("not a flag: {}", arg);
+	///     }
+	/// }
+	/// ```
+	pub fn skip_until_eq(&mut self, arg: &str) -> Option<Argument> {
+		self.skip_until(|a| *a == arg)
+	}
+}
+impl fmt::Debug for ArgsNative {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ArgsNative")
+			.field("next_arg", &self.next.cursor.as_ptr())
+			.field("is_arg0", &self.next.is_arg0)
+			.finish()
+	}
+}
+impl Iterator for ArgsNative {
+	type Item = Argument;
+	fn next(&mut self) -> Option<Self::Item> {
+		let current = Argument {
+			arg: self.next.cursor,
+			is_arg0: self.next.is_arg0,
+		};
+		if current.arg.peek() == None {
+			None
+		} else {
+			self.next.move_to_next_arg();
+			Some(current)
+		}
+	}
+}
+
+/// An iterator over the program's command line [`Argument`]s
+///
+/// The first argument is the program name. If the command line starts with
+/// whitespace, the program name is empty and the first word is the next
+/// argument, as it is for the C runtime. See [`LeadingWhitespace`].
+///
+/// ```
+/// for arg in winarg::args_native() {
+///     let arg: String = arg.scalars().collect();
+///     println!("{}", arg);
+/// }
+/// ```
+pub fn args_native() -> ArgsNative {
+	ArgsNative::from_env()
+}
+
+/// The zeroth argument, which is usually the name or path of the program.
+///
+/// The program name is whatever the parent process put at the start of the
+/// command line, so it may not be the path of the running executable. If the
+/// command line is empty or starts with whitespace, the argument is empty too.
+/// See [`LeadingWhitespace`].
+///
+/// ```
+/// let name: String = winarg::program_name().scalars().collect();
+/// println!("usage: {} [OPTIONS]", name);
+/// ```
+pub fn program_name() -> Argument {
+	ArgsNative::from_env().next().unwrap_or(Argument {
+		arg: WideIter::new(&[]),
+		is_arg0: true,
+	})
+}
+
+/// Split the command line at the first `--` argument.
+///
+/// Returns an iterator over the arguments before the `--`, starting with the
+/// zeroth argument, and the rest of the command line after the `--`. The rest
+/// of the command line is not parsed so that it can be passed on to another
+/// program verbatim. It's `None` if there is no `--` and empty if the `--` is
+/// the last argument.
+///
+/// Arguments are compared after parsing so a quoted `"--"` is also a
+/// separator. This is consistent with other parsers, which only see the
+/// parsed arguments. The zeroth argument is never a separator.
+///
+/// ```
+/// let (args, rest) = winarg::split_at_double_dash();
+/// for arg in args.skip(1) {
+///     let arg: String = arg.scalars().collect();
+///     println!("{}", arg);
+/// }
+/// if let Some(rest) = rest {
+///     println!("forwarding: {}", String::from_utf16_lossy(rest));
+/// }
+/// ```
+pub fn split_at_double_dash() -> (
+	impl Iterator<Item = Argument> + fmt::Debug,
+	Option<&'static [u16]>,
+) {
+	let mut args = ArgsNative::from_env();
+	let mut count = 0;
+	let mut rest = None;
+	while let Some(arg) = args.next() {
+		if count > 0 && arg == "--" {
+			rest = Some(args.next().map_or(&[][..], |arg| arg.raw_arg()));
+			break;
+		}
+		count += 1;
+	}
+	(ArgsNative::from_env().take(count), rest)
+}
+
+/// Split the command line into the program name and the rest of the command
+/// line.
+///
+/// The rest starts at the first argument after the program name and isn't
+/// parsed, so it can be passed on to another program verbatim. It's empty if
+/// there are no other arguments. Only the program name is parsed, using the
+/// rules for program names. See [`split_program_of`].
+///
+/// ```
+/// let (program, rest) = winarg::split_program();
+/// let program: String = program.scalars().collect();
+/// println!("{} was run with {}", program, String::from_utf16_lossy(rest));
+/// ```
+pub fn split_program() -> (Argument, &'static [u16]) {
+	let cmdline = command_line();
+	let (_, rest) = split_program_of(cmdline.as_slice());
+	let program = Argument {
+		arg: cmdline,
+		is_arg0: true,
+	};
+	(program, rest)
+}
+
+/// Split `cmdline` into the program name and the rest of the command line.
+///
+/// This is the same as [`split_program`] but for any command line. The
+/// program name is returned as its code units, parsed the same way as
+/// [`unquote_one`] with `is_program` set, so quotes are removed but
+/// backslashes never escape them. The rest of the command line starts after
+/// the whitespace that follows the program name and ends at the first null, if
+/// any.
+///
+/// If `cmdline` starts with whitespace, the program name is empty and the rest
+/// starts at the first argument, as it does for the C runtime. See
+/// [`LeadingWhitespace`].
+///
+/// ```
+/// let cmdline: Vec<u16> = r#""C:\Program Files\app.exe" "a b"  c"#.encode_utf16().collect();
+/// let (program, rest) = winarg::split_program_of(&cmdline);
+/// let program: Vec<u16> = program.collect();
+/// assert_eq!(String::from_utf16_lossy(&program), r"C:\Program Files\app.exe");
+/// assert_eq!(String::from_utf16_lossy(rest), r#""a b"  c"#);
+/// ```
+pub fn split_program_of(
+	cmdline: &[u16],
+) -> (impl Iterator<Item = u16> + fmt::Debug + Clone + '_, &[u16]) {
+	let (program, used) = unquote_one(cmdline, true);
+	let mut rest = WideIter::new(cmdline);
+	rest.slice = rest.slice.get(used..).unwrap_or_default();
+	rest.skip_whitespace(&[]);
+	(program, rest.as_slice())
+}
+
+/// The first argument after the program name that's equal to any of `names`.
+///
+/// Arguments are compared after parsing so a quoted `"--verbose"` matches
+/// `--verbose`. The search stops at the first match, or at a standalone `--`
+/// argument so options passed through to another program aren't seen, as in
+/// [`first_match`]. The other flag functions stop there too. Each comparison
+/// stops as soon as the argument and the name differ, including in length, so
+/// no more than one code unit past the end of a name is compared. Nothing is
+/// allocated.
+///
+/// ```
+/// if let Some(flag) = winarg::find_flag(&["-v", "--verbose"]) {
+///     println!("{} was passed", flag.scalars().collect::<String>());
+/// }
+/// ```
+pub fn find_flag(names: &[&str]) -> Option<Argument> {
+	find_flag_with(names, MatchOptions::default())
+}
+
+/// The first argument after the program name that matches any of `names`,
+/// using `options` to match them.
+///
+/// As with [`find_flag`], the search stops at a standalone `--`. See
+/// [`MatchOptions`].
+///
+/// ```
+/// use winarg::{CaseSensitivity, FlagStyle, MatchOptions};
+///
+/// let options = MatchOptions {
+///     case: CaseSensitivity::AsciiInsensitive,
+///     prefixes: FlagStyle::Slash,
+/// };
+/// if let Some(flag) = winarg::find_flag_with(&["nologo"], options) {
+///     println!("{} was passed", flag.scalars().collect::<String>());
+/// }
+/// ```
+pub fn find_flag_with(names: &[&str], options: MatchOptions) -> Option<Argument> {
+	flag_args().find(|arg| names.iter().any(|name| options.matches(arg, name)))
+}
+
+// The arguments after the program name and before a standalone `--`, which
+// are the only ones the flag functions look at.
+fn flag_args() -> impl Iterator<Item = Argument> {
+	ArgsNative::from_env()
+		.skip(1)
+		.take_while(|arg| *arg != "--")
+}
+
+/// Returns `true` if any argument after the program name, and before a
+/// standalone `--`, is equal to any of `names`.
+///
+/// See [`find_flag`] for how arguments are compared.
+///
+/// ```
+/// if winarg::has_flag(&["-h", "--help"]) {
+///     println!("help me!");
+/// }
+/// ```
+pub fn has_flag(names: &[&str]) -> bool {
+	find_flag(names).is_some()
+}
+
+/// Returns `true` if any argument after the program name, and before a
+/// standalone `--`, matches any of `names`, using `options` to match them.
+///
+/// See [`find_flag`] and [`MatchOptions`].
+pub fn has_flag_with(names: &[&str], options: MatchOptions) -> bool {
+	find_flag_with(names, options).is_some()
+}
+
+/// The first argument that's equal to any of `names`, along with its index.
+///
+/// The program name, at index `0`, is never matched and the search stops at a
+/// standalone `--` argument, so options passed through to another program
+/// aren't seen. Arguments are compared after parsing, as in [`find_flag`].
+/// Nothing is allocated.
+///
+/// ```
+/// if let Some((index, arg)) = winarg::first_match(&["-q", "--quiet"]) {
+///     println!("argument {} is {}", index, arg.scalars().collect::<String>());
+/// }
+/// ```
+pub fn first_match(names: &[&str]) -> Option<(usize, Argument)> {
+	ArgsNative::from_env()
+		.enumerate()
+		.skip(1)
+		.take_while(|(_, arg)| *arg != "--")
+		.find(|(_, arg)| names.iter().any(|name| arg == name))
+}
+
+/// Returns `true` if any argument before a standalone `--` is equal to any of
+/// `names`.
+///
+/// This is useful for checking for `--help` or `--version` before anything
+/// else, wherever they appear. See [`first_match`].
+///
+/// ```
+/// if winarg::contains_any(&["-h", "--help", "/?", "-?"]) {
+///     println!("usage: app [OPTIONS] FILE");
+///     return;
+/// }
+/// if winarg::contains_any(&["--version"]) {
+///     println!("app 1.0");
+///     return;
+/// }
+/// // Do the real work.
+/// ```
+pub fn contains_any(names: &[&str]) -> bool {
+	first_match(names).is_some()
+}
+
+/// The value of the option `name`, which can be written as `--name=value` or
+/// as `--name value`.
+///
+/// Only arguments after the program name and before a standalone `--` are
+/// searched, as in [`find_flag`]. If the option is given more than once, the
+/// last one wins. An argument used as a value is never treated as an option
+/// itself, so in `--out --out` the value is `--out`. But `--` still ends the
+/// search, so `--out --` has no value.
+///
+/// If the last occurrence is the last argument, so it has no value, the result
+/// is `None` even if an earlier occurrence had a value. `--name=` has an empty
+/// value.
+///
+/// ```
+/// if let Some(out) = winarg::value_of("--out") {
+///     println!("writing to {}", out.scalars().collect::<String>());
+/// }
+/// ```
+pub fn value_of(name: &str) -> Option<FlagValue> {
+	value_of_with(name, MatchOptions::default())
+}
+
+/// The value of the option `name`, using `options` to match the name.
+///
+/// See [`value_of`] and [`MatchOptions`]. As with [`value_of`], the search
+/// stops at a standalone `--`. The value is never case folded.
+///
 /// ```
-/// if let Some(arg) = winarg::ArgsNative::from_env().next() {
-///     if arg == "binname.exe" {
-///         println!("Program was called as `binname.exe`");
-///     }
+/// use winarg::{CaseSensitivity, MatchOptions};
+///
+/// let options = MatchOptions {
+///     case: CaseSensitivity::AsciiInsensitive,
+///     ..MatchOptions::default()
+/// };
+/// // Matches `--out`, `--OUT` or `--Out`.
+/// if let Some(out) = winarg::value_of_with("--out", options) {
+///     println!("writing to {}", out.scalars().collect::<String>());
 /// }
 /// ```
-#[derive(Clone)]
-pub struct Argument {
-	arg: WideIter,
-	is_arg0: bool,
+pub fn value_of_with(name: &str, options: MatchOptions) -> Option<FlagValue> {
+	occurrences(name, options).last().flatten()
 }
-impl Argument {
-	/// Iterates scalar values. Isolated surrogates will be replaced with
-	/// the replacement character (`�`).
+
+// Each occurrence of the option `name` along with its value, if it has one.
+pub(crate) fn occurrences(
+	name: &str,
+	options: MatchOptions,
+) -> impl Iterator<Item = Option<FlagValue>> + '_ {
+	let mut args = flag_args();
+	core::iter::from_fn(move || {
+		for arg in &mut args {
+			if options.matches(&arg, name) {
+				return Some(args.next().map(|arg| FlagValue { arg, start: 0 }));
+			} else if let Some(start) = options.value_start(&arg, name, EQUALS) {
+				return Some(Some(FlagValue { arg, start }));
+			}
+		}
+		None
+	})
+}
+
+/// The value of an option, found by [`value_of`] or [`slash_value_of`].
+///
+/// This is either a whole argument or the part of an argument after the `=`
+/// or `:`.
+#[derive(Clone, Debug)]
+pub struct FlagValue {
+	arg: Argument,
+	// The number of code units before the value.
+	start: usize,
+}
+impl FlagValue {
+	/// Iterates scalar values. Isolated surrogates are replaced with `�`.
 	///
-	/// ```
-	/// for arg in winarg::args_native() {
-	///     let arg: String = arg.scalars().collect();
-	///     println!("{}", arg);
-	/// }
-	/// ```
+	/// See [`Argument::scalars`].
 	pub fn scalars(&self) -> impl Iterator<Item = char> + fmt::Debug + Clone {
 		scalars(self.utf16_units())
 	}
-	/// Iterates code points. These are similar to scalar values except that
-	/// they may contain isolated surrogates.
+	/// Iterates code points, which may include isolated surrogates.
 	///
-	/// ```
-	/// use std::char;
-	///
-	/// for arg in winarg::args_native() {
-	///     let arg: String = arg.code_points()
-	///     .map(|cp| char::from_u32(cp)
-	///     .unwrap_or(char::REPLACEMENT_CHARACTER))
-	///     .collect();
-	///     println!("{}", arg);
-	/// }
-	/// ```
+	/// See [`Argument::code_points`].
 	pub fn code_points(&self) -> impl Iterator<Item = u32> + fmt::Debug + Clone {
 		code_points(self.utf16_units())
 	}
-
-	/// Iterates UTF-16 code units. May contain isolated surrogates, which means it's invalid Unicode.
-	///
-	/// ```
-	/// use std::os::windows::ffi::OsStringExt;
-	/// use std::ffi::OsString;
-	///
-	/// for arg in winarg::args_native() {
-	///     let arg: Vec<u16> = arg.utf16_units().collect();
-	///     let arg = OsString::from_wide(&arg);
-	///     println!("{:?}", arg);
-	/// }
-	/// ```
+	/// Iterates UTF-16 code units. This is lossless.
 	pub fn utf16_units(&self) -> impl Iterator<Item = u16> + fmt::Debug + Clone {
-		ParseArgs::new(self.arg, self.is_arg0)
+		self.units()
 	}
-
-	/// Get the rest of the command line as a single, unparsed, argument. This
-	/// may contain quotes and escape characters.
-	///
-	/// While this is rarely used, it can be useful for passing on arguments to
-	/// other programs or doing non-standard parsing.
-	///
-	/// ```
-	/// let mut args = winarg::args_native();
-	/// let mut raw_args = None;
-	/// while let Some(arg) = args.next() {
-	///     if arg == "--" {
-	///          // Gets the next argument and call `raw_arg`.
-	///          raw_args = args.next().map(|arg| arg.raw_arg());
-	///          // Stop parsing the arguments.
-	///          break;
-	///     }
-	/// }
-	/// ```
-	pub fn raw_arg(&self) -> &'static [u16] {
-		// SAFETY: `GetCommandLineW`'s memory is never freed for the lifetime of the process.
-		unsafe { self.arg.as_slice() }
+	/// The whole argument the value came from. For `--name=value` this
+	/// includes the `--name=`.
+	pub fn argument(&self) -> &Argument {
+		&self.arg
 	}
-
-	fn eq<I: Iterator<Item = u16>>(&self, other: I) -> bool {
-		self.utf16_units().eq(other)
+	/// Returns `true` if the value came from a separate argument, as in
+	/// `--name value`.
+	pub fn is_separate(&self) -> bool {
+		self.start == 0
 	}
-}
-impl fmt::Debug for Argument {
-	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		f.debug_struct("Argument")
-			.field("arg", &self.arg.ptr)
-			.field("is_arg0", &self.is_arg0)
-			.finish()
+	// The code units of the value.
+	fn units(&self) -> Utf16Units {
+		let mut units = (&self.arg).into_iter();
+		if let Some(before) = self.start.checked_sub(1) {
+			units.nth(before);
+		}
+		units
 	}
 }
-impl Eq for Argument {}
-impl PartialEq<Argument> for Argument {
-	fn eq(&self, other: &Argument) -> bool {
-		self.eq(other.utf16_units())
+impl PartialEq<&str> for FlagValue {
+	fn eq(&self, other: &&str) -> bool {
+		self.utf16_units().eq(other.encode_utf16())
 	}
 }
-impl PartialEq<Argument> for &str {
-	fn eq(&self, other: &Argument) -> bool {
-		other.eq(self.encode_utf16())
+impl PartialEq<&[u16]> for FlagValue {
+	fn eq(&self, other: &&[u16]) -> bool {
+		self.utf16_units().eq(other.iter().copied())
 	}
 }
-impl PartialEq<&str> for Argument {
-	fn eq(&self, other: &&str) -> bool {
-		self.eq(other.encode_utf16())
+impl PartialEq<FlagValue> for &str {
+	fn eq(&self, other: &FlagValue) -> bool {
+		other == self
 	}
 }
-impl PartialEq<&[u16]> for Argument {
-	fn eq(&self, other: &&[u16]) -> bool {
-		self.eq(other.iter().copied())
+impl PartialEq<FlagValue> for &[u16] {
+	fn eq(&self, other: &FlagValue) -> bool {
+		other == self
 	}
 }
-impl PartialEq<Argument> for &[u16] {
-	fn eq(&self, other: &Argument) -> bool {
-		other.eq(self.iter().copied())
-	}
+
+/// The value of the Windows style option `/name:value`.
+///
+/// `name` doesn't include the `/`. Only arguments after the program name that
+/// start with `/` and have a `:` are considered, and only if the part before
+/// the `:` is `name`. So absolute paths such as `/home/file` are never mistaken
+/// for options. As in [`find_flag`], the search stops at a standalone `--`.
+/// If the option is given more than once, the last one wins. The value may be
+/// empty, as in `/out:`.
+///
+/// If `case_insensitive` is true, ASCII letters in the name match either case,
+/// as most Windows tools do.
+///
+/// ```
+/// if let Some(out) = winarg::slash_value_of("out", true) {
+///     println!("writing to {}", out.scalars().collect::<String>());
+/// }
+/// ```
+pub fn slash_value_of(name: &str, case_insensitive: bool) -> Option<FlagValue> {
+	let case = if case_insensitive {
+		CaseSensitivity::AsciiInsensitive
+	} else {
+		CaseSensitivity::Sensitive
+	};
+	let options = MatchOptions {
+		case,
+		prefixes: FlagStyle::Slash,
+	};
+	slash_value_of_with(name, options)
 }
 
-/// An iterator over native command line [`Argument`]s.
-pub struct ArgsNative {
-	next: ParseArgs,
+/// The value of the option `name:value`, using `options` to match the name.
+///
+/// This is [`slash_value_of`] with other prefixes, and also stops at a
+/// standalone `--`. With [`FlagStyle::Verbatim`], `name` includes its prefix,
+/// such as `/out`. The value is never case folded.
+///
+/// ```
+/// use winarg::{CaseSensitivity, FlagStyle, MatchOptions};
+///
+/// let options = MatchOptions {
+///     case: CaseSensitivity::AsciiInsensitive,
+///     prefixes: FlagStyle::SlashOrDash,
+/// };
+/// // Matches `/out:file` or `-OUT:file`.
+/// if let Some(out) = winarg::slash_value_of_with("out", options) {
+///     println!("writing to {}", out.scalars().collect::<String>());
+/// }
+/// ```
+pub fn slash_value_of_with(name: &str, options: MatchOptions) -> Option<FlagValue> {
+	flag_args()
+		.filter_map(|arg| {
+			let start = options.value_start(&arg, name, COLON)?;
+			Some(FlagValue { arg, start })
+		})
+		.last()
 }
-impl ArgsNative {
-	/// Get the command line arguments from the environment.
-	///
-	/// ```
-	/// use winarg::ArgsNative;
+
+/// The length of the current process's command line, in UTF-16 code units.
+///
+/// This doesn't include the null terminator so it can be compared to
+/// [`MAX_COMMAND_LINE`] and [`quoted_len`].
+///
+/// ```
+/// assert!(winarg::command_line_len() <= winarg::MAX_COMMAND_LINE);
+/// ```
+pub fn command_line_len() -> usize {
+	command_line().as_slice().len()
+}
+
+/// The whole command line of the current process, without the null
+/// terminator.
+///
+/// This is the string from `GetCommandLineW`, exactly as the parent process
+/// wrote it, including the program name with any quotes around it. It's
+/// `'static` because Windows never frees or moves it. The length is worked out
+/// the first time it's needed and then remembered.
+///
+/// ```
+/// let cmdline = winarg::raw_command_line();
+/// eprintln!("started with {}", String::from_utf16_lossy(cmdline));
+/// ```
+pub fn raw_command_line() -> &'static [u16] {
+	command_line().as_slice()
+}
+
+/// Parse a single argument from the start of `input`.
+///
+/// Returns the code units of the argument, with quotes and escapes removed,
+/// and the number of code units of `input` that the argument used. The
+/// argument ends at the first space or tab outside of quotes, which isn't
+/// included in the count. A null ends the input, as it does a command line.
+///
+/// Whitespace at the start of `input` isn't skipped. If there is any, the
+/// argument is empty and no input is used. If `is_program` is true, the
+/// argument is parsed using the rules for the program name, where backslashes
+/// don't escape quotes.
+///
+/// ```
+/// let input: Vec<u16> = r#""a b"\"c d"#.encode_utf16().collect();
+/// let (arg, used) = winarg::unquote_one(&input, false);
+/// assert_eq!(String::from_utf16(&arg.collect::<Vec<u16>>()).unwrap(), r#"a b"c"#);
+/// assert_eq!(used, 8);
+/// ```
+pub fn unquote_one(
+	input: &[u16],
+	is_program: bool,
+) -> (impl Iterator<Item = u16> + fmt::Debug + Clone + '_, usize) {
+	unquote_one_with(input, is_program, ParserRules::Modern)
+}
+
+/// Like [`unquote_one`] but parses using `rules`.
+///
+/// ```
+/// use winarg::ParserRules;
+///
+/// let input: Vec<u16> = r#""a""b""#.encode_utf16().collect();
+/// let (arg, _) = winarg::unquote_one_with(&input, false, ParserRules::Pre2008);
+/// assert_eq!(String::from_utf16(&arg.collect::<Vec<u16>>()).unwrap(), "ab");
+/// ```
+pub fn unquote_one_with(
+	input: &[u16],
+	is_program: bool,
+	rules: ParserRules,
+) -> (impl Iterator<Item = u16> + fmt::Debug + Clone + '_, usize) {
+	let end = input.iter().position(|&u| u == 0).unwrap_or(input.len());
+	let mut parser = ParseArgs::new(WideIter::new(&input[..end]), is_program);
+	parser.rules = rules;
+	let mut rest = parser.clone();
+	while rest.next().is_some() {}
+	(parser, rest.offset())
+}
+
+/// An error returned when an argument is not valid Unicode.
+///
+/// Windows allows arguments to contain isolated surrogates, which cannot be
+/// converted to a `String` without loss.
+///
+/// The error gives the argument and the position of the surrogate within it.
+/// Use [`InvalidUnicodeError::context`] to show the text around it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidUnicodeError {
+	arg_index: Option<usize>,
+	offset: usize,
+	unit: u16,
+}
+impl InvalidUnicodeError {
+	/// The isolated surrogate that could not be decoded.
+	pub fn unpaired_surrogate(self) -> u16 {
+		self.unit
+	}
+	/// The index of the argument on the command line, if known. The program
+	/// name is `0`.
+	pub fn arg_index(self) -> Option<usize> {
+		self.arg_index
+	}
+	/// The position of the surrogate within the argument, in UTF-16 code units.
+	pub fn offset(self) -> usize {
+		self.offset
+	}
+	/// The code units either side of the surrogate, for showing in a
+	/// diagnostic.
 	///
-	/// for arg in ArgsNative::from_env() {
-	///     let arg: String = arg.scalars().collect();
-	///     println!("{}", arg);
-	/// }
-	/// ```
-	pub fn from_env() -> Self {
-		let arg = ParseArgs::from_env();
-		Self { next: arg }
+	/// `arg` must be the argument that caused the error. The result displays a
+	/// few code units either side of the surrogate, escaped like a Rust string.
+	pub fn context<I: IntoIterator<Item = u16>>(self, arg: I) -> InvalidUnicodeContext {
+		let start = self.offset.saturating_sub(CONTEXT_RADIUS);
+		let mut context = InvalidUnicodeContext {
+			units: [0; CONTEXT_RADIUS * 2 + 1],
+			start: 0,
+			end: 0,
+			before: start > 0,
+			after: false,
+		};
+		let mut iter = arg.into_iter().skip(start);
+		for (slot, unit) in context.units.iter_mut().zip(iter.by_ref()) {
+			*slot = unit;
+			context.end += 1;
+		}
+		context.after = iter.next().is_some();
+		// Don't split a surrogate pair at either edge.
+		if context.before && context.end > 0 && is_low_surrogate(context.units[0]) {
+			context.start = 1;
+		}
+		let last = context.units.get(context.end.wrapping_sub(1)).copied();
+		if context.after && last.is_some_and(is_high_surrogate) {
+			context.end -= 1;
+		}
+		context
 	}
 }
-impl fmt::Debug for ArgsNative {
+impl fmt::Display for InvalidUnicodeError {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		f.debug_struct("ArgsNative")
-			.field("next_arg", &self.next.cursor.ptr)
-			.field("is_arg0", &self.next.is_arg0)
-			.finish()
+		match self.arg_index {
+			Some(index) => write!(f, "argument {}", index)?,
+			None => f.write_str("argument")?,
+		}
+		write!(
+			f,
+			" contains an unpaired surrogate ({:#06x}) at offset {}",
+			self.unit, self.offset
+		)
 	}
 }
-impl Iterator for ArgsNative {
-	type Item = Argument;
-	fn next(&mut self) -> Option<Self::Item> {
-		let current = Argument {
-			arg: self.next.cursor,
-			is_arg0: self.next.is_arg0,
-		};
-		if current.arg.peek() == None {
-			None
-		} else {
-			self.next.move_to_next_arg();
-			Some(current)
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidUnicodeError {}
+
+// How many code units either side of an error are shown.
+const CONTEXT_RADIUS: usize = 8;
+
+/// The code units around an [`InvalidUnicodeError`].
+///
+/// Created by [`InvalidUnicodeError::context`]. Its `Display` implementation
+/// shows the code units in quotes, with `...` where the argument continues.
+#[derive(Clone, Copy, Debug)]
+pub struct InvalidUnicodeContext {
+	units: [u16; CONTEXT_RADIUS * 2 + 1],
+	start: usize,
+	end: usize,
+	// Whether the argument continues before or after the units.
+	before: bool,
+	after: bool,
+}
+impl fmt::Display for InvalidUnicodeContext {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.before {
+			f.write_str("...")?;
+		}
+		f.write_str("\"")?;
+		let units = self.units.get(self.start..self.end).unwrap_or_default();
+		for c in decode_utf16(units.iter().copied()) {
+			match c {
+				Ok(c) => write!(f, "{}", c.escape_debug())?,
+				Err(e) => write!(f, "\\u{{{:x}}}", e.unpaired_surrogate())?,
+			}
+		}
+		f.write_str("\"")?;
+		if self.after {
+			f.write_str("...")?;
 		}
+		Ok(())
 	}
 }
 
-/// An iterator over the program's command line [`Argument`]s
-///
-/// ```
-/// for arg in winarg::args_native() {
-///     let arg: String = arg.scalars().collect();
-///     println!("{}", arg);
-/// }
-/// ```
-pub fn args_native() -> ArgsNative {
-	ArgsNative::from_env()
+fn is_high_surrogate(unit: u16) -> bool {
+	(0xD800..0xDC00).contains(&unit)
+}
+fn is_low_surrogate(unit: u16) -> bool {
+	(0xDC00..0xE000).contains(&unit)
 }
 
-/// Simple iterator to encapsulate the unsafety inherent in using a null terminated array without a length.
+/// Simple iterator over a wide string.
+///
+/// This is always backed by a slice that does not include the null
+/// terminator. The only unsafety is in [`WideIter::from_ptr`], which finds the
+/// length of a null terminated string such as the one from `GetCommandLineW`.
 #[derive(Copy, Clone, Debug)]
-struct WideIter {
-	ptr: *const u16,
+struct WideIter<'a> {
+	slice: &'a [u16],
 }
-impl WideIter {
+impl<'a> WideIter<'a> {
+	/// Creates an iterator over `slice`, up to the first null (if any).
+	fn new(slice: &'a [u16]) -> Self {
+		let len = slice.iter().position(|&w| w == 0).unwrap_or(slice.len());
+		Self {
+			slice: &slice[..len],
+		}
+	}
 	/// # SAFETY
 	/// * `ptr` must point to a NULL terminated `u16` array.
-	/// * The array pointed to by `ptr` must exist for the lifetime of this struct.
-	unsafe fn new(ptr: *const u16) -> Self {
-		Self { ptr }
+	/// * The array pointed to by `ptr` must be valid and unmodified for `'a`.
+	#[cfg(windows)]
+	unsafe fn from_ptr(ptr: *const u16) -> Self {
+		let mut len = 0;
+		while *ptr.add(len) != 0 {
+			len += 1;
+		}
+		Self::new(core::slice::from_raw_parts(ptr, len))
 	}
 	fn next(&mut self) -> Option<u16> {
-		// SAFETY: The call to `peek` makes sure we haven't reached the NULL yet.
-		// Therefore it's safe to advance the pointer.
-		unsafe {
-			let next = self.peek()?;
-			self.ptr = self.ptr.add(1);
-			Some(next)
-		}
+		let (&next, rest) = self.slice.split_first()?;
+		self.slice = rest;
+		Some(next)
 	}
 	fn peek(&self) -> Option<u16> {
-		// SAFETY: It's always safe to read the current item because we don't
-		// ever move out of the array bounds.
-		match unsafe { *self.ptr } {
-			0 => None,
-			next => Some(next),
-		}
+		self.slice.first().copied()
 	}
-	// SAFETY: The lifetime of the slice cannot outlive the lifetime of the memory.
-	// This is not a problem for 'static memory.
-	unsafe fn as_slice<'a>(self) -> &'a [u16] {
-		let mut len = 0;
-		while *self.ptr.add(len) != 0 {
-			len += 1;
-		}
-		slice::from_raw_parts(self.ptr, len)
+	fn as_slice(self) -> &'a [u16] {
+		self.slice
+	}
+	fn as_ptr(self) -> *const u16 {
+		self.slice.as_ptr()
 	}
 
-	fn max_len(mut self) -> u16 {
-		let mut len: u16 = 0;
-		while self.next().is_some() {
-			len += 1;
-		}
-		len
+	fn max_len(self) -> usize {
+		self.slice.len()
 	}
 
-	fn skip_whitespace(&mut self) {
-		while self.peek() == Some(SPACE) || self.peek() == Some(TAB) {
+	/// Skip spaces, tabs and any of the `extra` separators.
+	fn skip_whitespace(&mut self, extra: &[u16]) {
+		while self.peek().is_some_and(|w| is_separator(w, extra)) {
 			self.next();
 		}
 	}
 }
+/// Whether `unit` separates arguments when it's not in quotes. `extra` is a
+/// sorted list of separators as well as space and tab. Quotes and backslashes
+/// are never separators.
+fn is_separator(unit: u16, extra: &[u16]) -> bool {
+	match unit {
+		SPACE | TAB => true,
+		QUOTE | SLASH => false,
+		_ => extra.binary_search(&unit).is_ok(),
+	}
+}
 // TODO: Don't implement iterator?
-impl Iterator for WideIter {
+impl Iterator for WideIter<'_> {
 	type Item = u16;
 	fn next(&mut self) -> Option<Self::Item> {
 		self.next()
@@ -424,16 +2094,22 @@ enum EscapeMode {
 struct EscapeIter {
 	counter: u16,
 	mode: EscapeMode,
+	// Where the run of slashes starts and where the quote is, if any.
+	offset: usize,
+	quote_offset: usize,
 }
 impl EscapeIter {
 	// Count the number of consecutive slashes and check if it ends with a quote.
-	fn new(iter: &mut WideIter) -> Self {
+	// `offset` is the position of the first slash, which has already been read.
+	fn new(iter: &mut WideIter<'_>, offset: usize) -> Self {
 		let mut counter: u16 = 1;
 		let mut mode = EscapeMode::Unescaped;
+		let mut quote_offset = 0;
 		loop {
 			match iter.peek() {
 				Some(SLASH) => counter += 1,
 				Some(QUOTE) => {
+					quote_offset = offset + usize::from(counter);
 					// If the counter is odd then output a literal quote instead
 					// of toggling quote mode.
 					if counter.is_odd() {
@@ -449,30 +2125,28 @@ impl EscapeIter {
 			}
 			iter.next();
 		}
-		Self { counter, mode }
+		Self {
+			counter,
+			mode,
+			offset,
+			quote_offset,
+		}
 	}
-	// Drain the saved state.
-	fn next(&mut self) -> Option<u16> {
+	// Drain the saved state, along with the offset each unit came from.
+	fn next(&mut self) -> Option<(u16, usize)> {
 		match self.counter.checked_sub(1) {
 			Some(n) => {
 				self.counter = n;
-				Some(SLASH)
+				Some((SLASH, self.offset))
 			}
 			None if self.mode == EscapeMode::LiteralQuote => {
 				self.mode = EscapeMode::Unescaped;
-				Some(QUOTE)
+				Some((QUOTE, self.quote_offset))
 			}
 			None => None,
 		}
 	}
 }
-impl Iterator for EscapeIter {
-	type Item = u16;
-
-	fn next(&mut self) -> Option<Self::Item> {
-		self.next()
-	}
-}
 
 /// An iterator over the UTF-16 code units of an argument
 ///
@@ -481,44 +2155,70 @@ impl Iterator for EscapeIter {
 /// This should be considered unstable because it's essentially leaking an
 /// implementation detail.
 #[derive(Clone, Debug)]
-struct ParseArgs {
-	cursor: WideIter,
+struct ParseArgs<'a> {
+	cursor: WideIter<'a>,
 	quote_mode: bool,
 	escape_iter: Option<EscapeIter>,
 	is_arg0: bool,
+	// The whole string being parsed, used to find offsets into it.
+	input: &'a [u16],
+	// Separators other than space and tab, sorted. This is only set when
+	// parsing leniently, which doesn't match Windows.
+	separators: &'a [u16],
+	rules: ParserRules,
 }
-impl ParseArgs {
+impl ParseArgs<'static> {
 	/// Creates an `ArgIter` from the environment, starting at the zeroth
 	/// argument.
 	fn from_env() -> Self {
 		Self::new(command_line(), true)
 	}
-	#[cfg(test)]
-	// For testing only.
-	// SAFETY: `lpwstr` must point to valid null terminated wide string.
-	// It must live at least as long as this struct and must not be modified.
-	unsafe fn from_ptr(lpwstr: *const u16) -> Self {
-		Self::new(WideIter::new(lpwstr), true)
-	}
-	fn new(arg: WideIter, is_arg0: bool) -> Self {
+}
+impl<'a> ParseArgs<'a> {
+	fn new(arg: WideIter<'a>, is_arg0: bool) -> Self {
 		Self {
 			cursor: arg,
 			quote_mode: false,
 			escape_iter: None,
 			is_arg0,
+			input: arg.as_slice(),
+			separators: &[],
+			rules: ParserRules::Modern,
 		}
 	}
+	/// The position of the cursor in the string being parsed.
+	fn offset(&self) -> usize {
+		self.input.len() - self.cursor.max_len()
+	}
+	/// The number of slashes just before the cursor.
+	fn pending_slashes(&self) -> usize {
+		// Skip the unread part from the end, rather than slicing, so there's no
+		// bounds check that could panic.
+		self.input
+			.iter()
+			.rev()
+			.skip(self.cursor.max_len())
+			.take_while(|&&w| w == SLASH)
+			.count()
+	}
 	/// Jump to the next argument. If there are any remaining characters in the
 	/// current argument then they will be skipped.
 	fn move_to_next_arg(&mut self) {
 		while self.next().is_some() {}
-		self.cursor.skip_whitespace();
+		self.cursor.skip_whitespace(self.separators);
 		self.is_arg0 = false;
 	}
-}
-impl Iterator for ParseArgs {
-	type Item = u16;
-	fn next(&mut self) -> Option<Self::Item> {
+	/// Get the next code unit along with the offset of the code unit in the
+	/// string being parsed that produced it.
+	fn next_spanned(&mut self) -> Option<(u16, usize)> {
+		self.next_visit(&mut |_, _| {})
+	}
+	/// Like `next_spanned` but also calls `visit` with each part of the string
+	/// as it's read, along with what the parser did with it.
+	fn next_visit<F>(&mut self, visit: &mut F) -> Option<(u16, usize)>
+	where
+		F: FnMut(SpanKind, Range<usize>),
+	{
 		loop {
 			// Consume any possibly escaped characters.
 			if let Some(iter) = self.escape_iter.as_mut() {
@@ -530,33 +2230,81 @@ impl Iterator for ParseArgs {
 
 			// Parse the arguments.
 			match self.cursor.peek()? {
-				SPACE | TAB if not(self.quote_mode) => {
+				w if not(self.quote_mode) && is_separator(w, self.separators) => {
 					return None;
 				}
 				SLASH if not(self.is_arg0) => {
+					let offset = self.offset();
 					self.cursor.next();
-					let slashes = EscapeIter::new(&mut self.cursor);
+					let slashes = EscapeIter::new(&mut self.cursor, offset);
+					let end = self.offset();
+					if slashes.mode == EscapeMode::LiteralQuote {
+						visit(SpanKind::Escape, offset..end - 1);
+						visit(SpanKind::Literal, end - 1..end);
+					} else if self.cursor.peek() == Some(QUOTE) {
+						visit(SpanKind::Escape, offset..end);
+					} else {
+						visit(SpanKind::Literal, offset..end);
+					}
 					self.escape_iter = Some(slashes);
 				}
 				QUOTE => {
+					let offset = self.offset();
 					self.cursor.next();
-					if not(self.is_arg0) && self.quote_mode && self.cursor.peek() == Some(QUOTE) {
-						return self.cursor.next();
+					visit(SpanKind::Quote, offset..offset + 1);
+					if not(self.is_arg0)
+						&& self.rules == ParserRules::Modern
+						&& self.quote_mode && self.cursor.peek() == Some(QUOTE)
+					{
+						visit(SpanKind::Literal, offset + 1..offset + 2);
+						return self.cursor.next().map(|w| (w, offset + 1));
 					} else {
 						self.quote_mode.toggle();
 					}
 				}
-				_ => return self.cursor.next(),
+				_ => {
+					let offset = self.offset();
+					visit(SpanKind::Literal, offset..offset + 1);
+					return self.cursor.next().map(|w| (w, offset));
+				}
 			}
 		}
 	}
 }
+impl Iterator for ParseArgs<'_> {
+	type Item = u16;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.next_spanned().map(|(w, _)| w)
+	}
+}
 
 fn scalars<I: Iterator<Item = u16> + fmt::Debug + Clone>(
 	iter: I,
 ) -> impl Iterator<Item = char> + fmt::Debug + Clone {
 	decode_utf16(iter).map(|u| u.unwrap_or(REPLACEMENT_CHARACTER))
 }
+fn scalars_with<I: Iterator<Item = u16> + fmt::Debug + Clone>(
+	iter: I,
+	replacement: Option<char>,
+) -> impl Iterator<Item = char> + fmt::Debug + Clone {
+	decode_utf16(iter).filter_map(move |u| u.ok().or(replacement))
+}
+// Map `A` to `Z` to lowercase, leaving every other code unit alone.
+fn to_ascii_lowercase(unit: u16) -> u16 {
+	if (u16::from(b'A')..=u16::from(b'Z')).contains(&unit) {
+		unit + u16::from(b'a' - b'A')
+	} else {
+		unit
+	}
+}
+// Map `a` to `z` to uppercase, leaving every other code unit alone.
+fn to_ascii_uppercase(unit: u16) -> u16 {
+	if (u16::from(b'a')..=u16::from(b'z')).contains(&unit) {
+		unit - u16::from(b'a' - b'A')
+	} else {
+		unit
+	}
+}
 fn code_points<I: Iterator<Item = u16> + fmt::Debug + Clone>(
 	iter: I,
 ) -> impl Iterator<Item = u32> + fmt::Debug + Clone {
@@ -589,11 +2337,34 @@ fn not(v: bool) -> bool {
 	!v
 }
 
-fn command_line() -> WideIter {
+fn command_line() -> WideIter<'static> {
+	#[cfg(any(test, feature = "test-override"))]
+	if let Some(cmdline) = testing::command_line_override() {
+		return WideIter::new(cmdline);
+	}
 	// SAFETY: `GetCommandLineW` returns a 'static null terminated wide string.
-	unsafe { WideIter::new(GetCommandLineW()) }
+	// It never changes so its length only needs to be found once.
+	#[cfg(windows)]
+	unsafe {
+		static LEN: AtomicUsize = AtomicUsize::new(usize::MAX);
+		let ptr = GetCommandLineW();
+		let len = match LEN.load(Ordering::Relaxed) {
+			usize::MAX => {
+				let len = WideIter::from_ptr(ptr).max_len();
+				LEN.store(len, Ordering::Relaxed);
+				len
+			}
+			len => len,
+		};
+		WideIter {
+			slice: core::slice::from_raw_parts(ptr, len),
+		}
+	}
+	#[cfg(not(windows))]
+	WideIter::new(&[])
 }
 
+#[cfg(windows)]
 extern "system" {
 	// GetCommandLineW cannot fail. The memory it points to cannot be written
 	// and cannot be freed (i.e. it's 'static).
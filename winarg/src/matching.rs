@@ -0,0 +1,145 @@
+//! How flag names are matched.
+
+use crate::{to_ascii_lowercase, Argument, DASH, FORWARD_SLASH};
+
+/// How [`find_flag_with`](crate::find_flag_with) and the other `_with`
+/// functions match flag names.
+///
+/// The default matches names exactly, as they're given, which is what the
+/// functions without `_with` do. Only names are affected. Values are always
+/// returned unchanged.
+///
+/// ```
+/// use winarg::{CaseSensitivity, FlagStyle, MatchOptions};
+///
+/// // Like most Windows tools, accept `/nologo`, `/NOLOGO` and `-NoLogo`.
+/// let options = MatchOptions {
+///     case: CaseSensitivity::AsciiInsensitive,
+///     prefixes: FlagStyle::SlashOrDash,
+/// };
+/// if winarg::has_flag_with(&["nologo"], options) {
+///     println!("no logo");
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchOptions {
+	/// Whether letters in a name must have the same case. Defaults to
+	/// [`CaseSensitivity::Sensitive`].
+	pub case: CaseSensitivity,
+	/// Which prefixes a name can be written with. Defaults to
+	/// [`FlagStyle::Verbatim`].
+	pub prefixes: FlagStyle,
+}
+impl MatchOptions {
+	// Whether `arg` is the flag `name`.
+	pub(crate) fn matches(self, arg: &Argument, name: &str) -> bool {
+		let mut units = arg.utf16_units();
+		self.match_name(&mut units, name).is_some() && units.next().is_none()
+	}
+
+	// Where the value starts if `arg` is the flag `name` followed by
+	// `separator` and a value.
+	pub(crate) fn value_start(self, arg: &Argument, name: &str, separator: u16) -> Option<usize> {
+		let mut units = arg.utf16_units();
+		let len = self.match_name(&mut units, name)?;
+		if units.next() == Some(separator) {
+			Some(len + 1)
+		} else {
+			None
+		}
+	}
+
+	// Takes the prefix and `name` from `units`, returning the number of code
+	// units taken if they match.
+	pub(crate) fn match_name<I: Iterator<Item = u16>>(
+		self,
+		units: &mut I,
+		name: &str,
+	) -> Option<usize> {
+		let mut len = match self.prefixes {
+			FlagStyle::Verbatim => 0,
+			FlagStyle::Slash => match units.next() {
+				Some(FORWARD_SLASH) => 1,
+				_ => return None,
+			},
+			FlagStyle::SlashOrDash => match units.next() {
+				Some(FORWARD_SLASH) | Some(DASH) => 1,
+				_ => return None,
+			},
+		};
+		for unit in name.encode_utf16() {
+			match units.next() {
+				Some(arg_unit) if self.case.eq(arg_unit, unit) => len += 1,
+				_ => return None,
+			}
+		}
+		Some(len)
+	}
+}
+
+/// Whether letters in flag names must have the same case.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaseSensitivity {
+	/// Names must match exactly.
+	#[default]
+	Sensitive,
+	/// ASCII letters match either case. Everything else must match exactly.
+	AsciiInsensitive,
+	/// Letters match either case using the operating system's case mapping,
+	/// the same way that file names are compared. This uses
+	/// `CompareStringOrdinal`.
+	#[cfg(all(windows, feature = "ordinal-case"))]
+	#[cfg_attr(docsrs, doc(cfg(feature = "ordinal-case")))]
+	OrdinalInsensitive,
+}
+impl CaseSensitivity {
+	pub(crate) fn eq(self, a: u16, b: u16) -> bool {
+		match self {
+			Self::Sensitive => a == b,
+			Self::AsciiInsensitive => to_ascii_lowercase(a) == to_ascii_lowercase(b),
+			#[cfg(all(windows, feature = "ordinal-case"))]
+			Self::OrdinalInsensitive => eq_ignore_case(&[a], &[b]),
+		}
+	}
+}
+
+/// Which prefixes a flag name can be written with.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlagStyle {
+	/// Names include their prefix, such as `--verbose` or `/nologo`.
+	#[default]
+	Verbatim,
+	/// Names don't include a prefix and flags start with `/`, such as
+	/// `/nologo`.
+	Slash,
+	/// Names don't include a prefix and flags start with either `/` or `-`,
+	/// such as `/nologo` or `-nologo`. Many Windows tools accept both.
+	SlashOrDash,
+}
+
+// Compare strings the way the file system does.
+#[cfg(all(windows, feature = "ordinal-case"))]
+pub(crate) fn eq_ignore_case(a: &[u16], b: &[u16]) -> bool {
+	const CSTR_EQUAL: i32 = 2;
+	if a.len() > i32::MAX as usize || b.len() > i32::MAX as usize {
+		return false;
+	}
+	// SAFETY: The lengths are the lengths of the slices.
+	unsafe {
+		CompareStringOrdinal(a.as_ptr(), a.len() as i32, b.as_ptr(), b.len() as i32, 1)
+			== CSTR_EQUAL
+	}
+}
+
+#[cfg(all(windows, feature = "ordinal-case"))]
+extern "system" {
+	fn CompareStringOrdinal(
+		lpString1: *const u16,
+		cchCount1: i32,
+		lpString2: *const u16,
+		cchCount2: i32,
+		bIgnoreCase: i32,
+	) -> i32;
+}
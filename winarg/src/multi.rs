@@ -0,0 +1,99 @@
+//! Parsing many command lines stored together.
+
+use crate::{cow_args_of, CowArgs};
+use core::iter::FusedIterator;
+
+/// Parse each command line in a list of null-terminated command lines.
+///
+/// Each record in `blob` ends with a null and the list may end with an extra
+/// null, like the `REG_MULTI_SZ` format. So `a\0b\0\0`, `a\0b\0` and `a\0b` are
+/// all the two command lines `a` and `b`. An empty record anywhere else, such
+/// as the one in `a\0\0b`, is an empty command line, which has no arguments.
+/// Use [`with_empty_records`](MultiArgs::with_empty_records) to leave those
+/// out.
+///
+/// Each command line's arguments are given by [`cow_args_of`], so most of them
+/// are borrowed from `blob` and nothing is allocated for a command line unless
+/// one of its arguments has quotes to remove.
+///
+/// ```
+/// let blob: Vec<u16> = "app.exe a\0tool.exe \"b c\"\0\0".encode_utf16().collect();
+/// for (index, args) in winarg::parse_multi(&blob).enumerate() {
+///     for arg in args {
+///         println!("{}: {}", index, String::from_utf16_lossy(&arg));
+///     }
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn parse_multi(blob: &[u16]) -> MultiArgs<'_> {
+	MultiArgs {
+		rest: blob,
+		empty: EmptyRecords::Keep,
+	}
+}
+
+/// What to do with an empty record in [`parse_multi`].
+///
+/// An empty record is an empty command line, which has no arguments at all.
+/// The null that ends the list is never a record.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EmptyRecords {
+	/// An empty command line is returned so that the `n`th item is always
+	/// the `n`th record.
+	#[default]
+	Keep,
+	/// Empty records are left out.
+	Skip,
+}
+
+/// An iterator over the command lines in a list.
+///
+/// Created by [`parse_multi`].
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Debug)]
+pub struct MultiArgs<'a> {
+	rest: &'a [u16],
+	empty: EmptyRecords,
+}
+impl MultiArgs<'_> {
+	/// Choose whether empty records are returned. See [`EmptyRecords`].
+	///
+	/// ```
+	/// use winarg::EmptyRecords;
+	///
+	/// let blob: Vec<u16> = "a\0\0b\0\0".encode_utf16().collect();
+	/// assert_eq!(winarg::parse_multi(&blob).count(), 3);
+	/// let records = winarg::parse_multi(&blob).with_empty_records(EmptyRecords::Skip);
+	/// assert_eq!(records.count(), 2);
+	/// ```
+	pub fn with_empty_records(mut self, empty: EmptyRecords) -> Self {
+		self.empty = empty;
+		self
+	}
+}
+impl<'a> Iterator for MultiArgs<'a> {
+	type Item = CowArgs<'a>;
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if self.rest.is_empty() {
+				return None;
+			}
+			let record = match self.rest.iter().position(|&u| u == 0) {
+				Some(end) => {
+					let record = self.rest.get(..end).unwrap_or_default();
+					self.rest = self.rest.get(end + 1..).unwrap_or_default();
+					record
+				}
+				None => core::mem::take(&mut self.rest),
+			};
+			if record.is_empty() && (self.rest.is_empty() || self.empty == EmptyRecords::Skip) {
+				// Either the null that ends the list or one to leave out.
+				continue;
+			}
+			return Some(cow_args_of(record));
+		}
+	}
+}
+impl FusedIterator for MultiArgs<'_> {}
@@ -0,0 +1,306 @@
+//! Parsing numbers from arguments.
+
+use crate::{Argument, FlagValue};
+use core::fmt;
+
+const PLUS: u16 = b'+' as _;
+const MINUS: u16 = b'-' as _;
+const ZERO: u16 = b'0' as _;
+
+/// The base of a number parsed by [`Argument::to_u64`] or [`Argument::to_i64`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Radix {
+	/// Base 2.
+	Binary,
+	/// Base 8.
+	Octal,
+	/// Base 10.
+	Decimal,
+	/// Base 16. Letters can be either case.
+	Hexadecimal,
+}
+impl Radix {
+	fn value(self) -> u32 {
+		match self {
+			Self::Binary => 2,
+			Self::Octal => 8,
+			Self::Decimal => 10,
+			Self::Hexadecimal => 16,
+		}
+	}
+}
+
+impl Argument {
+	/// Parse the argument as an unsigned integer.
+	///
+	/// The argument may start with a `+`. Everything else must be a digit in
+	/// `radix`. Whitespace and `_` separators aren't allowed. Nothing is
+	/// allocated, the code units are read as they're parsed.
+	///
+	/// ```
+	/// use winarg::Radix;
+	///
+	/// for arg in winarg::args_native().skip(1) {
+	///     match arg.to_u64(Radix::Decimal) {
+	///         Ok(count) => println!("repeating {} times", count),
+	///         Err(e) => eprintln!("bad count: {}", e),
+	///     }
+	/// }
+	/// ```
+	pub fn to_u64(&self, radix: Radix) -> Result<u64, ParseIntError> {
+		to_u64(self.into_iter(), radix)
+	}
+
+	/// Parse the argument as a signed integer.
+	///
+	/// The argument may start with a `+` or `-`. Otherwise this is the same
+	/// as [`to_u64`](Self::to_u64).
+	///
+	/// ```
+	/// use winarg::Radix;
+	///
+	/// for arg in winarg::args_native().skip(1) {
+	///     if let Ok(offset) = arg.to_i64(Radix::Decimal) {
+	///         println!("seeking by {}", offset);
+	///     }
+	/// }
+	/// ```
+	pub fn to_i64(&self, radix: Radix) -> Result<i64, ParseIntError> {
+		to_i64(self.into_iter(), radix)
+	}
+
+	/// Parse the argument as an unsigned integer, using its prefix to choose
+	/// the radix.
+	///
+	/// `0x` is hexadecimal, `0o` is octal and `0b` is binary. The prefix can
+	/// be either case. Anything else is decimal, so `010` is ten. The argument
+	/// may start with a `+` before the prefix. See [`to_u64`](Self::to_u64).
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     // Accepts `255`, `0xff` or `0b11111111`.
+	///     println!("{:?}", arg.to_u64_auto());
+	/// }
+	/// ```
+	pub fn to_u64_auto(&self) -> Result<u64, ParseIntError> {
+		to_u64_auto(self.into_iter())
+	}
+}
+
+impl FlagValue {
+	/// Parse the value as an unsigned integer.
+	///
+	/// Only the value is parsed, so `--port=8080` and `--port 8080` both give
+	/// `8080`. Offsets in errors are within the value. See
+	/// [`Argument::to_u64`].
+	///
+	/// ```
+	/// use winarg::Radix;
+	///
+	/// if let Some(port) = winarg::value_of("--port") {
+	///     match port.to_u64(Radix::Decimal) {
+	///         Ok(port) => println!("listening on {}", port),
+	///         Err(e) => eprintln!("bad port: {}", e),
+	///     }
+	/// }
+	/// ```
+	pub fn to_u64(&self, radix: Radix) -> Result<u64, ParseIntError> {
+		to_u64(self.utf16_units(), radix)
+	}
+
+	/// Parse the value as a signed integer. See [`Argument::to_i64`].
+	///
+	/// ```
+	/// use winarg::Radix;
+	///
+	/// if let Some(Ok(offset)) = winarg::value_of("--seek").map(|v| v.to_i64(Radix::Decimal)) {
+	///     println!("seeking by {}", offset);
+	/// }
+	/// ```
+	pub fn to_i64(&self, radix: Radix) -> Result<i64, ParseIntError> {
+		to_i64(self.utf16_units(), radix)
+	}
+
+	/// Parse the value as an unsigned integer, using its prefix to choose the
+	/// radix. See [`Argument::to_u64_auto`].
+	///
+	/// ```
+	/// if let Some(mask) = winarg::value_of("--mask") {
+	///     // Accepts `--mask=255`, `--mask 0xff` or `--mask=0b11111111`.
+	///     println!("{:?}", mask.to_u64_auto());
+	/// }
+	/// ```
+	pub fn to_u64_auto(&self) -> Result<u64, ParseIntError> {
+		to_u64_auto(self.utf16_units())
+	}
+}
+
+fn to_u64<I: Iterator<Item = u16> + Clone>(
+	mut units: I,
+	radix: Radix,
+) -> Result<u64, ParseIntError> {
+	let (negative, offset) = sign(&mut units);
+	if negative {
+		return Err(ParseIntError::InvalidDigit {
+			offset: 0,
+			unit: MINUS,
+		});
+	}
+	parse(units, offset, radix, u64::MAX)
+}
+
+fn to_i64<I: Iterator<Item = u16> + Clone>(
+	mut units: I,
+	radix: Radix,
+) -> Result<i64, ParseIntError> {
+	let (negative, offset) = sign(&mut units);
+	let max = i64::MAX as u64 + u64::from(negative);
+	let magnitude = parse(units, offset, radix, max)?;
+	// `magnitude` is at most `i64::MAX + 1` so this is `i64::MIN` at worst.
+	Ok(if negative {
+		0i64.wrapping_sub(magnitude as i64)
+	} else {
+		magnitude as i64
+	})
+}
+
+fn to_u64_auto<I: Iterator<Item = u16> + Clone>(mut units: I) -> Result<u64, ParseIntError> {
+	let (negative, mut offset) = sign(&mut units);
+	if negative {
+		return Err(ParseIntError::InvalidDigit {
+			offset: 0,
+			unit: MINUS,
+		});
+	}
+	let mut radix = Radix::Decimal;
+	let mut prefix = units.clone();
+	if prefix.next() == Some(ZERO) {
+		let found = match prefix.next().map(crate::to_ascii_lowercase) {
+			Some(x) if x == u16::from(b'x') => Some(Radix::Hexadecimal),
+			Some(o) if o == u16::from(b'o') => Some(Radix::Octal),
+			Some(b) if b == u16::from(b'b') => Some(Radix::Binary),
+			_ => None,
+		};
+		if let Some(found) = found {
+			radix = found;
+			units = prefix;
+			offset += 2;
+		}
+	}
+	parse(units, offset, radix, u64::MAX)
+}
+
+// Takes a `+` or `-` from the start of `units`. Returns whether the number is
+// negative and where the digits start.
+fn sign<I: Iterator<Item = u16> + Clone>(units: &mut I) -> (bool, usize) {
+	let mut peek = units.clone();
+	match peek.next() {
+		Some(PLUS) => {
+			*units = peek;
+			(false, 1)
+		}
+		Some(MINUS) => {
+			*units = peek;
+			(true, 1)
+		}
+		_ => (false, 0),
+	}
+}
+
+// Parse digits up to `max`. `offset` is the position of the first digit in
+// the argument or value.
+fn parse<I: Iterator<Item = u16>>(
+	units: I,
+	mut offset: usize,
+	radix: Radix,
+	max: u64,
+) -> Result<u64, ParseIntError> {
+	let radix = radix.value();
+	let mut value: Option<u64> = None;
+	for unit in units {
+		let digit = match digit(unit, radix) {
+			Some(digit) => digit,
+			None => return Err(ParseIntError::InvalidDigit { offset, unit }),
+		};
+		value = value
+			.unwrap_or(0)
+			.checked_mul(u64::from(radix))
+			.and_then(|v| v.checked_add(u64::from(digit)))
+			.filter(|&v| v <= max);
+		if value.is_none() {
+			return Err(ParseIntError::Overflow { offset });
+		}
+		offset += 1;
+	}
+	value.ok_or(ParseIntError::Empty { offset })
+}
+
+// The value of a digit if it's valid in `radix`.
+fn digit(unit: u16, radix: u32) -> Option<u32> {
+	let digit = match unit {
+		0x30..=0x39 => unit - 0x30,
+		0x41..=0x5A => unit - 0x41 + 10,
+		0x61..=0x7A => unit - 0x61 + 10,
+		_ => return None,
+	};
+	Some(u32::from(digit)).filter(|&digit| digit < radix)
+}
+
+/// The reason an argument couldn't be parsed as an integer.
+///
+/// Returned by [`Argument::to_u64`], [`Argument::to_i64`] and
+/// [`Argument::to_u64_auto`], and the same methods of [`FlagValue`]. Each
+/// variant has the offset of the code unit that's the problem, within the
+/// argument or value.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseIntError {
+	/// There are no digits. `offset` is where the first digit should be, which
+	/// is after any sign or prefix.
+	Empty {
+		/// Where a digit was expected.
+		offset: usize,
+	},
+	/// A code unit isn't a digit in the radix.
+	InvalidDigit {
+		/// The position of the code unit.
+		offset: usize,
+		/// The code unit.
+		unit: u16,
+	},
+	/// The number is too large or, for a negative number, too small.
+	Overflow {
+		/// The position of the digit that made the number too large.
+		offset: usize,
+	},
+}
+impl ParseIntError {
+	/// The position of the code unit that's the problem, within the argument
+	/// or value.
+	pub fn offset(self) -> usize {
+		match self {
+			Self::Empty { offset } => offset,
+			Self::InvalidDigit { offset, .. } => offset,
+			Self::Overflow { offset } => offset,
+		}
+	}
+}
+impl fmt::Display for ParseIntError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			Self::Empty { offset } => write!(f, "expected a digit at offset {}", offset),
+			Self::InvalidDigit { offset, unit } => {
+				// Only show visible ASCII characters as they are.
+				if (0x21..=0x7E).contains(&unit) {
+					write!(f, "invalid digit '{}'", unit as u8 as char)?;
+				} else {
+					write!(f, "invalid digit ({:#06x})", unit)?;
+				}
+				write!(f, " at offset {}", offset)
+			}
+			Self::Overflow { offset } => write!(f, "number out of range at offset {}", offset),
+		}
+	}
+}
+#[cfg(feature = "std")]
+impl std::error::Error for ParseIntError {}
@@ -0,0 +1,624 @@
+//! Functions that collect the arguments into owned types.
+
+use crate::{
+	arg_spans_of, command_line, occurrences, program_name, scalars, to_json, unquote_one, ArgSpans,
+	ArgsNative, Argument, FlagValue, InvalidUnicodeError, MatchOptions, Trimmed, QUOTE, SLASH,
+};
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{char::decode_utf16, iter::FusedIterator};
+#[cfg(all(windows, feature = "std"))]
+use std::os::windows::ffi::OsStringExt;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+#[cfg(all(windows, feature = "std"))]
+use std::{convert::Infallible, ffi::OsString};
+#[cfg(all(feature = "std", any(test, feature = "test-override")))]
+use std::{
+	ptr,
+	sync::{Mutex, PoisonError},
+};
+
+/// The arguments after the program name, as a `Vec<OsString>`.
+///
+/// The zeroth argument is skipped so the result can be given directly to
+/// parsers that only expect the arguments, such as [pico-args]. Arguments are
+/// converted losslessly so this never fails.
+///
+/// ```
+/// let mut args = pico_args::Arguments::from_vec(winarg::os_args_after_program());
+/// if args.contains(["-h", "--help"]) {
+///     println!("help me!");
+/// }
+/// ```
+///
+/// [pico-args]: https://crates.io/crates/pico-args
+#[cfg(all(windows, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn os_args_after_program() -> Vec<OsString> {
+	let mut buffer = Vec::new();
+	let args = collect_args_after_program(|arg| {
+		buffer.clear();
+		buffer.extend(arg.utf16_units());
+		Ok::<_, Infallible>(OsString::from_wide(&buffer))
+	});
+	match args {
+		Ok(args) => args,
+		Err(never) => match never {},
+	}
+}
+
+/// The arguments after the program name, as a `Vec<String>`.
+///
+/// The zeroth argument is skipped so the result can be given directly to
+/// parsers that only expect the arguments, such as [getopts]. An error is
+/// returned if any argument is not valid Unicode.
+///
+/// ```
+/// let args = winarg::string_args_after_program().expect("arguments must be Unicode");
+/// let mut opts = getopts::Options::new();
+/// opts.optflag("h", "help", "print this help menu");
+/// let matches = opts.parse(&args).unwrap();
+/// if matches.opt_present("h") {
+///     println!("help me!");
+/// }
+/// ```
+///
+/// [getopts]: https://crates.io/crates/getopts
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn string_args_after_program() -> Result<Vec<String>, InvalidUnicodeError> {
+	let mut index = 0;
+	collect_args_after_program(|arg| {
+		index += 1;
+		to_string(arg).map_err(|e| InvalidUnicodeError {
+			arg_index: Some(index),
+			..e
+		})
+	})
+}
+
+/// Every value of the option `name`, in the order they're given.
+///
+/// Values are found in the same way as [`value_of`](crate::value_of), so the
+/// search stops at a standalone `--`. An occurrence without a value, because
+/// it's the last argument before the end or `--`, is left out.
+///
+/// ```
+/// for include in winarg::values_of("--include") {
+///     println!("including {}", include.scalars().collect::<String>());
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn values_of(name: &str) -> Vec<FlagValue> {
+	values_of_with(name, MatchOptions::default())
+}
+
+/// The values of every occurrence of the option `name`, using `options` to
+/// match the name.
+///
+/// See [`values_of`] and [`MatchOptions`]. The search stops at a standalone
+/// `--`.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn values_of_with(name: &str, options: MatchOptions) -> Vec<FlagValue> {
+	occurrences(name, options).flatten().collect()
+}
+
+impl Argument {
+	/// Append the argument to a `String`, replacing isolated surrogates with
+	/// `�`.
+	///
+	/// The length is worked out first so `string` is only grown once, by
+	/// exactly as much as is needed. If it already has room nothing is
+	/// allocated, so one `String` can be cleared and reused for every argument.
+	///
+	/// ```
+	/// let mut buffer = String::new();
+	/// for arg in winarg::args_native().skip(1) {
+	///     buffer.clear();
+	///     arg.extend_string(&mut buffer);
+	///     println!("{}", buffer);
+	/// }
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+	pub fn extend_string(&self, string: &mut String) {
+		let scalars = self.scalars();
+		string.reserve_exact(scalars.clone().map(char::len_utf8).sum());
+		string.extend(scalars);
+	}
+
+	/// The argument as a `String`, with isolated surrogates replaced by
+	/// `replacement`.
+	///
+	/// If `replacement` is `None`, isolated surrogates are left out. See
+	/// [`scalars_with`](Self::scalars_with).
+	///
+	/// ```
+	/// for arg in winarg::args_native() {
+	///     println!("{}", arg.to_string_lossy_with(Some('\u{2370}')));
+	/// }
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+	pub fn to_string_lossy_with(&self, replacement: Option<char>) -> String {
+		self.scalars_with(replacement).collect()
+	}
+
+	/// Append the argument's UTF-16 code units to a `Vec`. This is lossless.
+	///
+	/// Like [`extend_string`](Self::extend_string), `wide` is only grown once
+	/// and not at all if it already has room.
+	///
+	/// ```
+	/// let mut buffer = Vec::new();
+	/// for arg in winarg::args_native().skip(1) {
+	///     buffer.clear();
+	///     arg.extend_wide(&mut buffer);
+	///     println!("{}", String::from_utf16_lossy(&buffer));
+	/// }
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+	pub fn extend_wide(&self, wide: &mut Vec<u16>) {
+		extend_exact(wide, self.utf16_units());
+	}
+}
+/// Compares with a `String` the same way as with a `&str`.
+///
+/// ```
+/// let expected = String::from("--help");
+/// for arg in winarg::args_native().skip(1) {
+///     if arg == expected {
+///         println!("help me!");
+///     }
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl PartialEq<String> for Argument {
+	fn eq(&self, other: &String) -> bool {
+		self.eq(other.encode_utf16())
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl PartialEq<Argument> for String {
+	fn eq(&self, other: &Argument) -> bool {
+		other.eq(self.encode_utf16())
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl PartialEq<String> for FlagValue {
+	fn eq(&self, other: &String) -> bool {
+		*self == other.as_str()
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl PartialEq<FlagValue> for String {
+	fn eq(&self, other: &FlagValue) -> bool {
+		*other == self.as_str()
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl PartialEq<String> for Trimmed {
+	fn eq(&self, other: &String) -> bool {
+		*self == other.as_str()
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl PartialEq<Trimmed> for String {
+	fn eq(&self, other: &Trimmed) -> bool {
+		*other == self.as_str()
+	}
+}
+
+/// Append every argument to a `Vec`, separated by nulls, the same as
+/// [`null_separated_list_wide`](crate::null_separated_list_wide).
+///
+/// `wide` is only grown once and not at all if it already has room. This
+/// means the command line is parsed twice, once to find the length and once
+/// to copy, so it's slower than pushing each argument as it's parsed. Use
+/// [`null_separated_list_wide`](crate::null_separated_list_wide) if speed
+/// matters more than the allocation.
+///
+/// ```
+/// let mut buffer = Vec::new();
+/// winarg::extend_null_separated(&mut buffer);
+/// for arg in buffer.split(|&w| w == 0) {
+///     println!("{}", String::from_utf16_lossy(arg));
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn extend_null_separated(wide: &mut Vec<u16>) {
+	let cmdline = command_line().as_slice();
+	let mut spans = arg_spans_of(cmdline);
+	let (mut len, mut count) = (0, 0_usize);
+	while let Some((_, arg_len)) = spans.next_with_len() {
+		len += arg_len;
+		count += 1;
+	}
+	// The arguments are separated by nulls.
+	wide.reserve_exact(len + count.saturating_sub(1));
+	for (index, span) in arg_spans_of(cmdline).enumerate() {
+		if index > 0 {
+			wide.push(0);
+		}
+		extend_unquoted(wide, &cmdline[span], index == 0);
+	}
+}
+
+/// Every argument in a `Vec`, separated by nulls, the same as
+/// [`null_separated_list_wide`](crate::null_separated_list_wide).
+///
+/// The length is worked out first so the `Vec` is allocated once, with no
+/// spare capacity. Arguments without quotes are copied straight from the
+/// command line. Working out the length means parsing the command line
+/// twice, which trades speed for the single, exact allocation. See
+/// [`extend_null_separated`].
+///
+/// ```
+/// let args = winarg::collect_wide();
+/// assert_eq!(args.capacity(), args.len());
+/// for arg in args.split(|&w| w == 0) {
+///     println!("{}", String::from_utf16_lossy(arg));
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn collect_wide() -> Vec<u16> {
+	let mut wide = Vec::new();
+	extend_null_separated(&mut wide);
+	wide
+}
+
+/// Every argument as a `Vec` of UTF-16 code units, starting with the program
+/// name.
+///
+/// Each `Vec` is allocated once, with no spare capacity, as is the outer
+/// `Vec`. Arguments without quotes are copied straight from the command line.
+///
+/// ```
+/// for arg in winarg::collect_args_wide() {
+///     println!("{}", String::from_utf16_lossy(&arg));
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn collect_args_wide() -> Vec<Vec<u16>> {
+	let cmdline = command_line().as_slice();
+	let mut args = Vec::with_capacity(arg_spans_of(cmdline).count());
+	let mut spans = arg_spans_of(cmdline);
+	while let Some((span, len)) = spans.next_with_len() {
+		let mut arg = Vec::with_capacity(len);
+		extend_unquoted(&mut arg, &cmdline[span], args.is_empty());
+		args.push(arg);
+	}
+	args
+}
+
+/// Every argument, borrowed from the command line where possible.
+///
+/// This is the same as [`cow_args_of`] for the program's command line.
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// for arg in winarg::cow_args() {
+///     if let Cow::Owned(_) = arg {
+///         println!("{} had quotes", String::from_utf16_lossy(&arg));
+///     }
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn cow_args() -> CowArgs<'static> {
+	cow_args_of(command_line().as_slice())
+}
+
+/// Every argument in `cmdline`, borrowed from it where possible.
+///
+/// Most arguments don't have quotes so the argument is the same as the text
+/// it came from. Those, and arguments whose only quotes surround the whole
+/// argument, are returned as [`Cow::Borrowed`] slices of `cmdline`. Anything
+/// else is parsed into a [`Cow::Owned`] `Vec`, which is allocated once with no
+/// spare capacity. Either way the content is the same as
+/// [`unquote_one`](crate::unquote_one) gives for each span from
+/// [`arg_spans_of`]. The command line ends at the first null, if any.
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// let cmdline: Vec<u16> = r#"app.exe C:\dir\file.txt "C:\My Documents" a\"b"#
+///     .encode_utf16()
+///     .collect();
+/// let args: Vec<Cow<[u16]>> = winarg::cow_args_of(&cmdline).collect();
+/// assert!(matches!(args[1], Cow::Borrowed(_)));
+/// assert!(matches!(args[2], Cow::Borrowed(_)));
+/// assert!(matches!(args[3], Cow::Owned(_)));
+/// assert_eq!(String::from_utf16_lossy(&args[2]), r"C:\My Documents");
+/// assert_eq!(String::from_utf16_lossy(&args[3]), r#"a"b"#);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn cow_args_of(cmdline: &[u16]) -> CowArgs<'_> {
+	CowArgs {
+		cmdline,
+		spans: arg_spans_of(cmdline),
+		is_program: true,
+	}
+}
+
+/// An iterator over arguments that are borrowed from the command line where
+/// possible.
+///
+/// Created by [`cow_args`] or [`cow_args_of`].
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Debug)]
+pub struct CowArgs<'a> {
+	cmdline: &'a [u16],
+	spans: ArgSpans<'a>,
+	is_program: bool,
+}
+impl<'a> Iterator for CowArgs<'a> {
+	type Item = Cow<'a, [u16]>;
+	fn next(&mut self) -> Option<Self::Item> {
+		let (span, len) = self.spans.next_with_len()?;
+		let raw = self.cmdline.get(span).unwrap_or_default();
+		let is_program = core::mem::replace(&mut self.is_program, false);
+		Some(match as_unquoted(raw, is_program) {
+			Some(arg) => Cow::Borrowed(arg),
+			None => {
+				let mut arg = Vec::with_capacity(len);
+				arg.extend(unquote_one(raw, is_program).0);
+				Cow::Owned(arg)
+			}
+		})
+	}
+}
+impl FusedIterator for CowArgs<'_> {}
+
+// Append the argument in `raw`, as found by `arg_spans`. Most arguments can
+// be copied without parsing them.
+fn extend_unquoted(wide: &mut Vec<u16>, raw: &[u16], is_program: bool) {
+	match as_unquoted(raw, is_program) {
+		Some(arg) => wide.extend_from_slice(arg),
+		None => wide.extend(unquote_one(raw, is_program).0),
+	}
+}
+
+// The argument in `raw`, as found by `arg_spans`, if it's part of `raw`
+// without any quotes or escapes to remove.
+fn as_unquoted(raw: &[u16], is_program: bool) -> Option<&[u16]> {
+	match raw {
+		// Without quotes, backslashes don't escape anything.
+		_ if !raw.contains(&QUOTE) => Some(raw),
+		// Quotes around the whole argument, without a backslash before the
+		// closing quote, are the only ones.
+		[QUOTE, inner @ .., QUOTE]
+			if !inner.contains(&QUOTE) && (is_program || inner.last() != Some(&SLASH)) =>
+		{
+			Some(inner)
+		}
+		_ => None,
+	}
+}
+
+// Count the items first so that `vec` is only grown once.
+fn extend_exact<T, I: Iterator<Item = T> + Clone>(vec: &mut Vec<T>, iter: I) {
+	vec.reserve_exact(iter.clone().count());
+	vec.extend(iter);
+}
+
+/// The arguments as a JSON array of strings.
+///
+/// See [`to_json`](crate::to_json) for how the arguments are written.
+///
+/// ```
+/// eprintln!("started with {}", winarg::to_json_string());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn to_json_string() -> String {
+	let mut json = String::new();
+	// Writing to a `String` never fails.
+	let _ = to_json(&mut json);
+	json
+}
+
+/// The whole command line as a `String`, for use in logs.
+///
+/// Isolated surrogates are replaced with `�`. The command line isn't split
+/// into arguments, so quotes and escapes are kept. See
+/// [`raw_command_line`](crate::raw_command_line).
+///
+/// The `String` is allocated once, with no spare capacity.
+///
+/// ```
+/// eprintln!("started with {}", winarg::command_line_lossy());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn command_line_lossy() -> String {
+	let scalars = scalars(command_line().as_slice().iter().copied());
+	let mut string = String::with_capacity(scalars.clone().map(char::len_utf8).sum());
+	string.extend(scalars);
+	string
+}
+
+/// The whole command line as a `String`, or an error if it isn't valid
+/// Unicode.
+///
+/// This is the same as [`command_line_lossy`] except that an isolated
+/// surrogate is an error. The error's [`offset`](InvalidUnicodeError::offset)
+/// is the position of the surrogate in the command line and it has no
+/// argument index.
+///
+/// ```
+/// match winarg::try_command_line() {
+///     Ok(cmdline) => eprintln!("started with {}", cmdline),
+///     Err(e) => {
+///         let cmdline = winarg::raw_command_line().iter().copied();
+///         eprintln!("the command line isn't Unicode: {}", e.context(cmdline));
+///     }
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn try_command_line() -> Result<String, InvalidUnicodeError> {
+	let cmdline = command_line().as_slice();
+	let (mut len, mut offset) = (0, 0);
+	for c in decode_utf16(cmdline.iter().copied()) {
+		let c = c.map_err(|e| InvalidUnicodeError {
+			arg_index: None,
+			offset,
+			unit: e.unpaired_surrogate(),
+		})?;
+		len += c.len_utf8();
+		offset += c.len_utf16();
+	}
+	let mut string = String::with_capacity(len);
+	string.extend(scalars(cmdline.iter().copied()));
+	Ok(string)
+}
+
+/// The program name as a `String`, for use in messages.
+///
+/// Isolated surrogates are replaced with `�`. If `file_stem` is true, the
+/// directory and any `.exe` extension are removed, so `C:\tools\tool.exe`
+/// becomes `tool`. Both `\` and `/` are treated as path separators.
+///
+/// ```
+/// let name = winarg::program_name_lossy(true);
+/// eprintln!("usage: {} [OPTIONS] FILE", name);
+/// ```
+///
+/// See [`program_name`](crate::program_name) for more about the program name.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn program_name_lossy(file_stem: bool) -> String {
+	let name: String = program_name().scalars().collect();
+	if file_stem {
+		self::file_stem(&name).into()
+	} else {
+		name
+	}
+}
+
+// Remove the directory and `.exe` extension from a path.
+fn file_stem(path: &str) -> &str {
+	let is_separator = |c| c == '\\' || c == '/';
+	let path = path.trim_end_matches(is_separator);
+	let name = path.rsplit(is_separator).next().unwrap_or(path);
+	let split = name.len().saturating_sub(4);
+	// `get` returns `None` if `split` is in the middle of a character.
+	match name.get(split..) {
+		Some(ext) if split > 0 && ext.eq_ignore_ascii_case(".exe") => &name[..split],
+		_ => name,
+	}
+}
+
+/// The arguments, starting with the program name, as `OsString`s that are
+/// only made once.
+///
+/// The command line is parsed the first time this is called and the same
+/// slice is returned every time after that, so libraries can look at the
+/// arguments as often as they like without having them passed in. Arguments
+/// are converted losslessly.
+///
+/// ```
+/// let args = winarg::args_cached();
+/// if args.iter().skip(1).any(|arg| arg == "--verbose") {
+///     println!("{:?} is verbose", args[0]);
+/// }
+/// ```
+///
+/// # Overriding the command line
+///
+/// With the `test-override` feature, a thread that has overridden the command
+/// line gets the arguments of its override instead, so tests don't see each
+/// other's arguments. They're also made once, the first time they're asked
+/// for with that override, and kept until the process exits. Setting a
+/// different command line, or none, gets different arguments. See the
+/// [`testing`](crate::testing) module.
+#[cfg(all(windows, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn args_cached() -> &'static [OsString] {
+	static ARGS: OnceLock<Vec<OsString>> = OnceLock::new();
+	let to_os_string =
+		|arg: Argument| OsString::from_wide(&arg.utf16_units().collect::<Vec<u16>>());
+	#[cfg(any(test, feature = "test-override"))]
+	if let Some(cmdline) = crate::testing::command_line_override() {
+		static OVERRIDES: Overrides<OsString> = Mutex::new(Vec::new());
+		return overridden(&OVERRIDES, cmdline, to_os_string);
+	}
+	ARGS.get_or_init(|| ArgsNative::from_env().map(to_os_string).collect())
+}
+
+/// The arguments, starting with the program name, as `String`s that are only
+/// made once.
+///
+/// This is the same as [`args_cached`] except isolated surrogates are
+/// replaced with `�`, and it doesn't need Windows.
+///
+/// ```
+/// let args = winarg::args_cached_lossy();
+/// if args.iter().skip(1).any(|arg| arg == "--verbose") {
+///     println!("{} is verbose", args[0]);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn args_cached_lossy() -> &'static [String] {
+	static ARGS: OnceLock<Vec<String>> = OnceLock::new();
+	let to_string = |arg: Argument| arg.scalars().collect();
+	#[cfg(any(test, feature = "test-override"))]
+	if let Some(cmdline) = crate::testing::command_line_override() {
+		static OVERRIDES: Overrides<String> = Mutex::new(Vec::new());
+		return overridden(&OVERRIDES, cmdline, to_string);
+	}
+	ARGS.get_or_init(|| ArgsNative::from_env().map(to_string).collect())
+}
+
+// The arguments made for each overridden command line.
+#[cfg(all(feature = "std", any(test, feature = "test-override")))]
+type Overrides<T> = Mutex<Vec<(&'static [u16], &'static [T])>>;
+
+// The arguments for the overridden command line `cmdline`, made the first time
+// they're needed. They're leaked, but still reachable from `overrides`.
+#[cfg(all(feature = "std", any(test, feature = "test-override")))]
+fn overridden<T>(
+	overrides: &Overrides<T>,
+	cmdline: &'static [u16],
+	convert: impl FnMut(Argument) -> T,
+) -> &'static [T] {
+	let mut overrides = overrides.lock().unwrap_or_else(PoisonError::into_inner);
+	if let Some(&(_, args)) = overrides.iter().find(|(c, _)| ptr::eq(*c, cmdline)) {
+		return args;
+	}
+	let args = Vec::leak(ArgsNative::from_env().map(convert).collect());
+	overrides.push((cmdline, args));
+	args
+}
+
+/// Convert each argument after the program name.
+///
+/// The arguments are counted first so the `Vec` is only allocated once.
+fn collect_args_after_program<T, E, F>(mut convert: F) -> Result<Vec<T>, E>
+where
+	F: FnMut(&Argument) -> Result<T, E>,
+{
+	let count = ArgsNative::from_env().count().saturating_sub(1);
+	let mut args = Vec::with_capacity(count);
+	for arg in ArgsNative::from_env().skip(1) {
+		args.push(convert(&arg)?);
+	}
+	Ok(args)
+}
+
+/// Decode an argument in a single pass, stopping at the first isolated surrogate.
+fn to_string(arg: &Argument) -> Result<String, InvalidUnicodeError> {
+	let mut string = String::new();
+	let mut offset = 0;
+	for c in decode_utf16(arg.utf16_units()) {
+		match c {
+			Ok(c) => {
+				string.push(c);
+				offset += c.len_utf16();
+			}
+			Err(e) => {
+				return Err(InvalidUnicodeError {
+					arg_index: None,
+					offset,
+					unit: e.unpaired_surrogate(),
+				})
+			}
+		}
+	}
+	Ok(string)
+}
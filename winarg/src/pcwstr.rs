@@ -0,0 +1,245 @@
+//! Conversions to and from `windows-sys` string types.
+
+use crate::quote::{quote_one, representable, QuotingRules, WhyNot};
+use crate::{debug_units, ArgsNative, Argument, CommandLine, ParseArgs, Parser, WideIter, SPACE};
+use alloc::vec::Vec;
+use core::fmt;
+use windows_sys::core::{PCWSTR, PWSTR};
+
+impl ArgsNative {
+	/// Parse the arguments of a command line other than the current one.
+	///
+	/// A null pointer is treated as an empty command line.
+	///
+	/// # Safety
+	///
+	/// `cmdline` must either be null or point to a null terminated string that
+	/// is valid and unmodified for the rest of the program. The string returned
+	/// by `GetCommandLineW` meets these requirements.
+	///
+	/// ```
+	/// use windows_sys::Win32::System::Environment::GetCommandLineW;
+	///
+	/// // SAFETY: The command line is never freed or modified.
+	/// let args = unsafe { winarg::ArgsNative::from_pcwstr(GetCommandLineW()) };
+	/// assert_eq!(args.count(), winarg::args_native().count());
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "windows-sys")))]
+	pub unsafe fn from_pcwstr(cmdline: PCWSTR) -> Self {
+		Self {
+			next: ParseArgs::new(wide_iter(cmdline), true),
+		}
+	}
+}
+
+impl Parser {
+	/// Parse a command line other than the current one.
+	///
+	/// A null pointer is treated as an empty command line.
+	///
+	/// # Safety
+	///
+	/// This has the same requirements as [`ArgsNative::from_pcwstr`].
+	///
+	/// ```
+	/// use windows_sys::Win32::System::Environment::GetCommandLineW;
+	///
+	/// // SAFETY: The command line is never freed or modified.
+	/// let parser = unsafe { winarg::Parser::from_pcwstr(GetCommandLineW()) };
+	/// assert!(parser.eq(winarg::Parser()));
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "windows-sys")))]
+	pub unsafe fn from_pcwstr(cmdline: PCWSTR) -> Self {
+		Parser {
+			iter: ParseArgs::new(wide_iter(cmdline), true),
+			arg_index: 0,
+		}
+	}
+}
+
+// SAFETY: See `ArgsNative::from_pcwstr`.
+unsafe fn wide_iter(cmdline: PCWSTR) -> WideIter<'static> {
+	if cmdline.is_null() {
+		WideIter::new(&[])
+	} else {
+		WideIter::from_ptr(cmdline)
+	}
+}
+
+impl Argument {
+	/// Copy the parsed argument into a null terminated buffer.
+	///
+	/// Arguments never contain a null so the buffer has exactly one, at the
+	/// end.
+	///
+	/// ```
+	/// for arg in winarg::args_native() {
+	///     let arg = arg.to_pcwstr_buf();
+	///     assert_eq!(arg.as_slice_with_nul().last(), Some(&0));
+	/// }
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "windows-sys")))]
+	pub fn to_pcwstr_buf(&self) -> WideBuf {
+		let mut units: Vec<u16> = self.utf16_units().collect();
+		units.push(0);
+		WideBuf { units }
+	}
+}
+
+/// An owned, null terminated, UTF-16 string that can be passed to Windows
+/// functions as a [`PCWSTR`] or [`PWSTR`].
+///
+/// This is returned by [`Argument::to_pcwstr_buf`] and [`WideBuf::join`]. The
+/// buffer has exactly one null, at the end, so Windows functions see all of
+/// it.
+///
+/// `CreateProcessW` may write to the command line it's given so it needs a
+/// [`PWSTR`]:
+///
+/// ```
+/// use std::{mem, ptr};
+/// use windows_sys::Win32::Foundation::CloseHandle;
+/// use windows_sys::Win32::System::Threading::{
+///     CreateProcessW, WaitForSingleObject, INFINITE, PROCESS_INFORMATION, STARTUPINFOW,
+/// };
+///
+/// let args = ["cmd.exe", "/c", "exit"];
+/// let args: Vec<Vec<u16>> = args.iter().map(|s| s.encode_utf16().collect()).collect();
+/// let mut command_line = winarg::WideBuf::join(&args).unwrap();
+///
+/// // SAFETY: The structs are plain data so can be zeroed. The command line is
+/// // null terminated and the buffer outlives the call.
+/// unsafe {
+///     let mut startup_info: STARTUPINFOW = mem::zeroed();
+///     startup_info.cb = mem::size_of::<STARTUPINFOW>() as u32;
+///     let mut process_info: PROCESS_INFORMATION = mem::zeroed();
+///     let created = CreateProcessW(
+///         ptr::null(),
+///         command_line.as_pwstr(),
+///         ptr::null(),
+///         ptr::null(),
+///         0,
+///         0,
+///         ptr::null(),
+///         ptr::null(),
+///         &startup_info,
+///         &mut process_info,
+///     );
+///     assert_ne!(created, 0);
+///     WaitForSingleObject(process_info.hProcess, INFINITE);
+///     CloseHandle(process_info.hProcess);
+///     CloseHandle(process_info.hThread);
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "windows-sys")))]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct WideBuf {
+	units: Vec<u16>,
+}
+impl WideBuf {
+	/// Make a command line from a list of arguments.
+	///
+	/// The first argument is the program name. The arguments are quoted in
+	/// the same way as [`quoted_len`](crate::quoted_len) so the length of the
+	/// result, not counting the null terminator, is the same.
+	///
+	/// Each argument is checked in the same way as
+	/// [`is_representable`](crate::is_representable), so it's an error if the
+	/// program name contains a quote or any argument contains a null.
+	///
+	/// ```
+	/// let args = ["app.exe", "a b"];
+	/// let args: Vec<Vec<u16>> = args.iter().map(|s| s.encode_utf16().collect()).collect();
+	/// let command_line = winarg::WideBuf::join(&args)?;
+	/// assert_eq!(command_line.len(), winarg::quoted_len(&args));
+	/// assert_eq!(String::from_utf16_lossy(command_line.as_slice()), r#"app.exe "a b""#);
+	/// # Ok::<(), winarg::WhyNot>(())
+	/// ```
+	pub fn join<I>(args: I) -> Result<Self, WhyNot>
+	where
+		I: IntoIterator,
+		I::Item: AsRef<[u16]>,
+	{
+		let mut units = Vec::new();
+		for (index, arg) in args.into_iter().enumerate() {
+			let arg = arg.as_ref();
+			representable(arg, index, QuotingRules::Modern)?;
+			if index > 0 {
+				units.push(SPACE);
+			}
+			quote_one(arg, index == 0, |u| units.push(u));
+		}
+		units.push(0);
+		Ok(Self { units })
+	}
+
+	/// Take ownership of a buffer that's already null terminated, such as the
+	/// one returned by [`rebuild`](crate::rebuild).
+	///
+	/// Returns `None` unless the last code unit is a null and it's the only
+	/// one.
+	///
+	/// ```
+	/// let command_line = winarg::rebuild(|_, _| winarg::Edit::Keep);
+	/// let command_line = winarg::WideBuf::from_vec_with_nul(command_line).unwrap();
+	/// assert_eq!(command_line.len(), winarg::command_line_len());
+	/// ```
+	pub fn from_vec_with_nul(units: Vec<u16>) -> Option<Self> {
+		if units.iter().position(|&u| u == 0) == Some(units.len().wrapping_sub(1)) {
+			Some(Self { units })
+		} else {
+			None
+		}
+	}
+
+	/// A pointer to the string for functions that only read it.
+	///
+	/// The pointer is valid until the buffer is modified or dropped.
+	pub fn as_pcwstr(&self) -> PCWSTR {
+		self.units.as_ptr()
+	}
+
+	/// A pointer to the string for functions that may write to it.
+	///
+	/// Only the code units before the null terminator should be written. The
+	/// pointer is valid until the buffer is dropped.
+	pub fn as_pwstr(&mut self) -> PWSTR {
+		self.units.as_mut_ptr()
+	}
+
+	/// The code units, not including the null terminator.
+	pub fn as_slice(&self) -> &[u16] {
+		&self.units[..self.units.len() - 1]
+	}
+
+	/// The code units, including the null terminator.
+	pub fn as_slice_with_nul(&self) -> &[u16] {
+		&self.units
+	}
+
+	/// The number of code units, not including the null terminator.
+	pub fn len(&self) -> usize {
+		self.units.len() - 1
+	}
+
+	/// Returns `true` if the string is empty, apart from the null terminator.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Convert into a `Vec` that includes the null terminator.
+	pub fn into_vec_with_nul(self) -> Vec<u16> {
+		self.units
+	}
+}
+impl fmt::Debug for WideBuf {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		debug_units(f, self.as_slice())
+	}
+}
+/// Removes the null terminator, which is the only null.
+impl From<WideBuf> for CommandLine {
+	fn from(buf: WideBuf) -> Self {
+		Self::from_wide(buf.units)
+	}
+}
@@ -0,0 +1,83 @@
+//! Finding what different versions of the C runtime read differently.
+
+use crate::{arg_spans_of, unquote_one_with, ParserRules, WideIter};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Find the parts of a command line that versions of the C runtime read
+/// differently.
+///
+/// Each argument is parsed with both [`ParserRules`] and a warning is returned
+/// for each one that's different, with what each of them reads. The only
+/// difference is `""` in quotes, such as in `"a""b"`. Where arguments start
+/// and end is always the same.
+///
+/// This doesn't check `CommandLineToArgvW`, which has rules of its own that
+/// this crate doesn't implement. The one exception is an empty command line,
+/// which is reported because `CommandLineToArgvW` documents that it returns
+/// the path of the program for one, where the C runtime reads no arguments.
+/// A command line without warnings may still be split differently by
+/// `CommandLineToArgvW`.
+///
+/// A command line with no warnings is read the same way by programs built
+/// with any version of the C runtime. [`quote_one`](crate::quote_one) and
+/// [`CommandLineBuilder`](crate::CommandLineBuilder) never write `""` in
+/// quotes, so the arguments they write never get a warning.
+///
+/// ```
+/// use winarg::PortabilityWarning;
+///
+/// let cmdline: Vec<u16> = r#"app.exe "say ""hi""" a"#.encode_utf16().collect();
+/// let warnings = winarg::check_portability(&cmdline);
+/// match &warnings[..] {
+///     [PortabilityWarning::Argument { index, modern, pre_2008, .. }] => {
+///         assert_eq!(*index, 1);
+///         assert_eq!(String::from_utf16_lossy(modern), r#"say "hi""#);
+///         assert_eq!(String::from_utf16_lossy(pre_2008), "say hi");
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn check_portability(cmdline: &[u16]) -> Vec<PortabilityWarning> {
+	let mut warnings = Vec::new();
+	if WideIter::new(cmdline).max_len() == 0 {
+		warnings.push(PortabilityWarning::EmptyCommandLine);
+	}
+	for (index, span) in arg_spans_of(cmdline).enumerate() {
+		let raw = cmdline.get(span.clone()).unwrap_or_default();
+		let (modern, _) = unquote_one_with(raw, index == 0, ParserRules::Modern);
+		let (pre_2008, _) = unquote_one_with(raw, index == 0, ParserRules::Pre2008);
+		if !modern.clone().eq(pre_2008.clone()) {
+			warnings.push(PortabilityWarning::Argument {
+				index,
+				span,
+				modern: modern.collect(),
+				pre_2008: pre_2008.collect(),
+			});
+		}
+	}
+	warnings
+}
+
+/// Something that isn't read the same way everywhere, found by
+/// [`check_portability`].
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PortabilityWarning {
+	/// An argument that's different with [`ParserRules::Pre2008`].
+	Argument {
+		/// The index of the argument. The program name is `0`.
+		index: usize,
+		/// The argument's code units, as indexes into the command line.
+		span: Range<usize>,
+		/// The argument read with [`ParserRules::Modern`].
+		modern: Vec<u16>,
+		/// The argument read with [`ParserRules::Pre2008`].
+		pre_2008: Vec<u16>,
+	},
+	/// The command line is empty. `CommandLineToArgvW` returns the path of the
+	/// program as the only argument, but the C runtime reads no arguments.
+	EmptyCommandLine,
+}
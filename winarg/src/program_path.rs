@@ -0,0 +1,223 @@
+//! The path of the executable using `GetModuleFileNameW`.
+
+use crate::{
+	matching::eq_ignore_case, program_name, verbatim::strip_verbatim_prefix, ArgsNative,
+	FORWARD_SLASH, SLASH, SPACE, TAB,
+};
+use alloc::vec::Vec;
+#[cfg(test)]
+use core::cell::Cell;
+use core::{ffi::c_void, ptr};
+#[cfg(test)]
+extern crate std;
+#[cfg(feature = "std")]
+use std::{ffi::OsString, os::windows::ffi::OsStringExt, path::PathBuf};
+
+const DOT: u16 = b'.' as _;
+
+// Paths, including the null terminator, can't be longer than this.
+const MAX_PATH_LEN: usize = 32768;
+
+/// The full path of the running executable.
+///
+/// Unlike [`program_name`], this comes from the operating system rather than
+/// the command line so it can't be set by the parent process. The path may
+/// start with `\\?\`, especially if it's long. If `strip_verbatim` is true, the
+/// prefix is removed so `\\?\C:\app.exe` becomes `C:\app.exe` and
+/// `\\?\UNC\server\share\app.exe` becomes `\\server\share\app.exe`.
+///
+/// Returns `None` if `GetModuleFileNameW` fails.
+///
+/// ```
+/// use std::ffi::OsString;
+/// use std::os::windows::ffi::OsStringExt;
+///
+/// if let Some(path) = winarg::program_path(true) {
+///     println!("running {:?}", OsString::from_wide(&path));
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "program-path")))]
+pub fn program_path(strip_verbatim: bool) -> Option<Vec<u16>> {
+	let mut path = module_file_name(260)?;
+	if strip_verbatim {
+		strip_verbatim_prefix(&mut path);
+	}
+	Some(path)
+}
+
+/// The full path of the running executable, as a `PathBuf`.
+///
+/// See [`program_path`] for details.
+///
+/// ```
+/// let path = winarg::program_path_buf(true).expect("the path of the executable");
+/// println!("running {}", path.display());
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "program-path", feature = "std"))))]
+pub fn program_path_buf(strip_verbatim: bool) -> Option<PathBuf> {
+	program_path(strip_verbatim).map(|path| OsString::from_wide(&path).into())
+}
+
+/// Check that the program name on the command line refers to the running
+/// executable.
+///
+/// The program name is usually the path the parent process used to start the
+/// program, but it can be anything. This is a cheap check that it hasn't been
+/// changed.
+///
+/// Paths are compared ignoring case, and `/` is treated the same as `\`. A
+/// relative program name, including a bare file name, matches if it's the end
+/// of the executable's path. Its `.exe` extension may be left out. Components
+/// before a `..` in a relative path aren't checked, and neither is the current
+/// directory, because the parent process may have had a different one.
+///
+/// ```
+/// if !winarg::program_path_matches_arg0() {
+///     eprintln!("warning: the program name doesn't match the executable");
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "program-path")))]
+pub fn program_path_matches_arg0() -> bool {
+	let arg0: Vec<u16> = program_name().utf16_units().collect();
+	match program_path(true) {
+		Some(path) => path_matches(&path, &arg0),
+		None => false,
+	}
+}
+
+/// The program name, or the path of the executable if the program name is
+/// empty.
+///
+/// Some processes are started with a command line that has no program name,
+/// such as ` -Embedding` for a COM server, so the program name isn't useful in
+/// messages. If it's empty or only spaces and tabs, this returns
+/// [`program_path(true)`](program_path) instead. If that fails too, the
+/// program name is returned as it is. `GetModuleFileNameW` is only called if
+/// the program name is empty.
+///
+/// ```
+/// let program = winarg::program_name_or_module();
+/// eprintln!("program: {}", String::from_utf16_lossy(&program));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "program-path")))]
+pub fn program_name_or_module() -> Vec<u16> {
+	or_module(program_name().utf16_units().collect())
+}
+
+impl ArgsNative {
+	/// Iterate the arguments as `Vec`s, replacing an empty or missing program
+	/// name with the path of the executable.
+	///
+	/// The program name is replaced in the same way as
+	/// [`program_name_or_module`]. If the command line is empty, the path is
+	/// the only argument. This is useful for passing the arguments on to
+	/// something that needs a program name. If the program name has already
+	/// been taken from the iterator, nothing is replaced.
+	///
+	/// ```
+	/// let argv: Vec<Vec<u16>> = winarg::args_native().program_or_module().collect();
+	/// println!("{} arguments after the program", argv.len() - 1);
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "program-path")))]
+	pub fn program_or_module(mut self) -> impl Iterator<Item = Vec<u16>> {
+		let program = if self.next.is_arg0 {
+			let arg0 = self.next().map(|arg| arg.utf16_units().collect());
+			Some(or_module(arg0.unwrap_or_default()))
+		} else {
+			None
+		};
+		program
+			.into_iter()
+			.chain(self.map(|arg| arg.utf16_units().collect()))
+	}
+}
+
+// Replace a program name that's empty or only whitespace with the path of the
+// executable.
+fn or_module(arg0: Vec<u16>) -> Vec<u16> {
+	if arg0.iter().any(|&unit| unit != SPACE && unit != TAB) {
+		return arg0;
+	}
+	program_path(true).unwrap_or(arg0)
+}
+
+// Get the path of the executable, starting with a buffer of `capacity` units.
+// The buffer is doubled until the path fits.
+pub(crate) fn module_file_name(mut capacity: usize) -> Option<Vec<u16>> {
+	#[cfg(test)]
+	MODULE_FILE_NAME_CALLS.with(|calls| calls.set(calls.get() + 1));
+	let mut buffer: Vec<u16> = Vec::new();
+	loop {
+		// An empty buffer would make the call fail.
+		capacity = capacity.clamp(1, MAX_PATH_LEN);
+		buffer.reserve(capacity);
+		// SAFETY: The buffer has room for `capacity` units.
+		let len =
+			unsafe { GetModuleFileNameW(ptr::null_mut(), buffer.as_mut_ptr(), capacity as u32) }
+				as usize;
+		if len == 0 {
+			return None;
+		}
+		// The path is truncated if it fills the buffer.
+		if len < capacity || capacity == MAX_PATH_LEN {
+			// SAFETY: `len` units have been written, which is no more than
+			// `capacity`.
+			unsafe { buffer.set_len(len) };
+			return Some(buffer);
+		}
+		capacity *= 2;
+	}
+}
+
+// The number of times this thread has called `module_file_name`, so tests can
+// check when the operating system is asked.
+#[cfg(test)]
+std::thread_local! {
+	pub(crate) static MODULE_FILE_NAME_CALLS: Cell<usize> = const { Cell::new(0) };
+}
+
+// Whether `arg0` refers to `path`, which must be absolute.
+//
+// This compares components from the end so that a relative `arg0` matches the
+// end of the path. An absolute `arg0` has as many components as the path so
+// it has to match all of it.
+pub(crate) fn path_matches(path: &[u16], arg0: &[u16]) -> bool {
+	let is_separator = |w: &u16| *w == SLASH || *w == FORWARD_SLASH;
+	let mut arg0: Vec<&[u16]> = arg0.split(is_separator).collect();
+	// Only the components after the last `..` can be checked.
+	if let Some(parent) = arg0.iter().rposition(|&c| c == [DOT, DOT]) {
+		arg0.drain(..=parent);
+	}
+	arg0.retain(|&c| !c.is_empty() && c != [DOT]);
+	let path: Vec<&[u16]> = path.split(is_separator).filter(|c| !c.is_empty()).collect();
+	if arg0.len() > path.len() {
+		return false;
+	}
+	let path = &path[path.len() - arg0.len()..];
+	match (path.split_last(), arg0.split_last()) {
+		(Some((name, dirs)), Some((arg0_name, arg0_dirs))) => {
+			dirs.iter()
+				.zip(arg0_dirs)
+				.all(|(a, b)| eq_ignore_case(a, b))
+				&& (eq_ignore_case(name, arg0_name)
+					|| without_exe(name).is_some_and(|stem| eq_ignore_case(stem, arg0_name)))
+		}
+		_ => false,
+	}
+}
+
+// The file name without a `.exe` extension, if it has one.
+fn without_exe(name: &[u16]) -> Option<&[u16]> {
+	let exe = [DOT, u16::from(b'e'), u16::from(b'x'), u16::from(b'e')];
+	let (stem, ext) = name.split_at(name.len().checked_sub(exe.len())?);
+	if eq_ignore_case(ext, &exe) {
+		Some(stem)
+	} else {
+		None
+	}
+}
+
+extern "system" {
+	fn GetModuleFileNameW(hModule: *mut c_void, lpFilename: *mut u16, nSize: u32) -> u32;
+}
@@ -0,0 +1,244 @@
+//! Writing arguments to a command line.
+
+use crate::{QUOTE, SLASH, SPACE, TAB};
+use core::fmt;
+
+/// The maximum length of a command line that can be passed to
+/// `CreateProcessW`, in UTF-16 code units. This doesn't include the null
+/// terminator.
+pub const MAX_COMMAND_LINE: usize = 32766;
+
+/// The maximum length of a command line that `cmd.exe` accepts, in UTF-16 code
+/// units. This applies to commands run with `cmd /c` as well as batch files.
+pub const MAX_CMD_EXE_LINE: usize = 8191;
+
+/// The length of the command line that would be made from `args`, in UTF-16
+/// code units.
+///
+/// The first argument is the program name. Arguments are separated by a
+/// single space and only quoted if they need to be. This doesn't include the
+/// null terminator so the result can be compared to [`MAX_COMMAND_LINE`].
+///
+/// Use [`is_representable`] to check the arguments can be written to a
+/// command line at all. If they can't, the length is still calculated as if
+/// they could.
+///
+/// ```
+/// let args = ["app.exe", "a b", r#"say "hi""#];
+/// let args: Vec<Vec<u16>> = args.iter().map(|s| s.encode_utf16().collect()).collect();
+/// // app.exe "a b" "say \"hi\""
+/// assert_eq!(winarg::quoted_len(&args), 26);
+/// assert!(winarg::quoted_len(&args) <= winarg::MAX_COMMAND_LINE);
+/// ```
+pub fn quoted_len<I>(args: I) -> usize
+where
+	I: IntoIterator,
+	I::Item: AsRef<[u16]>,
+{
+	let mut len = 0;
+	quote(args, |_| len += 1);
+	len
+}
+
+// Write a command line made from `args` one code unit at a time.
+//
+// All quoting goes through here so that `quoted_len` always agrees with the
+// command line that would be written.
+pub(crate) fn quote<I, F>(args: I, mut write: F)
+where
+	I: IntoIterator,
+	I::Item: AsRef<[u16]>,
+	F: FnMut(u16),
+{
+	for (index, arg) in args.into_iter().enumerate() {
+		if index > 0 {
+			write(SPACE);
+		}
+		quote_one(arg.as_ref(), index == 0, &mut write);
+	}
+}
+
+/// Write a single argument so that it's parsed back unchanged.
+///
+/// This is the opposite of [`unquote_one`](crate::unquote_one). `write` is
+/// called with each code unit. The argument is quoted if it's empty or
+/// contains a space or tab and quotes are escaped, along with any backslashes
+/// before them. If `is_program` is true, it's written using the rules for the
+/// program name, where nothing can be escaped. Use [`is_representable`] to
+/// check the argument can be written at all.
+///
+/// ```
+/// let arg: Vec<u16> = r#"say "hi""#.encode_utf16().collect();
+/// let mut quoted = Vec::new();
+/// winarg::quote_one(&arg, false, |u| quoted.push(u));
+/// assert_eq!(String::from_utf16(&quoted).unwrap(), r#""say \"hi\"""#);
+/// ```
+pub fn quote_one<F: FnMut(u16)>(arg: &[u16], is_program: bool, mut write: F) {
+	let needs_quotes = arg.is_empty() || arg.iter().any(|&u| u == SPACE || u == TAB);
+	if needs_quotes {
+		write(QUOTE);
+	}
+	if is_program {
+		// The program name has no escapes.
+		arg.iter().for_each(|&u| write(u));
+	} else {
+		// Backslashes are only escaped if they come before a quote.
+		let mut backslashes = 0;
+		for &unit in arg {
+			if unit == SLASH {
+				backslashes += 1;
+			} else {
+				if unit == QUOTE {
+					(0..=backslashes).for_each(|_| write(SLASH));
+				}
+				backslashes = 0;
+			}
+			write(unit);
+		}
+		// So are backslashes before the closing quote.
+		if needs_quotes {
+			(0..backslashes).for_each(|_| write(SLASH));
+		}
+	}
+	if needs_quotes {
+		write(QUOTE);
+	}
+}
+
+/// Check that a list of arguments can be written to a command line so that
+/// it's parsed back into the same arguments.
+///
+/// The first argument is the program name. This uses
+/// [`QuotingRules::Modern`]. See [`is_representable_with`] for details.
+///
+/// ```
+/// let args = ["app.exe", "a \"quoted\" word", ""];
+/// let args: Vec<Vec<u16>> = args.iter().map(|s| s.encode_utf16().collect()).collect();
+/// assert!(winarg::is_representable(&args).is_ok());
+///
+/// let args: Vec<Vec<u16>> = vec!["a\"pp.exe".encode_utf16().collect()];
+/// let error = winarg::is_representable(&args).unwrap_err();
+/// assert_eq!((error.index(), error.position()), (0, 1));
+/// ```
+pub fn is_representable<I>(args: I) -> Result<(), WhyNot>
+where
+	I: IntoIterator,
+	I::Item: AsRef<[u16]>,
+{
+	is_representable_with(args, QuotingRules::default())
+}
+
+/// Check that a list of arguments can be written to a command line so that
+/// it's parsed back into the same arguments by parsers using `rules`.
+///
+/// The first argument is the program name. Under any rules:
+///
+/// * No argument can contain a null because that ends the command line.
+/// * The program name can't contain a quote. Quotes in the program name
+///   are removed and there's no way to escape them.
+///
+/// With [`QuotingRules::Legacy`], the program name also can't contain a
+/// control character other than a tab.
+///
+/// Any other argument can be quoted so that it's read back unchanged.
+///
+/// The error gives the first argument that can't be represented and the
+/// position of the code unit that's the problem.
+pub fn is_representable_with<I>(args: I, rules: QuotingRules) -> Result<(), WhyNot>
+where
+	I: IntoIterator,
+	I::Item: AsRef<[u16]>,
+{
+	for (index, arg) in args.into_iter().enumerate() {
+		representable(arg.as_ref(), index, rules)?;
+	}
+	Ok(())
+}
+
+// Check the argument at `index` in the list, where the program name is `0`.
+pub(crate) fn representable(arg: &[u16], index: usize, rules: QuotingRules) -> Result<(), WhyNot> {
+	match unrepresentable(arg.iter().copied(), index == 0, rules) {
+		Some((position, unit)) => Err(WhyNot {
+			index,
+			position,
+			unit,
+		}),
+		None => Ok(()),
+	}
+}
+
+/// The parsers that need to read a command line the same way.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuotingRules {
+	/// The rules used by the C runtime since 2008, which are the rules winarg
+	/// uses.
+	#[default]
+	Modern,
+	/// Also be read the same way by older parsers that find the end of the
+	/// program name differently.
+	///
+	/// The `lpCmdLine` passed to `WinMain`, and code that copies it, treats
+	/// any control character as the end of an unquoted program name. Such a
+	/// program name can still be quoted but winarg can't tell whether it will
+	/// be, so this treats a control character other than a tab in the program
+	/// name as a problem.
+	Legacy,
+}
+
+/// The reason arguments can't be written to a command line.
+///
+/// Returned by [`is_representable`] and [`is_representable_with`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WhyNot {
+	index: usize,
+	position: usize,
+	unit: u16,
+}
+impl WhyNot {
+	/// The index of the argument that can't be represented. The program name
+	/// is `0`.
+	pub fn index(self) -> usize {
+		self.index
+	}
+	/// The position of the code unit that's the problem, within the argument.
+	pub fn position(self) -> usize {
+		self.position
+	}
+	/// The code unit that's the problem.
+	pub fn unit(self) -> u16 {
+		self.unit
+	}
+}
+impl fmt::Display for WhyNot {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let reason = match self.unit {
+			0 => "a null",
+			QUOTE => "a quote",
+			_ => "a control character",
+		};
+		if self.index == 0 {
+			write!(f, "the program name contains {}", reason)?;
+		} else {
+			write!(f, "argument {} contains {}", self.index, reason)?;
+		}
+		write!(f, " at position {}", self.position)
+	}
+}
+#[cfg(feature = "std")]
+impl std::error::Error for WhyNot {}
+
+// The first code unit that can't be represented, if any, and its position.
+pub(crate) fn unrepresentable<I: IntoIterator<Item = u16>>(
+	arg: I,
+	is_arg0: bool,
+	rules: QuotingRules,
+) -> Option<(usize, u16)> {
+	arg.into_iter().enumerate().find(|&(_, unit)| match unit {
+		0 => true,
+		QUOTE => is_arg0,
+		TAB => false,
+		0x01..=0x1F => is_arg0 && rules == QuotingRules::Legacy,
+		_ => false,
+	})
+}
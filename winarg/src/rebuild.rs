@@ -0,0 +1,337 @@
+//! Making a new command line from an existing one.
+
+use crate::{
+	arg_spans_of, command_line, cow_args_of, is_representable_with, quote::quote, quote_one,
+	quoted_len, unquote_one, ArgsNative, Argument, ParseArgs, QuotingRules, WhyNot, WideIter,
+	SPACE,
+};
+use alloc::{borrow::Cow, vec::Vec};
+
+/// What [`rebuild`] or [`splice`] does with an argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Edit {
+	/// Keep the argument as it was written on the command line.
+	Keep,
+	/// Leave the argument out.
+	Drop,
+	/// Use these arguments instead. They're quoted if they need to be. An
+	/// empty list is the same as [`Edit::Drop`].
+	Replace(Vec<Vec<u16>>),
+	/// Keep the argument and add these arguments after it. They're quoted if
+	/// they need to be.
+	Insert(Vec<Vec<u16>>),
+	/// Use this text, which is already quoted, instead. It's added to the
+	/// command line exactly as it is, so it can be more than one argument.
+	/// Empty text is the same as [`Edit::Drop`].
+	ReplaceRaw(Vec<u16>),
+}
+
+/// Make a command line from the current process's arguments, for starting a
+/// new process with `CreateProcessW`.
+///
+/// `edit` is called with each argument and its index, starting with the
+/// program name at index `0`, and decides what to do with it. Arguments are
+/// separated by a single space.
+///
+/// Kept arguments are copied from the original command line exactly so their
+/// quoting doesn't change. The exception is an argument that becomes the
+/// program name because the ones before it were dropped. The program name is
+/// parsed differently so the argument is quoted again. New arguments are
+/// quoted in the same way as [`quoted_len`](crate::quoted_len). Not every
+/// argument can be written to a command line, so use
+/// [`is_representable`](crate::is_representable) to check them first.
+///
+/// The result ends with a null so it can be passed to `CreateProcessW` as
+/// `lpCommandLine`.
+///
+/// ```
+/// use winarg::Edit;
+///
+/// let command_line = winarg::rebuild(|arg, index| {
+///     if *arg == "--update-pending" {
+///         Edit::Drop
+///     } else if index == 0 {
+///         Edit::Insert(vec!["--updated".encode_utf16().collect()])
+///     } else {
+///         Edit::Keep
+///     }
+/// });
+/// assert_eq!(command_line.last(), Some(&0));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn rebuild<F>(mut edit: F) -> Vec<u16>
+where
+	F: FnMut(&Argument, usize) -> Edit,
+{
+	let mut command_line = CommandLine::default();
+	for (index, arg) in ArgsNative::from_env().enumerate() {
+		let raw = arg.raw_arg();
+		let (_, used) = unquote_one(raw, arg.is_arg0);
+		let raw = raw.get(..used).unwrap_or_default();
+		command_line.edit(&edit(&arg, index), raw, arg.is_arg0, &[SPACE]);
+	}
+	command_line.units.push(0);
+	command_line.units
+}
+
+/// Edit the arguments of `cmdline` without changing the rest of it.
+///
+/// Each edit is the index of an argument, starting with the program name at
+/// index `0`, and what to do with it. Arguments without an edit are kept. If
+/// an index has more than one edit, only the first is used.
+///
+/// Unlike [`rebuild`], everything that isn't edited is copied from `cmdline`
+/// exactly, including the whitespace before each argument and at the end.
+/// This keeps the command line recognizable, for example when leaving out a
+/// secret before logging it. Each argument that's left out takes the
+/// whitespace before it with it, so no extra whitespace is left behind. New
+/// arguments added by [`Edit::Replace`] or [`Edit::Insert`] are separated by a
+/// single space and quoted as in [`rebuild`], as is an argument that becomes
+/// the program name because the ones before it were left out.
+///
+/// The command line ends at the first null, if any. The result doesn't end
+/// with a null.
+///
+/// ```
+/// use winarg::Edit;
+///
+/// let cmdline: Vec<u16> = r#"app.exe  --user "a b"  --password hunter2"#.encode_utf16().collect();
+/// let spliced = winarg::splice(&cmdline, &[(3, Edit::Drop), (4, Edit::Drop)]);
+/// assert_eq!(String::from_utf16_lossy(&spliced), r#"app.exe  --user "a b""#);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn splice(cmdline: &[u16], edits: &[(usize, Edit)]) -> Vec<u16> {
+	let cmdline = WideIter::new(cmdline).as_slice();
+	let mut command_line = CommandLine::default();
+	let mut end = 0;
+	for (index, span) in arg_spans_of(cmdline).enumerate() {
+		let whitespace = cmdline.get(end..span.start).unwrap_or_default();
+		end = span.end;
+		let raw = cmdline.get(span).unwrap_or_default();
+		let edit = edits
+			.iter()
+			.find(|&&(i, _)| i == index)
+			.map_or(&Edit::Keep, |(_, edit)| edit);
+		command_line.edit(edit, raw, index == 0, whitespace);
+	}
+	// Whitespace at the end would be an empty program name on its own.
+	if command_line.len > 0 {
+		command_line
+			.units
+			.extend_from_slice(cmdline.get(end..).unwrap_or_default());
+		// Only an empty program name was kept, which has to be written as
+		// whitespace.
+		if command_line.units.is_empty() {
+			command_line.units.push(SPACE);
+		}
+	}
+	command_line.units
+}
+
+/// Make a command line from the current process's command line with `extra`
+/// arguments added at the end, for starting a new process with
+/// `CreateProcessW`.
+///
+/// This is for wrappers that start another program with everything they were
+/// given and a few more arguments. Everything from the program name to the
+/// end of the last argument is copied from the original command line exactly
+/// so its quoting doesn't change. Whitespace at the end isn't copied. The new
+/// arguments are each separated by a single space and quoted in the same way
+/// as [`quoted_len`](crate::quoted_len). Not every argument can be written to
+/// a command line, so use [`is_representable`](crate::is_representable) to
+/// check them first.
+///
+/// If the last argument has a quote that's never closed, the new arguments
+/// would be part of it, so it's quoted again instead of being copied.
+///
+/// The result ends with a null so it can be passed to `CreateProcessW` as
+/// `lpCommandLine`. See [`with_program_and_appended`] to start a different
+/// program.
+///
+/// ```
+/// let command_line = winarg::with_appended(&["--extra1", "--extra2"]);
+/// assert_eq!(command_line.last(), Some(&0));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn with_appended<I>(extra: I) -> Vec<u16>
+where
+	I: IntoIterator,
+	I::Item: AsRef<str>,
+{
+	append(command_line().as_slice(), None, extra)
+}
+
+/// Like [`with_appended`] but the program name is replaced with `program`.
+///
+/// `program` is quoted if it needs to be. The program name is parsed
+/// differently from other arguments, so it can't contain quotes. The
+/// arguments after the program name are copied from the original command
+/// line exactly, including the whitespace before them.
+///
+/// ```
+/// let command_line = winarg::with_program_and_appended(
+///     r"C:\Program Files\Tool\tool.exe",
+///     &["--extra1", "--extra2"],
+/// );
+/// assert_eq!(command_line.last(), Some(&0));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn with_program_and_appended<P, I>(program: P, extra: I) -> Vec<u16>
+where
+	P: AsRef<str>,
+	I: IntoIterator,
+	I::Item: AsRef<str>,
+{
+	append(command_line().as_slice(), Some(program.as_ref()), extra)
+}
+
+// Copy `cmdline` up to the end of its last argument, replacing the program
+// name if there's a new one, then add `extra`.
+fn append<I>(cmdline: &[u16], program: Option<&str>, extra: I) -> Vec<u16>
+where
+	I: IntoIterator,
+	I::Item: AsRef<str>,
+{
+	let encode = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+	let mut command_line = CommandLine::default();
+	let mut spans = arg_spans_of(cmdline).enumerate().peekable();
+	let mut end = 0;
+	while let Some((index, span)) = spans.next() {
+		let whitespace = cmdline.get(end..span.start).unwrap_or_default();
+		end = span.end;
+		let raw = cmdline.get(span).unwrap_or_default();
+		let is_arg0 = index == 0;
+		match program {
+			Some(program) if is_arg0 => command_line.push(&encode(program), whitespace),
+			_ if spans.peek().is_none() && is_left_open(raw, is_arg0) => {
+				let (arg, _) = unquote_one(raw, is_arg0);
+				command_line.push(&arg.collect::<Vec<u16>>(), whitespace);
+			}
+			_ => command_line.keep(raw, is_arg0, whitespace),
+		}
+	}
+	// An empty command line has no program name at all.
+	if command_line.len == 0 {
+		command_line.push(&encode(program.unwrap_or_default()), &[]);
+	}
+	for arg in extra {
+		command_line.push(&encode(arg.as_ref()), &[SPACE]);
+	}
+	command_line.units.push(0);
+	command_line.units
+}
+
+// Returns `true` if the argument written as `raw` ends in quotes, so anything
+// written after it would be part of it.
+fn is_left_open(raw: &[u16], is_arg0: bool) -> bool {
+	let mut parser = ParseArgs::new(WideIter::new(raw), is_arg0);
+	while parser.next().is_some() {}
+	parser.quote_mode
+}
+
+/// Quote the arguments in `cmdline` again, as simply as possible.
+///
+/// The command line is parsed and its arguments are written back separated
+/// by a single space, quoting only the ones that need it in the same way as
+/// [`quoted_len`]. Command lines that have the same arguments are
+/// canonicalized to the same command line so the result can be used as a key
+/// to compare them. It's parsed into the same arguments as `cmdline` and
+/// canonicalizing it again doesn't change it. The result doesn't end with a
+/// null.
+///
+/// This uses [`QuotingRules::Modern`]. A program name never has quotes once
+/// it's parsed so it can always be written again, and this only fails if
+/// `rules` are stricter. See [`canonicalize_with`].
+///
+/// ```
+/// let cmdline: Vec<u16> = r#""app.exe"  "a"b "c d"  "" \"e"#.encode_utf16().collect();
+/// let canonical = winarg::canonicalize(&cmdline).unwrap();
+/// assert_eq!(String::from_utf16(&canonical).unwrap(), r#"app.exe ab "c d" "" \"e"#);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn canonicalize(cmdline: &[u16]) -> Result<Vec<u16>, WhyNot> {
+	canonicalize_with(cmdline, QuotingRules::Modern)
+}
+
+/// Like [`canonicalize`] but the result must be parsed the same way by the
+/// parsers in `rules`.
+///
+/// An error is returned, rather than changing an argument, if the arguments
+/// can't be written that way. See
+/// [`is_representable_with`](crate::is_representable_with).
+///
+/// ```
+/// use winarg::QuotingRules;
+///
+/// let cmdline: Vec<u16> = "\"app\nname.exe\" a".encode_utf16().collect();
+/// assert!(winarg::canonicalize_with(&cmdline, QuotingRules::Modern).is_ok());
+/// let error = winarg::canonicalize_with(&cmdline, QuotingRules::Legacy).unwrap_err();
+/// assert_eq!((error.index(), error.position()), (0, 3));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn canonicalize_with(cmdline: &[u16], rules: QuotingRules) -> Result<Vec<u16>, WhyNot> {
+	let args: Vec<Cow<'_, [u16]>> = cow_args_of(cmdline).collect();
+	is_representable_with(&args, rules)?;
+	let mut canonical = Vec::with_capacity(quoted_len(&args));
+	quote(&args, |unit| canonical.push(unit));
+	Ok(canonical)
+}
+
+// A command line being built one argument at a time.
+#[derive(Default)]
+struct CommandLine {
+	units: Vec<u16>,
+	len: usize,
+}
+impl CommandLine {
+	// Apply `edit` to the argument written as `raw` in the original command
+	// line. `separator` goes before it unless it's the first argument.
+	fn edit(&mut self, edit: &Edit, raw: &[u16], is_arg0: bool, separator: &[u16]) {
+		match edit {
+			Edit::Keep => self.keep(raw, is_arg0, separator),
+			Edit::Drop => {}
+			Edit::Replace(args) => {
+				let mut separator = separator;
+				for new in args {
+					self.push(new, separator);
+					separator = &[SPACE];
+				}
+			}
+			Edit::Insert(args) => {
+				self.keep(raw, is_arg0, separator);
+				args.iter().for_each(|new| self.push(new, &[SPACE]));
+			}
+			Edit::ReplaceRaw(text) if text.is_empty() => {}
+			Edit::ReplaceRaw(text) => {
+				self.separate(separator);
+				self.units.extend_from_slice(text);
+			}
+		}
+	}
+
+	fn separate(&mut self, separator: &[u16]) {
+		if self.len > 0 {
+			self.units.extend_from_slice(separator);
+		}
+		self.len += 1;
+	}
+
+	// Copy an argument from the original command line.
+	fn keep(&mut self, raw: &[u16], is_arg0: bool, separator: &[u16]) {
+		if is_arg0 != (self.len == 0) {
+			let (arg, _) = unquote_one(raw, is_arg0);
+			let arg: Vec<u16> = arg.collect();
+			return self.push(&arg, separator);
+		}
+		self.separate(separator);
+		self.units.extend_from_slice(raw);
+	}
+
+	// Quote a new argument.
+	fn push(&mut self, arg: &[u16], separator: &[u16]) {
+		let is_program = self.len == 0;
+		self.separate(separator);
+		quote_one(arg, is_program, |u| self.units.push(u));
+	}
+}
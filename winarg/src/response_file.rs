@@ -0,0 +1,560 @@
+//! Expanding `@file` arguments.
+
+use crate::{ArgsNative, ParseArgs, WideIter, QUOTE, SLASH, SPACE, TAB};
+use alloc::{
+	string::String,
+	vec::{self, Vec},
+};
+use core::{convert::TryFrom, fmt, mem, ptr};
+use std::os::windows::ffi::OsStringExt;
+use std::{
+	ffi::OsString,
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+const AT: u16 = b'@' as _;
+const CR: u16 = b'\r' as _;
+const LF: u16 = b'\n' as _;
+const SINGLE_QUOTE: u16 = b'\'' as _;
+
+impl ArgsNative {
+	/// Replace `@file` arguments with the arguments read from `file`.
+	///
+	/// See [`ResponseFileArgs`] for details.
+	///
+	/// ```
+	/// for arg in winarg::args_native().expand_response_files().skip(1) {
+	///     match arg {
+	///         Ok(arg) => println!("{:?}", arg),
+	///         Err(e) => eprintln!("{}", e),
+	///     }
+	/// }
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	pub fn expand_response_files(self) -> ResponseFileArgs {
+		ResponseFileArgs::new(self)
+	}
+	/// Like [`expand_response_files`](Self::expand_response_files) but
+	/// splits the files using the given rules.
+	///
+	/// ```
+	/// use winarg::ResponseFileRules;
+	///
+	/// let args = winarg::args_native().expand_response_files_with(ResponseFileRules::Gnu);
+	/// for arg in args.skip(1) {
+	///     println!("{:?}", arg);
+	/// }
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	pub fn expand_response_files_with(self, rules: ResponseFileRules) -> ResponseFileArgs {
+		ResponseFileArgs::with_rules(self, rules)
+	}
+}
+
+/// An iterator that expands response files.
+///
+/// An argument that starts with `@` is a response file. The rest of the
+/// argument is the path to the file and the arguments read from the file take
+/// the place of the `@file` argument.
+///
+/// To pass an argument starting with a literal `@`, use `@@` instead. The first
+/// `@` will be removed. An argument that is just `@` is passed through
+/// unchanged, as is the zeroth argument.
+///
+/// # Response files
+///
+/// Files that start with a byte order mark are decoded as UTF-8 or UTF-16
+/// as appropriate. Other files are decoded using the [`FallbackEncoding`],
+/// which is UTF-8 by default. Invalid UTF-8 is replaced with `�` unless
+/// strict decoding is used.
+///
+/// The contents are split into arguments using [`ResponseFileRules`], which
+/// defaults to the same rules as the command line.
+///
+/// Response files can include other response files. Relative paths are always
+/// relative to the current directory, not to the including file. Nesting is
+/// limited to [`DEFAULT_MAX_DEPTH`](Self::DEFAULT_MAX_DEPTH) files deep, which
+/// can be changed using [`max_depth`](Self::max_depth).
+///
+/// An error is returned if a response file can't be read or decoded, includes itself
+/// (directly or indirectly) or is nested too deeply. The arguments after the
+/// `@file` argument are still expanded after an error.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct ResponseFileArgs {
+	args: ArgsNative,
+	rules: ResponseFileRules,
+	fallback: FallbackEncoding,
+	max_depth: usize,
+	// The response files currently being expanded. The last is the innermost.
+	// Using an explicit stack means deep nesting doesn't use the call stack.
+	files: Vec<ResponseFile>,
+}
+#[derive(Debug)]
+struct ResponseFile {
+	// The path as given in the `@file` argument.
+	path: PathBuf,
+	// The canonical path, used to detect cycles.
+	canonical: PathBuf,
+	args: vec::IntoIter<Vec<u16>>,
+}
+impl ResponseFileArgs {
+	/// The default limit on how deeply response files can be nested.
+	pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+	/// Expand the response files in `args`.
+	pub fn new(args: ArgsNative) -> Self {
+		Self::with_rules(args, ResponseFileRules::default())
+	}
+	/// Expand the response files in `args`, splitting them using `rules`.
+	pub fn with_rules(args: ArgsNative, rules: ResponseFileRules) -> Self {
+		Self {
+			args,
+			rules,
+			fallback: FallbackEncoding::default(),
+			max_depth: Self::DEFAULT_MAX_DEPTH,
+			files: Vec::new(),
+		}
+	}
+	/// Set the maximum number of response files that can be nested.
+	///
+	/// A depth of `1` allows response files on the command line but they
+	/// can't include other response files. A depth of `0` makes every
+	/// response file an error.
+	///
+	/// ```
+	/// let args = winarg::args_native().expand_response_files().max_depth(1);
+	/// for arg in args.skip(1) {
+	///     println!("{:?}", arg);
+	/// }
+	/// ```
+	pub fn max_depth(mut self, depth: usize) -> Self {
+		self.max_depth = depth;
+		self
+	}
+
+	/// Set how response files without a byte order mark are decoded.
+	///
+	/// ```
+	/// use winarg::FallbackEncoding;
+	///
+	/// let args = winarg::args_native()
+	///     .expand_response_files()
+	///     .fallback_encoding(FallbackEncoding::Utf8Strict);
+	/// for arg in args.skip(1) {
+	///     println!("{:?}", arg);
+	/// }
+	/// ```
+	pub fn fallback_encoding(mut self, fallback: FallbackEncoding) -> Self {
+		self.fallback = fallback;
+		self
+	}
+
+	// Start expanding the response file at `path`.
+	fn open(&mut self, path: PathBuf) -> Result<(), ResponseFileError> {
+		let error = |files: &[ResponseFile], kind| {
+			let chain = files
+				.iter()
+				.map(|f| f.path.clone())
+				.chain(Some(path.clone()))
+				.collect();
+			ResponseFileError { chain, kind }
+		};
+		if self.files.len() >= self.max_depth {
+			return Err(error(&self.files, ErrorKind::TooDeep(self.max_depth)));
+		}
+		let canonical =
+			fs::canonicalize(&path).map_err(|e| error(&self.files, ErrorKind::Io(e)))?;
+		if self.files.iter().any(|f| f.canonical == canonical) {
+			return Err(error(&self.files, ErrorKind::Cycle));
+		}
+		let bytes = fs::read(&path).map_err(|e| error(&self.files, ErrorKind::Io(e)))?;
+		let contents = decode(&bytes, self.fallback)
+			.map_err(|(encoding, e)| error(&self.files, ErrorKind::Decode(encoding, e)))?;
+		let args = match self.rules {
+			ResponseFileRules::WindowsCommandLine => split_windows(&contents),
+			ResponseFileRules::Gnu => split_gnu(&contents),
+		};
+		self.files.push(ResponseFile {
+			path,
+			canonical,
+			args: args.into_iter(),
+		});
+		Ok(())
+	}
+}
+impl Iterator for ResponseFileArgs {
+	type Item = Result<OsString, ResponseFileError>;
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let units = match self.files.last_mut() {
+				Some(file) => match file.args.next() {
+					Some(units) => units,
+					None => {
+						self.files.pop();
+						continue;
+					}
+				},
+				None => {
+					let arg = self.args.next()?;
+					let units: Vec<u16> = arg.utf16_units().collect();
+					if arg.is_arg0 {
+						return Some(Ok(OsString::from_wide(&units)));
+					}
+					units
+				}
+			};
+			match units.split_first() {
+				Some((&AT, path)) if !path.is_empty() => {
+					if path[0] == AT {
+						return Some(Ok(OsString::from_wide(path)));
+					}
+					if let Err(e) = self.open(PathBuf::from(OsString::from_wide(path))) {
+						return Some(Err(e));
+					}
+				}
+				_ => return Some(Ok(OsString::from_wide(&units))),
+			}
+		}
+	}
+}
+
+/// The rules used to split a response file into arguments.
+///
+/// Different tools split response files in different ways. Use the rules that
+/// match the tool that the response file was written for.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResponseFileRules {
+	/// The rules used by MSVC tools such as `link.exe`.
+	///
+	/// Each line is split into arguments using the same rules as the command
+	/// line, except that there's no program name so backslashes and quotes are
+	/// always handled the same way. A quote that's not closed by the end of the
+	/// line ends with the line.
+	///
+	/// Backslashes are only special when they come before a quote so
+	/// `C:\dir\file.obj` is read unchanged but a quoted path that ends with
+	/// a backslash needs the backslash to be doubled: `"C:\dir name\\"`.
+	#[default]
+	WindowsCommandLine,
+	/// The rules used by GNU tools such as `gcc`.
+	///
+	/// Arguments are separated by any ASCII whitespace, including newlines.
+	/// Both single and double quotes can be used and a quoted string can span
+	/// multiple lines.
+	///
+	/// Backslashes are literal except that `\"` and `\'` produce a literal
+	/// quote. Inside single quotes everything is literal until the closing
+	/// quote. So `C:\dir\file.obj` is read unchanged and a quoted path that
+	/// ends with a backslash can be written as `'C:\dir name\'`.
+	Gnu,
+}
+/// An error expanding a response file.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct ResponseFileError {
+	chain: Vec<PathBuf>,
+	kind: ErrorKind,
+}
+#[derive(Debug)]
+enum ErrorKind {
+	Io(io::Error),
+	Decode(ResponseFileEncoding, DecodeError),
+	Cycle,
+	TooDeep(usize),
+}
+#[derive(Debug)]
+enum DecodeError {
+	// The byte offset of the invalid UTF-8.
+	InvalidUtf8(usize),
+	// UTF-16 must have an even number of bytes.
+	OddLength(usize),
+	// Too large for `MultiByteToWideChar`.
+	TooLarge,
+	// `MultiByteToWideChar` failed.
+	Ansi,
+}
+impl ResponseFileError {
+	/// The path of the response file that caused the error, as given in the
+	/// `@file` argument.
+	pub fn path(&self) -> &Path {
+		// The chain always includes the file that caused the error.
+		self.chain.last().map_or(Path::new(""), |path| path)
+	}
+	/// The paths of the response files that were being expanded, from the
+	/// outermost to the response file that caused the error.
+	///
+	/// For example, if `a.rsp` includes `b.rsp` which includes `a.rsp` then
+	/// the chain is `["a.rsp", "b.rsp", "a.rsp"]`.
+	pub fn chain(&self) -> &[PathBuf] {
+		&self.chain
+	}
+	/// The underlying IO error, if the response file couldn't be read.
+	pub fn io_error(&self) -> Option<&io::Error> {
+		match &self.kind {
+			ErrorKind::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+	/// The encoding of the response file, if it couldn't be decoded.
+	///
+	/// This is the detected encoding, or the fallback encoding if the file
+	/// doesn't have a byte order mark.
+	pub fn encoding(&self) -> Option<ResponseFileEncoding> {
+		match self.kind {
+			ErrorKind::Decode(encoding, _) => Some(encoding),
+			_ => None,
+		}
+	}
+	/// Returns `true` if the response file includes itself, either directly or
+	/// through other response files.
+	pub fn is_cycle(&self) -> bool {
+		matches!(self.kind, ErrorKind::Cycle)
+	}
+	/// Returns `true` if response files were nested too deeply.
+	pub fn is_too_deep(&self) -> bool {
+		matches!(self.kind, ErrorKind::TooDeep(_))
+	}
+	// The byte offset of invalid UTF-8, if that's the problem.
+	pub(crate) fn offset(&self) -> Option<usize> {
+		match self.kind {
+			ErrorKind::Decode(_, DecodeError::InvalidUtf8(offset)) => Some(offset),
+			_ => None,
+		}
+	}
+}
+impl fmt::Display for ResponseFileError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match &self.kind {
+			ErrorKind::Io(e) => write!(
+				f,
+				"failed to read response file {}: {}",
+				Chain(&self.chain),
+				e
+			),
+			ErrorKind::Decode(encoding, e) => {
+				write!(
+					f,
+					"failed to decode response file {} as {}: ",
+					Chain(&self.chain),
+					encoding
+				)?;
+				match e {
+					DecodeError::InvalidUtf8(offset) => {
+						write!(f, "invalid UTF-8 at byte {}", offset)
+					}
+					DecodeError::OddLength(len) => write!(f, "odd number of bytes ({})", len),
+					DecodeError::TooLarge => f.write_str("file is too large"),
+					DecodeError::Ansi => f.write_str("conversion from the ANSI code page failed"),
+				}
+			}
+			ErrorKind::Cycle => write!(f, "response file includes itself: {}", Chain(&self.chain)),
+			ErrorKind::TooDeep(limit) => write!(
+				f,
+				"response files are nested more than {} deep: {}",
+				limit,
+				Chain(&self.chain)
+			),
+		}
+	}
+}
+impl std::error::Error for ResponseFileError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match &self.kind {
+			ErrorKind::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+// Displays an inclusion chain as `a.rsp -> b.rsp`.
+struct Chain<'a>(&'a [PathBuf]);
+impl fmt::Display for Chain<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (i, path) in self.0.iter().enumerate() {
+			if i > 0 {
+				f.write_str(" -> ")?;
+			}
+			write!(f, "`{}`", path.display())?;
+		}
+		Ok(())
+	}
+}
+
+/// The encoding of a response file.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResponseFileEncoding {
+	/// UTF-8 without a byte order mark.
+	Utf8,
+	/// UTF-8 with a byte order mark.
+	Utf8Bom,
+	/// Little endian UTF-16 with a byte order mark.
+	Utf16Le,
+	/// Big endian UTF-16 with a byte order mark.
+	Utf16Be,
+	/// The system's ANSI code page.
+	Ansi,
+}
+impl fmt::Display for ResponseFileEncoding {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Utf8 => "UTF-8",
+			Self::Utf8Bom => "UTF-8 (with BOM)",
+			Self::Utf16Le => "UTF-16LE",
+			Self::Utf16Be => "UTF-16BE",
+			Self::Ansi => "ANSI",
+		})
+	}
+}
+
+/// How to decode response files that don't start with a byte order mark.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FallbackEncoding {
+	/// Decode as UTF-8, replacing invalid UTF-8 with `�`.
+	#[default]
+	Utf8Lossy,
+	/// Decode as UTF-8. Invalid UTF-8 is an error, including in files that
+	/// start with a UTF-8 byte order mark.
+	Utf8Strict,
+	/// Decode using the system's ANSI code page.
+	Ansi,
+}
+
+// Detect the encoding from the byte order mark and decode the file.
+fn decode(
+	bytes: &[u8],
+	fallback: FallbackEncoding,
+) -> Result<Vec<u16>, (ResponseFileEncoding, DecodeError)> {
+	let (encoding, contents) = match bytes {
+		[0xEF, 0xBB, 0xBF, rest @ ..] => (ResponseFileEncoding::Utf8Bom, rest),
+		[0xFF, 0xFE, rest @ ..] => (ResponseFileEncoding::Utf16Le, rest),
+		[0xFE, 0xFF, rest @ ..] => (ResponseFileEncoding::Utf16Be, rest),
+		_ if fallback == FallbackEncoding::Ansi => (ResponseFileEncoding::Ansi, bytes),
+		_ => (ResponseFileEncoding::Utf8, bytes),
+	};
+	let error = |e| Err((encoding, e));
+	match encoding {
+		ResponseFileEncoding::Utf8 | ResponseFileEncoding::Utf8Bom => {
+			if fallback == FallbackEncoding::Utf8Strict {
+				match core::str::from_utf8(contents) {
+					Ok(s) => Ok(s.encode_utf16().collect()),
+					Err(e) => error(DecodeError::InvalidUtf8(
+						e.valid_up_to() + bytes.len() - contents.len(),
+					)),
+				}
+			} else {
+				Ok(String::from_utf8_lossy(contents).encode_utf16().collect())
+			}
+		}
+		ResponseFileEncoding::Utf16Le | ResponseFileEncoding::Utf16Be => {
+			if contents.len() % 2 != 0 {
+				return error(DecodeError::OddLength(bytes.len()));
+			}
+			let from_bytes = match encoding {
+				ResponseFileEncoding::Utf16Le => u16::from_le_bytes,
+				_ => u16::from_be_bytes,
+			};
+			Ok(contents
+				.chunks_exact(2)
+				.map(|b| from_bytes([b[0], b[1]]))
+				.collect())
+		}
+		ResponseFileEncoding::Ansi => decode_ansi(contents).map_err(|e| (encoding, e)),
+	}
+}
+
+fn decode_ansi(bytes: &[u8]) -> Result<Vec<u16>, DecodeError> {
+	const CP_ACP: u32 = 0;
+	if bytes.is_empty() {
+		return Ok(Vec::new());
+	}
+	let len = i32::try_from(bytes.len()).map_err(|_| DecodeError::TooLarge)?;
+	// SAFETY: The first call only calculates the length. The second call
+	// writes at most `needed` units into a buffer of that size.
+	unsafe {
+		// Both calls return zero if they fail, which can't be a successful
+		// conversion of non-empty input.
+		let needed = MultiByteToWideChar(CP_ACP, 0, bytes.as_ptr(), len, ptr::null_mut(), 0);
+		if needed <= 0 {
+			return Err(DecodeError::Ansi);
+		}
+		let mut wide = Vec::with_capacity(needed as usize);
+		let written =
+			MultiByteToWideChar(CP_ACP, 0, bytes.as_ptr(), len, wide.as_mut_ptr(), needed);
+		if written != needed {
+			return Err(DecodeError::Ansi);
+		}
+		wide.set_len(written as usize);
+		Ok(wide)
+	}
+}
+
+// Split each line of the file into arguments.
+fn split_windows(contents: &[u16]) -> Vec<Vec<u16>> {
+	let mut args = Vec::new();
+	for line in contents.split(|&w| w == CR || w == LF) {
+		let mut parser = ParseArgs::new(WideIter::new(line), false);
+		parser.cursor.skip_whitespace(&[]);
+		while parser.cursor.peek().is_some() {
+			args.push((&mut parser).collect());
+			parser.move_to_next_arg();
+		}
+	}
+	args
+}
+
+// Split the file into arguments using GNU style quoting.
+fn split_gnu(contents: &[u16]) -> Vec<Vec<u16>> {
+	let mut args = Vec::new();
+	let mut arg = Vec::new();
+	// Quotes can create an empty argument so track if we're in an argument.
+	let mut in_arg = false;
+	let mut quote = None;
+	let mut iter = contents.iter().copied().peekable();
+	while let Some(w) = iter.next() {
+		match (quote, w) {
+			(Some(SINGLE_QUOTE), SINGLE_QUOTE) => quote = None,
+			(Some(SINGLE_QUOTE), _) => arg.push(w),
+			(_, SLASH) if matches!(iter.peek(), Some(&QUOTE) | Some(&SINGLE_QUOTE)) => {
+				arg.extend(iter.next());
+			}
+			(Some(QUOTE), QUOTE) => quote = None,
+			(None, QUOTE) | (None, SINGLE_QUOTE) => quote = Some(w),
+			(None, _) if is_ascii_whitespace(w) => {
+				if in_arg {
+					args.push(mem::take(&mut arg));
+					in_arg = false;
+				}
+				continue;
+			}
+			_ => arg.push(w),
+		}
+		in_arg = true;
+	}
+	if in_arg {
+		args.push(arg);
+	}
+	args
+}
+
+fn is_ascii_whitespace(w: u16) -> bool {
+	matches!(w, SPACE | TAB | CR | LF | 0x0B | 0x0C)
+}
+
+extern "system" {
+	fn MultiByteToWideChar(
+		CodePage: u32,
+		dwFlags: u32,
+		lpMultiByteStr: *const u8,
+		cbMultiByte: i32,
+		lpWideCharStr: *mut u16,
+		cchWideChar: i32,
+	) -> i32;
+}
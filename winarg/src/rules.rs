@@ -0,0 +1,65 @@
+//! Choosing which parsing rules to use.
+
+/// The rules used to parse a command line.
+///
+/// The rules are the same except for how quotes are read. Use
+/// [`Parser::with_rules`](crate::Parser::with_rules) or
+/// [`unquote_one_with`](crate::unquote_one_with) to parse with rules other
+/// than the default.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ParserRules {
+	/// The rules used by the C runtime since 2008, which are the rules winarg
+	/// uses everywhere else.
+	#[default]
+	Modern,
+	/// The rule for `""` used by programs built with older toolchains, before
+	/// 2008.
+	///
+	/// In quotes, `""` closes the quotes and immediately opens them again, so
+	/// it's removed and the argument stays quoted. The modern rules read it
+	/// as a literal quote instead. This is the only difference. Where each
+	/// argument starts and ends is the same, so [`arg_spans`](crate::arg_spans)
+	/// works for both.
+	///
+	/// | Command line              | Modern                 | Pre2008            |
+	/// |---------------------------|------------------------|--------------------|
+	/// | `EXE "a b c"""`           | `a b c"`               | `a b c`            |
+	/// | `EXE "a""b"`              | `a"b`                  | `ab`               |
+	/// | `EXE """CallMeIshmael"""` | `"CallMeIshmael"`      | `CallMeIshmael`    |
+	/// | `EXE """"Call Me"" b`     | `"Call`, `Me`, `b`     | `Call`, `Me`, `b`  |
+	///
+	/// Outside quotes `""` opens and closes quotes under both rules, so it's an
+	/// empty argument on its own. The program name never has literal quotes.
+	Pre2008,
+}
+
+/// What a command line that starts with whitespace is parsed into.
+///
+/// The C runtime reads the program name up to the first space or tab, so
+/// ` test` has an empty program name followed by the argument `test`. That's
+/// what the parent process asked for, so winarg does the same by default. But
+/// a command line written by a person, such as a template for running another
+/// program, may have whitespace at the start by accident. Use
+/// [`Parser::with_leading_whitespace`](crate::Parser::with_leading_whitespace)
+/// or [`ArgSpans::with_leading_whitespace`](crate::ArgSpans::with_leading_whitespace)
+/// to skip it instead.
+///
+/// | Command line     | `EmptyArg0`    | `SkipToFirstToken` |
+/// |------------------|----------------|--------------------|
+/// | ` test`          | `""`, `test`   | `test`             |
+/// | `\t\t` (tabs)    | `""`           | no arguments       |
+/// | `EXE a`          | `EXE`, `a`     | `EXE`, `a`         |
+///
+/// Here `""` is an empty argument.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LeadingWhitespace {
+	/// The program name is empty, as it is for the C runtime.
+	#[default]
+	EmptyArg0,
+	/// The whitespace is skipped and the first argument is the program name.
+	/// A command line that's only whitespace has no arguments at all, the
+	/// same as an empty one.
+	SkipToFirstToken,
+}
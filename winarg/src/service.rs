@@ -0,0 +1,126 @@
+//! Building and checking the command lines that start Windows services.
+
+#[cfg(all(windows, feature = "std"))]
+use crate::{is_representable, quote::quote, Error};
+use crate::{to_ascii_lowercase, validate, Diagnostic, DiagnosticKind, QUOTE, SPACE, TAB};
+use alloc::vec::Vec;
+#[cfg(all(windows, feature = "std"))]
+use std::os::windows::ffi::OsStrExt;
+#[cfg(all(windows, feature = "std"))]
+use std::path::Path;
+
+/// Make the command line that a service is started with, known as its
+/// `ImagePath`.
+///
+/// The path of the executable is quoted if it contains a space, so it can't be
+/// mistaken for a shorter path followed by arguments. The arguments follow it,
+/// quoted as [`quote_one`](crate::quote_one) does. The result is null
+/// terminated, ready to be passed to `CreateServiceW` or stored in the
+/// registry as the service's `ImagePath` value.
+///
+/// An error is returned if the path contains a quote or any part contains a
+/// null, since those can't be written to a command line.
+///
+/// ```
+/// use std::path::Path;
+///
+/// let exe = Path::new(r"C:\Program Files\My Service\svc.exe");
+/// let image_path = winarg::service_image_path(exe, ["--service", "a b"])?;
+/// assert_eq!(
+///     String::from_utf16_lossy(&image_path),
+///     "\"C:\\Program Files\\My Service\\svc.exe\" --service \"a b\"\0"
+/// );
+/// assert!(winarg::check_image_path(&image_path).is_empty());
+/// # Ok::<(), winarg::Error>(())
+/// ```
+#[cfg(all(windows, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn service_image_path<I>(exe: &Path, args: I) -> Result<Vec<u16>, Error>
+where
+	I: IntoIterator,
+	I::Item: AsRef<str>,
+{
+	let exe: Vec<u16> = exe.as_os_str().encode_wide().collect();
+	let mut all = Vec::new();
+	all.push(exe);
+	all.extend(
+		args.into_iter()
+			.map(|arg| arg.as_ref().encode_utf16().collect()),
+	);
+	is_representable(&all)?;
+	let mut image_path = Vec::new();
+	quote(&all, |unit| image_path.push(unit));
+	image_path.push(0);
+	Ok(image_path)
+}
+
+/// Check a service's `ImagePath`, or any other command line, for an
+/// executable path with spaces that isn't quoted.
+///
+/// Windows reads an unquoted `C:\Program Files\My Service\svc.exe` by trying
+/// `C:\Program.exe`, then `C:\Program Files\My.exe`, before the real path.
+/// Anyone who can create one of those files can have it run in place of the
+/// service, often with more privileges than they have. This is reported as
+/// [`DiagnosticKind::UnquotedPathWithSpaces`], with a span covering the path
+/// that needs quotes.
+///
+/// The path is taken to end at the first `.exe` followed by whitespace or the
+/// end of the command line, ignoring case. A path without `.exe` isn't
+/// reported. The diagnostics from [`validate`](crate::validate) for the same
+/// command line follow.
+///
+/// The command line ends at the first null, so a value read from the
+/// registry can be checked with its null terminator.
+///
+/// ```
+/// use winarg::DiagnosticKind;
+///
+/// let image_path: Vec<u16> = r"C:\Program Files\My Service\svc.exe -k".encode_utf16().collect();
+/// let diagnostics = winarg::check_image_path(&image_path);
+/// assert_eq!(diagnostics[0].kind, DiagnosticKind::UnquotedPathWithSpaces);
+/// assert_eq!(diagnostics[0].span, 0..35);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn check_image_path(image_path: &[u16]) -> Vec<Diagnostic> {
+	let end = image_path
+		.iter()
+		.position(|&u| u == 0)
+		.unwrap_or(image_path.len());
+	let image_path = image_path.get(..end).unwrap_or_default();
+	let mut diagnostics = Vec::new();
+	if let Some(end) = unquoted_path_with_spaces(image_path) {
+		diagnostics.push(Diagnostic {
+			kind: DiagnosticKind::UnquotedPathWithSpaces,
+			span: 0..end,
+		});
+	}
+	diagnostics.extend(validate(image_path));
+	diagnostics
+}
+
+// The end of the executable's path, if it isn't quoted and has whitespace in
+// it.
+fn unquoted_path_with_spaces(image_path: &[u16]) -> Option<usize> {
+	if image_path.first() == Some(&QUOTE) {
+		return None;
+	}
+	let is_space = |unit: &u16| *unit == SPACE || *unit == TAB;
+	let first_space = image_path.iter().position(is_space)?;
+	// Each place the path could end, which is before whitespace or at the end.
+	let path_end = (1..=image_path.len())
+		.filter(|&end| image_path.get(end).is_none_or(is_space))
+		.find(|&end| {
+			let extension = end
+				.checked_sub(4)
+				.and_then(|start| image_path.get(start..end));
+			extension.is_some_and(|extension| {
+				let extension = extension.iter().map(|&u| to_ascii_lowercase(u));
+				extension.eq(".exe".encode_utf16())
+			})
+		})?;
+	if path_end > first_space {
+		Some(path_end)
+	} else {
+		None
+	}
+}
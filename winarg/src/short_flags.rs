@@ -0,0 +1,119 @@
+//! Expanding combined short flags such as `-abc`.
+
+use crate::{scalars, Argument, FlagValue};
+use core::iter::FusedIterator;
+
+const DASH: char = '-';
+
+/// Expand combined short flags, so that `-abc` is read as `-a -b -c`.
+///
+/// An argument that starts with a single `-` is split into one
+/// [`ShortFlag::Flag`] for each character after the `-`. If a character is in
+/// `flags_taking_values` the expansion stops there and the rest of the
+/// argument is that flag's value, as in `-ofile`. If there's nothing left, the
+/// value is the next argument instead, as in `-o file`. An argument used as a
+/// value is never expanded.
+///
+/// Everything else is passed through as a [`ShortFlag::Arg`]. That includes
+/// long options such as `--verbose` and a `-` on its own, which usually means
+/// standard input. After a `--` argument nothing is expanded, although the
+/// `--` itself is passed through.
+///
+/// Isolated surrogates in a flag are replaced with `�`. Pass the arguments
+/// without the program name, which is never a flag.
+///
+/// ```
+/// use winarg::ShortFlag;
+///
+/// for item in winarg::split_short_flags(winarg::args_native().skip(1), &['o']) {
+///     match item {
+///         ShortFlag::Flag(flag) => println!("flag -{}", flag),
+///         ShortFlag::FlagWithValue(flag, Some(value)) => {
+///             println!("flag -{} = {}", flag, value.scalars().collect::<String>())
+///         }
+///         ShortFlag::FlagWithValue(flag, None) => eprintln!("-{} needs a value", flag),
+///         ShortFlag::Arg(arg) => println!("argument {}", arg.scalars().collect::<String>()),
+///         _ => {}
+///     }
+/// }
+/// ```
+pub fn split_short_flags<I>(args: I, flags_taking_values: &[char]) -> ShortFlags<'_, I::IntoIter>
+where
+	I: IntoIterator<Item = Argument>,
+{
+	ShortFlags {
+		args: args.into_iter(),
+		flags_taking_values,
+		current: None,
+		after_double_dash: false,
+	}
+}
+
+/// An item returned by [`split_short_flags`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum ShortFlag {
+	/// A single short flag, without the `-`.
+	Flag(char),
+	/// A short flag that takes a value, along with its value. The value is
+	/// `None` if the flag was the last argument.
+	FlagWithValue(char, Option<FlagValue>),
+	/// Any other argument, unchanged.
+	Arg(Argument),
+}
+
+/// The iterator returned by [`split_short_flags`].
+#[derive(Clone, Debug)]
+pub struct ShortFlags<'a, I> {
+	args: I,
+	flags_taking_values: &'a [char],
+	// The argument being expanded and the number of code units already used.
+	current: Option<(Argument, usize)>,
+	after_double_dash: bool,
+}
+impl<I: Iterator<Item = Argument>> Iterator for ShortFlags<'_, I> {
+	type Item = ShortFlag;
+	fn next(&mut self) -> Option<ShortFlag> {
+		if let Some((arg, start)) = self.current.take() {
+			return Some(self.expand(arg, start));
+		}
+		let arg = self.args.next()?;
+		if self.after_double_dash {
+			return Some(ShortFlag::Arg(arg));
+		}
+		let mut chars = arg.scalars();
+		match (chars.next(), chars.next()) {
+			(Some(DASH), Some(DASH)) => {
+				self.after_double_dash = chars.next().is_none();
+				Some(ShortFlag::Arg(arg))
+			}
+			(Some(DASH), Some(_)) => Some(self.expand(arg, 1)),
+			_ => Some(ShortFlag::Arg(arg)),
+		}
+	}
+}
+impl<I: FusedIterator<Item = Argument>> FusedIterator for ShortFlags<'_, I> {}
+impl<I: Iterator<Item = Argument>> ShortFlags<'_, I> {
+	// The flag that starts `start` code units into `arg`. There must be one.
+	fn expand(&mut self, arg: Argument, start: usize) -> ShortFlag {
+		let flag = scalars(arg.utf16_units().skip(start))
+			.next()
+			.unwrap_or(char::REPLACEMENT_CHARACTER);
+		// An isolated surrogate is one code unit, as is its replacement.
+		let end = start + flag.len_utf16();
+		let is_last = arg.utf16_units().nth(end).is_none();
+		if self.flags_taking_values.contains(&flag) {
+			let value = if is_last {
+				self.args.next().map(|arg| FlagValue { arg, start: 0 })
+			} else {
+				Some(FlagValue { arg, start: end })
+			};
+			ShortFlag::FlagWithValue(flag, value)
+		} else {
+			if !is_last {
+				self.current = Some((arg, end));
+			}
+			ShortFlag::Flag(flag)
+		}
+	}
+}
@@ -0,0 +1,189 @@
+//! Finding where each argument is in the command line.
+
+use crate::{command_line, is_separator, LeadingWhitespace, WideIter, QUOTE, SLASH};
+use core::{iter::FusedIterator, ops::Range};
+
+/// Iterates the range of the command line that each argument came from.
+///
+/// Each range is the raw text of an argument, including any quotes and
+/// backslashes, as indexes into the command line in UTF-16 code units. The
+/// whitespace between arguments isn't included. There's one range for each
+/// argument returned by [`args_native`](crate::args_native), starting with the
+/// program name.
+///
+/// This only finds where arguments start and end, without working out what's
+/// in them, so it's much faster than parsing the arguments. Nothing is
+/// allocated.
+///
+/// ```
+/// let len = winarg::command_line_len();
+/// for span in winarg::arg_spans() {
+///     println!("{}..{} of {}", span.start, span.end, len);
+/// }
+/// ```
+pub fn arg_spans() -> ArgSpans<'static> {
+	ArgSpans::new(command_line(), &[])
+}
+
+/// Iterates the range of `cmdline` that each argument came from.
+///
+/// This is the same as [`arg_spans`] but for any command line. The command
+/// line ends at the first null, if any.
+///
+/// ```
+/// let cmdline: Vec<u16> = r#"app.exe "a b"  c\"d"#.encode_utf16().collect();
+/// let spans: Vec<_> = winarg::arg_spans_of(&cmdline).collect();
+/// assert_eq!(spans, [0..7, 8..13, 15..19]);
+/// ```
+pub fn arg_spans_of(cmdline: &[u16]) -> ArgSpans<'_> {
+	ArgSpans::new(WideIter::new(cmdline), &[])
+}
+
+/// Like [`arg_spans_of`] but the code units in `separators` also separate
+/// arguments when they're not in quotes.
+///
+/// **This doesn't match how Windows parses command lines**, which only
+/// separates arguments with spaces and tabs. It's for command lines that have
+/// been copied from documents, where a space may have become a non-breaking
+/// space (U+00A0) or an ideographic space (U+3000). Never use it for the
+/// command line a program was actually run with.
+///
+/// `separators` must be sorted. `"` and `\` are never separators, even if
+/// they're in it. Use [`unquote_one`](crate::unquote_one) on each span to get
+/// the argument.
+///
+/// ```
+/// let cmdline: Vec<u16> = "app.exe a\u{A0}\"b\u{A0}c\"".encode_utf16().collect();
+/// let args: Vec<String> = winarg::arg_spans_lenient(&cmdline, &[0xA0, 0x3000])
+///     .enumerate()
+///     .map(|(index, span)| {
+///         let (arg, _) = winarg::unquote_one(&cmdline[span], index == 0);
+///         String::from_utf16_lossy(&arg.collect::<Vec<u16>>())
+///     })
+///     .collect();
+/// assert_eq!(args, ["app.exe", "a", "b\u{A0}c"]);
+/// ```
+pub fn arg_spans_lenient<'a>(cmdline: &'a [u16], separators: &'a [u16]) -> ArgSpans<'a> {
+	ArgSpans::new(WideIter::new(cmdline), separators)
+}
+
+/// An iterator over the ranges of the command line that arguments came from.
+///
+/// Created by [`arg_spans`], [`arg_spans_of`] or [`arg_spans_lenient`].
+#[derive(Clone, Debug)]
+pub struct ArgSpans<'a> {
+	cursor: WideIter<'a>,
+	len: usize,
+	is_arg0: bool,
+	separators: &'a [u16],
+}
+impl<'a> ArgSpans<'a> {
+	fn new(cursor: WideIter<'a>, separators: &'a [u16]) -> Self {
+		Self {
+			cursor,
+			len: cursor.max_len(),
+			is_arg0: true,
+			separators,
+		}
+	}
+
+	fn offset(&self) -> usize {
+		self.len - self.cursor.max_len()
+	}
+
+	// Move past the argument at the cursor, keeping track of quotes but not
+	// what the argument contains. Returns the length of the argument once its
+	// quotes and escapes are removed, using the modern rules.
+	fn skip_arg(&mut self) -> usize {
+		let mut quote_mode = false;
+		let mut len = 0;
+		while let Some(unit) = self.cursor.peek() {
+			match unit {
+				unit if !quote_mode && is_separator(unit, self.separators) => break,
+				SLASH if !self.is_arg0 => {
+					let mut slashes = 0_usize;
+					while self.cursor.peek() == Some(SLASH) {
+						self.cursor.next();
+						slashes += 1;
+					}
+					if self.cursor.peek() == Some(QUOTE) {
+						// Half the slashes are kept. An odd number escapes the quote.
+						len += slashes / 2;
+						if slashes % 2 == 1 {
+							self.cursor.next();
+							len += 1;
+						}
+					} else {
+						len += slashes;
+					}
+				}
+				QUOTE => {
+					self.cursor.next();
+					// `""` in quotes is a literal quote.
+					if !self.is_arg0 && quote_mode && self.cursor.peek() == Some(QUOTE) {
+						self.cursor.next();
+						len += 1;
+					} else {
+						quote_mode = !quote_mode;
+					}
+				}
+				_ => {
+					// Skip the run of code units that don't end the argument or
+					// change how it's parsed.
+					let rest = self.cursor.slice;
+					let run = 1 + rest
+						.iter()
+						.skip(1)
+						.position(|&u| {
+							u == SLASH
+								|| u == QUOTE || (!quote_mode && is_separator(u, self.separators))
+						})
+						.unwrap_or(rest.len() - 1);
+					self.cursor.slice = rest.get(run..).unwrap_or_default();
+					len += run;
+				}
+			}
+		}
+		len
+	}
+
+	// The next span and the length of the argument in it.
+	pub(crate) fn next_with_len(&mut self) -> Option<(Range<usize>, usize)> {
+		self.cursor.peek()?;
+		let start = self.offset();
+		let len = self.skip_arg();
+		let end = self.offset();
+		self.cursor.skip_whitespace(self.separators);
+		self.is_arg0 = false;
+		Some((start..end, len))
+	}
+}
+impl ArgSpans<'_> {
+	/// Choose what happens to whitespace at the start of the command line.
+	///
+	/// By default it ends an empty program name. See [`LeadingWhitespace`].
+	/// This has no effect once spans have been taken from the iterator.
+	///
+	/// ```
+	/// use winarg::LeadingWhitespace;
+	///
+	/// let cmdline: Vec<u16> = " app.exe a".encode_utf16().collect();
+	/// let spans: Vec<_> = winarg::arg_spans_of(&cmdline)
+	///     .with_leading_whitespace(LeadingWhitespace::SkipToFirstToken)
+	///     .collect();
+	/// assert_eq!(spans, [1..8, 9..10]);
+	/// ```
+	pub fn with_leading_whitespace(mut self, leading: LeadingWhitespace) -> Self {
+		if leading == LeadingWhitespace::SkipToFirstToken && self.offset() == 0 {
+			self.cursor.skip_whitespace(self.separators);
+		}
+		self
+	}
+}
+impl Iterator for ArgSpans<'_> {
+	type Item = Range<usize>;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.next_with_len().map(|(span, _)| span)
+	}
+}
+impl FusedIterator for ArgSpans<'_> {}
@@ -0,0 +1,147 @@
+//! Splitting an argument into fields.
+
+use crate::{Argument, FlagValue, Utf16Units};
+use core::fmt;
+use core::iter::Take;
+
+impl Argument {
+	/// Iterates the fields of the argument between each `sep`, such as the
+	/// directories in `a;b;c`.
+	///
+	/// Each field iterates UTF-16 code units. Fields can be cloned and used
+	/// after the iterator has moved on. Like [`str::split`], empty fields are
+	/// included so `a;;b` has three fields and an empty argument has one.
+	///
+	/// `sep` can be any `char`. One outside the Basic Multilingual Plane, such
+	/// as `😀`, matches its surrogate pair. An isolated surrogate in the
+	/// argument never matches.
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     for dir in arg.split(';') {
+	///         let dir: Vec<u16> = dir.collect();
+	///         println!("{}", String::from_utf16_lossy(&dir));
+	///     }
+	/// }
+	/// ```
+	pub fn split(
+		&self,
+		sep: char,
+	) -> impl Iterator<Item = impl Iterator<Item = u16> + fmt::Debug + Clone> + fmt::Debug + Clone
+	{
+		Split::new(self.into_iter(), sep, false)
+	}
+
+	/// Iterates the fields of the argument between each `sep`, leaving out an
+	/// empty last field.
+	///
+	/// This is the same as [`split`](Self::split) except that `sep` ends a
+	/// field instead of separating two, like [`str::split_terminator`]. So
+	/// `a;b;` has two fields and an empty argument has none.
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     println!("{} lines", arg.split_terminator('\n').count());
+	/// }
+	/// ```
+	pub fn split_terminator(
+		&self,
+		sep: char,
+	) -> impl Iterator<Item = impl Iterator<Item = u16> + fmt::Debug + Clone> + fmt::Debug + Clone
+	{
+		Split::new(self.into_iter(), sep, true)
+	}
+}
+
+impl FlagValue {
+	/// Iterates the fields of the value between each `sep`.
+	///
+	/// Only the value is split, so `--dirs=a;b` and `--dirs a;b` both have the
+	/// fields `a` and `b`. See [`Argument::split`].
+	///
+	/// ```
+	/// if let Some(dirs) = winarg::value_of("--dirs") {
+	///     for dir in dirs.split(';') {
+	///         let dir: Vec<u16> = dir.collect();
+	///         println!("{}", String::from_utf16_lossy(&dir));
+	///     }
+	/// }
+	/// ```
+	pub fn split(
+		&self,
+		sep: char,
+	) -> impl Iterator<Item = impl Iterator<Item = u16> + fmt::Debug + Clone> + fmt::Debug + Clone
+	{
+		Split::new(self.units(), sep, false)
+	}
+
+	/// Iterates the fields of the value between each `sep`, leaving out an
+	/// empty last field. See [`Argument::split_terminator`].
+	pub fn split_terminator(
+		&self,
+		sep: char,
+	) -> impl Iterator<Item = impl Iterator<Item = u16> + fmt::Debug + Clone> + fmt::Debug + Clone
+	{
+		Split::new(self.units(), sep, true)
+	}
+}
+
+#[derive(Clone, Debug)]
+struct Split {
+	rest: Utf16Units,
+	sep: [u16; 2],
+	sep_len: usize,
+	// Leave out the last field if it's empty.
+	terminator: bool,
+	finished: bool,
+}
+impl Split {
+	fn new(rest: Utf16Units, sep: char, terminator: bool) -> Self {
+		let mut units = [0; 2];
+		let sep_len = sep.encode_utf16(&mut units).len();
+		Self {
+			rest,
+			sep: units,
+			sep_len,
+			terminator,
+			finished: false,
+		}
+	}
+
+	// Takes the separator from the start of `rest` if it's there.
+	fn take_sep(&mut self) -> bool {
+		let mut rest = self.rest.clone();
+		let found = self
+			.sep
+			.iter()
+			.take(self.sep_len)
+			.all(|&unit| rest.next() == Some(unit));
+		if found {
+			self.rest = rest;
+		}
+		found
+	}
+}
+impl Iterator for Split {
+	type Item = Take<Utf16Units>;
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.finished {
+			return None;
+		}
+		let start = self.rest.clone();
+		let mut len = 0;
+		loop {
+			if self.take_sep() {
+				return Some(start.take(len));
+			}
+			if self.rest.next().is_none() {
+				self.finished = true;
+				if self.terminator && len == 0 {
+					return None;
+				}
+				return Some(start.take(len));
+			}
+			len += 1;
+		}
+	}
+}
@@ -0,0 +1,91 @@
+//! Counting how arguments were written.
+
+use crate::{ArgsNative, Argument, ParseArgs, SpanKind};
+use core::iter::Sum;
+
+impl Argument {
+	/// Count how the argument was written, without keeping its contents.
+	///
+	/// The argument is parsed once. This is cheap enough to run on every
+	/// argument of every process, for example to flag command lines with far
+	/// more quotes than they need, which is a common way of hiding what a
+	/// command does.
+	///
+	/// ```
+	/// for arg in winarg::args_native() {
+	///     let stats = arg.stats();
+	///     if stats.quotes > 2 || stats.escapes > 0 {
+	///         println!("{} is quoted oddly", arg);
+	///     }
+	/// }
+	/// ```
+	pub fn stats(&self) -> ArgStats {
+		let (mut quotes, mut escapes) = (0, 0);
+		let mut visit = |kind, _| match kind {
+			SpanKind::Quote => quotes += 1,
+			SpanKind::Escape => escapes += 1,
+			_ => {}
+		};
+		let mut parser = ParseArgs::new(self.arg, self.is_arg0);
+		let mut stats = ArgStats::default();
+		while let Some((unit, _)) = parser.next_visit(&mut visit) {
+			stats.len += 1;
+			stats.has_non_ascii |= unit >= 0x80;
+			stats.has_control |= unit < 0x20 || unit == 0x7F;
+		}
+		stats.raw_len = parser.offset();
+		stats.quotes = quotes;
+		stats.escapes = escapes;
+		stats
+	}
+}
+
+/// The statistics for every argument in the command line, added together.
+///
+/// The lengths don't include the whitespace between arguments. See
+/// [`Argument::stats`].
+///
+/// ```
+/// let stats = winarg::command_line_stats();
+/// println!("{} quotes in {} code units", stats.quotes, stats.raw_len);
+/// ```
+pub fn command_line_stats() -> ArgStats {
+	ArgsNative::from_env().map(|arg| arg.stats()).sum()
+}
+
+/// How an argument was written. Returned by [`Argument::stats`].
+///
+/// Statistics for several arguments can be added together with
+/// [`Iterator::sum`]. The counts are added and the flags are set if they're
+/// set for any of the arguments.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ArgStats {
+	/// The number of quotes that start or end quoted text. Quotes that are
+	/// part of the argument aren't counted.
+	pub quotes: usize,
+	/// The number of runs of backslashes before a quote, which escape it or
+	/// each other.
+	pub escapes: usize,
+	/// The length of the argument as it's written on the command line, in
+	/// UTF-16 code units.
+	pub raw_len: usize,
+	/// The length of the argument once it's parsed, in UTF-16 code units.
+	pub len: usize,
+	/// Whether the argument has any code units that aren't ASCII.
+	pub has_non_ascii: bool,
+	/// Whether the argument has any ASCII control characters, including tabs.
+	pub has_control: bool,
+}
+impl Sum for ArgStats {
+	fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+		iter.fold(Self::default(), |total, stats| Self {
+			quotes: total.quotes + stats.quotes,
+			escapes: total.escapes + stats.escapes,
+			raw_len: total.raw_len + stats.raw_len,
+			len: total.len + stats.len,
+			has_non_ascii: total.has_non_ascii || stats.has_non_ascii,
+			has_control: total.has_control || stats.has_control,
+		})
+	}
+}
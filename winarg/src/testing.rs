@@ -0,0 +1,88 @@
+//! Overriding the command line in tests.
+//!
+//! **This is for tests only.** Enabling the `test-override` feature makes every
+//! function that reads the command line check for an override first. Use it as
+//! a dev-dependency feature so it's never enabled in release builds:
+//!
+//! ```ini
+//! [dev-dependencies.winarg]
+//! version = "0.2.0"
+//! features = ["test-override"]
+//! ```
+//!
+//! # Threads
+//!
+//! The override only applies to the thread that set it. Other threads,
+//! including threads spawned while an override is active, still see the real
+//! command line (or their own override). This means tests that run in parallel
+//! won't interfere with each other.
+//!
+//! # Cached arguments
+//!
+//! `args_cached` and [`args_cached_lossy`](crate::args_cached_lossy) make the
+//! arguments of each overridden command line separately, the first time
+//! they're asked for with it, so tests don't see each other's arguments. Each
+//! call to [`set_command_line`] with a different slice gets new arguments,
+//! even if the text is the same. The `cached` module is different: it reads
+//! the command line once, for whichever thread uses it first.
+//!
+//! # Example
+//!
+//! ```
+//! let cmdline: Vec<u16> = "app.exe --verbose file.txt".encode_utf16().collect();
+//! let _guard = winarg::testing::set_command_line(cmdline.leak());
+//! let args: Vec<_> = winarg::args_native().collect();
+//! assert_eq!(args, ["app.exe", "--verbose", "file.txt"]);
+//! ```
+
+extern crate std;
+
+use core::{cell::Cell, fmt, marker::PhantomData};
+use std::thread_local;
+
+thread_local! {
+	static COMMAND_LINE: Cell<Option<&'static [u16]>> = const { Cell::new(None) };
+}
+
+/// Use `cmdline` as the command line for the current thread.
+///
+/// The override lasts until the returned guard is dropped, after which the
+/// previous command line is restored. Guards should be dropped in the reverse
+/// order they were created.
+///
+/// The command line is truncated at the first null, if any.
+///
+/// Arguments taken from the command line borrow it for `'static` so they
+/// remain valid after the guard is dropped.
+pub fn set_command_line(cmdline: &'static [u16]) -> CommandLineGuard {
+	let previous = COMMAND_LINE.with(|c| c.replace(Some(cmdline)));
+	CommandLineGuard {
+		previous,
+		_not_send: PhantomData,
+	}
+}
+
+/// Restores the previous command line when dropped.
+///
+/// Created by [`set_command_line`].
+#[must_use = "the override ends when the guard is dropped"]
+pub struct CommandLineGuard {
+	previous: Option<&'static [u16]>,
+	// The override is per thread so the guard must be dropped on the same thread.
+	_not_send: PhantomData<*const u16>,
+}
+impl Drop for CommandLineGuard {
+	fn drop(&mut self) {
+		COMMAND_LINE.with(|c| c.set(self.previous));
+	}
+}
+impl fmt::Debug for CommandLineGuard {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("CommandLineGuard").finish_non_exhaustive()
+	}
+}
+
+// Used by `command_line` in place of `GetCommandLineW`, if set.
+pub(crate) fn command_line_override() -> Option<&'static [u16]> {
+	COMMAND_LINE.with(|c| c.get())
+}
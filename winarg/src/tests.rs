@@ -1,10 +1,61 @@
-// Currently this tests the parsing but not the API.
-// Doc tests do some limited testing of the API but it'd be good to have something more thorough.
-// This situation should be improved before 1.0.
+// This tests the parsing rules. The public API is tested in `tests/api.rs`.
 
 extern crate alloc;
-use super::{scalars, Parser};
-use alloc::{string::String, vec::Vec};
+extern crate std;
+use alloc::{
+	boxed::Box,
+	format,
+	string::{String, ToString},
+	vec,
+	vec::Vec,
+};
+use std::sync::Mutex;
+
+mod analyze;
+mod api;
+mod arg_match;
+mod bounds;
+#[cfg(feature = "alloc")]
+mod builder;
+#[cfg(feature = "alloc")]
+mod bytes;
+#[cfg(feature = "cached")]
+mod cached;
+mod classify;
+#[cfg(feature = "alloc")]
+mod cmd;
+#[cfg(feature = "alloc")]
+mod command_line;
+#[cfg(all(windows, feature = "std"))]
+mod dropped;
+mod eq;
+mod error;
+mod explain;
+#[cfg(all(windows, feature = "glob", feature = "std"))]
+mod glob;
+#[cfg(all(windows, feature = "windows"))]
+mod hstring;
+mod number;
+#[cfg(all(windows, feature = "windows-sys"))]
+mod pcwstr;
+mod program_name;
+#[cfg(all(windows, feature = "program-path"))]
+mod program_path;
+mod quote;
+#[cfg(feature = "alloc")]
+mod rebuild;
+#[cfg(all(windows, feature = "std"))]
+mod response_file;
+mod roundtrip;
+mod rules;
+#[cfg(feature = "alloc")]
+mod service;
+mod surrogates;
+#[cfg(feature = "widestring")]
+mod u16str;
+#[cfg(feature = "alloc")]
+mod utf8;
+mod validate;
 
 /*-*-*-*-*
 
@@ -16,25 +67,37 @@ and with additions from https://daviddeley.com/autohotkey/parameters/parameters.
 
 *-*-*-*-*/
 
-// Currently this library always uses `GetCommandLineW` so for testing we need
-// a new function that uses the parser.
-fn null_separated_list(cmdline: &[u16]) -> String {
-	unsafe {
-		// Note: `from_ptr` is not public and *probably* never will be.
-		// However, it might make sense to have a public function that operates on a slice.
-		scalars(Parser::from_ptr(cmdline.as_ptr()).map(|t| t.as_u16())).collect()
-	}
-}
-
 fn chk(string: &str, parts: &[&str]) {
-	let cmdline: Vec<u16> = string.encode_utf16().chain(Some(0)).collect();
-	let args = null_separated_list(&cmdline);
+	let args: String = with_command_line(string, || crate::null_separated_list().collect());
 	let mut len = 0;
 	for (arg, &part) in args.split('\0').zip(parts) {
 		assert_eq!(arg, part);
 		len += 1;
 	}
 	assert_eq!(len, parts.len());
+	chk_arg_spans(string, parts);
+}
+
+// Check that `arg_spans` finds each argument, by parsing each span on its own.
+fn chk_arg_spans(string: &str, parts: &[&str]) {
+	let cmdline: Vec<u16> = string.encode_utf16().collect();
+	let spans: Vec<_> = crate::arg_spans_of(&cmdline).collect();
+	assert_eq!(spans.len(), parts.len(), "{:?}", string);
+	for (index, (span, &part)) in spans.iter().zip(parts).enumerate() {
+		let (arg, used) = crate::unquote_one(&cmdline[span.clone()], index == 0);
+		assert_eq!(String::from_utf16_lossy(&arg.collect::<Vec<u16>>()), part);
+		assert_eq!(used, span.len());
+	}
+	// They're the same as the arguments found by `analyze`.
+	let mut arguments = Vec::new();
+	crate::analyze(&cmdline, |span| {
+		if span.kind == crate::SpanKind::Argument {
+			arguments.push(span.range);
+		}
+	});
+	assert_eq!(spans, arguments);
+	let env: Vec<_> = with_command_line(string, || crate::arg_spans().collect());
+	assert_eq!(spans, env);
 }
 
 #[test]
@@ -53,6 +116,84 @@ fn official_examples() {
 	chk(r#"EXE a\\\\"b c" d e"#, &["EXE", r"a\\b c", "d", "e"]);
 }
 
+#[test]
+fn lenient_separators() {
+	const SEPARATORS: &[u16] = &[0xA0, 0x3000];
+	fn lenient(string: &str) -> Vec<String> {
+		let cmdline: Vec<u16> = string.encode_utf16().collect();
+		let spans: Vec<_> = crate::arg_spans_lenient(&cmdline, SEPARATORS).collect();
+		// `analyze_lenient` finds the same arguments.
+		let mut arguments = Vec::new();
+		crate::analyze_lenient(&cmdline, SEPARATORS, |span| {
+			if span.kind == crate::SpanKind::Argument {
+				arguments.push(span.range);
+			}
+		});
+		assert_eq!(spans, arguments);
+		spans
+			.into_iter()
+			.enumerate()
+			.map(|(index, span)| {
+				let (arg, _) = crate::unquote_one(&cmdline[span], index == 0);
+				String::from_utf16_lossy(&arg.collect::<Vec<u16>>())
+			})
+			.collect()
+	}
+
+	// Between arguments, including runs mixed with spaces.
+	assert_eq!(lenient("EXE a\u{A0}b"), ["EXE", "a", "b"]);
+	assert_eq!(lenient("EXE\u{3000}a \u{A0}\tb\u{A0}"), ["EXE", "a", "b"]);
+	// In quotes it's part of the argument.
+	assert_eq!(
+		lenient("\"E\u{A0}XE\" \"a\u{A0}b\" c\\\"\u{A0}d"),
+		["E\u{A0}XE", "a\u{A0}b", "c\"", "d"]
+	);
+	// By default it's an ordinary character.
+	chk(
+		"EXE a\u{A0}b \"c\u{3000}d\"",
+		&["EXE", "a\u{A0}b", "c\u{3000}d"],
+	);
+}
+
+// Check the offset in the command line that each token comes from.
+fn chk_spanned(string: &str, offsets: &[usize]) {
+	use crate::Token;
+
+	let cmdline: Vec<u16> = string.encode_utf16().collect();
+	let tokens: Vec<_> = with_command_line(string, || crate::Parser().spanned().collect());
+	for t in &tokens {
+		match t.token {
+			// Every unit comes from a copy of itself.
+			Token::Unit(unit) => assert_eq!(cmdline[t.offset], unit.get()),
+			// The next argument starts after whitespace.
+			Token::NextArg => assert_eq!(cmdline[t.offset - 1], u16::from(b' ')),
+		}
+	}
+	let tokens: Vec<usize> = tokens.iter().map(|t| t.offset).collect();
+	assert_eq!(tokens, offsets);
+}
+
+#[test]
+fn official_examples_spanned() {
+	chk_spanned(r#"EXE "abc" d e"#, &[0, 1, 2, 4, 5, 6, 7, 10, 10, 12, 12]);
+	// Each slash in a run comes from the start of the run.
+	chk_spanned(
+		r#"EXE a\\\b d"e f"g h"#,
+		&[0, 1, 2, 4, 4, 5, 5, 5, 8, 10, 10, 12, 13, 14, 16, 18, 18],
+	);
+	// An escaped quote comes from the quote.
+	chk_spanned(
+		r#"EXE a\\\"b c d"#,
+		&[0, 1, 2, 4, 4, 5, 8, 9, 11, 11, 13, 13],
+	);
+	chk_spanned(
+		r#"EXE a\\\\"b c" d e"#,
+		&[0, 1, 2, 4, 4, 5, 5, 10, 11, 12, 15, 15, 17, 17],
+	);
+	// A quote from `""` comes from the second quote.
+	chk_spanned(r#"EXE "a"" b"#, &[0, 1, 2, 4, 5, 7, 8, 9]);
+}
+
 #[test]
 fn whitespace_behavior() {
 	chk(" test", &["", "test"]);
@@ -125,3 +266,1285 @@ fn post_2008() {
 		&["EXE", r#""Call"#, "Me", "Ishmael", "b", "c"],
 	);
 }
+
+/*-*-*-*-*
+
+# Environment tests
+
+Functions that use `GetCommandLineW` are tested by overriding the command line
+for the current thread using `testing::set_command_line`. Each test thread has
+its own override so tests can still run in parallel.
+
+The override is backed by a slice so these tests, including the parsing tests
+above, can be run under Miri. Each slice ends where its allocation does, with
+no null after it, so Miri and AddressSanitizer catch any read past the end.
+
+*-*-*-*-*/
+
+// Run `f` using `cmdline` as the command line for the current thread.
+fn with_command_line_wide<R>(cmdline: &[u16], f: impl FnOnce() -> R) -> R {
+	// Arguments are 'static so the command line has to be leaked.
+	let _guard = crate::testing::set_command_line(leak(cmdline));
+	f()
+}
+// Leak a copy of `units`. Keeping it reachable from a static tells Miri the
+// leak is intentional.
+fn leak(units: &[u16]) -> &'static [u16] {
+	static LEAKED: Mutex<Vec<&'static [u16]>> = Mutex::new(Vec::new());
+	let units: &'static [u16] = Box::leak(units.into());
+	LEAKED.lock().unwrap().push(units);
+	units
+}
+fn with_command_line<R>(cmdline: &str, f: impl FnOnce() -> R) -> R {
+	with_command_line_wide(&units(cmdline), f)
+}
+fn args(cmdline: &str) -> Vec<crate::Argument> {
+	with_command_line(cmdline, || crate::args_native().collect())
+}
+fn units(s: &str) -> Vec<u16> {
+	s.encode_utf16().collect()
+}
+
+#[cfg(all(windows, feature = "std"))]
+#[test]
+fn os_args_after_program() {
+	use crate::os_args_after_program;
+	use std::ffi::OsString;
+	use std::os::windows::ffi::OsStringExt;
+
+	with_command_line(r#"EXE "a b" c"#, || {
+		assert_eq!(os_args_after_program(), ["a b", "c"]);
+	});
+	with_command_line("EXE", || assert!(os_args_after_program().is_empty()));
+	with_command_line("", || assert!(os_args_after_program().is_empty()));
+	// Isolated surrogates are kept.
+	let cmdline = [b'E' as u16, b' ' as u16, 0xD800, b'a' as u16];
+	with_command_line_wide(&cmdline, || {
+		assert_eq!(
+			os_args_after_program(),
+			[OsString::from_wide(&[0xD800, b'a' as u16])]
+		);
+	});
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn string_args_after_program() {
+	use crate::string_args_after_program;
+
+	with_command_line(r#"EXE "a b" c"#, || {
+		assert_eq!(string_args_after_program().unwrap(), ["a b", "c"]);
+	});
+	with_command_line("EXE", || {
+		assert!(string_args_after_program().unwrap().is_empty())
+	});
+	with_command_line("", || {
+		assert!(string_args_after_program().unwrap().is_empty())
+	});
+	let cmdline = [b'E' as u16, b' ' as u16, 0xD800, b'a' as u16];
+	with_command_line_wide(&cmdline, || {
+		let error = string_args_after_program().unwrap_err();
+		assert_eq!(error.unpaired_surrogate(), 0xD800);
+		assert_eq!((error.arg_index(), error.offset()), (Some(1), 0));
+	});
+	let cmdline: Vec<u16> = "E a 🦀b"
+		.encode_utf16()
+		.chain([0xDFFF].iter().copied())
+		.collect();
+	with_command_line_wide(&cmdline, || {
+		let error = string_args_after_program().unwrap_err();
+		assert_eq!(error.unpaired_surrogate(), 0xDFFF);
+		assert_eq!((error.arg_index(), error.offset()), (Some(2), 3));
+	});
+	// The program name is never decoded.
+	let cmdline = [0xDC00, b' ' as u16, b'a' as u16];
+	with_command_line_wide(&cmdline, || {
+		assert_eq!(string_args_after_program().unwrap(), ["a"]);
+	});
+}
+
+#[cfg(all(windows, feature = "std"))]
+#[test]
+fn args_cached() {
+	use crate::args_cached;
+	use std::ffi::OsString;
+	use std::os::windows::ffi::OsStringExt;
+
+	let cmdline = [b'E' as u16, b' ' as u16, 0xD800, b'a' as u16];
+	with_command_line_wide(&cmdline, || {
+		let args = args_cached();
+		assert_eq!(
+			args,
+			[
+				OsString::from("E"),
+				OsString::from_wide(&[0xD800, b'a' as u16])
+			]
+		);
+		assert!(core::ptr::eq(args, args_cached()));
+	});
+	with_command_line("", || assert!(args_cached().is_empty()));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn args_cached_lossy() {
+	use crate::{args_cached_lossy, testing::set_command_line};
+	use core::ptr;
+
+	// The same slice is returned every time.
+	let cmdline: Vec<u16> = r#"EXE "a b" c"#.encode_utf16().collect();
+	let cmdline = leak(&cmdline);
+	let first = {
+		let _guard = set_command_line(cmdline);
+		let args = args_cached_lossy();
+		assert_eq!(args, ["EXE", "a b", "c"]);
+		assert!(ptr::eq(args, args_cached_lossy()));
+		args
+	};
+	// Even after the override ends and starts again.
+	let _guard = set_command_line(cmdline);
+	assert!(ptr::eq(first, args_cached_lossy()));
+
+	// A different command line is parsed again, even if it's the same text.
+	with_command_line(r#"EXE "a b" c"#, || {
+		assert_eq!(args_cached_lossy(), first);
+		assert!(!ptr::eq(args_cached_lossy(), first));
+	});
+	with_command_line("", || assert!(args_cached_lossy().is_empty()));
+	let cmdline = [b'E' as u16, b' ' as u16, 0xD800, b'a' as u16];
+	with_command_line_wide(&cmdline, || {
+		assert_eq!(args_cached_lossy(), ["E", "\u{FFFD}a"]);
+	});
+
+	// Without an override, the process's own arguments are only made once.
+	let process = std::thread::spawn(|| args_cached_lossy().as_ptr() as usize);
+	let process = process.join().unwrap();
+	let again = std::thread::spawn(|| args_cached_lossy().as_ptr() as usize);
+	assert_eq!(again.join().unwrap(), process);
+}
+
+#[test]
+fn program_name() {
+	let name = |cmdline| {
+		with_command_line(cmdline, || {
+			crate::program_name().scalars().collect::<String>()
+		})
+	};
+	assert_eq!(
+		name(r#""C:\Program Files\app.exe" a"#),
+		r"C:\Program Files\app.exe"
+	);
+	assert_eq!(name("app a"), "app");
+	assert_eq!(name(" a"), "");
+	assert_eq!(name(""), "");
+	assert!(with_command_line("", || crate::program_name()
+		.raw_arg()
+		.is_empty()));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn program_name_lossy() {
+	let name =
+		|cmdline, file_stem| with_command_line(cmdline, || crate::program_name_lossy(file_stem));
+	// Quoted with spaces.
+	let cmdline = r#""C:\Program Files\My App\app.exe" a"#;
+	assert_eq!(name(cmdline, false), r"C:\Program Files\My App\app.exe");
+	assert_eq!(name(cmdline, true), "app");
+	// Forward slashes and trailing separators.
+	assert_eq!(name("C:/tools/tool.EXE a", true), "tool");
+	assert_eq!(name(r"tools\/bin/tool.exe\/ a", true), "tool");
+	assert_eq!(name(r"..\tool.exe\ a", false), r"..\tool.exe\");
+	// No separators.
+	assert_eq!(name("tool.exe", true), "tool");
+	assert_eq!(name("tool", true), "tool");
+	assert_eq!(name("tool.exe.txt", true), "tool.exe.txt");
+	assert_eq!(name(".exe", true), ".exe");
+	assert_eq!(name("é.exe", true), "é");
+	assert_eq!(name("", true), "");
+	assert_eq!(name(r"\", true), "");
+	// Isolated surrogates are replaced.
+	let cmdline = [
+		0xD800,
+		u16::from(b'.'),
+		u16::from(b'e'),
+		u16::from(b'x'),
+		u16::from(b'e'),
+	];
+	let name = with_command_line_wide(&cmdline, || crate::program_name_lossy(true));
+	assert_eq!(name, "\u{FFFD}");
+}
+
+#[test]
+fn split_at_double_dash() {
+	fn split(cmdline: &str) -> (Vec<String>, Option<String>) {
+		with_command_line(cmdline, || {
+			let (args, rest) = crate::split_at_double_dash();
+			let args = args.map(|arg| arg.scalars().collect()).collect();
+			(args, rest.map(|rest| String::from_utf16(rest).unwrap()))
+		})
+	}
+	assert_eq!(
+		split("EXE a b"),
+		(vec!["EXE".into(), "a".into(), "b".into()], None)
+	);
+	assert_eq!(
+		split(r#"EXE a -- "b c"  d\"#),
+		(vec!["EXE".into(), "a".into()], Some(r#""b c"  d\"#.into()))
+	);
+	// `--` as the last argument.
+	assert_eq!(
+		split("EXE a --"),
+		(vec!["EXE".into(), "a".into()], Some("".into()))
+	);
+	assert_eq!(
+		split("EXE a --  "),
+		(vec!["EXE".into(), "a".into()], Some("".into()))
+	);
+	// Only the first `--` splits.
+	assert_eq!(
+		split("EXE -- a -- b"),
+		(vec!["EXE".into()], Some("a -- b".into()))
+	);
+	// Quoted `--` is also a separator, but other arguments starting with `--` aren't.
+	assert_eq!(
+		split(r#"EXE --a "--" b"#),
+		(vec!["EXE".into(), "--a".into()], Some("b".into()))
+	);
+	// The program name is never a separator.
+	assert_eq!(split("-- a"), (vec!["--".into(), "a".into()], None));
+	assert_eq!(split(""), (vec![], None));
+}
+
+#[test]
+fn value_of() {
+	fn value(cmdline: &str) -> Option<(String, bool)> {
+		with_command_line(cmdline, || {
+			crate::value_of("--out").map(|v| (v.scalars().collect(), v.is_separate()))
+		})
+	}
+	let sep = |s: &str| Some((s.into(), true));
+	let eq = |s: &str| Some((s.into(), false));
+	assert_eq!(value("EXE --out a.txt b"), sep("a.txt"));
+	assert_eq!(value("EXE --out=a.txt b"), eq("a.txt"));
+	assert_eq!(value(r#"EXE "--out=a b.txt""#), eq("a b.txt"));
+	assert_eq!(value(r#"EXE --out "a b.txt""#), sep("a b.txt"));
+	assert_eq!(value("EXE --out="), eq(""));
+	assert_eq!(value("EXE --out=a=b"), eq("a=b"));
+	assert_eq!(value("EXE a b"), None);
+	assert_eq!(value("EXE --output=a --ou=a --out-dir a"), None);
+	// The last one wins.
+	assert_eq!(value("EXE --out a --out=b"), eq("b"));
+	assert_eq!(value("EXE --out=a --out b"), sep("b"));
+	// Even if it has no value.
+	assert_eq!(value("EXE --out a --out"), None);
+	// A value is never an option.
+	assert_eq!(value("EXE --out --out"), sep("--out"));
+	assert_eq!(value("EXE --out --out x"), sep("--out"));
+	// The program name is never an option.
+	assert_eq!(value("--out a"), None);
+	assert_eq!(value("--out=a"), None);
+	// Nor is anything after `--`, which also can't be a value.
+	assert_eq!(value("EXE -- --out=a"), None);
+	assert_eq!(value("EXE --out=a -- --out=b"), eq("a"));
+	assert_eq!(value("EXE --out -- a"), None);
+
+	with_command_line("EXE --out=a.txt", || {
+		let value = crate::value_of("--out").unwrap();
+		assert!(value == "a.txt");
+		assert!(*value.argument() == "--out=a.txt");
+		assert_eq!(value.utf16_units().count(), 5);
+	});
+}
+
+#[test]
+fn as_slash_option() {
+	let options = with_command_line(
+		r#"EXE /nologo "/out:a b" /D:X=1 /out: / /home/x a:b"#,
+		|| {
+			crate::args_native()
+				.skip(1)
+				.map(|arg| {
+					arg.as_slash_option().map(|(name, value)| {
+						let name = String::from_utf16(&name.collect::<Vec<u16>>()).unwrap();
+						let value =
+							value.map(|v| String::from_utf16(&v.collect::<Vec<u16>>()).unwrap());
+						(name, value)
+					})
+				})
+				.collect::<Vec<_>>()
+		},
+	);
+	let option = |name: &str, value: Option<&str>| Some((name.into(), value.map(String::from)));
+	assert_eq!(
+		options,
+		[
+			option("nologo", None),
+			option("out", Some("a b")),
+			option("D", Some("X=1")),
+			option("out", Some("")),
+			option("", None),
+			// Paths are reported as they are.
+			option("home/x", None),
+			None,
+		]
+	);
+}
+
+#[test]
+fn slash_value_of() {
+	fn value(cmdline: &str, name: &str, case_insensitive: bool) -> Option<String> {
+		with_command_line(cmdline, || {
+			crate::slash_value_of(name, case_insensitive).map(|v| v.scalars().collect())
+		})
+	}
+	assert_eq!(
+		value(r#"EXE /nologo /out:"a b" c"#, "out", false),
+		Some("a b".into())
+	);
+	assert_eq!(value("EXE /D:X=1", "D", false), Some("X=1".into()));
+	assert_eq!(value("EXE /out:", "out", false), Some("".into()));
+	// A name without a value isn't matched.
+	assert_eq!(value("EXE /nologo", "nologo", false), None);
+	// Nor is a path that starts with the name.
+	assert_eq!(value("EXE /out/x:y /outx:y", "out", false), None);
+	assert_eq!(value("EXE out:x -out:x", "out", false), None);
+	// The last one wins.
+	assert_eq!(value("EXE /out:a /out:b", "out", false), Some("b".into()));
+	// Case.
+	assert_eq!(value("EXE /OUT:a", "out", false), None);
+	assert_eq!(value("EXE /OUT:a", "out", true), Some("a".into()));
+	assert_eq!(value("EXE /Out:a", "oUT", true), Some("a".into()));
+	// The program name is never an option.
+	assert_eq!(value("/out:a", "out", false), None);
+	// Nor is anything after `--`.
+	assert_eq!(
+		value("EXE /out:a -- /out:b", "out", false),
+		Some("a".into())
+	);
+	with_command_line("EXE /out:x", || {
+		assert!(!crate::slash_value_of("out", false).unwrap().is_separate());
+	});
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn values_of() {
+	let values = with_command_line("--out=x EXE --out a --out=b c --out -- --out=d", || {
+		crate::values_of("--out")
+			.iter()
+			.map(|v| v.scalars().collect::<String>())
+			.collect::<Vec<_>>()
+	});
+	assert_eq!(values, ["a", "b"]);
+}
+
+#[test]
+fn find_flag() {
+	fn find(cmdline: &str, names: &[&str]) -> Option<String> {
+		with_command_line(cmdline, || {
+			assert_eq!(crate::has_flag(names), crate::find_flag(names).is_some());
+			crate::find_flag(names).map(|arg| arg.raw_arg().len())
+		})
+		.map(|len| cmdline[cmdline.len() - len..].into())
+	}
+	let names = &["-v", "--verbose"];
+	assert_eq!(find("EXE a --verbose b", names), Some("--verbose b".into()));
+	assert_eq!(find("EXE a b", names), None);
+	// The first match wins.
+	assert_eq!(
+		find("EXE -v --verbose -v", names),
+		Some("-v --verbose -v".into())
+	);
+	assert_eq!(find("EXE --verbose -v", names), Some("--verbose -v".into()));
+	// Arguments are compared after parsing.
+	assert_eq!(
+		find(r#"EXE "--verbose""#, names),
+		Some(r#""--verbose""#.into())
+	);
+	assert_eq!(find("EXE --verbosity -vv", names), None);
+	// The program name is never a flag.
+	assert_eq!(find("--verbose a", names), None);
+	assert_eq!(find("-v", names), None);
+	assert_eq!(find("", names), None);
+	assert_eq!(find("EXE -v", &[]), None);
+	// Flags after a standalone `--` aren't found, the same as `first_match`.
+	assert_eq!(find("EXE a -- --verbose", names), None);
+	assert_eq!(find(r#"EXE "--" -v"#, names), None);
+	assert_eq!(find("EXE -v -- -v", names), Some("-v -- -v".into()));
+	assert_eq!(find("EXE --- -v", names), Some("-v".into()));
+	with_command_line("EXE -- --verbose", || {
+		assert!(!crate::has_flag(&["--verbose"]));
+		assert!(!crate::contains_any(&["--verbose"]));
+	});
+}
+
+#[test]
+fn split_short_flags() {
+	use crate::ShortFlag;
+	fn split(cmdline: &str) -> Vec<String> {
+		with_command_line(cmdline, || {
+			let args = crate::args_native().skip(1);
+			crate::split_short_flags(args, &['o', 'é'])
+				.map(|item| match item {
+					ShortFlag::Flag(flag) => format!("-{}", flag),
+					ShortFlag::FlagWithValue(flag, Some(value)) => {
+						let sep = if value.is_separate() { " " } else { "=" };
+						format!("-{}{}{}", flag, sep, value.scalars().collect::<String>())
+					}
+					ShortFlag::FlagWithValue(flag, None) => format!("-{}?", flag),
+					ShortFlag::Arg(arg) => arg.scalars().collect(),
+				})
+				.collect()
+		})
+	}
+	assert_eq!(split("EXE -abc d"), ["-a", "-b", "-c", "d"]);
+	assert_eq!(split(r#"EXE "-a b""#), ["-a", "- ", "-b"]);
+	// A value in the same argument.
+	assert_eq!(split("EXE -aofile -o=x"), ["-a", "-o=file", "-o==x"]);
+	// Or in the next one, which isn't expanded.
+	assert_eq!(split("EXE -ao file"), ["-a", "-o file"]);
+	assert_eq!(split("EXE -o -ab"), ["-o -ab"]);
+	assert_eq!(split(r#"EXE -o """#), ["-o "]);
+	assert_eq!(split("EXE -a -o"), ["-a", "-o?"]);
+	// `-` is an argument.
+	assert_eq!(split("EXE - -a"), ["-", "-a"]);
+	// Long options aren't expanded and nothing is after `--`.
+	assert_eq!(split("EXE --ab -a"), ["--ab", "-a"]);
+	assert_eq!(split("EXE -a -- -b --"), ["-a", "--", "-b", "--"]);
+	// Non-ASCII flags.
+	assert_eq!(split("EXE -ñ😀é"), ["-ñ", "-😀", "-é?"]);
+	assert_eq!(split("EXE -😀éfile"), ["-😀", "-é=file"]);
+	assert_eq!(split("EXE -😀é 😀"), ["-😀", "-é 😀"]);
+	with_command_line_wide(&[0x45, 0x20, 0x2D, 0xD800, 0x61], || {
+		let args = crate::args_native().skip(1);
+		let flags: Vec<_> = crate::split_short_flags(args, &[])
+			.map(|item| match item {
+				ShortFlag::Flag(flag) => flag,
+				_ => unreachable!(),
+			})
+			.collect();
+		assert_eq!(flags, ['\u{FFFD}', 'a']);
+	});
+}
+
+#[test]
+fn match_options() {
+	use crate::{CaseSensitivity, FlagStyle, MatchOptions};
+	let options = |case, prefixes| MatchOptions { case, prefixes };
+	let ascii = options(CaseSensitivity::AsciiInsensitive, FlagStyle::Verbatim);
+	with_command_line("EXE --Verbose --OUT=File.TXT /NoLogo -OUT:A.b", || {
+		// Mixed-case long options.
+		assert!(crate::has_flag_with(&["--verbose"], ascii));
+		assert!(!crate::has_flag(&["--verbose"]));
+		assert!(crate::has_flag_with(&["--VERBOSE"], ascii));
+		assert!(!crate::has_flag_with(&["--verbos", "--verbosee"], ascii));
+		let value = crate::value_of_with("--out", ascii).unwrap();
+		assert!(*value.argument() == "--OUT=File.TXT");
+		// Values are never case folded.
+		assert!(value == "File.TXT");
+		assert!(crate::value_of("--out").is_none());
+
+		// Mixed-case slash options.
+		let slash = options(CaseSensitivity::AsciiInsensitive, FlagStyle::Slash);
+		assert!(crate::has_flag_with(&["nologo"], slash));
+		assert!(!crate::has_flag_with(&["/nologo"], slash));
+		assert!(crate::has_flag_with(&["/nologo"], ascii));
+		assert!(!crate::has_flag_with(&["nologo"], MatchOptions::default()));
+		assert!(crate::slash_value_of_with("out", slash).is_none());
+		let either = options(CaseSensitivity::AsciiInsensitive, FlagStyle::SlashOrDash);
+		assert!(crate::has_flag_with(&["NOLOGO"], either));
+		let value = crate::slash_value_of_with("Out", either).unwrap();
+		assert!(value == "A.b");
+		assert!(crate::slash_value_of_with("-OUT", ascii).unwrap() == "A.b");
+		let exact = options(CaseSensitivity::Sensitive, FlagStyle::SlashOrDash);
+		assert!(crate::slash_value_of_with("out", exact).is_none());
+		assert!(crate::slash_value_of_with("OUT", exact).unwrap() == "A.b");
+	});
+	// Only ASCII letters are folded.
+	with_command_line("EXE /É /[", || {
+		let slash = options(CaseSensitivity::AsciiInsensitive, FlagStyle::Slash);
+		assert!(!crate::has_flag_with(&["é"], slash));
+		assert!(!crate::has_flag_with(&["{"], slash));
+		assert!(crate::has_flag_with(&["É"], slash));
+	});
+	#[cfg(feature = "alloc")]
+	with_command_line("EXE --I a --i B --x", || {
+		let values = crate::values_of_with("--i", ascii);
+		let values: Vec<String> = values.iter().map(|v| v.scalars().collect()).collect();
+		assert_eq!(values, ["a", "B"]);
+	});
+}
+
+#[cfg(all(windows, feature = "ordinal-case"))]
+#[test]
+fn match_options_ordinal() {
+	use crate::{CaseSensitivity, FlagStyle, MatchOptions};
+	let ordinal = MatchOptions {
+		case: CaseSensitivity::OrdinalInsensitive,
+		prefixes: FlagStyle::Slash,
+	};
+	with_command_line("EXE /É /Out:Ñ", || {
+		assert!(crate::has_flag_with(&["é"], ordinal));
+		assert!(crate::slash_value_of_with("OUT", ordinal).unwrap() == "Ñ");
+		let arg = crate::args_native().nth(2).unwrap();
+		let rest = arg.strip_prefix_with("/oUT:", CaseSensitivity::OrdinalInsensitive);
+		assert!(rest.unwrap().eq("Ñ".encode_utf16()));
+	});
+}
+
+#[test]
+fn first_match() {
+	fn find(cmdline: &str) -> Option<(usize, String)> {
+		let names = &["-h", "--help", "/?"];
+		with_command_line(cmdline, || {
+			let found = crate::first_match(names);
+			assert_eq!(crate::contains_any(names), found.is_some());
+			found.map(|(index, arg)| (index, arg.scalars().collect()))
+		})
+	}
+	assert_eq!(find("EXE a /? --help"), Some((2, "/?".into())));
+	assert_eq!(find(r#"EXE a b "--help""#), Some((3, "--help".into())));
+	// None match.
+	assert_eq!(find("EXE a b --helpme -H"), None);
+	assert_eq!(find("EXE"), None);
+	assert_eq!(find(""), None);
+	// The program name is never matched.
+	assert_eq!(find("-h a"), None);
+	// Scanning stops at `--`.
+	assert_eq!(find("EXE a -- -h"), None);
+	assert_eq!(find("EXE a -h -- -h"), Some((2, "-h".into())));
+	assert_eq!(find(r#"EXE "--" -h"#), None);
+	assert_eq!(find("EXE --- -h"), Some((2, "-h".into())));
+}
+
+#[test]
+fn ascii_case() {
+	with_command_line("EXE AbC-xYz_ÉéÀ€Ꮳ", || {
+		let arg = crate::args_native().nth(1).unwrap();
+		let lower: String = char::decode_utf16(arg.ascii_lowercase())
+			.map(Result::unwrap)
+			.collect();
+		assert_eq!(lower, "abc-xyz_ÉéÀ€Ꮳ");
+		let upper: Vec<u16> = arg.ascii_uppercase().collect();
+		assert_eq!(String::from_utf16(&upper).unwrap(), "ABC-XYZ_ÉéÀ€Ꮳ");
+	});
+	// `@` and `[` are either side of `A` to `Z`, as are `` ` `` and `{`.
+	with_command_line("EXE @AZ[`az{", || {
+		let arg = crate::args_native().nth(1).unwrap();
+		let lower: Vec<u16> = arg.ascii_lowercase().collect();
+		let upper: Vec<u16> = arg.ascii_uppercase().collect();
+		assert_eq!(String::from_utf16(&lower).unwrap(), "@az[`az{");
+		assert_eq!(String::from_utf16(&upper).unwrap(), "@AZ[`AZ{");
+	});
+	// Surrogates, paired or not, are unchanged.
+	let cmdline = [0x45, 0x20, 0x41, 0xD800, 0x61, 0xD83D, 0xDE00, 0xDC00];
+	with_command_line_wide(&cmdline, || {
+		let arg = crate::args_native().nth(1).unwrap();
+		let lower: Vec<u16> = arg.ascii_lowercase().collect();
+		let upper: Vec<u16> = arg.ascii_uppercase().collect();
+		assert_eq!(lower, [0x61, 0xD800, 0x61, 0xD83D, 0xDE00, 0xDC00]);
+		assert_eq!(upper, [0x41, 0xD800, 0x41, 0xD83D, 0xDE00, 0xDC00]);
+	});
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn extend_reuses_buffers() {
+	with_command_line_wide(
+		&[0x45, 0x20, 0x61, 0xD800, 0x20, 0x22, 0xE9, 0x20, 0x62, 0x22],
+		|| {
+			let args: Vec<_> = crate::args_native().collect();
+
+			// Each call reserves exactly what it needs.
+			let mut string = String::new();
+			args[1].extend_string(&mut string);
+			assert_eq!(string, "a\u{FFFD}");
+			assert_eq!(string.capacity(), string.len());
+			let mut wide = Vec::new();
+			args[1].extend_wide(&mut wide);
+			assert_eq!(wide, [0x61, 0xD800]);
+			assert_eq!(wide.capacity(), 2);
+
+			// Appending keeps what's already there.
+			args[2].extend_string(&mut string);
+			assert_eq!(string, "a\u{FFFD}é b");
+			args[2].extend_wide(&mut wide);
+			assert_eq!(wide, [0x61, 0xD800, 0xE9, 0x20, 0x62]);
+
+			// Clearing and reusing a buffer with room doesn't reallocate and gives
+			// the same result every time.
+			let mut string = String::with_capacity(64);
+			let mut wide = Vec::with_capacity(64);
+			let (string_ptr, wide_ptr) = (string.as_ptr(), wide.as_ptr());
+			for _ in 0..3 {
+				for arg in &args {
+					string.clear();
+					arg.extend_string(&mut string);
+					assert_eq!(string, arg.scalars().collect::<String>());
+					wide.clear();
+					arg.extend_wide(&mut wide);
+					assert!(*arg == &wide[..]);
+				}
+			}
+			assert_eq!((string.capacity(), wide.capacity()), (64, 64));
+			assert_eq!((string.as_ptr(), wide.as_ptr()), (string_ptr, wide_ptr));
+
+			let expected: Vec<u16> = crate::null_separated_list_wide().collect();
+			let mut list = Vec::new();
+			crate::extend_null_separated(&mut list);
+			assert_eq!(list, expected);
+			assert_eq!(list.capacity(), expected.len());
+			let capacity = list.capacity();
+			for _ in 0..3 {
+				list.clear();
+				crate::extend_null_separated(&mut list);
+				assert_eq!(list, expected);
+				assert_eq!(list.capacity(), capacity);
+			}
+		},
+	);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn collect_wide() {
+	let cmdlines = [
+		"",
+		" ",
+		"EXE",
+		r#"C:\dir\app.exe a\\b "c d" e"f\\"g h\\\"i "#,
+		r#""C:\Program Files\app.exe" "" """" a"b\\"#,
+		"EXE \u{1F980}\t\"\u{E9} x\"",
+	];
+	let mut wide: Vec<Vec<u16>> = cmdlines
+		.iter()
+		.map(|s| s.encode_utf16().collect())
+		.collect();
+	wide.push(vec![0x45, 0x20, 0xD800, 0x22, 0xDC00, 0x20, 0x22]);
+	for cmdline in &wide {
+		with_command_line_wide(cmdline, || {
+			// Each is allocated once, with exactly the space needed.
+			let list = crate::collect_wide();
+			assert_eq!(
+				list,
+				crate::null_separated_list_wide().collect::<Vec<u16>>()
+			);
+			assert_eq!(list.capacity(), list.len());
+
+			let args = crate::collect_args_wide();
+			let expected: Vec<Vec<u16>> = crate::args_native()
+				.map(|arg| arg.utf16_units().collect())
+				.collect();
+			assert_eq!(args, expected);
+			assert_eq!(args.capacity(), args.len());
+			for arg in &args {
+				assert_eq!(arg.capacity(), arg.len());
+			}
+		});
+	}
+}
+
+#[test]
+fn parse_into_buffer() {
+	let cmdlines: [&[u16]; 7] = [
+		&[],
+		&[0x20],
+		&[0x45, 0x58, 0x45],
+		&[
+			0x45, 0x20, 0x22, 0x22, 0x20, 0x61, 0x5C, 0x5C, 0x22, 0x62, 0x20, 0x63,
+		],
+		&[0x45, 0x20, 0x22, 0x61, 0x20, 0x62, 0x22, 0x09, 0x09, 0x63],
+		&[0x45, 0x20, 0xD800, 0x22, 0xDC00, 0x20, 0x22],
+		&[0x45, 0x20, 0x22, 0x22, 0x20, 0x22, 0x22],
+	];
+	for cmdline in &cmdlines {
+		with_command_line_wide(cmdline, || {
+			let expected: Vec<u16> = crate::null_separated_list_wide().collect();
+			let args: Vec<Vec<u16>> = crate::args_native()
+				.map(|arg| arg.utf16_units().collect())
+				.collect();
+
+			// Extra space at the end is left alone.
+			let mut buffer = vec![0xFFFF; expected.len() + 3];
+			let start = buffer.as_ptr() as usize;
+			let mut asked = None;
+			let parsed = crate::parse_into_buffer(|len| {
+				asked = Some(len);
+				&mut buffer
+			})
+			.unwrap();
+			assert_eq!(asked, Some(expected.len()));
+			assert_eq!(parsed.len(), args.len());
+			assert_eq!(parsed.as_slice(), expected.as_slice());
+			let parsed: Vec<&[u16]> = parsed.collect();
+			assert_eq!(parsed, args);
+			// Each argument is in the buffer, in order.
+			let mut offset = 0;
+			for arg in &parsed {
+				assert_eq!(arg.as_ptr() as usize, start + 2 * offset);
+				offset += arg.len() + 1;
+			}
+			assert_eq!(&buffer[..expected.len()], expected.as_slice());
+			assert_eq!(&buffer[expected.len()..], [0xFFFF; 3]);
+
+			// Nothing is written to a buffer that's too short.
+			if let Some(short) = expected.len().checked_sub(1) {
+				let mut buffer = vec![0xFFFF; short];
+				assert!(crate::parse_into_buffer(|_| &mut buffer).is_none());
+				assert!(buffer.iter().all(|&u| u == 0xFFFF));
+			}
+		});
+	}
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn cow_args() {
+	use alloc::borrow::Cow;
+
+	// Each argument and whether it's borrowed.
+	let cases: &[(&str, &[bool])] = &[
+		("", &[]),
+		(" ", &[true]),
+		(r"C:\dir\app.exe a\\b c\ \\", &[true, true, true, true]),
+		(
+			r#""C:\dir\" "a b" "" "a\\" "a\"""#,
+			&[true, true, true, false, false],
+		),
+		(
+			r#"EXE a"b" "a"b "a""b" a\"b """#,
+			&[true, false, false, false, false, true],
+		),
+		(r#""EXE"x "\"" a"#, &[false, false, true]),
+		("EXE \u{1F980}\t\"\u{E9} x\"", &[true, true, true]),
+	];
+	for &(cmdline, borrowed) in cases {
+		let wide: Vec<u16> = cmdline.encode_utf16().collect();
+		with_command_line_wide(&wide, || {
+			let expected: Vec<Vec<u16>> = crate::args_native()
+				.map(|arg| arg.utf16_units().collect())
+				.collect();
+			let args: Vec<Cow<[u16]>> = crate::cow_args_of(&wide).collect();
+			assert_eq!(args, expected, "{:?}", cmdline);
+			assert_eq!(crate::cow_args().collect::<Vec<_>>(), expected);
+			let kinds: Vec<bool> = args
+				.iter()
+				.map(|arg| match arg {
+					Cow::Borrowed(arg) => {
+						// Borrowed arguments are part of the command line.
+						let range = wide.as_ptr_range();
+						assert!(range.start <= arg.as_ptr() && arg.as_ptr() <= range.end);
+						assert!(arg.len() <= wide.len());
+						true
+					}
+					Cow::Owned(arg) => {
+						assert_eq!(arg.capacity(), arg.len());
+						false
+					}
+				})
+				.collect();
+			assert_eq!(kinds, borrowed, "{:?}", cmdline);
+		});
+	}
+	// The command line ends at a null.
+	let wide = [0x61, 0x20, 0x62, 0, 0x63];
+	let args: Vec<Cow<[u16]>> = crate::cow_args_of(&wide).collect();
+	assert_eq!(args, [&[0x61][..], &[0x62]]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn parse_multi() {
+	use crate::EmptyRecords;
+
+	let parse = |blob: &str, empty| -> Vec<Vec<String>> {
+		let blob: Vec<u16> = blob.encode_utf16().collect();
+		crate::parse_multi(&blob)
+			.with_empty_records(empty)
+			.map(|args| args.map(|arg| String::from_utf16(&arg).unwrap()).collect())
+			.collect()
+	};
+	let quoted = r#""C:\Program Files\app.exe" "a\\\"b" "c""d" e\\"#;
+	let blob = format!("app.exe -v\0\0{}\0\0", quoted);
+	assert_eq!(
+		parse(&blob, EmptyRecords::Keep),
+		[
+			vec!["app.exe", "-v"],
+			vec![],
+			vec![r"C:\Program Files\app.exe", r#"a\"b"#, r#"c"d"#, r"e\\"],
+		]
+	);
+	assert_eq!(
+		parse(&blob, EmptyRecords::Skip),
+		[
+			vec!["app.exe", "-v"],
+			vec![r"C:\Program Files\app.exe", r#"a\"b"#, r#"c"d"#, r"e\\"],
+		]
+	);
+
+	// The null at the end of the list, or of the last record, is optional.
+	for blob in ["a b\0c", "a b\0c\0", "a b\0c\0\0"] {
+		assert_eq!(
+			parse(blob, EmptyRecords::Keep),
+			[vec!["a", "b"], vec!["c"]],
+			"{:?}",
+			blob
+		);
+	}
+	// Only the last empty record ends the list.
+	assert_eq!(parse("a\0\0\0", EmptyRecords::Keep), [vec!["a"], vec![]]);
+	assert_eq!(parse("\0\0", EmptyRecords::Keep), [Vec::<String>::new()]);
+	for blob in ["", "\0"] {
+		assert!(parse(blob, EmptyRecords::Keep).is_empty(), "{:?}", blob);
+	}
+	// A record that's only whitespace isn't empty.
+	assert_eq!(parse(" \0", EmptyRecords::Skip), [vec![""]]);
+}
+
+#[test]
+fn write_lossy() {
+	use core::fmt::{self, Write};
+
+	// Writes up to a fixed number of bytes, then fails.
+	struct Fixed {
+		buf: [u8; 8],
+		len: usize,
+		writes: usize,
+	}
+	impl Write for Fixed {
+		fn write_str(&mut self, s: &str) -> fmt::Result {
+			self.writes += 1;
+			let end = self.len + s.len();
+			self.buf
+				.get_mut(self.len..end)
+				.ok_or(fmt::Error)?
+				.copy_from_slice(s.as_bytes());
+			self.len = end;
+			Ok(())
+		}
+	}
+	fn fixed() -> Fixed {
+		Fixed {
+			buf: [0; 8],
+			len: 0,
+			writes: 0,
+		}
+	}
+	// Always fails.
+	struct Failing(usize);
+	impl Write for Failing {
+		fn write_str(&mut self, _: &str) -> fmt::Result {
+			self.0 += 1;
+			Err(fmt::Error)
+		}
+	}
+
+	with_command_line_wide(
+		&[0x45, 0x20, 0x61, 0xD800, 0x20, 0x22, 0xE9, 0x20, 0x62, 0x22],
+		|| {
+			let args: Vec<_> = crate::args_native().collect();
+			let mut out = String::new();
+			args[1].write_lossy(&mut out).unwrap();
+			assert_eq!(out, "a\u{FFFD}");
+			assert_eq!(format!("{}|{}", args[1], args[2]), "a\u{FFFD}|é b");
+			out.clear();
+			crate::write_args(&mut out, ", ").unwrap();
+			assert_eq!(out, "E, a\u{FFFD}, é b");
+
+			// Errors are returned at the first failed write.
+			let mut failing = Failing(0);
+			assert_eq!(args[2].write_lossy(&mut failing), Err(fmt::Error));
+			assert_eq!(failing.0, 1);
+			let mut failing = Failing(0);
+			assert_eq!(crate::write_args(&mut failing, " "), Err(fmt::Error));
+			assert_eq!(failing.0, 1);
+			assert!(write!(Failing(0), "{}", args[1]).is_err());
+
+			// The sink fills in the middle of an argument. Everything that fitted
+			// has been written and nothing more is tried.
+			let mut sink = fixed();
+			assert_eq!(crate::write_args(&mut sink, "--"), Err(fmt::Error));
+			assert_eq!(&sink.buf[..sink.len], "E--a\u{FFFD}".as_bytes());
+			assert_eq!(sink.writes, 5);
+			let mut sink = fixed();
+			args[2].write_lossy(&mut sink).unwrap();
+			assert_eq!(&sink.buf[..sink.len], "é b".as_bytes());
+		},
+	);
+}
+
+#[test]
+fn to_json() {
+	let mut cmdline: Vec<u16> = "app.exe \"say \\\"hi\\\"\" C:\\dir\\ \"a\tb\" x"
+		.encode_utf16()
+		.collect();
+	cmdline.push(0xD800);
+	with_command_line_wide(&cmdline, || {
+		let mut json = String::new();
+		crate::to_json(&mut json).unwrap();
+		assert_eq!(
+			json,
+			"[\"app.exe\",\"say \\\"hi\\\"\",\"C:\\\\dir\\\\\",\"a\\u0009b\",\"x\u{FFFD}\"]"
+		);
+		#[cfg(feature = "alloc")]
+		assert_eq!(crate::to_json_string(), json);
+		let parsed: Vec<String> = serde_json::from_str(&json).unwrap();
+		assert_eq!(
+			parsed,
+			["app.exe", "say \"hi\"", r"C:\dir\", "a\tb", "x\u{FFFD}"]
+		);
+	});
+}
+
+#[test]
+fn into_iter() {
+	fn collect<I: IntoIterator<Item = u16>>(units: I) -> String {
+		let units: Vec<u16> = units.into_iter().collect();
+		String::from_utf16(&units).unwrap()
+	}
+	with_command_line(r#"EXE "a b"\" c"#, || {
+		let arg = crate::args_native().nth(1).unwrap();
+		// Borrowing doesn't use anything up.
+		let borrowed = &arg;
+		assert_eq!(collect(borrowed), r#"a b""#);
+		assert_eq!(collect(borrowed), r#"a b""#);
+		let (mut first, mut second) = (borrowed.into_iter(), borrowed.into_iter());
+		assert_eq!(first.next(), Some(u16::from(b'a')));
+		assert!(first.clone().eq(arg.utf16_units().skip(1)));
+		assert!(second.by_ref().eq(arg.utf16_units()));
+		assert_eq!(second.next(), None);
+		assert_eq!(first.count(), 3);
+		let mut count = 0;
+		for unit in &arg {
+			assert_eq!(Some(unit), arg.utf16_units().nth(count));
+			count += 1;
+		}
+		assert_eq!(count, 4);
+		// Nor does cloning before iterating by value.
+		assert_eq!(collect(arg.clone()), r#"a b""#);
+		assert_eq!(collect(arg), r#"a b""#);
+	});
+	// The program name has different rules.
+	with_command_line(r#""C:\a b\"x"#, || {
+		assert_eq!(collect(crate::program_name()), r"C:\a b\x");
+	});
+}
+
+#[test]
+fn strip_prefix_ignore_case() {
+	use crate::CaseSensitivity;
+	fn strip(cmdline: &str, prefix: &str) -> Option<String> {
+		with_command_line(cmdline, || {
+			let arg = crate::args_native().nth(1).unwrap();
+			let rest = arg.strip_prefix_ignore_case(prefix);
+			assert_eq!(arg.starts_with_ignore_case(prefix), rest.is_some());
+			rest.map(|rest| String::from_utf16(&rest.collect::<Vec<u16>>()).unwrap())
+		})
+	}
+	// Prefixes that differ only in case.
+	assert_eq!(strip("EXE /OUT:File.TXT", "/out:"), Some("File.TXT".into()));
+	assert_eq!(strip("EXE /out:File.TXT", "/OUT:"), Some("File.TXT".into()));
+	assert_eq!(strip("EXE /OuT:", "/oUt:"), Some("".into()));
+	assert_eq!(strip("EXE /out:x", ""), Some("/out:x".into()));
+	assert_eq!(strip("EXE /in:x", "/out:"), None);
+	// Prefixes longer than the argument.
+	assert_eq!(strip("EXE /OUT", "/out:"), None);
+	assert_eq!(strip(r#"EXE """#, "a"), None);
+	assert_eq!(strip(r#"EXE """#, ""), Some("".into()));
+	// Non-ASCII characters in the prefix compare exactly.
+	assert_eq!(strip("EXE /ÉTAPE:1", "/étape:"), None);
+	assert_eq!(strip("EXE /ÉTAPE:1", "/Étape:"), Some("1".into()));
+	assert_eq!(strip("EXE /ǅ:1", "/ǆ:"), None);
+	// Only ASCII letters are folded, not the characters either side of them.
+	assert_eq!(strip("EXE [x", "{x"), None);
+	assert_eq!(strip("EXE @x", "`X"), None);
+
+	with_command_line("EXE --Out", || {
+		let arg = crate::args_native().nth(1).unwrap();
+		let sensitive = |prefix| arg.strip_prefix_with(prefix, CaseSensitivity::Sensitive);
+		assert_eq!(sensitive("--O").map(Iterator::count), Some(2));
+		assert!(sensitive("--o").is_none());
+	});
+}
+
+#[test]
+fn cmp_ignore_case() {
+	use core::cmp::Ordering;
+
+	// Sorted by their lowercase names.
+	const TABLE: &[&str] = &["--all", "--Help", "--help-all", "/nologo", "/OUT", "build"];
+	let lookup = |cmdline: &str| {
+		with_command_line(cmdline, || {
+			let arg = crate::args_native().nth(1).unwrap();
+			TABLE.binary_search_by(|name| arg.cmp_ignore_case(name).reverse())
+		})
+	};
+	assert_eq!(lookup("EXE --HELP"), Ok(1));
+	assert_eq!(lookup("EXE --help-ALL"), Ok(2));
+	assert_eq!(lookup("EXE /NoLogo"), Ok(3));
+	assert_eq!(lookup("EXE BUILD"), Ok(5));
+	// Between two entries, including ones it's a prefix of.
+	assert_eq!(lookup("EXE --b"), Err(1));
+	assert_eq!(lookup("EXE --help-"), Err(2));
+	assert_eq!(lookup("EXE /out:x"), Err(5));
+	assert_eq!(lookup("EXE --"), Err(0));
+	assert_eq!(lookup(r#"EXE """#), Err(0));
+	assert_eq!(lookup("EXE zzz"), Err(6));
+
+	let cmp = |cmdline: &str, other: &str| {
+		with_command_line(cmdline, || {
+			crate::args_native().nth(1).unwrap().cmp_ignore_case(other)
+		})
+	};
+	// `_` is between `Z` and `a`, so the order depends on folding first.
+	assert_eq!(cmp("EXE A", "_"), Ordering::Greater);
+	assert_eq!(cmp("EXE _", "a"), Ordering::Less);
+	// Non-ASCII letters compare exactly, by code point.
+	assert_eq!(cmp("EXE É", "é"), Ordering::Less);
+	assert_eq!(cmp("EXE é", "é"), Ordering::Equal);
+	assert_eq!(cmp("EXE 😅", "\u{FFFF}"), Ordering::Greater);
+
+	// Random ASCII strings compare the same way as their lowercase forms, and
+	// are only equal when `eq_ignore_ascii_case` says so.
+	const CHARS: &[u8] = b"aAbBzZ_-/0";
+	let cases = if cfg!(miri) { 20 } else { 1000 };
+	let seed = roundtrip::seed();
+	let mut rng = roundtrip::Rng(seed);
+	let random = |rng: &mut roundtrip::Rng| -> String {
+		let len = rng.below(5);
+		(0..len)
+			.map(|_| char::from(CHARS[rng.below(CHARS.len())]))
+			.collect()
+	};
+	for _ in 0..cases {
+		let a = random(&mut rng);
+		// Sometimes compare with the same string in a different case.
+		let b = match rng.below(3) {
+			0 => a
+				.chars()
+				.map(|c| match rng.below(2) {
+					0 => c.to_ascii_uppercase(),
+					_ => c.to_ascii_lowercase(),
+				})
+				.collect(),
+			_ => random(&mut rng),
+		};
+		let ordering = cmp(&format!(r#"EXE "{}""#, a), &b);
+		assert_eq!(
+			ordering,
+			a.to_ascii_lowercase().cmp(&b.to_ascii_lowercase()),
+			"{:?} and {:?} with seed {}",
+			a,
+			b,
+			seed
+		);
+		assert_eq!(
+			ordering == Ordering::Equal,
+			a.eq_ignore_ascii_case(&b),
+			"{:?} and {:?} with seed {}",
+			a,
+			b,
+			seed
+		);
+	}
+}
+
+#[test]
+fn split() {
+	fn fields(arg: &crate::Argument, sep: char, terminator: bool) -> Vec<String> {
+		let to_string = |field: Vec<u16>| String::from_utf16(&field).unwrap();
+		if terminator {
+			arg.split_terminator(sep)
+				.map(|f| to_string(f.collect()))
+				.collect()
+		} else {
+			arg.split(sep).map(|f| to_string(f.collect())).collect()
+		}
+	}
+	fn split(arg: &str, sep: char) -> (Vec<String>, Vec<String>) {
+		let cmdline = ["EXE ", arg].concat();
+		with_command_line(&cmdline, || {
+			let arg = crate::args_native().nth(1).unwrap();
+			(fields(&arg, sep, false), fields(&arg, sep, true))
+		})
+	}
+	let check = |arg, sep, expected: &[&str], terminated: &[&str]| {
+		let (fields, terminated_fields) = split(arg, sep);
+		assert_eq!(fields, expected, "split {:?}", arg);
+		assert_eq!(terminated_fields, terminated, "split_terminator {:?}", arg);
+		// The same as `str`.
+		let unquoted = arg.trim_matches('"');
+		assert_eq!(unquoted.split(sep).collect::<Vec<_>>(), expected);
+		let terminated: Vec<_> = unquoted.split_terminator(sep).collect();
+		assert_eq!(terminated, terminated_fields);
+	};
+	check("a;b;c", ';', &["a", "b", "c"], &["a", "b", "c"]);
+	check("a;;b", ';', &["a", "", "b"], &["a", "", "b"]);
+	// Leading and trailing separators.
+	check(";a", ';', &["", "a"], &["", "a"]);
+	check("a;", ';', &["a", ""], &["a"]);
+	check("a;;", ';', &["a", "", ""], &["a", ""]);
+	check(";", ';', &["", ""], &[""]);
+	check(r#""""#, ';', &[""], &[]);
+	// A separator that never appears.
+	check("abc", ';', &["abc"], &["abc"]);
+	// Quotes are removed first.
+	check(r#""a b;c d""#, ';', &["a b", "c d"], &["a b", "c d"]);
+	// Non-ASCII separators, including one outside the BMP.
+	check("aébéc", 'é', &["a", "b", "c"], &["a", "b", "c"]);
+	check(
+		"😀a😀😀b😀",
+		'😀',
+		&["", "a", "", "b", ""],
+		&["", "a", "", "b"],
+	);
+	check("😁😀😁", '😀', &["😁", "😁"], &["😁", "😁"]);
+
+	// Fields are independent of each other and of the iterator.
+	with_command_line("EXE ab;cd", || {
+		let arg = crate::args_native().nth(1).unwrap();
+		let mut split = arg.split(';');
+		let first = split.next().unwrap();
+		let second = split.next().unwrap();
+		assert_eq!(split.next().map(Iterator::count), None);
+		let copy = first.clone();
+		assert!(first.eq("ab".encode_utf16()));
+		assert!(copy.eq("ab".encode_utf16()));
+		assert!(second.clone().eq("cd".encode_utf16()));
+		assert!(second.eq("cd".encode_utf16()));
+	});
+	// Isolated surrogates never match half of a pair.
+	let cmdline = [0x45, 0x20, 0x61, 0xD83D, 0x62, 0xDE00, 0xD83D, 0xDE00, 0x63];
+	with_command_line_wide(&cmdline, || {
+		let arg = crate::args_native().nth(1).unwrap();
+		let fields: Vec<Vec<u16>> = arg.split('😀').map(Iterator::collect).collect();
+		assert_eq!(fields, [&[0x61, 0xD83D, 0x62, 0xDE00][..], &[0x63]]);
+	});
+	// Only a flag's value is split.
+	with_command_line(r#"EXE --dirs=a;b --more "c;d;" --none="#, || {
+		let fields = |name, terminator| -> Vec<String> {
+			let value = crate::value_of(name).unwrap();
+			let to_string = |field: Vec<u16>| String::from_utf16(&field).unwrap();
+			if terminator {
+				value
+					.split_terminator(';')
+					.map(|f| to_string(f.collect()))
+					.collect()
+			} else {
+				value.split(';').map(|f| to_string(f.collect())).collect()
+			}
+		};
+		assert_eq!(fields("--dirs", false), ["a", "b"]);
+		assert_eq!(fields("--more", false), ["c", "d", ""]);
+		assert_eq!(fields("--more", true), ["c", "d"]);
+		assert_eq!(fields("--none", false), [""]);
+		assert!(fields("--none", true).is_empty());
+	});
+}
+
+#[test]
+fn trim() {
+	fn trim(arg: &str) -> [String; 4] {
+		let cmdline = ["EXE ", arg].concat();
+		with_command_line(&cmdline, || {
+			let arg = crate::args_native().nth(1).unwrap();
+			[
+				arg.trim().scalars().collect(),
+				arg.trim_start().scalars().collect(),
+				arg.trim_end().scalars().collect(),
+				arg.trim_matches('-').scalars().collect(),
+			]
+		})
+	}
+	// Whitespace that was quoted.
+	assert_eq!(
+		trim(r#"" C:\path ""#),
+		[r"C:\path", r"C:\path ", r" C:\path", r" C:\path "]
+	);
+	assert_eq!(
+		trim("\"\t\r\na b\n\""),
+		["a b", "a b\n", "\t\r\na b", "\t\r\na b\n"]
+	);
+	// No-op cases.
+	assert_eq!(trim("a"), ["a", "a", "a", "a"]);
+	assert_eq!(trim(r#""""#), ["", "", "", ""]);
+	assert_eq!(trim(r#""a  b""#), ["a  b", "a  b", "a  b", "a  b"]);
+	// All whitespace trims to empty.
+	assert_eq!(trim(r#""   ""#), ["", "", "", "   "]);
+	assert_eq!(trim("---"), ["---", "---", "---", ""]);
+	assert_eq!(trim("--a-b--"), ["--a-b--", "--a-b--", "--a-b--", "a-b"]);
+	// Only ASCII whitespace is trimmed.
+	assert_eq!(trim("\"\u{3000}a\u{A0}\""), ["\u{3000}a\u{A0}"; 4]);
+
+	with_command_line(r#"EXE " 😀a😀 ""#, || {
+		let arg = crate::args_native().nth(1).unwrap();
+		let trimmed = arg.trim();
+		assert!(trimmed == "😀a😀");
+		assert!(trimmed == &"😀a😀".encode_utf16().collect::<Vec<u16>>()[..]);
+		assert!(trimmed != " 😀a😀");
+		assert_eq!((trimmed.len(), trimmed.is_empty()), (5, false));
+		assert!(*trimmed.argument() == " 😀a😀 ");
+	});
+	// Characters outside the BMP, and isolated surrogates that are never
+	// trimmed.
+	let cmdline = [
+		0x45, 0x20, 0xD83D, 0xDE00, 0xD83D, 0x61, 0xDE00, 0xD83D, 0xDE00,
+	];
+	with_command_line_wide(&cmdline, || {
+		let arg = crate::args_native().nth(1).unwrap();
+		let trimmed = arg.trim_matches('😀');
+		assert!(trimmed == &[0xD83D, 0x61, 0xDE00][..]);
+		assert!(arg.trim().len() == 7);
+	});
+	// Only a flag's value is trimmed.
+	with_command_line(r#"EXE --path=" C:\x " --sep " - " --blank="  ""#, || {
+		let value = |name| crate::value_of(name).unwrap();
+		let path = value("--path");
+		let trimmed: [String; 4] = [
+			path.trim().scalars().collect(),
+			path.trim_start().scalars().collect(),
+			path.trim_end().scalars().collect(),
+			path.trim_matches(' ').scalars().collect(),
+		];
+		assert_eq!(trimmed, [r"C:\x", r"C:\x ", r" C:\x", r"C:\x"]);
+		assert!(*path.trim().argument() == r"--path= C:\x ");
+		assert!(value("--sep").trim() == "-");
+		assert!(value("--sep").trim_matches('-') == " - ");
+		assert!(value("--blank").trim().is_empty());
+		assert!(value("--blank").trim_start().is_empty());
+		assert!(value("--blank").trim_end().is_empty());
+	});
+}
+
+#[test]
+fn eq_ascii_bytes() {
+	with_command_line_wide(
+		&[0x45, 0x20, 0x2D, 0x68, 0x20, 0xE9, 0x20, 0x22, 0x22],
+		|| {
+			let args: Vec<_> = crate::args_native().collect();
+			// ASCII matches, in both directions.
+			assert!(args[1] == b"-h");
+			assert!(b"-h" == args[1]);
+			assert!(args[1] == &b"-h"[..]);
+			assert!(&b"-h"[..] == args[1]);
+			assert!(args[3] == b"");
+			assert!(args[1] != b"-H");
+			assert!(args[1] != b"-h ");
+			assert!(args[1] != b"-");
+			// A byte of 0x80 or more never matches, not even as Latin-1.
+			assert!(args[2] != b"\xE9");
+			assert!(b"\xE9" != args[2]);
+			// Nor does a non-ASCII code unit.
+			assert!(args[2] != b"e");
+			assert!(args[2] != b"\xC3\xA9");
+		},
+	);
+	// A byte that's 0x80 or more doesn't match a code unit with the same
+	// value.
+	with_command_line_wide(&[0x45, 0x20, 0x61, 0x80, 0xFF], || {
+		let arg = crate::args_native().nth(1).unwrap();
+		assert!(arg != b"a\x80\xFF");
+		assert!(arg == &[0x61_u16, 0x80, 0xFF][..]);
+	});
+}
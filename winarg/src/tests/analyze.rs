@@ -0,0 +1,259 @@
+// Tests for `analyze`.
+
+use super::{String, Vec};
+use crate::{analyze, SpanKind, SpanKind::*};
+
+// The spans of a command line along with the text they cover.
+fn spans(cmdline: &str) -> Vec<(SpanKind, String)> {
+	let cmdline: Vec<u16> = cmdline.encode_utf16().collect();
+	let mut spans = Vec::new();
+	let mut arg_end = 0;
+	analyze(&cmdline, |span| {
+		// The spans in an argument cover all of it.
+		if span.kind == Argument {
+			assert_eq!(arg_end, 0);
+			arg_end = span.range.end;
+		} else {
+			assert!(span.range.end <= arg_end);
+			if span.range.end == arg_end {
+				arg_end = 0;
+			}
+		}
+		let text = String::from_utf16(&cmdline[span.range.clone()]).unwrap();
+		spans.push((span.kind, text));
+	});
+	spans
+}
+
+fn s(spans: &[(SpanKind, &str)]) -> Vec<(SpanKind, String)> {
+	spans
+		.iter()
+		.map(|&(kind, text)| (kind, text.into()))
+		.collect()
+}
+
+#[test]
+fn official_examples() {
+	assert_eq!(
+		spans(r#"EXE "abc" d e"#),
+		s(&[
+			(Argument, "EXE"),
+			(Literal, "EXE"),
+			(Argument, r#""abc""#),
+			(Quote, r#"""#),
+			(Literal, "abc"),
+			(Quote, r#"""#),
+			(Argument, "d"),
+			(Literal, "d"),
+			(Argument, "e"),
+			(Literal, "e"),
+		])
+	);
+	assert_eq!(
+		spans(r#"EXE a\\\b d"e f"g h"#),
+		s(&[
+			(Argument, "EXE"),
+			(Literal, "EXE"),
+			(Argument, r"a\\\b"),
+			(Literal, r"a\\\b"),
+			(Argument, r#"d"e f"g"#),
+			(Literal, "d"),
+			(Quote, r#"""#),
+			(Literal, "e f"),
+			(Quote, r#"""#),
+			(Literal, "g"),
+			(Argument, "h"),
+			(Literal, "h"),
+		])
+	);
+	assert_eq!(
+		spans(r#"EXE a\\\"b c d"#),
+		s(&[
+			(Argument, "EXE"),
+			(Literal, "EXE"),
+			(Argument, r#"a\\\"b"#),
+			(Literal, "a"),
+			(Escape, r"\\\"),
+			(Literal, r#""b"#),
+			(Argument, "c"),
+			(Literal, "c"),
+			(Argument, "d"),
+			(Literal, "d"),
+		])
+	);
+	assert_eq!(
+		spans(r#"EXE a\\\\"b c" d e"#),
+		s(&[
+			(Argument, "EXE"),
+			(Literal, "EXE"),
+			(Argument, r#"a\\\\"b c""#),
+			(Literal, "a"),
+			(Escape, r"\\\\"),
+			(Quote, r#"""#),
+			(Literal, "b c"),
+			(Quote, r#"""#),
+			(Argument, "d"),
+			(Literal, "d"),
+			(Argument, "e"),
+			(Literal, "e"),
+		])
+	);
+}
+
+#[test]
+fn genius_quotes() {
+	let q = (Quote, r#"""#);
+	let exe = [(Argument, "EXE"), (Literal, "EXE")];
+	let with_exe =
+		|spans: &[(SpanKind, &str)]| -> Vec<(SpanKind, String)> { s(&[&exe[..], spans].concat()) };
+
+	assert_eq!(
+		spans(r#"EXE "" """#),
+		with_exe(&[(Argument, r#""""#), q, q, (Argument, r#""""#), q, q])
+	);
+	assert_eq!(
+		spans(r#"EXE "" """"#),
+		with_exe(&[
+			(Argument, r#""""#),
+			q,
+			q,
+			(Argument, r#"""""#),
+			q,
+			q,
+			(Literal, r#"""#)
+		])
+	);
+	assert_eq!(
+		spans(r#"EXE "this is """all""" in the same argument""#),
+		with_exe(&[
+			(Argument, r#""this is """all""" in the same argument""#),
+			q,
+			(Literal, "this is "),
+			// `""` in quotes is a literal quote.
+			q,
+			(Literal, r#"""#),
+			q,
+			(Literal, "all"),
+			q,
+			q,
+			(Literal, r#"" in the same argument"#),
+			q
+		])
+	);
+	assert_eq!(
+		spans(r#"EXE "a"""#),
+		with_exe(&[
+			(Argument, r#""a"""#),
+			q,
+			(Literal, "a"),
+			q,
+			(Literal, r#"""#)
+		])
+	);
+	assert_eq!(
+		spans(r#"EXE "a"" a"#),
+		with_exe(&[
+			(Argument, r#""a"" a"#),
+			q,
+			(Literal, "a"),
+			q,
+			(Literal, r#"" a"#)
+		])
+	);
+	// quotes cannot be escaped in command names
+	assert_eq!(
+		spans(r#""EXE" check"#),
+		s(&[
+			(Argument, r#""EXE""#),
+			q,
+			(Literal, "EXE"),
+			q,
+			(Argument, "check"),
+			(Literal, "check")
+		])
+	);
+	assert_eq!(
+		spans(r#""EXE check""#),
+		s(&[(Argument, r#""EXE check""#), q, (Literal, "EXE check"), q])
+	);
+	assert_eq!(
+		spans(r#""EXE """for""" check"#),
+		s(&[
+			(Argument, r#""EXE """for""" check"#),
+			q,
+			(Literal, "EXE "),
+			q,
+			q,
+			q,
+			(Literal, "for"),
+			q,
+			q,
+			q,
+			(Literal, " check")
+		])
+	);
+	assert_eq!(
+		spans(r#""EXE \"for\" check"#),
+		s(&[
+			(Argument, r#""EXE \"for\" check"#),
+			q,
+			(Literal, r"EXE \"),
+			q,
+			(Literal, r"for\"),
+			q,
+			(Literal, " check")
+		])
+	);
+	assert_eq!(
+		spans(r#""EXE \" for \" check"#),
+		s(&[
+			(Argument, r#""EXE \""#),
+			q,
+			(Literal, r"EXE \"),
+			q,
+			(Argument, "for"),
+			(Literal, "for"),
+			(Argument, r#"\""#),
+			(Escape, r"\"),
+			(Literal, r#"""#),
+			(Argument, "check"),
+			(Literal, "check")
+		])
+	);
+	assert_eq!(
+		spans(r#"E"X"E test"#),
+		s(&[
+			(Argument, r#"E"X"E"#),
+			(Literal, "E"),
+			q,
+			(Literal, "X"),
+			q,
+			(Literal, "E"),
+			(Argument, "test"),
+			(Literal, "test")
+		])
+	);
+	assert_eq!(
+		spans(r#"EX""E test"#),
+		s(&[
+			(Argument, r#"EX""E"#),
+			(Literal, "EX"),
+			q,
+			q,
+			(Literal, "E"),
+			(Argument, "test"),
+			(Literal, "test")
+		])
+	);
+}
+
+#[test]
+fn empty_and_nul() {
+	assert_eq!(spans(""), []);
+	// An empty program name is still an argument.
+	assert_eq!(
+		spans(" a"),
+		s(&[(Argument, ""), (Argument, "a"), (Literal, "a")])
+	);
+	assert_eq!(spans("a\0b"), s(&[(Argument, "a"), (Literal, "a")]));
+}
@@ -0,0 +1,708 @@
+// Tests for the public API, as opposed to the parsing rules.
+
+use super::{args, format, units, with_command_line, with_command_line_wide, String, Vec};
+use crate::{args_native, ArgsNative, Argument, GroupedToken, Parser, ParserEvent, Token};
+
+fn tokens(cmdline: &str) -> Vec<Token> {
+	with_command_line(cmdline, || Parser::from_env().collect())
+}
+
+#[test]
+fn partial_eq() {
+	let args = args(r#"EXE "a b" a"#);
+	let ab = &args[1];
+	let wide = units("a b");
+
+	assert!(*ab == "a b");
+	assert!("a b" == *ab);
+	assert!(*ab == &wide[..]);
+	assert!(&wide[..] == *ab);
+
+	// Prefixes aren't equal in either direction.
+	assert!(*ab != "a");
+	assert!("a" != *ab);
+	assert!(*ab != "a b c");
+	assert!(*ab != &wide[..1]);
+	assert!(&wide[..1] != *ab);
+
+	// Arguments compare their parsed values, not what's on the command line.
+	let other = self::args(r#"EXE a" "b"#);
+	assert!(*ab == other[1]);
+	assert!(other[1] == *ab);
+	assert!(args[2] != *ab);
+	assert!(args[2] == args[2]);
+}
+
+#[test]
+fn code_points() {
+	let args = args("EXE a😅");
+	assert_eq!(args[1].code_points().collect::<Vec<u32>>(), [0x61, 0x1F605]);
+	assert_eq!(args[1].utf16_units().collect::<Vec<u16>>(), units("a😅"));
+}
+
+#[test]
+fn raw_arg() {
+	let args = args(r#"EXE "a b" c\"d "#);
+	// The raw argument includes the rest of the command line, unparsed.
+	assert_eq!(args[0].raw_arg(), &units(r#"EXE "a b" c\"d "#)[..]);
+	assert_eq!(args[1].raw_arg(), &units(r#""a b" c\"d "#)[..]);
+	assert_eq!(args[2].raw_arg(), &units(r#"c\"d "#)[..]);
+	assert_eq!(args.len(), 3);
+}
+
+#[test]
+fn as_raw_verbatim() {
+	let cmdline = r#"C:\dir\app.exe a\\b c\"d "e" "" f""g C:\dir\ ""#;
+	let args = args(cmdline);
+	let verbatim: Vec<Option<&[u16]>> = args.iter().map(Argument::as_raw_verbatim).collect();
+	let units = units(cmdline);
+	let slice = |start: usize, end: usize| Some(&units[start..end]);
+	assert_eq!(
+		verbatim,
+		[
+			slice(0, 14),
+			// A backslash that isn't before a quote is kept.
+			slice(15, 19),
+			// One before a quote is an escape.
+			None,
+			// The content is the raw text without its quotes, which isn't
+			// the same as the raw text.
+			None,
+			None,
+			None,
+			slice(37, 44),
+			None,
+		]
+	);
+	// The slices are the parsed arguments.
+	for (arg, verbatim) in args.iter().zip(&verbatim) {
+		if let Some(verbatim) = verbatim {
+			assert!(arg == verbatim);
+		}
+	}
+
+	// The program name doesn't treat backslashes as escapes, but it's still
+	// not verbatim if it's quoted.
+	let args = self::args(r#""C:\dir\app.exe" a"#);
+	assert_eq!(args[0].as_raw_verbatim(), None);
+	assert_eq!(args[1].as_raw_verbatim(), Some(&[0x61][..]));
+	// An empty program name, from a command line that starts with a space.
+	let args = self::args(" a");
+	assert_eq!(args[0].as_raw_verbatim(), Some(&[][..]));
+}
+
+#[test]
+fn is_arg0() {
+	// Backslashes don't escape quotes in the zeroth argument.
+	let args = args(r#""C:\dir\" "C:\dir\" b"#);
+	assert!(args[0] == r"C:\dir\");
+	assert!(args[1] == r#"C:\dir" b"#);
+	assert_eq!(args.len(), 2);
+
+	// Nor does `""` produce a quote.
+	let args = self::args(r#""a""b" "a""b""#);
+	assert!(args[0] == "ab");
+	assert!(args[1] == r#"a"b"#);
+}
+
+#[test]
+fn clone() {
+	with_command_line("EXE a b c", || {
+		let mut args = ArgsNative::from_env();
+		assert!(args.next().unwrap() == "EXE");
+		let mut cloned = args.clone();
+
+		// The clone starts where the original was...
+		assert!(cloned.next().unwrap() == "a");
+		assert!(cloned.next().unwrap() == "b");
+		// ...and is independent of it.
+		assert!(args.next().unwrap() == "a");
+		assert!(cloned.next().unwrap() == "c");
+		assert!(cloned.next().is_none());
+		assert_eq!(args.count(), 2);
+	});
+}
+
+#[test]
+fn empty_quotes() {
+	use Token::NextArg;
+
+	// An empty zeroth argument and nothing else.
+	assert!(tokens(r#""""#).is_empty());
+	assert!(tokens(r#""" "#).is_empty());
+	// Empty arguments are still separated.
+	assert_eq!(tokens(r#""" """#), [NextArg]);
+	assert_eq!(tokens(r#""" "" """#), [NextArg, NextArg]);
+	let exe: Vec<Token> = tokens("EXE")
+		.into_iter()
+		.chain([NextArg, NextArg])
+		.collect();
+	assert_eq!(tokens(r#"EXE "" """#), exe);
+
+	assert_eq!(args(r#""" "" """#).len(), 3);
+	assert!(args(r#""" "" """#).iter().all(|arg| *arg == ""));
+}
+
+#[test]
+fn token_accessors() {
+	use Token::NextArg;
+
+	let tokens = tokens("EXE \\\"\u{e9}\u{1F605}");
+	assert_eq!(tokens.len(), 8);
+	assert!(tokens[..3].iter().all(|t| t.is_unit()));
+	assert_eq!(tokens[0].unit().map(|u| u.get()), Some(u16::from(b'E')));
+	assert_eq!(tokens[0].as_char(), Some('E'));
+	assert!(tokens[0] == 'E');
+	assert!('E' == tokens[0]);
+	assert!(tokens[0] == u16::from(b'E'));
+	assert!(u16::from(b'E') == tokens[0]);
+	assert!(tokens[0] != 'X');
+
+	assert_eq!(tokens[3], NextArg);
+	// The escaped quote.
+	assert!(tokens[4] == '"');
+	assert_eq!(tokens[5].as_char(), Some('\u{e9}'));
+	// Surrogates aren't characters by themselves.
+	assert_eq!(tokens[6].as_char(), None);
+	assert!(tokens[6] == 0xD83D);
+	assert!(tokens[6] != '\u{1F605}');
+
+	assert!(!NextArg.is_unit());
+	assert_eq!(NextArg.unit(), None);
+	assert_eq!(NextArg.as_char(), None);
+	// Even though `as_u16` returns `0`.
+	assert!(NextArg != 0 && NextArg != '\0');
+}
+
+#[test]
+fn events() {
+	// The tokens, or `None` for the end.
+	let events = |cmdline| -> Vec<Option<Token>> {
+		with_command_line(cmdline, || {
+			Parser::from_env()
+				.events()
+				.map(|event| match event {
+					ParserEvent::Token(token) => Some(token.token),
+					ParserEvent::End { .. } => None,
+				})
+				.collect()
+		})
+	};
+	// `End` is returned even when there are no tokens.
+	assert_eq!(events(""), [None]);
+	assert_eq!(events(r#""""#), [None]);
+	let exe: Vec<Option<Token>> = tokens("EXE").into_iter().map(Some).chain([None]).collect();
+	assert_eq!(events("EXE"), exe);
+	assert_eq!(events("EXE  "), exe);
+	assert_eq!(
+		events(r#"EXE "" """#)[3..],
+		[Some(Token::NextArg), Some(Token::NextArg), None]
+	);
+
+	// Only once, and then the parser is fused.
+	with_command_line("EXE a", || {
+		let mut parser = Parser::from_env().events();
+		assert_eq!(parser.size_hint(), (1, Some(6)));
+		// Skip to the next argument.
+		let next_arg = parser.by_ref().find(|event| match event {
+			ParserEvent::Token(token) => token.token.is_next_arg(),
+			_ => false,
+		});
+		assert!(next_arg.is_some());
+		assert!(matches!(parser.next(), Some(ParserEvent::Token(_))));
+		assert_eq!(parser.next(), Some(ParserEvent::End { offset: 5 }));
+		assert_eq!(parser.size_hint(), (0, Some(0)));
+		assert_eq!(parser.next(), None);
+		assert_eq!(parser.next(), None);
+	});
+
+	// The end is after any whitespace, up to the first null.
+	with_command_line("EXE a \0b", || {
+		let end = Parser::from_env().events().last();
+		assert_eq!(end, Some(ParserEvent::End { offset: 6 }));
+	});
+}
+
+// The grouped tokens as a string, with `<i>` for the start of argument `i` and
+// `<p>` for the start of the program name.
+fn grouped(cmdline: &str) -> String {
+	let mut s = String::new();
+	with_command_line(cmdline, || {
+		for t in Parser::from_env().grouped() {
+			match t {
+				GroupedToken::ArgStart {
+					index: 0,
+					is_program,
+				} => {
+					assert!(is_program);
+					s.push_str("<p>");
+				}
+				GroupedToken::ArgStart { index, is_program } => {
+					assert!(!is_program);
+					s.push_str(&format!("<{}>", index));
+				}
+				GroupedToken::Unit(unit) => s.push(char::from_u32(unit.get().into()).unwrap()),
+			}
+		}
+	});
+	s
+}
+
+#[test]
+fn grouped_tokens() {
+	// Empty command lines have no arguments.
+	assert_eq!(grouped(""), "");
+	assert_eq!(args("").len(), 0);
+	// Empty program names still get an `ArgStart`.
+	assert_eq!(grouped(" test"), "<p><1>test");
+	assert_eq!(grouped(r#""""#), "<p>");
+	assert_eq!(grouped("EXE"), "<p>EXE");
+	assert_eq!(grouped("EXE a  b "), "<p>EXE<1>a<2>b");
+
+	// The `genius_quotes` parsing tests.
+	assert_eq!(grouped(r#"EXE "" """#), "<p>EXE<1><2>");
+	assert_eq!(grouped(r#"EXE "" """"#), r#"<p>EXE<1><2>""#);
+	assert_eq!(
+		grouped(r#"EXE "this is """all""" in the same argument""#),
+		r#"<p>EXE<1>this is "all" in the same argument"#
+	);
+	assert_eq!(grouped(r#"EXE "a"""#), r#"<p>EXE<1>a""#);
+	assert_eq!(grouped(r#"EXE "a"" a"#), r#"<p>EXE<1>a" a"#);
+	assert_eq!(grouped(r#""EXE" check"#), "<p>EXE<1>check");
+	assert_eq!(grouped(r#""EXE check""#), "<p>EXE check");
+	assert_eq!(grouped(r#""EXE """for""" check"#), "<p>EXE for check");
+	assert_eq!(grouped(r#""EXE \"for\" check"#), r"<p>EXE \for\ check");
+	assert_eq!(
+		grouped(r#""EXE \" for \" check"#),
+		r#"<p>EXE \<1>for<2>"<3>check"#
+	);
+	assert_eq!(grouped(r#"E"X"E test"#), "<p>EXE<1>test");
+	assert_eq!(grouped(r#"EX""E test"#), "<p>EXE<1>test");
+}
+
+#[test]
+fn unquote_one() {
+	let unquote = |input: &str, is_program| {
+		let input = units(input);
+		let (arg, used) = crate::unquote_one(&input, is_program);
+		(
+			String::from_utf16(&arg.collect::<Vec<u16>>()).unwrap(),
+			used,
+		)
+	};
+	// Starting with a quote.
+	assert_eq!(unquote(r#""a b" c"#, false), ("a b".into(), 5));
+	assert_eq!(unquote(r#""a b"c" d" e"#, false), ("a bc d".into(), 10));
+	// Backslashes before a quote.
+	assert_eq!(unquote(r#"a\\\"b c"#, false), (r#"a\"b"#.into(), 6));
+	assert_eq!(unquote(r#"a\\"b c" d"#, false), (r"a\b c".into(), 8));
+	assert_eq!(unquote(r#""C:\dir\" a"#, true), (r"C:\dir\".into(), 9));
+	// Using the entire input.
+	assert_eq!(unquote("abc", false), ("abc".into(), 3));
+	assert_eq!(unquote(r#""a b"#, false), ("a b".into(), 4));
+	assert_eq!(unquote(r"a\\", false), (r"a\\".into(), 3));
+	assert_eq!(unquote("", false), ("".into(), 0));
+	// Leading whitespace isn't skipped.
+	assert_eq!(unquote(" a", false), ("".into(), 0));
+	// A null ends the input.
+	assert_eq!(unquote("a\0b", false), ("a".into(), 1));
+}
+
+// The parser's state after reading each prefix of `cmdline`, as
+// `(in_quotes, pending_backslashes, current_arg_index)`.
+fn states(cmdline: &str) -> Vec<(bool, u16, usize)> {
+	let cmdline = units(cmdline);
+	(1..=cmdline.len())
+		.map(|len| {
+			with_command_line_wide(&cmdline[..len], || {
+				let mut parser = Parser::from_env();
+				while parser.next().is_some() {}
+				(
+					parser.in_quotes(),
+					parser.pending_backslashes(),
+					parser.current_arg_index(),
+				)
+			})
+		})
+		.collect()
+}
+
+#[test]
+fn parser_state() {
+	let f = false;
+	let t = true;
+	assert_eq!(
+		states(r#"E "a b" c"#),
+		[
+			(f, 0, 0),
+			(f, 0, 0),
+			(t, 0, 1),
+			(t, 0, 1),
+			(t, 0, 1),
+			(t, 0, 1),
+			(f, 0, 1),
+			(f, 0, 1),
+			(f, 0, 2)
+		]
+	);
+	// Backslashes are pending until something other than a backslash.
+	assert_eq!(
+		states(r#"E a\\\"b"#),
+		[
+			(f, 0, 0),
+			(f, 0, 0),
+			(f, 0, 1),
+			(f, 1, 1),
+			(f, 2, 1),
+			(f, 3, 1),
+			(f, 0, 1),
+			(f, 0, 1)
+		]
+	);
+	assert_eq!(
+		states(r#"E "\\" a"#),
+		[
+			(f, 0, 0),
+			(f, 0, 0),
+			(t, 0, 1),
+			(t, 1, 1),
+			(t, 2, 1),
+			(f, 0, 1),
+			(f, 0, 1),
+			(f, 0, 2)
+		]
+	);
+	// But not in the program name.
+	assert_eq!(
+		states(r#""C:\ a"#),
+		[
+			(t, 0, 0),
+			(t, 0, 0),
+			(t, 0, 0),
+			(t, 0, 0),
+			(t, 0, 0),
+			(t, 0, 0)
+		]
+	);
+	assert_eq!(states(""), []);
+}
+
+#[test]
+fn parser_state_mid_argument() {
+	// The state is after the last token, even if the parser has read further.
+	with_command_line(r#"E "a\\\"#, || {
+		let mut parser = Parser::from_env();
+		let mut states = Vec::new();
+		while parser.next().is_some() {
+			states.push((parser.in_quotes(), parser.pending_backslashes()));
+		}
+		assert_eq!(
+			states,
+			[
+				(false, 0),
+				(false, 0),
+				(true, 0),
+				(true, 3),
+				(true, 3),
+				(true, 3)
+			]
+		);
+	});
+}
+
+#[test]
+fn outlives_iterator() {
+	// The iterator is dropped as soon as the arguments are collected.
+	let mut args: Vec<Argument> =
+		with_command_line(r#"EXE "a b" c"#, || args_native().take(2).collect());
+	// The command line override has also ended but the arguments are still valid.
+	assert!(args.pop().unwrap() == "a b");
+	assert!(args.pop().unwrap() == "EXE");
+}
+
+#[test]
+fn command_line_len() {
+	assert_eq!(with_command_line("", crate::command_line_len), 0);
+	assert_eq!(
+		with_command_line(r#"EXE "a b""#, crate::command_line_len),
+		9
+	);
+	// Surrogate pairs are two units.
+	assert_eq!(with_command_line("EXE 😅", crate::command_line_len), 6);
+	// The command line ends at a null.
+	assert_eq!(with_command_line("EXE\0a", crate::command_line_len), 3);
+}
+
+#[test]
+fn raw_command_line() {
+	for &cmdline in &["", " a", r#""C:\dir\app.exe" "a b""#, "EXE 😅\0a"] {
+		with_command_line(cmdline, || {
+			let raw = crate::raw_command_line();
+			let expected = units(cmdline.split('\0').next().unwrap());
+			assert_eq!(raw, &expected[..]);
+			assert_eq!(raw.len(), crate::command_line_len());
+			// It starts with the program name, as it was given.
+			if let Some(span) = crate::arg_spans().next() {
+				assert!(raw.starts_with(&expected[span]));
+			}
+			#[cfg(feature = "alloc")]
+			assert_eq!(
+				crate::command_line_lossy(),
+				String::from_utf16_lossy(&expected)
+			);
+		});
+	}
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn command_line_lossy() {
+	let cases: &[(&[u16], &str)] = &[
+		(&[], ""),
+		(&[0x45, 0x20, 0xD83D, 0xDE05], "E 😅"),
+		(&[0x45, 0x20, 0xD800, 0x61], "E \u{FFFD}a"),
+		(&[0xDC00, 0x22, 0xD800], "\u{FFFD}\"\u{FFFD}"),
+	];
+	for &(cmdline, lossy) in cases {
+		with_command_line_wide(cmdline, || {
+			let string = crate::command_line_lossy();
+			assert_eq!(string, lossy);
+			assert_eq!(string.capacity(), string.len());
+			match crate::try_command_line() {
+				Ok(string) => {
+					assert_eq!(string, lossy);
+					assert_eq!(string.capacity(), string.len());
+				}
+				Err(e) => {
+					let at = cmdline.iter().position(|u| (0xD800..0xE000).contains(u));
+					assert_eq!(Some(e.offset()), at);
+					assert_eq!(e.unpaired_surrogate(), cmdline[e.offset()]);
+					assert_eq!(e.arg_index(), None);
+					assert!(lossy.contains('\u{FFFD}'));
+				}
+			}
+		});
+	}
+
+	// The longest command line, where each code unit is two bytes of UTF-8.
+	// Miri is much slower so it uses a shorter one.
+	let len = if cfg!(miri) {
+		256
+	} else {
+		crate::MAX_COMMAND_LINE
+	};
+	let cmdline = super::vec![0xE9; len];
+	with_command_line_wide(&cmdline, || {
+		for string in &[
+			crate::command_line_lossy(),
+			crate::try_command_line().unwrap(),
+		] {
+			assert_eq!(string.len(), 2 * len);
+			assert_eq!(string.capacity(), string.len());
+		}
+	});
+}
+
+#[test]
+fn args_equivalent() {
+	use crate::{args_equivalent, args_equivalent_with, CaseSensitivity};
+
+	let same = [
+		(r#""a b" c"#, r#"a" "b "c""#),
+		(r#"EXE "" a"#, r#"EXE "" "a""#),
+		(r#"EXE a\\"b c""#, r#"EXE "a\b c""#),
+		(r#""C:\dir\app.exe""#, r#"C:"\dir\"app.exe"#),
+		("EXE\ta  b", "EXE a\tb "),
+		("EXE a\0b", "EXE a"),
+		("", ""),
+	];
+	let different = [
+		("a b", r#""a b""#),
+		("EXE a", "EXE a b"),
+		("EXE a", "EXE A"),
+		(r#"EXE "" a"#, r#"EXE """" a"#),
+		(r#"EXE a\"b"#, r#"EXE a\\"b"#),
+		(" a", "a"),
+		("", "EXE"),
+	];
+	for &(a, b) in same.iter().chain(different.iter()) {
+		let expected = args(a) == args(b);
+		assert_eq!(expected, same.contains(&(a, b)), "{:?} {:?}", a, b);
+		assert_eq!(
+			args_equivalent(&units(a), &units(b)),
+			expected,
+			"{:?} {:?}",
+			a,
+			b
+		);
+		assert_eq!(
+			args_equivalent(&units(b), &units(a)),
+			expected,
+			"{:?} {:?}",
+			b,
+			a
+		);
+	}
+
+	// Only the program name can differ in case.
+	let (a, b) = (units(r#""C:\App.exe" /Run"#), units(r"c:\app.exe /Run"));
+	assert!(!args_equivalent(&a, &b));
+	assert!(args_equivalent_with(
+		&a,
+		&b,
+		CaseSensitivity::AsciiInsensitive
+	));
+	let (a, b) = (units(r"C:\App.exe /Run"), units(r"C:\App.exe /run"));
+	assert!(!args_equivalent_with(
+		&a,
+		&b,
+		CaseSensitivity::AsciiInsensitive
+	));
+
+	with_command_line(r#"app.exe "a b""#, || {
+		assert!(crate::args_equivalent_to(&units(r#""app.exe" a" b""#)));
+		assert!(!crate::args_equivalent_to(&units("app.exe a b")));
+	});
+}
+
+#[test]
+fn split_program() {
+	let cases = [
+		// A quoted program with spaces. Backslashes don't escape quotes in it.
+		(
+			r#""C:\Program Files\app.exe" "a b"  c"#,
+			r"C:\Program Files\app.exe",
+			r#""a b"  c"#,
+		),
+		(r#""C:\dir\"x y"#, r"C:\dir\x", "y"),
+		(
+			r#"C:\"Program Files"\app.exe a\"b"#,
+			r"C:\Program Files\app.exe",
+			r#"a\"b"#,
+		),
+		// An unquoted program.
+		("app.exe\t \t-v --", "app.exe", "-v --"),
+		// Leading whitespace is an empty program name.
+		("  app.exe a", "", "app.exe a"),
+		// Only a program.
+		("app.exe", "app.exe", ""),
+		("app.exe \t", "app.exe", ""),
+		(r#""app.exe""#, "app.exe", ""),
+		("", "", ""),
+		// The command line ends at a null.
+		("app.exe a\0b", "app.exe", "a"),
+		("app.exe\0 a", "app.exe", ""),
+	];
+	for &(cmdline, program, rest) in &cases {
+		let wide = units(cmdline);
+		let (name, tail) = crate::split_program_of(&wide);
+		assert_eq!(name.collect::<Vec<u16>>(), units(program), "{:?}", cmdline);
+		assert_eq!(tail, &units(rest)[..], "{:?}", cmdline);
+		// The rest is part of the command line.
+		assert!(wide.as_ptr_range().contains(&tail.as_ptr()) || tail.is_empty());
+
+		with_command_line_wide(&wide, || {
+			let (name, tail) = crate::split_program();
+			assert_eq!(name, program, "{:?}", cmdline);
+			assert_eq!(name, crate::program_name());
+			assert_eq!(tail, &units(rest)[..], "{:?}", cmdline);
+		});
+	}
+}
+
+#[test]
+fn skip_until() {
+	fn rest(args: ArgsNative) -> Vec<String> {
+		args.map(|arg| arg.scalars().collect()).collect()
+	}
+	with_command_line(r#"--target "a b" --target x "--" -- c"#, || {
+		// The program name can match.
+		let mut args = args_native();
+		let matched = args.skip_until_eq("--target").unwrap();
+		assert_eq!(matched, "--target");
+		assert_eq!(rest(args), ["a b", "--target", "x", "--", "--", "c"]);
+
+		// Only the first match is taken.
+		let mut args = args_native();
+		args.next();
+		assert_eq!(args.skip_until_eq("--target").unwrap(), "--target");
+		assert_eq!(rest(args.clone()), ["x", "--", "--", "c"]);
+		// The quoted `--` is the same argument once quotes are removed.
+		assert_eq!(args.skip_until_eq("--").unwrap(), "--");
+		assert_eq!(rest(args), ["--", "c"]);
+
+		// The predicate sees each argument once, in order.
+		let mut args = args_native();
+		let mut seen = Vec::new();
+		let matched = args.skip_until(|arg| {
+			seen.push(arg.scalars().collect::<String>());
+			arg.utf16_units().count() == 1
+		});
+		assert_eq!(matched.unwrap(), "x");
+		assert_eq!(seen, ["--target", "a b", "--target", "x"]);
+		assert_eq!(rest(args), ["--", "--", "c"]);
+
+		// Nothing matches.
+		let mut args = args_native();
+		assert!(args.skip_until_eq("--help").is_none());
+		assert_eq!(args.next(), None);
+		assert!(args.skip_until(|_| true).is_none());
+	});
+	with_command_line("", || {
+		assert!(args_native().skip_until(|_| true).is_none());
+	});
+}
+
+#[test]
+fn stats() {
+	use crate::ArgStats;
+
+	let stats = |cmdline: &str| -> Vec<ArgStats> {
+		with_command_line(cmdline, || args_native().map(|arg| arg.stats()).collect())
+	};
+	let expected = |quotes, escapes, raw_len, len| ArgStats {
+		quotes,
+		escapes,
+		raw_len,
+		len,
+		..ArgStats::default()
+	};
+
+	// Plain arguments.
+	assert_eq!(
+		stats(r"C:\dir\app.exe /c"),
+		[expected(0, 0, 14, 14), expected(0, 0, 2, 2)]
+	);
+	// Quotes that do nothing, as in `c"m"d`, and escapes. Of the quotes in
+	// `""""`, the one that's part of the argument isn't counted.
+	assert_eq!(
+		stats(r#"c"m"d.exe /"c" "p"o"w"e"r"s"h"e"l"l a\\\"b """""#),
+		[
+			expected(2, 0, 9, 7),
+			expected(2, 0, 4, 2),
+			expected(10, 0, 20, 10),
+			expected(0, 1, 6, 4),
+			expected(3, 0, 4, 1),
+		]
+	);
+	// Backslashes that aren't before a quote aren't escapes.
+	assert_eq!(stats(r"EXE a\\b\")[1], expected(0, 0, 5, 5));
+
+	let units: Vec<ArgStats> = stats("EXE \u{E9} \"a\tb\" \x7F");
+	assert!(units[1].has_non_ascii && !units[1].has_control);
+	assert!(!units[2].has_non_ascii && units[2].has_control);
+	assert!(units[3].has_control);
+	assert!(!units[0].has_non_ascii && !units[0].has_control);
+
+	// The totals.
+	let cmdline = r#"c"m"d.exe /"c" a\\\"b"#;
+	let total = with_command_line(cmdline, crate::command_line_stats);
+	assert_eq!(total, expected(4, 1, 19, 13));
+	assert_eq!(total, stats(cmdline).into_iter().sum());
+	let total = with_command_line("EXE \u{E9}", crate::command_line_stats);
+	assert!(total.has_non_ascii && !total.has_control);
+	assert_eq!(
+		with_command_line("", crate::command_line_stats),
+		ArgStats::default()
+	);
+}
@@ -0,0 +1,123 @@
+// Tests for the `arg_match!` macro. Each arm records what it matched.
+
+use super::{format, with_command_line, String, Vec};
+use crate::args_native;
+
+fn matched(cmdline: &str) -> Vec<String> {
+	with_command_line(cmdline, || {
+		let mut matched = Vec::new();
+		crate::arg_match!(args_native().skip(1), {
+			"--help" | "-h" => { matched.push("help".into()) }
+			"--out" | "-o" = value => {
+				matched.push(match value {
+					Some(value) => format!("out {}", value.scalars().collect::<String>()),
+					None => "out missing".into(),
+				})
+			}
+			"--level" = value => { matched.push(format!("level {:?}", value.map(|v| v.is_separate()))) }
+			positional(arg) => { matched.push(format!("positional {}", arg)) }
+			rest(raw) => { matched.push(format!("rest {}", String::from_utf16_lossy(raw))) }
+		});
+		matched
+	})
+}
+
+#[test]
+fn names() {
+	assert_eq!(matched("app.exe -h --help"), ["help", "help"]);
+	// Arguments are compared once they're parsed.
+	assert_eq!(matched(r#"app.exe "--help" -"h""#), ["help", "help"]);
+	assert_eq!(
+		matched("app.exe --HELP --help=x -hh"),
+		["positional --HELP", "positional --help=x", "positional -hh"]
+	);
+	assert!(matched("app.exe").is_empty());
+	// The program name is skipped by the iterator, not the macro.
+	with_command_line("-h -h", || {
+		let mut count = 0;
+		crate::arg_match!(args_native(), { "-h" => { count += 1 } });
+		assert_eq!(count, 2);
+	});
+}
+
+#[test]
+fn values() {
+	assert_eq!(
+		matched(r#"app.exe --out "a b" -o=c --out= -o"#),
+		["out a b", "out c", "out ", "out missing"]
+	);
+	// The next argument is always the value, even if it looks like an option.
+	assert_eq!(matched("app.exe --out --help -h"), ["out --help", "help"]);
+	assert_eq!(matched("app.exe --out -- x"), ["out --", "positional x"]);
+	assert_eq!(
+		matched("app.exe --level 1 --level=2 --level"),
+		["level Some(true)", "level Some(false)", "level None"]
+	);
+	// Only `=` separates a value.
+	assert_eq!(matched("app.exe --out:a"), ["positional --out:a"]);
+}
+
+#[test]
+fn positional_and_rest() {
+	assert_eq!(
+		matched(r#"app.exe a -x -- --help  "b c"  "#),
+		["positional a", "positional -x", r#"rest --help  "b c"  "#]
+	);
+	// Nothing after `--`.
+	assert_eq!(matched("app.exe a --"), ["positional a", "rest "]);
+	assert_eq!(matched("app.exe -- "), ["rest "]);
+	// A quoted `--` is still `--`.
+	assert_eq!(matched(r#"app.exe "--" -h"#), ["rest -h"]);
+}
+
+#[test]
+fn optional_arms() {
+	// Without `positional`, other arguments are ignored. Without `rest`, `--`
+	// is an argument like any other.
+	with_command_line("app.exe a -h -- -h b", || {
+		let mut count = 0;
+		crate::arg_match!(args_native().skip(1), { "-h" => { count += 1 } });
+		assert_eq!(count, 2);
+	});
+	with_command_line("app.exe a -- b", || {
+		let mut seen = Vec::new();
+		crate::arg_match!(args_native().skip(1), {
+			positional(arg) => { seen.push(arg) },
+		});
+		assert_eq!(seen, ["a", "--", "b"]);
+	});
+	// No arms at all.
+	with_command_line("app.exe a", || crate::arg_match!(args_native(), {}));
+}
+
+#[test]
+fn control_flow() {
+	// The first matching arm wins.
+	with_command_line("app.exe -a", || {
+		let mut first = None;
+		crate::arg_match!(args_native().skip(1), {
+			"-a" => { first.get_or_insert(1); },
+			"-a" | "-b" => { first.get_or_insert(2); },
+		});
+		assert_eq!(first, Some(1));
+	});
+	// `break` stops matching.
+	with_command_line("app.exe a b c", || {
+		let mut seen = Vec::new();
+		crate::arg_match!(args_native().skip(1), {
+			positional(arg) => {
+				if arg == "b" {
+					break;
+				}
+				seen.push(arg);
+			}
+		});
+		assert_eq!(seen, ["a"]);
+	});
+	// Arguments left in the iterator can still be used.
+	with_command_line("app.exe a stop b c", || {
+		let mut args = args_native().skip(1);
+		crate::arg_match!(&mut args, { "stop" => { break } });
+		assert_eq!(args.collect::<Vec<_>>(), ["b", "c"]);
+	});
+}
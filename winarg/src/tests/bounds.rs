@@ -0,0 +1,108 @@
+// Tests that the parser stays within the command line when there's no null at
+// the end of it.
+//
+// Each command line is copied to its own heap allocation so that it ends
+// exactly where the allocation does. Reading past the end is then undefined
+// behaviour, which Miri reports, and a heap overflow, which AddressSanitizer
+// reports. The command lines end in each state the parser can be in.
+
+use super::{leak, units, vec, String, ToString, Vec};
+use crate::{
+	analyze, arg_spans_of, testing, unquote_one_with, validate, ArgsNative, Parser, ParserRules,
+	Token,
+};
+
+// Check `cmdline` is parsed into `parts` by everything that parses a slice.
+fn chk(cmdline: &[u16], parts: &[&[u16]]) {
+	let cmdline = leak(cmdline);
+	assert!(!cmdline.contains(&0));
+	let lossy = String::from_utf16_lossy(cmdline);
+	let _guard = testing::set_command_line(cmdline);
+
+	let args: Vec<Vec<u16>> = ArgsNative::from_env()
+		.map(|arg| arg.utf16_units().collect())
+		.collect();
+	assert_eq!(args, parts, "{:?}", lossy);
+	// Displaying an argument decodes it, which looks ahead for low surrogates.
+	let displayed: Vec<_> = ArgsNative::from_env().map(|arg| arg.to_string()).collect();
+	let expected: Vec<_> = parts.iter().map(|p| String::from_utf16_lossy(p)).collect();
+	assert_eq!(displayed, expected, "{:?}", lossy);
+
+	for &rules in &[ParserRules::Modern, ParserRules::Pre2008] {
+		let mut args = vec![Vec::new()];
+		for token in Parser().with_rules(rules) {
+			match token {
+				Token::NextArg => args.push(Vec::new()),
+				token => args.last_mut().unwrap().push(token.as_u16()),
+			}
+		}
+		// There are no tokens at all, not even for the program name.
+		if cmdline.is_empty() {
+			args.clear();
+		}
+
+		let mut spanned = Vec::new();
+		for (index, span) in arg_spans_of(cmdline).enumerate() {
+			let (arg, used) = unquote_one_with(&cmdline[span.clone()], index == 0, rules);
+			assert_eq!(used, span.len());
+			spanned.push(arg.collect::<Vec<u16>>());
+		}
+		assert_eq!(args, spanned, "{:?} under {:?}", lossy, rules);
+		if rules == ParserRules::Modern {
+			assert_eq!(args, parts, "{:?}", lossy);
+		}
+	}
+
+	let mut end = 0;
+	analyze(cmdline, |span| end = end.max(span.range.end));
+	assert!(end <= cmdline.len());
+	for diagnostic in validate(cmdline) {
+		assert!(diagnostic.span.end <= cmdline.len());
+	}
+}
+
+#[test]
+fn empty() {
+	chk(&[], &[]);
+}
+
+#[test]
+fn only_whitespace() {
+	chk(&units(" "), &[&[]]);
+	chk(&units(" \t "), &[&[]]);
+}
+
+#[test]
+fn mid_quote() {
+	chk(&units(r#"EXE "a b"#), &[&units("EXE"), &units("a b")]);
+	chk(&units(r#""EXE"#), &[&units("EXE")]);
+	chk(&units(r#"EXE "a"""#), &[&units("EXE"), &units(r#"a""#)]);
+}
+
+#[test]
+fn after_opening_quote() {
+	chk(&units(r#"EXE ""#), &[&units("EXE"), &[]]);
+	chk(&units(r#"""#), &[&[]]);
+	chk(&units(r#"EXE a""#), &[&units("EXE"), &units("a")]);
+}
+
+#[test]
+fn after_backslashes() {
+	chk(&units(r"EXE \"), &[&units("EXE"), &units(r"\")]);
+	chk(&units(r"EXE a\\\"), &[&units("EXE"), &units(r"a\\\")]);
+	chk(&units(r#"EXE "a\"#), &[&units("EXE"), &units(r"a\")]);
+	chk(&units(r#"EXE "a\\\"#), &[&units("EXE"), &units(r"a\\\")]);
+	// The program name has no escapes.
+	chk(&units(r"EXE\"), &[&units(r"EXE\")]);
+}
+
+#[test]
+fn on_high_surrogate() {
+	let mut cmdline = units("EXE ");
+	cmdline.push(0xD800);
+	chk(&cmdline, &[&units("EXE"), &[0xD800]]);
+	let mut cmdline = units(r#"EXE "a"#);
+	cmdline.push(0xDBFF);
+	chk(&cmdline, &[&units("EXE"), &[0x61, 0xDBFF]]);
+	chk(&[0xD800], &[&[0xD800]]);
+}
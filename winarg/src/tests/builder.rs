@@ -0,0 +1,289 @@
+// Tests for `CommandLineBuilder`.
+
+use super::{format, units, String, ToString, Vec};
+use crate::CommandLineBuilder;
+
+fn lossy(builder: &CommandLineBuilder) -> String {
+	String::from_utf16_lossy(builder.as_slice())
+}
+
+#[test]
+fn arguments() {
+	let mut builder = CommandLineBuilder::new(units(r"C:\Program Files\app.exe")).unwrap();
+	builder
+		.arg(units(""))
+		.unwrap()
+		.arg(units(r"C:\dir\"))
+		.unwrap()
+		.args(&[units(r"a\\b"), units(r#"a "b" c\"#)])
+		.unwrap();
+	assert_eq!(
+		lossy(&builder),
+		r#""C:\Program Files\app.exe" "" C:\dir\ a\\b "a \"b\" c\\""#
+	);
+	// It's the same as joining the arguments.
+	let args = [
+		r"C:\Program Files\app.exe",
+		"",
+		r"C:\dir\",
+		r"a\\b",
+		r#"a "b" c\"#,
+	];
+	let args: Vec<Vec<u16>> = args.iter().map(|s| units(s)).collect();
+	assert_eq!(builder.len(), crate::quoted_len(&args));
+
+	let command_line = builder.build_for_create_process();
+	assert_eq!(command_line.split_last(), Some((&0, builder.as_slice())));
+}
+
+#[test]
+fn outputs() {
+	let mut builder = CommandLineBuilder::new(units("app.exe")).unwrap();
+	builder
+		.arg(units("a b"))
+		.unwrap()
+		.arg([0x61, 0xD800, 0x62])
+		.unwrap()
+		.arg(units("\u{1F600}\\"))
+		.unwrap();
+	let command_line = builder.build_for_create_process();
+	let (&nul, command_line) = command_line.split_last().unwrap();
+	assert_eq!(nul, 0);
+
+	// Every output is the same command line, apart from the null.
+	let lossy = builder.build_string_lossy();
+	assert_eq!(lossy, String::from_utf16_lossy(command_line));
+	assert_eq!(lossy, "app.exe \"a b\" a\u{FFFD}b \u{1F600}\\");
+	assert_eq!(format!("{}", builder), lossy);
+	#[cfg(all(windows, feature = "std"))]
+	{
+		use std::os::windows::ffi::OsStrExt;
+		let os_string = builder.build_os_string();
+		assert_eq!(os_string.encode_wide().collect::<Vec<u16>>(), command_line);
+	}
+}
+
+#[test]
+fn reuse() {
+	let build = |builder: &mut CommandLineBuilder, n: usize| {
+		for i in 0..n {
+			builder.arg(units(&format!("arg {}", i))).unwrap();
+		}
+	};
+	let fresh = |n: usize| {
+		let mut builder = CommandLineBuilder::new(units("app.exe")).unwrap();
+		build(&mut builder, n);
+		builder
+	};
+
+	// Each build reserves exactly what it needs.
+	let builder = fresh(3);
+	let mut buffer = Vec::new();
+	builder.build_into(&mut buffer);
+	assert_eq!(buffer.capacity(), builder.len() + 1);
+	assert_eq!(buffer, builder.build_for_create_process());
+	assert_eq!(
+		builder.build_for_create_process().capacity(),
+		builder.len() + 1
+	);
+	assert_eq!(&*builder.build_boxed(), &buffer[..]);
+
+	// Resetting keeps the builder's buffer and starts again from the program
+	// name. Building into a buffer with room doesn't reallocate.
+	let mut builder = fresh(0);
+	let mut buffer = Vec::with_capacity(128);
+	let buffer_ptr = buffer.as_ptr();
+	build(&mut builder, 3);
+	let (capacity, builder_ptr) = (builder.units.capacity(), builder.as_slice().as_ptr());
+	for n in [3, 0, 2, 3, 1] {
+		builder.reset();
+		assert_eq!(builder, fresh(0));
+		build(&mut builder, n);
+		assert_eq!(builder, fresh(n));
+		builder.build_into(&mut buffer);
+		assert_eq!(buffer, fresh(n).build_for_create_process());
+		assert_eq!(builder.units.capacity(), capacity);
+		assert_eq!(builder.as_slice().as_ptr(), builder_ptr);
+		assert_eq!(buffer.as_ptr(), buffer_ptr);
+	}
+}
+
+#[test]
+fn unrepresentable() {
+	// A quote in the program name would end it early, moving the boundary
+	// between the program name and the first argument.
+	let error = CommandLineBuilder::new(units(r#"a" b"#)).unwrap_err();
+	assert_eq!((error.index(), error.position()), (0, 1));
+	assert_eq!(
+		error.to_string(),
+		"the program name contains a quote at position 1"
+	);
+	assert!(CommandLineBuilder::new([0x61, 0]).is_err());
+
+	// A null would end the command line. Nothing is added.
+	let mut builder = CommandLineBuilder::new(units("app.exe")).unwrap();
+	builder.arg(units("a")).unwrap();
+	let before = builder.clone();
+	let error = builder.arg([0x62, 0, 0x63]).unwrap_err();
+	assert_eq!((error.index(), error.position(), error.unit()), (2, 1, 0));
+	assert_eq!(builder, before);
+
+	// Nor are any of the other arguments in the same call.
+	let error = builder
+		.args(&[units("b"), units("c"), [0x64, 0].to_vec()])
+		.unwrap_err();
+	assert_eq!((error.index(), error.position()), (4, 1));
+	assert_eq!(builder, before);
+
+	// Quotes are fine in an argument and count towards the index.
+	builder.arg(units(r#"say "hi""#)).unwrap();
+	assert_eq!(builder.arg([0]).unwrap_err().index(), 3);
+	builder.reset();
+	assert_eq!(builder.arg([0]).unwrap_err().index(), 1);
+}
+
+#[test]
+fn verbatim() {
+	use crate::{Diagnostic, DiagnosticKind, Error};
+
+	let mut builder = CommandLineBuilder::new(units("cmd.exe")).unwrap();
+	builder.arg(units("/c")).unwrap();
+
+	// A nested command line with balanced quotes is added as it is.
+	let nested = r#""app.exe "a b" "c\"d" e""#;
+	assert_eq!(
+		builder.push_verbatim_checked(&units(nested)),
+		Ok(Vec::new())
+	);
+	assert_eq!(lossy(&builder), ["cmd.exe /c ", nested].concat());
+	let before = builder.clone();
+
+	// A quote that's never closed isn't added.
+	let error = builder
+		.push_verbatim_checked(&units(r#"x "unbalanced"#))
+		.unwrap_err();
+	assert_eq!(error.kind(), DiagnosticKind::UnterminatedQuote);
+	assert_eq!(error.position(), 2);
+	assert_eq!(
+		error.to_string(),
+		"the verbatim text has a quote that's never closed at position 2"
+	);
+	assert_eq!(builder, before);
+
+	// Nor is a null.
+	let error = Error::from(builder.push_verbatim_checked(&[0x61, 0]).unwrap_err());
+	assert_eq!(error.offset(), Some(1));
+	assert_eq!(error.unit(), Some(0));
+	assert_eq!(builder, before);
+
+	// The escaped quote leaves the quotes open. If this were added, the next
+	// quoted argument would close them and the two would become one.
+	let error = builder
+		.push_verbatim_checked(&units(r#""C:\My Files\""#))
+		.unwrap_err();
+	assert_eq!(error.kind(), DiagnosticKind::UnterminatedQuote);
+	assert_eq!(error.position(), 0);
+	assert_eq!(builder, before);
+
+	// A later quote closes them again, so this is added with a warning about
+	// the backslash. The span is in the whole command line.
+	let start = builder.len() + 1;
+	let warnings = builder
+		.push_verbatim_checked(&units(r#""C:\My Files\" b""#))
+		.unwrap();
+	assert_eq!(
+		warnings,
+		[Diagnostic {
+			kind: DiagnosticKind::TrailingBackslashesBeforeEnd,
+			span: start + 12..start + 13,
+		}]
+	);
+
+	// Trailing backslashes outside quotes are kept apart from the next
+	// argument's quote by a space.
+	let mut builder = CommandLineBuilder::new(units("EXE")).unwrap();
+	assert_eq!(
+		builder.push_verbatim_checked(&units(r"C:\dir\")),
+		Ok(Vec::new())
+	);
+	builder.arg(units("a b")).unwrap();
+	assert_eq!(lossy(&builder), r#"EXE C:\dir\ "a b""#);
+}
+
+#[cfg(all(windows, feature = "std"))]
+#[test]
+fn paths() {
+	use crate::PathOptions;
+	use std::path::Path;
+
+	// Add a path and check it's parsed back as `expected`.
+	fn chk(path: &str, options: PathOptions, quoted: &str, expected: &str) {
+		let mut builder = CommandLineBuilder::new(units("EXE")).unwrap();
+		builder.arg_path_with(Path::new(path), options).unwrap();
+		assert_eq!(lossy(&builder), ["EXE ", quoted].concat());
+		let arg = &builder.as_slice()[4..];
+		let (parsed, used) = crate::unquote_one(arg, false);
+		assert_eq!(
+			String::from_utf16_lossy(&parsed.collect::<Vec<u16>>()),
+			expected
+		);
+		assert_eq!(used, arg.len());
+	}
+	let keep = PathOptions::default();
+	let strip = PathOptions {
+		strip_verbatim_prefix: true,
+		..PathOptions::default()
+	};
+	let trailing = PathOptions {
+		ensure_trailing_separator: true,
+		..PathOptions::default()
+	};
+
+	// The backslashes at the start of a UNC path are left alone.
+	let unc = r"\\server\share\file name.txt";
+	chk(unc, keep, &["\"", unc, "\""].concat(), unc);
+	chk(unc, strip, &["\"", unc, "\""].concat(), unc);
+
+	let verbatim = r"\\?\C:\very long\file.txt";
+	chk(verbatim, keep, &["\"", verbatim, "\""].concat(), verbatim);
+	chk(
+		verbatim,
+		strip,
+		r#""C:\very long\file.txt""#,
+		r"C:\very long\file.txt",
+	);
+	chk(
+		r"\\?\unc\server\share\a b",
+		strip,
+		r#""\\server\share\a b""#,
+		r"\\server\share\a b",
+	);
+	// There's no other form of a volume path.
+	chk(
+		r"\\?\Volume{1}\a",
+		strip,
+		r"\\?\Volume{1}\a",
+		r"\\?\Volume{1}\a",
+	);
+
+	// Trailing backslashes are doubled before a closing quote.
+	chk(r"C:\My Files\", keep, r#""C:\My Files\\""#, r"C:\My Files\");
+	chk(
+		r"C:\My Files",
+		trailing,
+		r#""C:\My Files\\""#,
+		r"C:\My Files\",
+	);
+	chk(r"C:\dir\", trailing, r"C:\dir\", r"C:\dir\");
+	chk(r"C:\dir", trailing, r"C:\dir\", r"C:\dir\");
+	chk("dir/", trailing, "dir/", "dir/");
+
+	// Forward slashes are passed through unchanged.
+	chk(
+		"dir/sub dir/file",
+		keep,
+		r#""dir/sub dir/file""#,
+		"dir/sub dir/file",
+	);
+	chk("./dir/file", keep, "./dir/file", "./dir/file");
+}
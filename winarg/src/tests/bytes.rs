@@ -0,0 +1,140 @@
+// Tests for parsing UTF-16 bytes.
+
+use super::{format, units, String, ToString, Vec};
+use crate::{
+	parse_utf16_bytes, parse_utf16le_bytes, CommandLine, DecodedCommandLine, Endianness, Error,
+};
+
+fn le_bytes(s: &str) -> Vec<u8> {
+	s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn args(cmdline: &DecodedCommandLine<'_>) -> Vec<String> {
+	cmdline
+		.args()
+		.map(|arg| String::from_utf16(&arg).unwrap())
+		.collect()
+}
+
+// Copy `bytes` to a buffer where they start at an odd or even address, and
+// call `f` with them.
+fn with_alignment<R>(bytes: &[u8], odd: bool, f: impl FnOnce(&[u8]) -> R) -> R {
+	let mut buffer = Vec::with_capacity(bytes.len() + 1);
+	let start = usize::from((buffer.as_ptr() as usize % 2 == 1) != odd);
+	buffer.resize(start, 0);
+	buffer.extend_from_slice(bytes);
+	f(&buffer[start..])
+}
+
+#[test]
+fn alignment() {
+	let cmdline = r#""C:\Program Files\app.exe" "a b" c\"d "" 🦀"#;
+	let expected = [r"C:\Program Files\app.exe", "a b", r#"c"d"#, "", "🦀"];
+	let bytes = le_bytes(cmdline);
+	for &odd in &[false, true] {
+		with_alignment(&bytes, odd, |bytes| {
+			assert_eq!(bytes.as_ptr() as usize % 2 == 1, odd);
+			let decoded = parse_utf16le_bytes(bytes).unwrap();
+			assert_eq!(args(&decoded), expected);
+			// Aligned bytes are used in place.
+			let borrowed = bytes
+				.as_ptr_range()
+				.contains(&decoded.as_slice().as_ptr().cast());
+			assert_eq!(borrowed, !odd && cfg!(target_endian = "little"));
+		});
+	}
+}
+
+#[test]
+fn byte_order_mark() {
+	let cmdline = r#"app.exe "a b""#;
+	let mut with_bom = [0xFF, 0xFE].to_vec();
+	with_bom.extend(le_bytes(cmdline));
+	for &odd in &[false, true] {
+		for bytes in [&le_bytes(cmdline), &with_bom] {
+			with_alignment(bytes, odd, |bytes| {
+				let decoded = parse_utf16le_bytes(bytes).unwrap();
+				assert_eq!(String::from_utf16(decoded.as_slice()).unwrap(), cmdline);
+				assert_eq!(args(&decoded), ["app.exe", "a b"]);
+			});
+		}
+	}
+
+	// Only the mark at the start for the given byte order is skipped.
+	let bytes = [0xFF, 0xFE, 0xFF, 0xFE, b'a', 0];
+	let decoded = parse_utf16le_bytes(&bytes).unwrap();
+	assert_eq!(decoded.as_slice(), [0xFEFF, u16::from(b'a')]);
+	let decoded = parse_utf16_bytes(&bytes, Endianness::Big).unwrap();
+	assert_eq!(decoded.as_slice(), [0xFFFE, 0xFFFE, 0x6100]);
+}
+
+#[test]
+fn big_endian() {
+	let cmdline = r#""a b"c d\"e"#;
+	let bytes: Vec<u8> = cmdline.encode_utf16().flat_map(u16::to_be_bytes).collect();
+	let mut with_bom = [0xFE, 0xFF].to_vec();
+	with_bom.extend_from_slice(&bytes);
+	for &odd in &[false, true] {
+		for bytes in [&bytes, &with_bom] {
+			with_alignment(bytes, odd, |bytes| {
+				let decoded = parse_utf16_bytes(bytes, Endianness::Big).unwrap();
+				assert_eq!(args(&decoded), ["a bc", r#"d"e"#]);
+			});
+		}
+	}
+}
+
+#[test]
+fn odd_length() {
+	let bytes = le_bytes("app.exe a");
+	let error = parse_utf16le_bytes(&bytes[..bytes.len() - 1]).unwrap_err();
+	assert_eq!(error.len(), 17);
+	assert_eq!(error.to_string(), "odd number of bytes (17)");
+	// The byte order mark is counted.
+	let error = parse_utf16le_bytes(&[0xFF, 0xFE, b'a']).unwrap_err();
+	assert_eq!(error.len(), 3);
+	let error = Error::from(error);
+	assert_eq!(error.to_string(), "odd number of bytes (3)");
+	assert_eq!(
+		(error.index(), error.offset(), error.unit()),
+		(None, None, None)
+	);
+
+	// Empty is fine, with or without a byte order mark.
+	for bytes in [&[][..], &[0xFF, 0xFE]] {
+		let decoded = parse_utf16le_bytes(bytes).unwrap();
+		assert!(decoded.as_slice().is_empty());
+		assert_eq!(decoded.args().count(), 0);
+	}
+}
+
+#[test]
+fn null() {
+	// The command line ends at a null, as it does everywhere else.
+	let bytes = le_bytes("app.exe a\0b");
+	for &odd in &[false, true] {
+		with_alignment(&bytes, odd, |bytes| {
+			let decoded = parse_utf16le_bytes(bytes).unwrap();
+			assert_eq!(args(&decoded), ["app.exe", "a"]);
+			assert_eq!(decoded.as_slice(), units("app.exe a"));
+			assert_eq!(format!("{:?}", decoded), r#""app.exe a""#);
+		});
+	}
+	let decoded = parse_utf16_bytes(b"\0a\0\0\0b", Endianness::Big).unwrap();
+	assert_eq!(decoded.as_slice(), units("a"));
+}
+
+#[test]
+fn into_command_line() {
+	let bytes = le_bytes("app.exe \"a\tb\"");
+	let decoded = parse_utf16le_bytes(&bytes).unwrap();
+	assert_eq!(format!("{:?}", decoded), r#""app.exe \"a\tb\"""#);
+	let cmdline = CommandLine::from(decoded.clone());
+	assert_eq!(&cmdline[..], decoded.as_slice());
+	assert_eq!(format!("{:?}", cmdline), format!("{:?}", decoded));
+
+	// Anything after a null is dropped.
+	let bytes = le_bytes("app.exe a\0b");
+	let cmdline = CommandLine::from(parse_utf16le_bytes(&bytes).unwrap());
+	assert_eq!(cmdline.as_slice_with_nul(), units("app.exe a\0"));
+}
@@ -0,0 +1,132 @@
+// Tests for the `cached` module. Each test uses its own `ArgCache` so the
+// command line it reads is the one the test sets.
+
+use super::{leak, with_command_line, String, Vec};
+use crate::{
+	args_native,
+	cached::{self, ArgCache},
+	has_flag, testing, Argument,
+};
+use std::{sync::Barrier, thread};
+
+fn strings(args: impl Iterator<Item = Argument>) -> Vec<(String, usize)> {
+	args.map(|arg| (arg.scalars().collect(), arg.raw_arg().len()))
+		.collect()
+}
+
+const CMDLINES: &[&str] = &[
+	"",
+	" ",
+	" a",
+	"app.exe",
+	r#""C:\Program Files\app.exe"  --verbose "a b"	c\"d "#,
+	r#"app.exe "--verbose" "unclosed"#,
+	"app.exe -- --verbose",
+];
+
+#[test]
+fn same_as_args_native() {
+	for &cmdline in CMDLINES {
+		with_command_line(cmdline, || {
+			let cache = ArgCache::<8>::new();
+			assert_eq!(
+				strings(cache.args()),
+				strings(args_native()),
+				"{:?}",
+				cmdline
+			);
+			assert!(cache.is_complete());
+			assert_eq!(
+				cache.has_flag(&["--verbose"]),
+				has_flag(&["--verbose"]),
+				"{:?}",
+				cmdline
+			);
+		});
+	}
+}
+
+#[test]
+fn over_capacity() {
+	for &cmdline in CMDLINES {
+		with_command_line(cmdline, || {
+			let len = args_native().count();
+			let fits = ArgCache::<3>::new();
+			assert_eq!(fits.is_complete(), len <= 3, "{:?}", cmdline);
+			let none = ArgCache::<0>::new();
+			assert_eq!(none.is_complete(), len == 0, "{:?}", cmdline);
+			// The arguments are parsed again, with the same results.
+			for (args, flag) in [
+				(fits.args(), fits.has_flag(&["--verbose"])),
+				(none.args(), none.has_flag(&["--verbose"])),
+			] {
+				assert_eq!(strings(args), strings(args_native()), "{:?}", cmdline);
+				assert_eq!(flag, has_flag(&["--verbose"]), "{:?}", cmdline);
+			}
+		});
+	}
+
+	// Exactly full.
+	with_command_line("app.exe a b", || {
+		let cache = ArgCache::<3>::new();
+		assert!(cache.is_complete());
+		assert_eq!(cache.args().count(), 3);
+	});
+}
+
+#[test]
+fn double_dash() {
+	// As with the crate root functions, flags after `--` aren't found.
+	with_command_line("app.exe -v -- --verbose", || {
+		let cache = ArgCache::<4>::new();
+		assert!(!cache.has_flag(&["--verbose"]));
+		assert_eq!(cache.find_flag(&["-v", "--verbose"]).unwrap(), "-v");
+	});
+}
+
+#[test]
+fn read_once() {
+	let cache = ArgCache::<4>::new();
+	with_command_line("app.exe --verbose", || {
+		assert!(cache.has_flag(&["--verbose"]))
+	});
+	// The command line isn't read again.
+	with_command_line("app.exe", || {
+		assert!(cache.has_flag(&["--verbose"]));
+		let flag = cache.find_flag(&["-v", "--verbose"]).unwrap();
+		assert_eq!(flag, "--verbose");
+	});
+}
+
+#[test]
+fn concurrent_first_use() {
+	const THREADS: usize = 8;
+	let cmdline: Vec<u16> = r#"app.exe a "b c" --verbose"#.encode_utf16().collect();
+	let cmdline = leak(&cmdline);
+	let expected = ["app.exe", "a", "b c", "--verbose"];
+	let cache = ArgCache::<4>::new();
+	let barrier = Barrier::new(THREADS);
+	thread::scope(|s| {
+		for _ in 0..THREADS {
+			s.spawn(|| {
+				let _guard = testing::set_command_line(cmdline);
+				barrier.wait();
+				let args: Vec<String> = cache.args().map(|arg| arg.scalars().collect()).collect();
+				assert_eq!(args, expected);
+				assert!(cache.has_flag(&["--verbose"]));
+			});
+		}
+	});
+}
+
+// The only test that uses the module's own cache.
+#[test]
+fn module_functions() {
+	with_command_line(r#"app.exe "a b" -v"#, || {
+		let args: Vec<String> = cached::args().map(|arg| arg.scalars().collect()).collect();
+		assert_eq!(args, ["app.exe", "a b", "-v"]);
+		assert!(cached::has_flag(&["-v", "--verbose"]));
+		assert!(!cached::has_flag(&["app.exe", "--verbose"]));
+		assert_eq!(cached::find_flag(&["a b"]).unwrap(), "a b");
+	});
+}
@@ -0,0 +1,104 @@
+// Tests for `Argument::classify`.
+
+use super::{with_command_line, Vec};
+use crate::{ArgClass, Argument, ClassifyStyle};
+
+const DASH: ClassifyStyle = ClassifyStyle {
+	dash: true,
+	slash: false,
+};
+const SLASH: ClassifyStyle = ClassifyStyle {
+	dash: false,
+	slash: true,
+};
+const SLASH_OR_DASH: ClassifyStyle = ClassifyStyle {
+	dash: true,
+	slash: true,
+};
+
+fn classify(cmdline: &str, style: ClassifyStyle) -> Vec<ArgClass> {
+	let args: Vec<Argument> = with_command_line(cmdline, || crate::args_native().skip(1).collect());
+	args.iter().map(|arg| arg.classify(style)).collect()
+}
+
+const CMDLINE: &str = r#"app.exe - -- -x --x=y /x:y /usr/bin/x """#;
+
+#[test]
+fn dash() {
+	use ArgClass::*;
+	assert_eq!(
+		classify(CMDLINE, DASH),
+		[
+			Positional,
+			DoubleDash,
+			Flag,
+			FlagWithValue,
+			Positional,
+			Positional,
+			Positional
+		]
+	);
+}
+
+#[test]
+fn slash() {
+	use ArgClass::*;
+	assert_eq!(
+		classify(CMDLINE, SLASH),
+		[
+			Positional,
+			Positional,
+			Positional,
+			Positional,
+			FlagWithValue,
+			Flag,
+			Positional
+		]
+	);
+	assert_eq!(classify("app.exe / //x", SLASH), [Positional, Flag]);
+}
+
+#[test]
+fn slash_or_dash() {
+	use ArgClass::*;
+	assert_eq!(
+		classify(CMDLINE, SLASH_OR_DASH),
+		[
+			Positional,
+			DoubleDash,
+			Flag,
+			FlagWithValue,
+			FlagWithValue,
+			Flag,
+			Positional
+		]
+	);
+}
+
+#[test]
+fn values() {
+	use ArgClass::*;
+	let style = SLASH_OR_DASH;
+	// The separator must follow at least one character of the name.
+	assert_eq!(
+		classify("app.exe -x: -x= --=y -:y /=y", style),
+		[FlagWithValue, FlagWithValue, Flag, Flag, Flag]
+	);
+	// Quotes have already been removed.
+	assert_eq!(
+		classify(r#"app.exe "--" "--out=a b" -"o":x"#, style),
+		[DoubleDash, FlagWithValue, FlagWithValue]
+	);
+	assert_eq!(classify("app.exe --- -1 ---x", style), [Flag, Flag, Flag]);
+}
+
+#[test]
+fn neither() {
+	let style = ClassifyStyle {
+		dash: false,
+		slash: false,
+	};
+	assert!(classify(CMDLINE, style)
+		.iter()
+		.all(|&class| class == ArgClass::Positional));
+}
@@ -0,0 +1,89 @@
+// Tests for quoting command lines for `cmd /c`.
+
+use super::{units, String, Vec};
+use crate::{cow_args_of, nest_for_cmd_c};
+
+// What `cmd` runs for `nested`, following its rules for the text after `/c`
+// and for `^`. Panics if `cmd` would treat anything as special.
+fn through_cmd(nested: &[u16]) -> Vec<u16> {
+	let nested = String::from_utf16(nested).unwrap();
+	// More than two quotes, so the first and last are always removed.
+	assert!(nested.matches('"').count() > 2, "{:?}", nested);
+	let inner = nested.strip_prefix('"').unwrap().strip_suffix('"').unwrap();
+	let mut command = String::new();
+	let mut in_quotes = false;
+	let mut chars = inner.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'%' => panic!("{:?} could expand a variable", nested),
+			'^' if !in_quotes => command.push(chars.next().unwrap()),
+			'"' => {
+				in_quotes = !in_quotes;
+				command.push(c);
+			}
+			'(' | ')' | '!' | '<' | '>' | '&' | '|' if !in_quotes => {
+				panic!("{:?} has a special character", nested)
+			}
+			_ => command.push(c),
+		}
+	}
+	assert!(!in_quotes, "{:?}", nested);
+	units(&command)
+}
+
+#[test]
+fn nested() {
+	let nest = |args: &[&str]| {
+		let args: Vec<Vec<u16>> = args.iter().map(|arg| units(arg)).collect();
+		String::from_utf16(&nest_for_cmd_c(&args)).unwrap()
+	};
+	assert_eq!(nest(&["app.exe"]), r#"""app.exe"""#);
+	assert_eq!(nest(&["app.exe", "a"]), r#"""app.exe" a""#);
+	assert_eq!(
+		nest(&[r"C:\Program Files\app.exe", "a b", ""]),
+		r#"""C:\Program Files\app.exe" ^"a b^" ^"^"""#
+	);
+	assert_eq!(
+		nest(&["app.exe", r#"say "hi""#, r"a\", r"b\ c\"]),
+		r#"""app.exe" ^"say \^"hi\^"^" a\ ^"b\ c\\^"""#
+	);
+	assert_eq!(
+		nest(&["app.exe", "a&b|c", "(x)", "<in>", "100%", "!x!", "^"]),
+		r#"""app.exe" a^&b^|c ^(x^) ^<in^> 100^% ^!x^! ^^""#
+	);
+	// Special characters in the program name are in quotes.
+	assert_eq!(nest(&["C:\\a&b (x)\\app.exe"]), r#"""C:\a&b (x)\app.exe"""#);
+	// Without a program name there's nothing for `cmd` to run.
+	assert_eq!(nest(&[]), r#""""#);
+}
+
+#[test]
+fn round_trip() {
+	let cases: &[&[&str]] = &[
+		&["app.exe"],
+		&[r"C:\Program Files (x86)\app & co\app.exe", "a"],
+		&["app.exe", "", "a b", "\t"],
+		&[
+			"app.exe", "a&b", "a & b", "a|b", "a>b", "a<b", "(a)", "^", "^^", "!a!",
+		],
+		&["app.exe", "100%", "%PATH%", "\"%PATH%\"", "a%b%c"],
+		&[
+			"app.exe",
+			r#"say "hi""#,
+			r#"""#,
+			r#""""#,
+			r#"\""#,
+			r#"\\"&\\""#,
+		],
+		&["app.exe", r"a\", r"a b\", r"\\server\share\", r"C:\dir\ "],
+		&["app.exe", "\"&\" & \"|\"", "a \"b & c\" d", "^\"^"],
+		&["app.exe", "é", "日本 語", "🦀&🦀"],
+	];
+	for args in cases {
+		let args: Vec<Vec<u16>> = args.iter().map(|arg| units(arg)).collect();
+		let nested = nest_for_cmd_c(&args);
+		let command = through_cmd(&nested);
+		let parsed: Vec<Vec<u16>> = cow_args_of(&command).map(|arg| arg.into_owned()).collect();
+		assert_eq!(parsed, args, "{:?}", String::from_utf16_lossy(&nested));
+	}
+}
@@ -0,0 +1,108 @@
+// Tests for the owned `CommandLine`.
+
+use super::{format, units, vec, with_command_line, with_command_line_wide, String, Vec};
+use crate::{arg_spans_of, CommandLine, DiagnosticKind, Parser, Token};
+
+fn args(cmdline: &CommandLine) -> Vec<String> {
+	cmdline
+		.args()
+		.map(|arg| String::from_utf16(&arg).unwrap())
+		.collect()
+}
+
+#[test]
+fn from_wide() {
+	let cmdline = CommandLine::from_wide(units(r#"app.exe "a b" c"#));
+	assert_eq!(&cmdline[..], &units(r#"app.exe "a b" c"#)[..]);
+	assert_eq!(cmdline.as_slice_with_nul().last(), Some(&0));
+	assert_eq!(args(&cmdline), ["app.exe", "a b", "c"]);
+
+	// A null terminator isn't doubled, and anything after the first null is
+	// removed.
+	let cmdline = CommandLine::from_wide(units("app.exe a\0"));
+	assert_eq!(cmdline.as_slice_with_nul(), &units("app.exe a\0")[..]);
+	let cmdline = CommandLine::from_wide(units("app.exe a\0b\0c"));
+	assert_eq!(cmdline.as_slice_with_nul(), &units("app.exe a\0")[..]);
+	assert_eq!(args(&cmdline), ["app.exe", "a"]);
+
+	// Isolated surrogates are kept.
+	let cmdline = CommandLine::from_wide(vec![0x61, 0x20, 0xD800]);
+	assert_eq!(&cmdline[..], [0x61, 0x20, 0xD800]);
+	assert_eq!(format!("{:?}", cmdline), "\"a \u{FFFD}\"");
+
+	for empty in [vec![], vec![0], vec![0, 0x61]] {
+		let cmdline = CommandLine::from_wide(empty);
+		assert!(cmdline.is_empty());
+		assert_eq!(cmdline.as_slice_with_nul(), [0]);
+		assert_eq!(cmdline.args().count(), 0);
+		assert_eq!(cmdline.into_vec_with_nul(), [0]);
+	}
+}
+
+#[test]
+fn from_str() {
+	let cmdline = CommandLine::from_str(r#""C:\Program Files\app.exe" a\"b "" 🦀"#);
+	assert_eq!(
+		args(&cmdline),
+		[r"C:\Program Files\app.exe", r#"a"b"#, "", "🦀"]
+	);
+	assert_eq!(
+		cmdline,
+		CommandLine::from_wide(units(&String::from_utf16(&cmdline).unwrap()))
+	);
+	assert_eq!(CommandLine::from_str("a\0b"), CommandLine::from_str("a"));
+	assert_eq!(
+		format!("{:?}", CommandLine::from_str("a \"b\"\t")),
+		r#""a \"b\"\t""#
+	);
+}
+
+#[test]
+fn from_current_process() {
+	let cmdline = with_command_line(r#"app.exe "a b" c"#, CommandLine::from_current_process);
+	assert_eq!(args(&cmdline), ["app.exe", "a b", "c"]);
+	// It's a copy so it outlives the override.
+	assert_eq!(cmdline, CommandLine::from_str(r#"app.exe "a b" c"#));
+
+	let wide = [0x45, 0x20, 0xDC00];
+	let cmdline = with_command_line_wide(&wide, CommandLine::from_current_process);
+	assert_eq!(&cmdline[..], wide);
+}
+
+#[test]
+fn tokens() {
+	let cases = [
+		"",
+		"   ",
+		"app.exe",
+		r#"app.exe "a b" c\"d "" e  "#,
+		r#""C:\Program Files\app.exe" a\\\"b "c"#,
+		"a\tb\t",
+	];
+	for case in cases {
+		let cmdline = CommandLine::from_str(case);
+		let expected: Vec<Token> = with_command_line(case, || Parser().collect());
+		assert_eq!(cmdline.tokens().collect::<Vec<_>>(), expected, "{:?}", case);
+		let mut tokens = cmdline.tokens();
+		while tokens.next().is_some() {}
+		assert_eq!(tokens.next(), None);
+	}
+}
+
+#[test]
+fn validate() {
+	let cmdline = CommandLine::from_str(r#"app.exe "a b"#);
+	let kinds: Vec<DiagnosticKind> = cmdline.validate().map(|d| d.kind).collect();
+	assert_eq!(kinds, [DiagnosticKind::UnterminatedQuote]);
+	assert_eq!(CommandLine::from_str("app.exe a b").validate().count(), 0);
+}
+
+#[test]
+fn deref() {
+	let cmdline = CommandLine::from_str(r#"app.exe "a b" c"#);
+	assert_eq!(cmdline.len(), 15);
+	assert_eq!(arg_spans_of(&cmdline).count(), 3);
+	let clone = cmdline.clone();
+	drop(cmdline);
+	assert_eq!(args(&clone), ["app.exe", "a b", "c"]);
+}
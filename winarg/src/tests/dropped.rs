@@ -0,0 +1,63 @@
+// Tests for `dropped_files` and `dropped_files_checked`.
+
+use super::{with_command_line, with_command_line_wide, Vec};
+use crate::{dropped_files, dropped_files_checked};
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::path::PathBuf;
+
+#[test]
+fn dropped_files_lossless() {
+	let cmdline = r#"C:\tools\app.exe "C:\My Files\a b.txt" \\server\share\c.txt "\\?\C:\long path\d" --flag"#;
+	with_command_line(cmdline, || {
+		assert_eq!(
+			dropped_files().collect::<Vec<_>>(),
+			[
+				PathBuf::from(r"C:\My Files\a b.txt"),
+				PathBuf::from(r"\\server\share\c.txt"),
+				PathBuf::from(r"\\?\C:\long path\d"),
+				PathBuf::from("--flag"),
+			]
+		);
+	});
+	// Isolated surrogates are kept.
+	let cmdline = [
+		b'E' as u16,
+		b' ' as u16,
+		b'C' as u16,
+		b':' as u16,
+		b'\\' as u16,
+		0xD800,
+	];
+	with_command_line_wide(&cmdline, || {
+		let paths: Vec<_> = dropped_files().collect();
+		assert_eq!(paths, [PathBuf::from(OsString::from_wide(&cmdline[2..]))]);
+	});
+	with_command_line("app.exe", || assert_eq!(dropped_files().count(), 0));
+}
+
+#[test]
+fn dropped_files_checked_paths() {
+	let cmdline = r#"app.exe "C:\My Files\a b.txt" --flag \\server\share\c.txt d:/e \\.\pipe\f \\?\UNC\server\share\g relative\h C:i \j //k "#;
+	with_command_line(cmdline, || {
+		let (paths, others): (Vec<_>, Vec<_>) = dropped_files_checked().partition(Result::is_ok);
+		let paths: Vec<PathBuf> = paths.into_iter().map(Result::unwrap).collect();
+		assert_eq!(
+			paths,
+			[
+				PathBuf::from(r"C:\My Files\a b.txt"),
+				PathBuf::from(r"\\server\share\c.txt"),
+				PathBuf::from("d:/e"),
+				PathBuf::from(r"\\.\pipe\f"),
+				PathBuf::from(r"\\?\UNC\server\share\g"),
+				PathBuf::from("//k"),
+			]
+		);
+		let others: Vec<_> = others.into_iter().map(Result::unwrap_err).collect();
+		assert_eq!(others, ["--flag", r"relative\h", "C:i", r"\j"]);
+	});
+	// Drive letters are ASCII.
+	with_command_line("app.exe é:\\a 1:\\b", || {
+		assert!(dropped_files_checked().all(|arg| arg.is_err()));
+	});
+}
@@ -0,0 +1,112 @@
+// Tests that arguments compare equal to each kind of string, either way round.
+
+#[cfg(feature = "alloc")]
+use super::String;
+use super::{args, units, with_command_line};
+
+#[test]
+fn str() {
+	let args = args(r#"app.exe --help "" ünï"#);
+	assert_eq!(args[1], "--help");
+	assert_eq!("--help", args[1]);
+	assert_eq!(args[2], "");
+	assert_eq!("", args[2]);
+	assert_eq!(args[3], "ünï");
+	assert_eq!("ünï", args[3]);
+	assert_ne!(args[1], "--hel");
+	assert_ne!("--help ", args[1]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn string() {
+	let args = args(r#"app.exe --help "" ünï"#);
+	assert_eq!(args[1], String::from("--help"));
+	assert_eq!(String::from("--help"), args[1]);
+	assert_eq!(args[2], String::new());
+	assert_eq!(String::new(), args[2]);
+	assert_eq!(args[3], String::from("ünï"));
+	assert_eq!(String::from("ünï"), args[3]);
+	assert_ne!(args[1], String::from("--HELP"));
+	assert_ne!(String::new(), args[1]);
+}
+
+#[test]
+fn slices() {
+	let args = args(r#"app.exe --help """#);
+	let help = units("--help");
+	assert_eq!(args[1], &help[..]);
+	assert_eq!(&help[..], args[1]);
+	assert_eq!(args[1], &b"--help"[..]);
+	assert_eq!(&b"--help"[..], args[1]);
+	assert_eq!(args[2], &[][..] as &[u16]);
+	assert_eq!(&[][..] as &[u8], args[2]);
+	assert_ne!(args[1], &help[1..]);
+	assert_ne!(&b"--hel\xF0"[..], args[1]);
+}
+
+#[test]
+fn arrays() {
+	let args = args(r#"app.exe --help """#);
+	assert_eq!(args[1], b"--help");
+	assert_eq!(b"--help", args[1]);
+	assert_eq!(args[2], b"");
+	assert_eq!(b"", args[2]);
+	assert_ne!(args[1], b"--hel");
+	assert_ne!(b"--hel\xF0", args[1]);
+}
+
+#[test]
+fn arguments() {
+	let args = args(r#"app.exe a "a" b"#);
+	assert_eq!(args[1], args[2]);
+	assert_eq!(args[2], args[1]);
+	assert_ne!(args[1], args[3]);
+}
+
+#[test]
+fn trimmed() {
+	let args = args(r#"app.exe " a ""#);
+	let a = units("a");
+	assert_eq!(args[1].trim(), "a");
+	assert_eq!("a", args[1].trim());
+	assert_eq!(args[1].trim(), &a[..]);
+	assert_eq!(&a[..], args[1].trim());
+	assert_ne!(" a", args[1].trim());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn trimmed_string() {
+	let args = args(r#"app.exe " a ""#);
+	assert_eq!(args[1].trim(), String::from("a"));
+	assert_eq!(String::from("a"), args[1].trim());
+	assert_ne!(args[1].trim(), String::from(" a"));
+	assert_ne!(String::new(), args[1].trim());
+}
+
+#[test]
+fn flag_value() {
+	with_command_line("app.exe --out=ünï", || {
+		let value = crate::value_of("--out").unwrap();
+		let expected = units("ünï");
+		assert_eq!(value, "ünï");
+		assert_eq!("ünï", value);
+		assert_eq!(value, &expected[..]);
+		assert_eq!(&expected[..], value);
+		assert_ne!("--out=ünï", value);
+		assert_ne!(&expected[1..], value);
+	});
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn flag_value_string() {
+	with_command_line("app.exe --out=ünï", || {
+		let value = crate::value_of("--out").unwrap();
+		assert_eq!(value, String::from("ünï"));
+		assert_eq!(String::from("ünï"), value);
+		assert_ne!(value, String::from("--out=ünï"));
+		assert_ne!(String::new(), value);
+	});
+}
@@ -0,0 +1,90 @@
+// Tests for `winarg::Error`.
+
+use super::{ToString, Vec};
+use crate::{is_representable, Error, InvalidUnicodeError, WhyNot};
+
+fn why_not(args: &[&str]) -> WhyNot {
+	let args: Vec<Vec<u16>> = args.iter().map(|s| s.encode_utf16().collect()).collect();
+	is_representable(args).unwrap_err()
+}
+
+fn invalid_unicode_error(arg_index: Option<usize>, offset: usize) -> InvalidUnicodeError {
+	InvalidUnicodeError {
+		arg_index,
+		offset,
+		unit: 0xD800,
+	}
+}
+
+#[test]
+fn invalid_unicode() {
+	let error = invalid_unicode_error(Some(2), 3);
+	assert_eq!(
+		error.to_string(),
+		"argument 2 contains an unpaired surrogate (0xd800) at offset 3"
+	);
+	let error = Error::from(error);
+	assert_eq!(error.unit(), Some(0xD800));
+	assert_eq!(error.index(), Some(2));
+	assert_eq!(error.offset(), Some(3));
+
+	let error = Error::from(invalid_unicode_error(None, 0));
+	assert_eq!(
+		error.to_string(),
+		"argument contains an unpaired surrogate (0xd800) at offset 0"
+	);
+	assert_eq!(error.index(), None);
+}
+
+#[test]
+fn invalid_unicode_context() {
+	let context = |offset, arg: &[u16]| {
+		invalid_unicode_error(None, offset)
+			.context(arg.iter().copied())
+			.to_string()
+	};
+	let mut arg: Vec<u16> = "a\"b\n".encode_utf16().collect();
+	arg.push(0xD800);
+	assert_eq!(context(4, &arg), r#""a\"b\n\u{d800}""#);
+
+	// Only a few units either side are shown.
+	let mut arg: Vec<u16> = "0123456789".encode_utf16().collect();
+	arg.push(0xD800);
+	arg.extend("0123456789".encode_utf16());
+	assert_eq!(context(10, &arg), r#"..."23456789\u{d800}01234567"..."#);
+
+	// Surrogate pairs at the edges aren't split.
+	let mut arg: Vec<u16> = "🦀0123456".encode_utf16().collect();
+	arg.push(0xDC00);
+	arg.extend("0123456🦀".encode_utf16());
+	assert_eq!(context(9, &arg), r#"..."0123456\u{dc00}0123456"..."#);
+	assert_eq!(context(8, &arg), r#""🦀0123456\u{dc00}0123456"..."#);
+}
+
+#[test]
+fn unrepresentable() {
+	let error = Error::from(why_not(&["EXE", "a", "bc\0"]));
+	assert_eq!(
+		error.to_string(),
+		"argument 2 contains a null at position 2"
+	);
+	assert_eq!(error.index(), Some(2));
+	assert_eq!(error.offset(), Some(2));
+	assert_eq!(error.unit(), Some(0));
+
+	let error = Error::from(why_not(&["a\"b"]));
+	assert_eq!(
+		error.to_string(),
+		"the program name contains a quote at position 1"
+	);
+	assert_eq!(error.index(), Some(0));
+}
+
+#[test]
+fn question_mark() {
+	fn f() -> Result<(), Error> {
+		is_representable([[0_u16]])?;
+		Ok(())
+	}
+	assert!(matches!(f(), Err(Error::Unrepresentable(_))));
+}
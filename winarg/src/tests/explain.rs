@@ -0,0 +1,64 @@
+// Tests for `explain`. The output is meant to be stable so these lock it.
+
+use super::{with_command_line, String};
+
+fn explain(cmdline: &str) -> String {
+	with_command_line(cmdline, || {
+		let mut out = String::new();
+		crate::explain(&mut out).unwrap();
+		out
+	})
+}
+
+#[test]
+fn genius_quotes() {
+	assert_eq!(
+		explain(r#"EXE "" """"#),
+		r#"arg 0 0..3 "EXE" -> "EXE"
+arg 1 4..6 "\"\"" -> ""
+  4 open quote
+  5 close quote
+arg 2 7..10 "\"\"\"" -> "\""
+  7 open quote
+  8 "" -> literal quote
+"#
+	);
+	assert_eq!(
+		explain(r#"EXE "this is """all""" in the same argument""#),
+		r#"arg 0 0..3 "EXE" -> "EXE"
+arg 1 4..44 "\"this is \"\"\"all\"\"\" in the same argument\"" -> "this is \"all\" in the same argument"
+  4 open quote
+  13 "" -> literal quote
+  15 close quote
+  19 open quote
+  20 "" -> literal quote
+  43 close quote
+"#
+	);
+	// Quotes can't be escaped in the program name.
+	assert_eq!(
+		explain(r#""EXE \"for\" check"#),
+		r#"arg 0 0..18 "\"EXE \\\"for\\\" check" -> "EXE \\for\\ check"
+  0 open quote
+  6 close quote
+  11 open quote
+"#
+	);
+}
+
+#[test]
+fn escapes() {
+	assert_eq!(
+		explain(r#"app.exe "a\\"b"" c\\\"d"#),
+		r#"arg 0 0..7 "app.exe" -> "app.exe"
+arg 1 8..16 "\"a\\\\\"b\"\"" -> "a\\b"
+  8 open quote
+  10 backslashes 2 -> 1
+  12 close quote
+  14 open quote
+  15 close quote
+arg 2 17..23 "c\\\\\\\"d" -> "c\\\"d"
+  18 backslashes 3 -> 1 + literal quote
+"#
+	);
+}
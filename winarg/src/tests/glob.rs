@@ -0,0 +1,137 @@
+use super::with_command_line;
+use crate::{glob_one, glob_one_with, ArgsNative, GlobOptions};
+use alloc::{format, string::String, vec::Vec};
+use std::{
+	env, fs,
+	os::windows::fs::OpenOptionsExt,
+	path::PathBuf,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+
+// A directory of files in the temp directory that's removed when dropped.
+struct TempDir {
+	path: PathBuf,
+}
+impl TempDir {
+	fn new() -> Self {
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+		let name = format!(
+			"winarg-glob-{}-{}",
+			std::process::id(),
+			COUNTER.fetch_add(1, Ordering::Relaxed)
+		);
+		let path = env::temp_dir().join(name);
+		fs::create_dir(&path).unwrap();
+		for name in ["a.rs", "b.rs", "ab.rs", "c.txt"] {
+			fs::write(path.join(name), b"").unwrap();
+		}
+		fs::create_dir(path.join("sub")).unwrap();
+		fs::write(path.join("sub").join("d.rs"), b"").unwrap();
+		fs::OpenOptions::new()
+			.write(true)
+			.create(true)
+			.attributes(FILE_ATTRIBUTE_HIDDEN)
+			.open(path.join("hidden.rs"))
+			.unwrap();
+		Self { path }
+	}
+}
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.path);
+	}
+}
+
+fn glob(pattern: &str, options: GlobOptions) -> Vec<String> {
+	let arg = with_command_line(&format!(r#"EXE "{}""#, pattern), || {
+		ArgsNative::from_env().nth(1).unwrap()
+	});
+	let mut paths: Vec<String> = glob_one_with(&arg, options)
+		.map(|path| String::from_utf16(&path).unwrap())
+		.collect();
+	paths.sort();
+	paths
+}
+
+#[test]
+fn wildcards() {
+	let dir = TempDir::new();
+	let dir = dir.path.display();
+	let options = GlobOptions::default();
+	assert_eq!(
+		glob(&format!(r"{}\*.rs", dir), options),
+		[
+			format!(r"{}\a.rs", dir),
+			format!(r"{}\ab.rs", dir),
+			format!(r"{}\b.rs", dir),
+			format!(r"{}\hidden.rs", dir),
+		]
+	);
+	assert_eq!(
+		glob(&format!(r"{}\?.rs", dir), options),
+		[format!(r"{}\a.rs", dir), format!(r"{}\b.rs", dir)]
+	);
+	assert_eq!(
+		glob(&format!(r"{}\sub\*", dir), options),
+		[format!(r"{}\sub\d.rs", dir)]
+	);
+	// Forward slashes are also separators.
+	assert_eq!(
+		glob(&format!("{}/c.*", dir), options),
+		[format!("{}/c.txt", dir)]
+	);
+}
+
+#[test]
+fn no_match() {
+	let dir = TempDir::new();
+	let dir = dir.path.display();
+	let options = GlobOptions::default();
+	assert!(glob(&format!(r"{}\*.none", dir), options).is_empty());
+	assert!(glob(&format!(r"{}\missing\*.rs", dir), options).is_empty());
+	// Only the last component may contain wildcards.
+	assert!(glob(&format!(r"{}\s*\d.rs", dir), options).is_empty());
+}
+
+#[test]
+fn options() {
+	let dir = TempDir::new();
+	let dir = dir.path.display();
+	let options = GlobOptions {
+		hidden: false,
+		..GlobOptions::default()
+	};
+	assert_eq!(
+		glob(&format!(r"{}\*.rs", dir), options),
+		[
+			format!(r"{}\a.rs", dir),
+			format!(r"{}\ab.rs", dir),
+			format!(r"{}\b.rs", dir),
+		]
+	);
+
+	let pattern = format!(r"{}\sub\*", dir);
+	let options = GlobOptions {
+		dot_entries: true,
+		..GlobOptions::default()
+	};
+	assert_eq!(
+		glob(&pattern, options),
+		[
+			format!(r"{}\sub\.", dir),
+			format!(r"{}\sub\..", dir),
+			format!(r"{}\sub\d.rs", dir),
+		]
+	);
+}
+
+#[test]
+fn default_options() {
+	let dir = TempDir::new();
+	let arg = with_command_line(&format!(r#"EXE "{}\*.txt""#, dir.path.display()), || {
+		ArgsNative::from_env().nth(1).unwrap()
+	});
+	assert_eq!(glob_one(&arg).count(), 1);
+}
@@ -0,0 +1,87 @@
+// Tests for the `windows` conversions.
+
+use super::{args, leak, units, vec, with_command_line_wide, String, Vec};
+use crate::{ArgsNative, Argument, CommandLineBuilder, Parser, Token};
+use core::ptr;
+use windows_strings::{HSTRING, PCWSTR};
+
+// Clippy suggests comparing with `&str` instead, which tests something else.
+fn hstring(s: &str) -> HSTRING {
+	HSTRING::from(s)
+}
+
+#[test]
+fn from_windows_pcwstr() {
+	let cmdline = leak(&units("app.exe \"a b\" c\0"));
+	// SAFETY: The command line is null terminated and leaked.
+	let args: Vec<String> = unsafe { ArgsNative::from_windows_pcwstr(PCWSTR(cmdline.as_ptr())) }
+		.map(|arg| arg.scalars().collect())
+		.collect();
+	assert_eq!(args, ["app.exe", "a b", "c"]);
+	// SAFETY: As above.
+	let parser = unsafe { Parser::from_windows_pcwstr(PCWSTR(cmdline.as_ptr())) };
+	assert_eq!(parser.filter(|t| t.is_next_arg()).count(), 2);
+
+	// SAFETY: A null pointer is allowed.
+	let args = unsafe { ArgsNative::from_windows_pcwstr(PCWSTR(ptr::null())) };
+	assert_eq!(args.count(), 0);
+	// SAFETY: As above.
+	let mut parser = unsafe { Parser::from_windows_pcwstr(PCWSTR::null()) };
+	assert_eq!(parser.next(), None::<Token>);
+}
+
+#[test]
+fn eq() {
+	let args = args(r#"EXE a "" "b c" ab "héllo wörld" 日本語 😀"#);
+	assert!(args[1] == hstring("a"));
+	assert!(hstring("a") == args[1]);
+	assert!(args[3] == hstring("b c"));
+	assert!(hstring("b c") == args[3]);
+	// Non-ASCII text, including a surrogate pair.
+	assert!(args[5] == hstring("héllo wörld"));
+	assert!(hstring("héllo wörld") == args[5]);
+	assert!(args[6] == hstring("日本語"));
+	assert!(args[7] == hstring("😀"));
+	assert!(hstring("😀") == args[7]);
+	assert!(args[5] != hstring("hello world"));
+	assert!(args[6] != hstring("日本"));
+	// Empty strings.
+	assert!(args[2] == HSTRING::new());
+	assert!(HSTRING::new() == args[2]);
+	// Different lengths never match.
+	assert!(args[1] != HSTRING::new());
+	assert!(HSTRING::new() != args[1]);
+	assert!(args[4] != hstring("a"));
+	assert!(hstring("a") != args[4]);
+}
+
+#[test]
+fn to_hstring() {
+	let cmdline = [0x45, 0x20, 0x22, 0x61, 0x20, 0xD800, 0x22, 0x20, 0x22, 0x22];
+	let args: Vec<Argument> = with_command_line_wide(&cmdline, || crate::args_native().collect());
+	let arg = args[1].to_hstring();
+	assert_eq!(&arg[..], [0x61, 0x20, 0xD800]);
+	assert!(args[1] == arg);
+	assert_eq!(args[2].to_hstring(), HSTRING::new());
+	assert!(args[2].to_hstring().is_empty());
+
+	let args = self::args("app.exe ünï\\\"code\\\\");
+	assert_eq!(args[1].to_hstring(), "ünï\"code\\\\");
+}
+
+#[test]
+fn build_hstring() {
+	let mut builder = CommandLineBuilder::new(units("app.exe")).unwrap();
+	builder
+		.arg(units("a b"))
+		.unwrap()
+		.arg(units("ü"))
+		.unwrap()
+		.arg(vec![])
+		.unwrap();
+	let built = builder.build_hstring();
+	assert_eq!(built, r#"app.exe "a b" ü """#);
+	let mut with_nul = builder.build_for_create_process();
+	with_nul.pop();
+	assert_eq!(&built[..], &with_nul[..]);
+}
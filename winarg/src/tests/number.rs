@@ -0,0 +1,189 @@
+// Tests for parsing numbers from arguments.
+
+use super::{with_command_line, ToString};
+use crate::{Error, ParseIntError, Radix};
+
+fn arg1<R>(cmdline: &str, f: impl FnOnce(&crate::Argument) -> R) -> R {
+	with_command_line(cmdline, || f(&crate::args_native().nth(1).unwrap()))
+}
+fn u64(arg: &str, radix: Radix) -> Result<u64, ParseIntError> {
+	arg1(&["EXE ", arg].concat(), |arg| arg.to_u64(radix))
+}
+fn i64(arg: &str, radix: Radix) -> Result<i64, ParseIntError> {
+	arg1(&["EXE ", arg].concat(), |arg| arg.to_i64(radix))
+}
+fn auto(arg: &str) -> Result<u64, ParseIntError> {
+	arg1(&["EXE ", arg].concat(), |arg| arg.to_u64_auto())
+}
+fn invalid(offset: usize, unit: u8) -> Result<u64, ParseIntError> {
+	Err(ParseIntError::InvalidDigit {
+		offset,
+		unit: unit.into(),
+	})
+}
+
+#[test]
+fn unsigned() {
+	use Radix::*;
+	assert_eq!(u64("8080", Decimal), Ok(8080));
+	assert_eq!(u64("+0", Decimal), Ok(0));
+	assert_eq!(u64("007", Decimal), Ok(7));
+	assert_eq!(u64("ff", Hexadecimal), Ok(255));
+	assert_eq!(u64("DeadBeef", Hexadecimal), Ok(0xDEAD_BEEF));
+	assert_eq!(u64("777", Octal), Ok(0o777));
+	assert_eq!(u64("101", Binary), Ok(5));
+	// Max values and overflow by one.
+	assert_eq!(u64("18446744073709551615", Decimal), Ok(u64::MAX));
+	let over = Err(ParseIntError::Overflow { offset: 19 });
+	assert_eq!(u64("18446744073709551616", Decimal), over);
+	assert_eq!(u64("ffffffffffffffff", Hexadecimal), Ok(u64::MAX));
+	let over = Err(ParseIntError::Overflow { offset: 16 });
+	assert_eq!(u64("10000000000000000", Hexadecimal), over);
+	// Leading zeros don't overflow.
+	assert_eq!(u64("0000000000000000000001", Decimal), Ok(1));
+	// Trailing garbage and separators.
+	assert_eq!(u64("80a", Decimal), invalid(2, b'a'));
+	assert_eq!(u64("1_000", Decimal), invalid(1, b'_'));
+	assert_eq!(u64("12", Binary), invalid(1, b'2'));
+	assert_eq!(u64("8", Octal), invalid(0, b'8'));
+	assert_eq!(u64("fg", Hexadecimal), invalid(1, b'g'));
+	assert_eq!(u64(r#"" 1""#, Decimal), invalid(0, b' '));
+	assert_eq!(u64("-1", Decimal), invalid(0, b'-'));
+	assert_eq!(u64("-0", Decimal), invalid(0, b'-'));
+	assert_eq!(u64("++1", Decimal), invalid(1, b'+'));
+	// Empty input.
+	assert_eq!(
+		u64(r#""""#, Decimal),
+		Err(ParseIntError::Empty { offset: 0 })
+	);
+	assert_eq!(u64("+", Decimal), Err(ParseIntError::Empty { offset: 1 }));
+	// Non-ASCII digits aren't digits.
+	let fullwidth_one = Err(ParseIntError::InvalidDigit {
+		offset: 0,
+		unit: 0xFF11,
+	});
+	assert_eq!(u64("１", Decimal), fullwidth_one);
+}
+
+#[test]
+fn signed() {
+	use Radix::*;
+	assert_eq!(i64("-12", Decimal), Ok(-12));
+	assert_eq!(i64("+12", Decimal), Ok(12));
+	assert_eq!(i64("-0", Decimal), Ok(0));
+	assert_eq!(i64("-ff", Hexadecimal), Ok(-255));
+	// Min and max values and overflow by one.
+	assert_eq!(i64("9223372036854775807", Decimal), Ok(i64::MAX));
+	assert_eq!(i64("-9223372036854775808", Decimal), Ok(i64::MIN));
+	let over = Err(ParseIntError::Overflow { offset: 18 });
+	assert_eq!(i64("9223372036854775808", Decimal), over);
+	let over = Err(ParseIntError::Overflow { offset: 19 });
+	assert_eq!(i64("-9223372036854775809", Decimal), over);
+	assert_eq!(i64("-8000000000000000", Hexadecimal), Ok(i64::MIN));
+	assert_eq!(i64("7fffffffffffffff", Hexadecimal), Ok(i64::MAX));
+	// A lone `-`.
+	assert_eq!(i64("-", Decimal), Err(ParseIntError::Empty { offset: 1 }));
+	assert_eq!(
+		i64("--1", Decimal),
+		Err(ParseIntError::InvalidDigit {
+			offset: 1,
+			unit: b'-'.into()
+		})
+	);
+}
+
+#[test]
+fn prefixes() {
+	assert_eq!(auto("255"), Ok(255));
+	assert_eq!(auto("010"), Ok(10));
+	assert_eq!(auto("0"), Ok(0));
+	assert_eq!(auto("0xff"), Ok(255));
+	assert_eq!(auto("0XFF"), Ok(255));
+	assert_eq!(auto("0o17"), Ok(0o17));
+	assert_eq!(auto("0O17"), Ok(0o17));
+	assert_eq!(auto("0b101"), Ok(5));
+	assert_eq!(auto("0B101"), Ok(5));
+	assert_eq!(auto("+0x10"), Ok(16));
+	assert_eq!(auto("0xffffffffffffffff"), Ok(u64::MAX));
+	assert_eq!(
+		auto("0x10000000000000000"),
+		Err(ParseIntError::Overflow { offset: 18 })
+	);
+	// The digits must match the prefix.
+	assert_eq!(auto("0b102"), invalid(4, b'2'));
+	assert_eq!(auto("0o8"), invalid(2, b'8'));
+	assert_eq!(auto("0xfg"), invalid(3, b'g'));
+	// A prefix with no digits.
+	assert_eq!(auto("0x"), Err(ParseIntError::Empty { offset: 2 }));
+	assert_eq!(auto("+0b"), Err(ParseIntError::Empty { offset: 3 }));
+	// Not a prefix.
+	assert_eq!(auto("0d10"), invalid(1, b'd'));
+	assert_eq!(auto("x10"), invalid(0, b'x'));
+	assert_eq!(auto("-0x1"), invalid(0, b'-'));
+}
+
+#[test]
+fn quoted() {
+	// Quotes on the original command line have been removed.
+	assert_eq!(u64(r#""8080""#, Radix::Decimal), Ok(8080));
+	assert_eq!(u64(r#"80"80""#, Radix::Decimal), Ok(8080));
+	assert_eq!(i64(r#""-12""#, Radix::Decimal), Ok(-12));
+	assert_eq!(auto(r#""0x"ff"#), Ok(255));
+	with_command_line(r#"EXE --port "443""#, || {
+		let port = crate::value_of("--port").unwrap();
+		assert_eq!(port.to_u64(Radix::Decimal), Ok(443));
+	});
+}
+
+#[test]
+fn flag_value() {
+	// Only the value is parsed, however it was given.
+	with_command_line(
+		r#"EXE --port=443 --seek "-12" --mask="0xff" --bad=4x"#,
+		|| {
+			let value = |name| crate::value_of(name).unwrap();
+			assert_eq!(value("--port").to_u64(Radix::Decimal), Ok(443));
+			assert_eq!(value("--port").to_i64(Radix::Decimal), Ok(443));
+			assert_eq!(value("--seek").to_i64(Radix::Decimal), Ok(-12));
+			assert_eq!(value("--mask").to_u64_auto(), Ok(255));
+			// Offsets are within the value.
+			assert_eq!(value("--bad").to_u64(Radix::Decimal), invalid(1, b'x'));
+			// The whole argument isn't a number.
+			assert_eq!(
+				value("--port").argument().to_u64(Radix::Decimal),
+				invalid(0, b'-')
+			);
+		},
+	);
+	with_command_line("EXE --port=", || {
+		let port = crate::value_of("--port").unwrap();
+		assert_eq!(
+			port.to_u64(Radix::Decimal),
+			Err(ParseIntError::Empty { offset: 0 })
+		);
+	});
+}
+
+#[test]
+fn error() {
+	let error = u64("12a", Radix::Decimal).unwrap_err();
+	assert_eq!(error.offset(), 2);
+	assert_eq!(error.to_string(), "invalid digit 'a' at offset 2");
+	let error = Error::from(error);
+	assert_eq!((error.index(), error.offset()), (None, Some(2)));
+	assert_eq!(error.unit(), Some(u16::from(b'a')));
+	assert_eq!(error.to_string(), "invalid digit 'a' at offset 2");
+
+	let error = u64("99999999999999999999", Radix::Decimal).unwrap_err();
+	assert_eq!(error.to_string(), "number out of range at offset 19");
+	assert_eq!(Error::from(error).unit(), None);
+	let error = u64(r#""""#, Radix::Decimal).unwrap_err();
+	assert_eq!(error.to_string(), "expected a digit at offset 0");
+	let error = ParseIntError::InvalidDigit {
+		offset: 1,
+		unit: 0xD800,
+	};
+	assert_eq!(error.to_string(), "invalid digit (0xd800) at offset 1");
+	let error = u64(r#"" 1""#, Radix::Decimal).unwrap_err();
+	assert_eq!(error.to_string(), "invalid digit (0x0020) at offset 0");
+}
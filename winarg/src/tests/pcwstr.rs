@@ -0,0 +1,93 @@
+// Tests for the `windows-sys` conversions.
+
+use super::{format, leak, units, vec, with_command_line, String, Vec};
+use crate::{args_native, quoted_len, ArgsNative, CommandLine, Parser, Token, WideBuf};
+use core::ptr;
+
+#[test]
+fn from_pcwstr() {
+	let cmdline = leak(&units("app.exe \"a b\" c\0"));
+	// SAFETY: The command line is null terminated and leaked.
+	let args: Vec<String> = unsafe { ArgsNative::from_pcwstr(cmdline.as_ptr()) }
+		.map(|arg| arg.scalars().collect())
+		.collect();
+	assert_eq!(args, ["app.exe", "a b", "c"]);
+	// SAFETY: As above.
+	let parser = unsafe { Parser::from_pcwstr(cmdline.as_ptr()) };
+	assert_eq!(parser.filter(|t| t.is_next_arg()).count(), 2);
+
+	// SAFETY: A null pointer is allowed.
+	assert_eq!(unsafe { ArgsNative::from_pcwstr(ptr::null()) }.count(), 0);
+	// SAFETY: As above.
+	assert_eq!(
+		unsafe { Parser::from_pcwstr(ptr::null()) }.next(),
+		None::<Token>
+	);
+}
+
+#[test]
+fn to_pcwstr_buf() {
+	with_command_line(r#"app.exe "a b" """#, || {
+		let bufs: Vec<WideBuf> = args_native().map(|arg| arg.to_pcwstr_buf()).collect();
+		let expected = ["app.exe", "a b", ""];
+		assert_eq!(bufs.len(), expected.len());
+		for (buf, expected) in bufs.iter().zip(expected.iter()) {
+			assert_eq!(buf.as_slice(), &units(expected)[..]);
+			assert_eq!(buf.as_slice_with_nul().last(), Some(&0));
+			assert_eq!(buf.len(), expected.len());
+			// SAFETY: The buffer is null terminated and alive.
+			assert_eq!(unsafe { *buf.as_pcwstr().add(buf.len()) }, 0);
+		}
+		assert!(bufs[2].is_empty());
+	});
+}
+
+#[test]
+fn join() {
+	let args: Vec<Vec<u16>> = ["app.exe", "a b", r#"say "hi""#, ""]
+		.iter()
+		.map(|s| units(s))
+		.collect();
+	let mut buf = WideBuf::join(&args).unwrap();
+	assert_eq!(
+		buf.as_slice(),
+		&units(r#"app.exe "a b" "say \"hi\"" """#)[..]
+	);
+	assert_eq!(buf.len(), quoted_len(&args));
+	assert_eq!(
+		format!("{:?}", buf),
+		r#""app.exe \"a b\" \"say \\\"hi\\\"\" \"\"""#
+	);
+	assert_eq!(buf.as_pwstr() as *const u16, buf.as_pcwstr());
+	assert_eq!(buf.clone().into_vec_with_nul().last(), Some(&0));
+
+	let empty = WideBuf::join(Vec::<Vec<u16>>::new()).unwrap();
+	assert!(empty.is_empty());
+	assert_eq!(empty.as_slice_with_nul(), [0]);
+
+	// A quote would end the program name early and a null would end the
+	// command line, so neither can be joined.
+	let error = WideBuf::join(&[units(r#"a" b"#)]).unwrap_err();
+	assert_eq!((error.index(), error.position()), (0, 1));
+	let error = WideBuf::join(&[units("app.exe"), units("a"), vec![0x62, 0]]).unwrap_err();
+	assert_eq!((error.index(), error.position(), error.unit()), (2, 1, 0));
+}
+
+#[test]
+fn from_vec_with_nul() {
+	assert_eq!(WideBuf::from_vec_with_nul(Vec::new()), None);
+	assert_eq!(WideBuf::from_vec_with_nul(units("a")), None);
+	assert_eq!(WideBuf::from_vec_with_nul(units("a\0b\0")), None);
+	let buf = WideBuf::from_vec_with_nul(vec![u16::from(b'a'), 0]).unwrap();
+	assert_eq!(buf.as_slice(), [u16::from(b'a')]);
+}
+
+#[test]
+fn into_command_line() {
+	let args = [units("app.exe"), units("a b")];
+	let buf = WideBuf::join(&args).unwrap();
+	assert_eq!(format!("{:?}", buf), r#""app.exe \"a b\"""#);
+	let cmdline = CommandLine::from(buf.clone());
+	assert_eq!(cmdline.as_slice_with_nul(), buf.as_slice_with_nul());
+	assert_eq!(format!("{:?}", cmdline), format!("{:?}", buf));
+}
@@ -0,0 +1,350 @@
+// Program names made from `\`, `"`, `a` and spaces, followed by ` "x y" z`.
+//
+// Generated by `cargo run --bin testing -- --program-names` in the testing
+// crate, which runs each command line through `args.exe`. Don't edit by hand.
+
+use super::chk;
+
+#[test]
+fn program_names() {
+	chk(r#"\ "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#"" "x y" z"#, &[" x", "y z"]);
+	chk(r#"  "x y" z"#, &["", "x y", "z"]);
+	chk(r#"a "x y" z"#, &["a", "x y", "z"]);
+	chk(r#"\\ "x y" z"#, &[r"\\", "x y", "z"]);
+	chk(r#""\ "x y" z"#, &[r"\ x", "y z"]);
+	chk(r#" \ "x y" z"#, &["", r"\", "x y", "z"]);
+	chk(r#"a\ "x y" z"#, &[r"a\", "x y", "z"]);
+	chk(r#"\" "x y" z"#, &[r"\ x", "y z"]);
+	chk(r#""" "x y" z"#, &["", "x y", "z"]);
+	chk(r#" " "x y" z"#, &["", " x", "y z"]);
+	chk(r#"a" "x y" z"#, &["a x", "y z"]);
+	chk(r#"\  "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#""  "x y" z"#, &["  x", "y z"]);
+	chk(r#"   "x y" z"#, &["", "x y", "z"]);
+	chk(r#"a  "x y" z"#, &["a", "x y", "z"]);
+	chk(r#"\a "x y" z"#, &[r"\a", "x y", "z"]);
+	chk(r#""a "x y" z"#, &["a x", "y z"]);
+	chk(r#" a "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#"aa "x y" z"#, &["aa", "x y", "z"]);
+	chk(r#"\\\ "x y" z"#, &[r"\\\", "x y", "z"]);
+	chk(r#""\\ "x y" z"#, &[r"\\ x", "y z"]);
+	chk(r#" \\ "x y" z"#, &["", r"\\", "x y", "z"]);
+	chk(r#"a\\ "x y" z"#, &[r"a\\", "x y", "z"]);
+	chk(r#"\"\ "x y" z"#, &[r"\\ x", "y z"]);
+	chk(r#"""\ "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#" "\ "x y" z"#, &["", r"\ x", "y z"]);
+	chk(r#"a"\ "x y" z"#, &[r"a\ x", "y z"]);
+	chk(r#"\ \ "x y" z"#, &[r"\", r"\", "x y", "z"]);
+	chk(r#"" \ "x y" z"#, &[r" \ x", "y z"]);
+	chk(r#"  \ "x y" z"#, &["", r"\", "x y", "z"]);
+	chk(r#"a \ "x y" z"#, &["a", r"\", "x y", "z"]);
+	chk(r#"\a\ "x y" z"#, &[r"\a\", "x y", "z"]);
+	chk(r#""a\ "x y" z"#, &[r"a\ x", "y z"]);
+	chk(r#" a\ "x y" z"#, &["", r"a\", "x y", "z"]);
+	chk(r#"aa\ "x y" z"#, &[r"aa\", "x y", "z"]);
+	chk(r#"\\" "x y" z"#, &[r"\\ x", "y z"]);
+	chk(r#""\" "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#" \" "x y" z"#, &["", r#"""#, "x y", "z"]);
+	chk(r#"a\" "x y" z"#, &[r"a\ x", "y z"]);
+	chk(r#"\"" "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#"""" "x y" z"#, &[" x", "y z"]);
+	chk(r#" "" "x y" z"#, &["", "", "x y", "z"]);
+	chk(r#"a"" "x y" z"#, &["a", "x y", "z"]);
+	chk(r#"\ " "x y" z"#, &[r"\", " x", "y z"]);
+	chk(r#"" " "x y" z"#, &[" ", "x y", "z"]);
+	chk(r#"  " "x y" z"#, &["", " x", "y z"]);
+	chk(r#"a " "x y" z"#, &["a", " x", "y z"]);
+	chk(r#"\a" "x y" z"#, &[r"\a x", "y z"]);
+	chk(r#""a" "x y" z"#, &["a", "x y", "z"]);
+	chk(r#" a" "x y" z"#, &["", "a x", "y z"]);
+	chk(r#"aa" "x y" z"#, &["aa x", "y z"]);
+	chk(r#"\\  "x y" z"#, &[r"\\", "x y", "z"]);
+	chk(r#""\  "x y" z"#, &[r"\  x", "y z"]);
+	chk(r#" \  "x y" z"#, &["", r"\", "x y", "z"]);
+	chk(r#"a\  "x y" z"#, &[r"a\", "x y", "z"]);
+	chk(r#"\"  "x y" z"#, &[r"\  x", "y z"]);
+	chk(r#"""  "x y" z"#, &["", "x y", "z"]);
+	chk(r#" "  "x y" z"#, &["", "  x", "y z"]);
+	chk(r#"a"  "x y" z"#, &["a  x", "y z"]);
+	chk(r#"\   "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#""   "x y" z"#, &["   x", "y z"]);
+	chk(r#"    "x y" z"#, &["", "x y", "z"]);
+	chk(r#"a   "x y" z"#, &["a", "x y", "z"]);
+	chk(r#"\a  "x y" z"#, &[r"\a", "x y", "z"]);
+	chk(r#""a  "x y" z"#, &["a  x", "y z"]);
+	chk(r#" a  "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#"aa  "x y" z"#, &["aa", "x y", "z"]);
+	chk(r#"\\a "x y" z"#, &[r"\\a", "x y", "z"]);
+	chk(r#""\a "x y" z"#, &[r"\a x", "y z"]);
+	chk(r#" \a "x y" z"#, &["", r"\a", "x y", "z"]);
+	chk(r#"a\a "x y" z"#, &[r"a\a", "x y", "z"]);
+	chk(r#"\"a "x y" z"#, &[r"\a x", "y z"]);
+	chk(r#"""a "x y" z"#, &["a", "x y", "z"]);
+	chk(r#" "a "x y" z"#, &["", "a x", "y z"]);
+	chk(r#"a"a "x y" z"#, &["aa x", "y z"]);
+	chk(r#"\ a "x y" z"#, &[r"\", "a", "x y", "z"]);
+	chk(r#"" a "x y" z"#, &[" a x", "y z"]);
+	chk(r#"  a "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#"a a "x y" z"#, &["a", "a", "x y", "z"]);
+	chk(r#"\aa "x y" z"#, &[r"\aa", "x y", "z"]);
+	chk(r#""aa "x y" z"#, &["aa x", "y z"]);
+	chk(r#" aa "x y" z"#, &["", "aa", "x y", "z"]);
+	chk(r#"aaa "x y" z"#, &["aaa", "x y", "z"]);
+	chk(r#"\\\\ "x y" z"#, &[r"\\\\", "x y", "z"]);
+	chk(r#""\\\ "x y" z"#, &[r"\\\ x", "y z"]);
+	chk(r#" \\\ "x y" z"#, &["", r"\\\", "x y", "z"]);
+	chk(r#"a\\\ "x y" z"#, &[r"a\\\", "x y", "z"]);
+	chk(r#"\"\\ "x y" z"#, &[r"\\\ x", "y z"]);
+	chk(r#"""\\ "x y" z"#, &[r"\\", "x y", "z"]);
+	chk(r#" "\\ "x y" z"#, &["", r"\\ x", "y z"]);
+	chk(r#"a"\\ "x y" z"#, &[r"a\\ x", "y z"]);
+	chk(r#"\ \\ "x y" z"#, &[r"\", r"\\", "x y", "z"]);
+	chk(r#"" \\ "x y" z"#, &[r" \\ x", "y z"]);
+	chk(r#"  \\ "x y" z"#, &["", r"\\", "x y", "z"]);
+	chk(r#"a \\ "x y" z"#, &["a", r"\\", "x y", "z"]);
+	chk(r#"\a\\ "x y" z"#, &[r"\a\\", "x y", "z"]);
+	chk(r#""a\\ "x y" z"#, &[r"a\\ x", "y z"]);
+	chk(r#" a\\ "x y" z"#, &["", r"a\\", "x y", "z"]);
+	chk(r#"aa\\ "x y" z"#, &[r"aa\\", "x y", "z"]);
+	chk(r#"\\"\ "x y" z"#, &[r"\\\ x", "y z"]);
+	chk(r#""\"\ "x y" z"#, &[r"\\", "x y", "z"]);
+	chk(r#" \"\ "x y" z"#, &["", r#""\"#, "x y", "z"]);
+	chk(r#"a\"\ "x y" z"#, &[r"a\\ x", "y z"]);
+	chk(r#"\""\ "x y" z"#, &[r"\\", "x y", "z"]);
+	chk(r#""""\ "x y" z"#, &[r"\ x", "y z"]);
+	chk(r#" ""\ "x y" z"#, &["", r"\", "x y", "z"]);
+	chk(r#"a""\ "x y" z"#, &[r"a\", "x y", "z"]);
+	chk(r#"\ "\ "x y" z"#, &[r"\", r"\ x", "y z"]);
+	chk(r#"" "\ "x y" z"#, &[r" \", "x y", "z"]);
+	chk(r#"  "\ "x y" z"#, &["", r"\ x", "y z"]);
+	chk(r#"a "\ "x y" z"#, &["a", r"\ x", "y z"]);
+	chk(r#"\a"\ "x y" z"#, &[r"\a\ x", "y z"]);
+	chk(r#""a"\ "x y" z"#, &[r"a\", "x y", "z"]);
+	chk(r#" a"\ "x y" z"#, &["", r"a\ x", "y z"]);
+	chk(r#"aa"\ "x y" z"#, &[r"aa\ x", "y z"]);
+	chk(r#"\\ \ "x y" z"#, &[r"\\", r"\", "x y", "z"]);
+	chk(r#""\ \ "x y" z"#, &[r"\ \ x", "y z"]);
+	chk(r#" \ \ "x y" z"#, &["", r"\", r"\", "x y", "z"]);
+	chk(r#"a\ \ "x y" z"#, &[r"a\", r"\", "x y", "z"]);
+	chk(r#"\" \ "x y" z"#, &[r"\ \ x", "y z"]);
+	chk(r#""" \ "x y" z"#, &["", r"\", "x y", "z"]);
+	chk(r#" " \ "x y" z"#, &["", r" \ x", "y z"]);
+	chk(r#"a" \ "x y" z"#, &[r"a \ x", "y z"]);
+	chk(r#"\  \ "x y" z"#, &[r"\", r"\", "x y", "z"]);
+	chk(r#""  \ "x y" z"#, &[r"  \ x", "y z"]);
+	chk(r#"   \ "x y" z"#, &["", r"\", "x y", "z"]);
+	chk(r#"a  \ "x y" z"#, &["a", r"\", "x y", "z"]);
+	chk(r#"\a \ "x y" z"#, &[r"\a", r"\", "x y", "z"]);
+	chk(r#""a \ "x y" z"#, &[r"a \ x", "y z"]);
+	chk(r#" a \ "x y" z"#, &["", "a", r"\", "x y", "z"]);
+	chk(r#"aa \ "x y" z"#, &["aa", r"\", "x y", "z"]);
+	chk(r#"\\a\ "x y" z"#, &[r"\\a\", "x y", "z"]);
+	chk(r#""\a\ "x y" z"#, &[r"\a\ x", "y z"]);
+	chk(r#" \a\ "x y" z"#, &["", r"\a\", "x y", "z"]);
+	chk(r#"a\a\ "x y" z"#, &[r"a\a\", "x y", "z"]);
+	chk(r#"\"a\ "x y" z"#, &[r"\a\ x", "y z"]);
+	chk(r#"""a\ "x y" z"#, &[r"a\", "x y", "z"]);
+	chk(r#" "a\ "x y" z"#, &["", r"a\ x", "y z"]);
+	chk(r#"a"a\ "x y" z"#, &[r"aa\ x", "y z"]);
+	chk(r#"\ a\ "x y" z"#, &[r"\", r"a\", "x y", "z"]);
+	chk(r#"" a\ "x y" z"#, &[r" a\ x", "y z"]);
+	chk(r#"  a\ "x y" z"#, &["", r"a\", "x y", "z"]);
+	chk(r#"a a\ "x y" z"#, &["a", r"a\", "x y", "z"]);
+	chk(r#"\aa\ "x y" z"#, &[r"\aa\", "x y", "z"]);
+	chk(r#""aa\ "x y" z"#, &[r"aa\ x", "y z"]);
+	chk(r#" aa\ "x y" z"#, &["", r"aa\", "x y", "z"]);
+	chk(r#"aaa\ "x y" z"#, &[r"aaa\", "x y", "z"]);
+	chk(r#"\\\" "x y" z"#, &[r"\\\ x", "y z"]);
+	chk(r#""\\" "x y" z"#, &[r"\\", "x y", "z"]);
+	chk(r#" \\" "x y" z"#, &["", r"\ x", "y z"]);
+	chk(r#"a\\" "x y" z"#, &[r"a\\ x", "y z"]);
+	chk(r#"\"\" "x y" z"#, &[r"\\", "x y", "z"]);
+	chk(r#"""\" "x y" z"#, &[r"\ x", "y z"]);
+	chk(r#" "\" "x y" z"#, &["", r#"" x"#, "y z"]);
+	chk(r#"a"\" "x y" z"#, &[r"a\", "x y", "z"]);
+	chk(r#"\ \" "x y" z"#, &[r"\", r#"""#, "x y", "z"]);
+	chk(r#"" \" "x y" z"#, &[r" \", "x y", "z"]);
+	chk(r#"  \" "x y" z"#, &["", r#"""#, "x y", "z"]);
+	chk(r#"a \" "x y" z"#, &["a", r#"""#, "x y", "z"]);
+	chk(r#"\a\" "x y" z"#, &[r"\a\ x", "y z"]);
+	chk(r#""a\" "x y" z"#, &[r"a\", "x y", "z"]);
+	chk(r#" a\" "x y" z"#, &["", r#"a""#, "x y", "z"]);
+	chk(r#"aa\" "x y" z"#, &[r"aa\ x", "y z"]);
+	chk(r#"\\"" "x y" z"#, &[r"\\", "x y", "z"]);
+	chk(r#""\"" "x y" z"#, &[r"\ x", "y z"]);
+	chk(r#" \"" "x y" z"#, &["", r#"" x"#, "y z"]);
+	chk(r#"a\"" "x y" z"#, &[r"a\", "x y", "z"]);
+	chk(r#"\""" "x y" z"#, &[r"\ x", "y z"]);
+	chk(r#""""" "x y" z"#, &["", "x y", "z"]);
+	chk(r#" """ "x y" z"#, &["", r#"" x"#, "y z"]);
+	chk(r#"a""" "x y" z"#, &["a x", "y z"]);
+	chk(r#"\ "" "x y" z"#, &[r"\", "", "x y", "z"]);
+	chk(r#"" "" "x y" z"#, &["  x", "y z"]);
+	chk(r#"  "" "x y" z"#, &["", "", "x y", "z"]);
+	chk(r#"a "" "x y" z"#, &["a", "", "x y", "z"]);
+	chk(r#"\a"" "x y" z"#, &[r"\a", "x y", "z"]);
+	chk(r#""a"" "x y" z"#, &["a x", "y z"]);
+	chk(r#" a"" "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#"aa"" "x y" z"#, &["aa", "x y", "z"]);
+	chk(r#"\\ " "x y" z"#, &[r"\\", " x", "y z"]);
+	chk(r#""\ " "x y" z"#, &[r"\ ", "x y", "z"]);
+	chk(r#" \ " "x y" z"#, &["", r"\", " x", "y z"]);
+	chk(r#"a\ " "x y" z"#, &[r"a\", " x", "y z"]);
+	chk(r#"\" " "x y" z"#, &[r"\ ", "x y", "z"]);
+	chk(r#""" " "x y" z"#, &["", " x", "y z"]);
+	chk(r#" " " "x y" z"#, &["", " ", "x y", "z"]);
+	chk(r#"a" " "x y" z"#, &["a ", "x y", "z"]);
+	chk(r#"\  " "x y" z"#, &[r"\", " x", "y z"]);
+	chk(r#""  " "x y" z"#, &["  ", "x y", "z"]);
+	chk(r#"   " "x y" z"#, &["", " x", "y z"]);
+	chk(r#"a  " "x y" z"#, &["a", " x", "y z"]);
+	chk(r#"\a " "x y" z"#, &[r"\a", " x", "y z"]);
+	chk(r#""a " "x y" z"#, &["a ", "x y", "z"]);
+	chk(r#" a " "x y" z"#, &["", "a", " x", "y z"]);
+	chk(r#"aa " "x y" z"#, &["aa", " x", "y z"]);
+	chk(r#"\\a" "x y" z"#, &[r"\\a x", "y z"]);
+	chk(r#""\a" "x y" z"#, &[r"\a", "x y", "z"]);
+	chk(r#" \a" "x y" z"#, &["", r"\a x", "y z"]);
+	chk(r#"a\a" "x y" z"#, &[r"a\a x", "y z"]);
+	chk(r#"\"a" "x y" z"#, &[r"\a", "x y", "z"]);
+	chk(r#"""a" "x y" z"#, &["a x", "y z"]);
+	chk(r#" "a" "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#"a"a" "x y" z"#, &["aa", "x y", "z"]);
+	chk(r#"\ a" "x y" z"#, &[r"\", "a x", "y z"]);
+	chk(r#"" a" "x y" z"#, &[" a", "x y", "z"]);
+	chk(r#"  a" "x y" z"#, &["", "a x", "y z"]);
+	chk(r#"a a" "x y" z"#, &["a", "a x", "y z"]);
+	chk(r#"\aa" "x y" z"#, &[r"\aa x", "y z"]);
+	chk(r#""aa" "x y" z"#, &["aa", "x y", "z"]);
+	chk(r#" aa" "x y" z"#, &["", "aa x", "y z"]);
+	chk(r#"aaa" "x y" z"#, &["aaa x", "y z"]);
+	chk(r#"\\\  "x y" z"#, &[r"\\\", "x y", "z"]);
+	chk(r#""\\  "x y" z"#, &[r"\\  x", "y z"]);
+	chk(r#" \\  "x y" z"#, &["", r"\\", "x y", "z"]);
+	chk(r#"a\\  "x y" z"#, &[r"a\\", "x y", "z"]);
+	chk(r#"\"\  "x y" z"#, &[r"\\  x", "y z"]);
+	chk(r#"""\  "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#" "\  "x y" z"#, &["", r"\  x", "y z"]);
+	chk(r#"a"\  "x y" z"#, &[r"a\  x", "y z"]);
+	chk(r#"\ \  "x y" z"#, &[r"\", r"\", "x y", "z"]);
+	chk(r#"" \  "x y" z"#, &[r" \  x", "y z"]);
+	chk(r#"  \  "x y" z"#, &["", r"\", "x y", "z"]);
+	chk(r#"a \  "x y" z"#, &["a", r"\", "x y", "z"]);
+	chk(r#"\a\  "x y" z"#, &[r"\a\", "x y", "z"]);
+	chk(r#""a\  "x y" z"#, &[r"a\  x", "y z"]);
+	chk(r#" a\  "x y" z"#, &["", r"a\", "x y", "z"]);
+	chk(r#"aa\  "x y" z"#, &[r"aa\", "x y", "z"]);
+	chk(r#"\\"  "x y" z"#, &[r"\\  x", "y z"]);
+	chk(r#""\"  "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#" \"  "x y" z"#, &["", r#"""#, "x y", "z"]);
+	chk(r#"a\"  "x y" z"#, &[r"a\  x", "y z"]);
+	chk(r#"\""  "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#""""  "x y" z"#, &["  x", "y z"]);
+	chk(r#" ""  "x y" z"#, &["", "", "x y", "z"]);
+	chk(r#"a""  "x y" z"#, &["a", "x y", "z"]);
+	chk(r#"\ "  "x y" z"#, &[r"\", "  x", "y z"]);
+	chk(r#"" "  "x y" z"#, &[" ", "x y", "z"]);
+	chk(r#"  "  "x y" z"#, &["", "  x", "y z"]);
+	chk(r#"a "  "x y" z"#, &["a", "  x", "y z"]);
+	chk(r#"\a"  "x y" z"#, &[r"\a  x", "y z"]);
+	chk(r#""a"  "x y" z"#, &["a", "x y", "z"]);
+	chk(r#" a"  "x y" z"#, &["", "a  x", "y z"]);
+	chk(r#"aa"  "x y" z"#, &["aa  x", "y z"]);
+	chk(r#"\\   "x y" z"#, &[r"\\", "x y", "z"]);
+	chk(r#""\   "x y" z"#, &[r"\   x", "y z"]);
+	chk(r#" \   "x y" z"#, &["", r"\", "x y", "z"]);
+	chk(r#"a\   "x y" z"#, &[r"a\", "x y", "z"]);
+	chk(r#"\"   "x y" z"#, &[r"\   x", "y z"]);
+	chk(r#"""   "x y" z"#, &["", "x y", "z"]);
+	chk(r#" "   "x y" z"#, &["", "   x", "y z"]);
+	chk(r#"a"   "x y" z"#, &["a   x", "y z"]);
+	chk(r#"\    "x y" z"#, &[r"\", "x y", "z"]);
+	chk(r#""    "x y" z"#, &["    x", "y z"]);
+	chk(r#"     "x y" z"#, &["", "x y", "z"]);
+	chk(r#"a    "x y" z"#, &["a", "x y", "z"]);
+	chk(r#"\a   "x y" z"#, &[r"\a", "x y", "z"]);
+	chk(r#""a   "x y" z"#, &["a   x", "y z"]);
+	chk(r#" a   "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#"aa   "x y" z"#, &["aa", "x y", "z"]);
+	chk(r#"\\a  "x y" z"#, &[r"\\a", "x y", "z"]);
+	chk(r#""\a  "x y" z"#, &[r"\a  x", "y z"]);
+	chk(r#" \a  "x y" z"#, &["", r"\a", "x y", "z"]);
+	chk(r#"a\a  "x y" z"#, &[r"a\a", "x y", "z"]);
+	chk(r#"\"a  "x y" z"#, &[r"\a  x", "y z"]);
+	chk(r#"""a  "x y" z"#, &["a", "x y", "z"]);
+	chk(r#" "a  "x y" z"#, &["", "a  x", "y z"]);
+	chk(r#"a"a  "x y" z"#, &["aa  x", "y z"]);
+	chk(r#"\ a  "x y" z"#, &[r"\", "a", "x y", "z"]);
+	chk(r#"" a  "x y" z"#, &[" a  x", "y z"]);
+	chk(r#"  a  "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#"a a  "x y" z"#, &["a", "a", "x y", "z"]);
+	chk(r#"\aa  "x y" z"#, &[r"\aa", "x y", "z"]);
+	chk(r#""aa  "x y" z"#, &["aa  x", "y z"]);
+	chk(r#" aa  "x y" z"#, &["", "aa", "x y", "z"]);
+	chk(r#"aaa  "x y" z"#, &["aaa", "x y", "z"]);
+	chk(r#"\\\a "x y" z"#, &[r"\\\a", "x y", "z"]);
+	chk(r#""\\a "x y" z"#, &[r"\\a x", "y z"]);
+	chk(r#" \\a "x y" z"#, &["", r"\\a", "x y", "z"]);
+	chk(r#"a\\a "x y" z"#, &[r"a\\a", "x y", "z"]);
+	chk(r#"\"\a "x y" z"#, &[r"\\a x", "y z"]);
+	chk(r#"""\a "x y" z"#, &[r"\a", "x y", "z"]);
+	chk(r#" "\a "x y" z"#, &["", r"\a x", "y z"]);
+	chk(r#"a"\a "x y" z"#, &[r"a\a x", "y z"]);
+	chk(r#"\ \a "x y" z"#, &[r"\", r"\a", "x y", "z"]);
+	chk(r#"" \a "x y" z"#, &[r" \a x", "y z"]);
+	chk(r#"  \a "x y" z"#, &["", r"\a", "x y", "z"]);
+	chk(r#"a \a "x y" z"#, &["a", r"\a", "x y", "z"]);
+	chk(r#"\a\a "x y" z"#, &[r"\a\a", "x y", "z"]);
+	chk(r#""a\a "x y" z"#, &[r"a\a x", "y z"]);
+	chk(r#" a\a "x y" z"#, &["", r"a\a", "x y", "z"]);
+	chk(r#"aa\a "x y" z"#, &[r"aa\a", "x y", "z"]);
+	chk(r#"\\"a "x y" z"#, &[r"\\a x", "y z"]);
+	chk(r#""\"a "x y" z"#, &[r"\a", "x y", "z"]);
+	chk(r#" \"a "x y" z"#, &["", r#""a"#, "x y", "z"]);
+	chk(r#"a\"a "x y" z"#, &[r"a\a x", "y z"]);
+	chk(r#"\""a "x y" z"#, &[r"\a", "x y", "z"]);
+	chk(r#""""a "x y" z"#, &["a x", "y z"]);
+	chk(r#" ""a "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#"a""a "x y" z"#, &["aa", "x y", "z"]);
+	chk(r#"\ "a "x y" z"#, &[r"\", "a x", "y z"]);
+	chk(r#"" "a "x y" z"#, &[" a", "x y", "z"]);
+	chk(r#"  "a "x y" z"#, &["", "a x", "y z"]);
+	chk(r#"a "a "x y" z"#, &["a", "a x", "y z"]);
+	chk(r#"\a"a "x y" z"#, &[r"\aa x", "y z"]);
+	chk(r#""a"a "x y" z"#, &["aa", "x y", "z"]);
+	chk(r#" a"a "x y" z"#, &["", "aa x", "y z"]);
+	chk(r#"aa"a "x y" z"#, &["aaa x", "y z"]);
+	chk(r#"\\ a "x y" z"#, &[r"\\", "a", "x y", "z"]);
+	chk(r#""\ a "x y" z"#, &[r"\ a x", "y z"]);
+	chk(r#" \ a "x y" z"#, &["", r"\", "a", "x y", "z"]);
+	chk(r#"a\ a "x y" z"#, &[r"a\", "a", "x y", "z"]);
+	chk(r#"\" a "x y" z"#, &[r"\ a x", "y z"]);
+	chk(r#""" a "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#" " a "x y" z"#, &["", " a x", "y z"]);
+	chk(r#"a" a "x y" z"#, &["a a x", "y z"]);
+	chk(r#"\  a "x y" z"#, &[r"\", "a", "x y", "z"]);
+	chk(r#""  a "x y" z"#, &["  a x", "y z"]);
+	chk(r#"   a "x y" z"#, &["", "a", "x y", "z"]);
+	chk(r#"a  a "x y" z"#, &["a", "a", "x y", "z"]);
+	chk(r#"\a a "x y" z"#, &[r"\a", "a", "x y", "z"]);
+	chk(r#""a a "x y" z"#, &["a a x", "y z"]);
+	chk(r#" a a "x y" z"#, &["", "a", "a", "x y", "z"]);
+	chk(r#"aa a "x y" z"#, &["aa", "a", "x y", "z"]);
+	chk(r#"\\aa "x y" z"#, &[r"\\aa", "x y", "z"]);
+	chk(r#""\aa "x y" z"#, &[r"\aa x", "y z"]);
+	chk(r#" \aa "x y" z"#, &["", r"\aa", "x y", "z"]);
+	chk(r#"a\aa "x y" z"#, &[r"a\aa", "x y", "z"]);
+	chk(r#"\"aa "x y" z"#, &[r"\aa x", "y z"]);
+	chk(r#"""aa "x y" z"#, &["aa", "x y", "z"]);
+	chk(r#" "aa "x y" z"#, &["", "aa x", "y z"]);
+	chk(r#"a"aa "x y" z"#, &["aaa x", "y z"]);
+	chk(r#"\ aa "x y" z"#, &[r"\", "aa", "x y", "z"]);
+	chk(r#"" aa "x y" z"#, &[" aa x", "y z"]);
+	chk(r#"  aa "x y" z"#, &["", "aa", "x y", "z"]);
+	chk(r#"a aa "x y" z"#, &["a", "aa", "x y", "z"]);
+	chk(r#"\aaa "x y" z"#, &[r"\aaa", "x y", "z"]);
+	chk(r#""aaa "x y" z"#, &["aaa x", "y z"]);
+	chk(r#" aaa "x y" z"#, &["", "aaa", "x y", "z"]);
+	chk(r#"aaaa "x y" z"#, &["aaaa", "x y", "z"]);
+}
@@ -0,0 +1,122 @@
+// Tests for `program_path`. The path of the test executable isn't known in
+// advance so these mostly check it's consistent.
+
+use super::{units, with_command_line, with_command_line_wide, String, Vec};
+use crate::program_path::{module_file_name, path_matches, MODULE_FILE_NAME_CALLS};
+use crate::verbatim::strip_verbatim_prefix;
+use crate::{args_native, program_name_or_module, program_path, program_path_matches_arg0};
+
+#[test]
+fn live_process() {
+	let path = program_path(true).unwrap();
+	let name = String::from_utf16(&path).unwrap().to_ascii_lowercase();
+	assert!(name.ends_with(".exe"));
+	#[cfg(feature = "std")]
+	assert_eq!(
+		crate::program_path_buf(true).unwrap(),
+		std::env::current_exe().unwrap()
+	);
+
+	// The program name matches if it's the path in any form.
+	with_command_line_wide(&path, || assert!(program_path_matches_arg0()));
+	let file_name = path.rsplit(|&w| w == u16::from(b'\\')).next().unwrap();
+	with_command_line_wide(file_name, || assert!(program_path_matches_arg0()));
+	with_command_line("not-the-test.exe", || assert!(!program_path_matches_arg0()));
+}
+
+#[test]
+fn buffer_growth() {
+	let path = module_file_name(260).unwrap();
+	for capacity in [0, 1, 2, 3, path.len(), path.len() + 1] {
+		assert_eq!(module_file_name(capacity).unwrap(), path);
+	}
+}
+
+#[test]
+fn strip_verbatim() {
+	let strip = |path: &str| {
+		let mut path = units(path);
+		strip_verbatim_prefix(&mut path);
+		String::from_utf16(&path).unwrap()
+	};
+	assert_eq!(strip(r"\\?\C:\app.exe"), r"C:\app.exe");
+	assert_eq!(
+		strip(r"\\?\UNC\server\share\app.exe"),
+		r"\\server\share\app.exe"
+	);
+	assert_eq!(
+		strip(r"\\?\unc\server\share\app.exe"),
+		r"\\server\share\app.exe"
+	);
+	assert_eq!(strip(r"C:\app.exe"), r"C:\app.exe");
+	assert_eq!(strip(r"\\server\share\app.exe"), r"\\server\share\app.exe");
+	// Other verbatim paths can't be written any other way.
+	assert_eq!(strip(r"\\?\Volume{1}\app.exe"), r"\\?\Volume{1}\app.exe");
+	assert_eq!(strip(r"\\?\1:\app.exe"), r"\\?\1:\app.exe");
+	assert_eq!(strip(r"\\?\UNCserver\app.exe"), r"\\?\UNCserver\app.exe");
+}
+
+#[test]
+fn matches() {
+	let matches = |arg0: &str| path_matches(&units(r"C:\Program Files\App\app.exe"), &units(arg0));
+	assert!(matches(r"C:\Program Files\App\app.exe"));
+	assert!(matches(r"c:\program files\app\APP.EXE"));
+	assert!(matches("C:/Program Files/App/app.exe"));
+	assert!(matches(r"C:\Program Files\App\app"));
+	assert!(matches(r"C:\\Program Files\.\App\app.exe"));
+	assert!(matches("app.exe"));
+	assert!(matches("app"));
+	assert!(matches(r"App\app.exe"));
+	assert!(matches(r".\app.exe"));
+	assert!(matches(r"..\App\app.exe"));
+	assert!(matches(r"..\..\..\App\app.exe"));
+
+	assert!(!matches(r"D:\Program Files\App\app.exe"));
+	assert!(!matches(r"C:\Program Files\Other\app.exe"));
+	assert!(!matches(r"C:\Program Files\App\app.ex"));
+	assert!(!matches(r"Other\app.exe"));
+	assert!(!matches(r"x:\y\C:\Program Files\App\app.exe"));
+	assert!(!matches("other.exe"));
+	assert!(!matches("app.exe.exe"));
+	assert!(!matches(""));
+	assert!(!matches(".."));
+}
+
+#[test]
+fn empty_program_name() {
+	let path = program_path(true).unwrap();
+	let calls = || MODULE_FILE_NAME_CALLS.with(|calls| calls.get());
+	let chk = |cmdline: &str, expected: &[&[u16]], asks_os: bool| {
+		with_command_line(cmdline, || {
+			let before = calls();
+			assert_eq!(program_name_or_module(), expected[0], "{:?}", cmdline);
+			let args: Vec<Vec<u16>> = args_native().program_or_module().collect();
+			assert_eq!(args, expected, "{:?}", cmdline);
+			let after = calls();
+			assert_eq!(after - before, if asks_os { 2 } else { 0 }, "{:?}", cmdline);
+		});
+	};
+	// A COM server is started without a program name.
+	chk(" -Embedding", &[&path, &units("-Embedding")], true);
+	chk("", &[&path], true);
+	chk("\" \t\" a", &[&path, &units("a")], true);
+	// The operating system isn't asked if there's a program name.
+	chk(
+		r#"app.exe "a b""#,
+		&[&units("app.exe"), &units("a b")],
+		false,
+	);
+	chk(
+		r#""C:\My App\app.exe""#,
+		&[&units(r"C:\My App\app.exe")],
+		false,
+	);
+
+	// Nothing is replaced once the program name has been taken.
+	with_command_line(" a b", || {
+		let mut args = args_native();
+		args.next();
+		let rest: Vec<Vec<u16>> = args.program_or_module().collect();
+		assert_eq!(rest, [units("a"), units("b")]);
+	});
+}
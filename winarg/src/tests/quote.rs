@@ -0,0 +1,167 @@
+// Tests for `is_representable` and `Argument::is_roundtrip_safe`.
+
+use super::{with_command_line_wide, String, ToString, Vec};
+use crate::{is_representable, is_representable_with, ArgsNative, QuotingRules, WhyNot};
+
+fn units(args: &[&str]) -> Vec<Vec<u16>> {
+	args.iter().map(|s| s.encode_utf16().collect()).collect()
+}
+
+fn why_not(args: &[&str], rules: QuotingRules) -> Option<(usize, usize, u16)> {
+	is_representable_with(units(args), rules)
+		.err()
+		.map(|e| (e.index(), e.position(), e.unit()))
+}
+
+#[test]
+fn representable() {
+	let args = units(&[
+		"C:\\Program Files\\app.exe",
+		"",
+		" ",
+		"\"",
+		"a\\\"b\\",
+		"\n",
+	]);
+	assert_eq!(is_representable(&args), Ok(()));
+	assert_eq!(is_representable_with(&args, QuotingRules::Legacy), Ok(()));
+	assert_eq!(is_representable(units(&[])), Ok(()));
+	assert_eq!(is_representable(units(&[""])), Ok(()));
+}
+
+#[test]
+fn unrepresentable() {
+	for &rules in &[QuotingRules::Modern, QuotingRules::Legacy] {
+		// Quotes can't be escaped in the program name.
+		assert_eq!(why_not(&["a\"b"], rules), Some((0, 1, b'"' as u16)));
+		assert_eq!(why_not(&["\"", "a"], rules), Some((0, 0, b'"' as u16)));
+		// A null ends the command line.
+		assert_eq!(why_not(&["a\0"], rules), Some((0, 1, 0)));
+		assert_eq!(why_not(&["EXE", "a", "bc\0"], rules), Some((2, 2, 0)));
+		// Only the first problem is reported.
+		assert_eq!(why_not(&["EXE", "\0", "\0"], rules), Some((1, 0, 0)));
+	}
+}
+
+#[test]
+fn legacy() {
+	// `WinMain` ends the program name at a control character.
+	let args = ["C:\\new\nline.exe", "a"];
+	assert_eq!(why_not(&args, QuotingRules::Modern), None);
+	assert_eq!(
+		why_not(&args, QuotingRules::Legacy),
+		Some((0, 6, b'\n' as u16))
+	);
+	// A tab always ends an unquoted program name so it can be quoted.
+	assert_eq!(why_not(&["a\tb"], QuotingRules::Legacy), None);
+	// Other arguments are unaffected.
+	assert_eq!(why_not(&["EXE", "\n"], QuotingRules::Legacy), None);
+}
+
+#[test]
+fn display() {
+	let error = |args: &[&str]| -> WhyNot { is_representable(units(args)).unwrap_err() };
+	assert_eq!(
+		error(&["a\"b"]).to_string(),
+		"the program name contains a quote at position 1"
+	);
+	assert_eq!(
+		error(&["EXE", "x", "a\0"]).to_string(),
+		"argument 2 contains a null at position 1"
+	);
+}
+
+#[test]
+fn is_roundtrip_safe() {
+	let cmdline: Vec<u16> = "EXE a \\\"b \"c d\" \"e\nf\"".encode_utf16().collect();
+	let safe: Vec<bool> = with_command_line_wide(&cmdline, || {
+		ArgsNative::from_env()
+			.map(|arg| arg.is_roundtrip_safe())
+			.collect()
+	});
+	assert_eq!(safe, [true, true, false, true, false]);
+}
+
+fn quote(args: &[Vec<u16>]) -> Vec<u16> {
+	let mut cmdline = Vec::new();
+	crate::quote::quote(args, |u| cmdline.push(u));
+	cmdline
+}
+
+#[test]
+fn quoted_len() {
+	let len = |args: &[&str]| crate::quoted_len(units(args));
+	assert_eq!(len(&[]), 0);
+	assert_eq!(len(&[""]), 2);
+	assert_eq!(len(&["EXE", "a"]), 5);
+	assert_eq!(len(&["E E", "a b"]), 11);
+	// `"` is escaped and so are the backslashes before it.
+	assert_eq!(len(&["EXE", "\\\""]), 8);
+	// Backslashes are doubled before the closing quote but not otherwise.
+	assert_eq!(len(&["EXE", "a\\"]), 6);
+	assert_eq!(len(&["EXE", "a \\"]), 10);
+	assert_eq!(
+		String::from_utf16(&quote(&units(&["E E", "a \\", "\"", "\\\\\"\\"]))).unwrap(),
+		r#""E E" "a \\" \" \\\\\"\"#
+	);
+}
+
+// `quoted_len` is the length of the quoted command line, which parses back
+// into the same arguments.
+#[test]
+fn quoted_len_matches_quoting() {
+	// splitmix64, so failures are reproducible.
+	let mut state = 0_u64;
+	let mut next = |n: u64| {
+		state = state.wrapping_add(0x9E3779B97F4A7C15);
+		let mut z = state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+		(z ^ (z >> 31)) % n
+	};
+	let units = [b'a', b' ', b'\t', b'\\', b'"', b'\n'];
+	for _ in 0..2000 {
+		let argc = next(5) + 1;
+		let args: Vec<Vec<u16>> = (0..argc)
+			.map(|index| {
+				let len = next(6);
+				// The program name can't contain quotes.
+				let choices = if index == 0 { 4 } else { 6 };
+				(0..len)
+					.map(|_| u16::from(units[next(choices) as usize]))
+					.filter(|&u| index > 0 || u != u16::from(b'"'))
+					.collect()
+			})
+			.collect();
+		let cmdline = quote(&args);
+		assert_eq!(crate::quoted_len(&args), cmdline.len(), "{:?}", args);
+		let parsed: Vec<Vec<u16>> = with_command_line_wide(&cmdline, || {
+			ArgsNative::from_env()
+				.map(|arg| arg.utf16_units().collect())
+				.collect()
+		});
+		assert_eq!(parsed, args, "{:?}", String::from_utf16_lossy(&cmdline));
+	}
+}
+
+#[test]
+fn quote_one() {
+	let quote_one = |arg: &str, is_program| {
+		let arg: Vec<u16> = arg.encode_utf16().collect();
+		let mut quoted = Vec::new();
+		crate::quote_one(&arg, is_program, |u| quoted.push(u));
+		// It's parsed back into the same argument.
+		let (unquoted, used) = crate::unquote_one(&quoted, is_program);
+		assert_eq!(unquoted.collect::<Vec<u16>>(), arg);
+		assert_eq!(used, quoted.len());
+		String::from_utf16(&quoted).unwrap()
+	};
+	assert_eq!(quote_one("a", false), "a");
+	assert_eq!(quote_one("", false), r#""""#);
+	assert_eq!(quote_one(r"a \", false), r#""a \\""#);
+	assert_eq!(quote_one(r#"\"a"#, false), r#"\\\"a"#);
+	assert_eq!(quote_one(r"C:\dir\", false), r"C:\dir\");
+	// The program name isn't escaped.
+	assert_eq!(quote_one(r"C:\new dir\", true), r#""C:\new dir\""#);
+	assert_eq!(quote_one("", true), r#""""#);
+}
@@ -0,0 +1,353 @@
+// Tests for `rebuild` and `with_appended`. The output is checked by parsing it
+// again.
+
+use super::{units, vec, with_command_line, with_command_line_wide, String, Vec};
+use crate::{args_native, rebuild, with_appended, with_program_and_appended, Argument, Edit};
+
+// Rebuild `cmdline`, returning the new command line and its arguments.
+fn rebuild_with(
+	cmdline: &str,
+	edit: impl FnMut(&Argument, usize) -> Edit,
+) -> (String, Vec<String>) {
+	let rebuilt = with_command_line(cmdline, || rebuild(edit));
+	assert_eq!(rebuilt.last(), Some(&0));
+	let args = with_command_line_wide(&rebuilt, || {
+		args_native()
+			.map(|arg| String::from_utf16(&arg.utf16_units().collect::<Vec<u16>>()).unwrap())
+			.collect()
+	});
+	let rebuilt = String::from_utf16(&rebuilt[..rebuilt.len() - 1]).unwrap();
+	(rebuilt, args)
+}
+
+#[test]
+fn keep_verbatim() {
+	let cmdline = r#""C:\Program Files\app.exe" a"b c"d \\"x y" "" \a\\"#;
+	let (rebuilt, args) = rebuild_with(cmdline, |_, _| Edit::Keep);
+	assert_eq!(rebuilt, cmdline);
+	assert_eq!(
+		args,
+		[r"C:\Program Files\app.exe", "ab cd", r#"\x y"#, "", r"\a\\"]
+	);
+	// Only the whitespace between arguments is normalized.
+	let (rebuilt, _) = rebuild_with("app.exe \t \"a\"  b\t", |_, _| Edit::Keep);
+	assert_eq!(rebuilt, r#"app.exe "a" b"#);
+	// An empty program name is kept.
+	let (rebuilt, args) = rebuild_with(" a", |_, _| Edit::Keep);
+	assert_eq!(rebuilt, " a");
+	assert_eq!(args, ["", "a"]);
+	let (rebuilt, args) = rebuild_with("", |_, _| Edit::Keep);
+	assert_eq!(rebuilt, "");
+	assert!(args.is_empty());
+}
+
+#[test]
+fn drop_and_insert() {
+	let cmdline = r#"app.exe "--update-pending" --keep="a b" x\"y"#;
+	let (rebuilt, args) = rebuild_with(cmdline, |arg, index| {
+		if *arg == "--update-pending" {
+			Edit::Drop
+		} else if index == 0 {
+			Edit::Insert(vec![units("--updated")])
+		} else {
+			Edit::Keep
+		}
+	});
+	assert_eq!(rebuilt, r#"app.exe --updated --keep="a b" x\"y"#);
+	assert_eq!(args, ["app.exe", "--updated", "--keep=a b", r#"x"y"#]);
+}
+
+#[test]
+fn replace() {
+	let new = [r#"say "hi""#, r"trailing\ ", r"a\b", ""];
+	let (rebuilt, args) = rebuild_with(r#"app.exe "old" keep"#, |_, index| {
+		if index == 1 {
+			Edit::Replace(new.iter().map(|s| units(s)).collect())
+		} else {
+			Edit::Keep
+		}
+	});
+	assert_eq!(rebuilt, r#"app.exe "say \"hi\"" "trailing\ " a\b "" keep"#);
+	assert_eq!(
+		args,
+		["app.exe", r#"say "hi""#, r"trailing\ ", r"a\b", "", "keep"]
+	);
+	// Replacing with nothing drops the argument.
+	let (rebuilt, _) = rebuild_with("app.exe a b", |_, index| {
+		if index == 1 {
+			Edit::Replace(Vec::new())
+		} else {
+			Edit::Keep
+		}
+	});
+	assert_eq!(rebuilt, "app.exe b");
+}
+
+#[test]
+fn new_program_name() {
+	// An argument that becomes the program name is quoted again, because
+	// backslashes don't escape quotes in the program name.
+	let (rebuilt, args) = rebuild_with(r#"app.exe "other app.exe" a\"b"#, |_, index| {
+		if index == 0 {
+			Edit::Drop
+		} else {
+			Edit::Keep
+		}
+	});
+	assert_eq!(rebuilt, r#""other app.exe" a\"b"#);
+	assert_eq!(args, ["other app.exe", r#"a"b"#]);
+	let (rebuilt, args) = rebuild_with(r#"app.exe "a b"c"#, |_, index| {
+		if index == 0 {
+			Edit::Drop
+		} else {
+			Edit::Keep
+		}
+	});
+	assert_eq!(rebuilt, r#""a bc""#);
+	assert_eq!(args, ["a bc"]);
+	// A replacement program name isn't escaped.
+	let (rebuilt, args) = rebuild_with(r"app.exe a", |_, index| {
+		if index == 0 {
+			Edit::Replace(vec![units(r"C:\new dir\"), units(r"b\")])
+		} else {
+			Edit::Keep
+		}
+	});
+	assert_eq!(rebuilt, r#""C:\new dir\" b\ a"#);
+	assert_eq!(args, [r"C:\new dir\", r"b\", "a"]);
+}
+
+#[test]
+fn canonicalize() {
+	use crate::{canonicalize, canonicalize_with, QuotingRules};
+
+	let canonical =
+		|cmdline: &str| String::from_utf16(&canonicalize(&units(cmdline)).unwrap()).unwrap();
+	let cases = [
+		("", ""),
+		(" ", r#""""#),
+		(" \t a ", r#""" a"#),
+		(r#""app.exe"  "a"  b	"#, "app.exe a b"),
+		(
+			r#""C:\Program Files\app.exe" "a b""#,
+			r#""C:\Program Files\app.exe" "a b""#,
+		),
+		(
+			r#"C:\Program" "Files\app.exe"#,
+			r#""C:\Program Files\app.exe""#,
+		),
+		(
+			r#"EXE a" "b """" c""d "x\\" y\"#,
+			r#"EXE "a b" \" cd x\ y\"#,
+		),
+		(r#"EXE "" """#, r#"EXE "" """#),
+		("EXE a\0b c", "EXE a"),
+	];
+	for &(cmdline, expected) in &cases {
+		assert_eq!(canonical(cmdline), expected, "{:?}", cmdline);
+		assert_eq!(canonical(expected), expected, "{:?}", expected);
+	}
+
+	// Stricter rules are checked rather than mangling the program name.
+	let cmdline = units("\"app\tname\x07.exe\" a");
+	assert_eq!(
+		String::from_utf16(&canonicalize(&cmdline).unwrap()).unwrap(),
+		"\"app\tname\x07.exe\" a"
+	);
+	let error = canonicalize_with(&cmdline, QuotingRules::Legacy).unwrap_err();
+	assert_eq!((error.index(), error.position(), error.unit()), (0, 8, 7));
+}
+
+#[test]
+fn splice() {
+	use crate::splice;
+
+	let splice_str = |cmdline: &str, edits: &[(usize, Edit)]| -> String {
+		String::from_utf16(&splice(&units(cmdline), edits)).unwrap()
+	};
+	let cmdline = "app.exe  -v   --token=abc\t\"a b\"  ";
+
+	// Nothing is changed without edits, or with edits past the end.
+	assert_eq!(splice_str(cmdline, &[]), cmdline);
+	assert_eq!(
+		splice_str(cmdline, &[(4, Edit::Drop), (1, Edit::Keep)]),
+		cmdline
+	);
+
+	// Removing an argument removes the whitespace before it. Everything else is
+	// the same as it was.
+	let removed = splice_str(cmdline, &[(2, Edit::Drop)]);
+	assert_eq!(removed, "app.exe  -v\t\"a b\"  ");
+	assert_eq!(removed, [&cmdline[..11], &cmdline[25..]].concat());
+	let removed = splice_str(cmdline, &[(3, Edit::Drop)]);
+	assert_eq!(removed, "app.exe  -v   --token=abc  ");
+	assert_eq!(removed, [&cmdline[..25], &cmdline[31..]].concat());
+	assert_eq!(
+		splice_str(
+			cmdline,
+			&[(1, Edit::Drop), (2, Edit::Drop), (3, Edit::Drop)]
+		),
+		"app.exe  "
+	);
+
+	// Replacing the program name.
+	let replaced = splice_str(
+		cmdline,
+		&[(0, Edit::Replace(vec![units(r"C:\new dir\app.exe")]))],
+	);
+	assert_eq!(
+		replaced,
+		[r#""C:\new dir\app.exe""#, &cmdline[7..]].concat()
+	);
+	let replaced = splice_str(cmdline, &[(0, Edit::ReplaceRaw(units(r#""C:\app.exe""#)))]);
+	assert_eq!(replaced, [r#""C:\app.exe""#, &cmdline[7..]].concat());
+	// An argument that becomes the program name is quoted again and loses
+	// the whitespace before it.
+	assert_eq!(
+		splice_str(r#"app.exe  "a b"c\ d"#, &[(0, Edit::Drop)]),
+		r#""a bc\" d"#
+	);
+
+	// New arguments are separated by a single space.
+	assert_eq!(
+		splice_str(
+			cmdline,
+			&[(1, Edit::Insert(vec![units("x y"), units("z")]))]
+		),
+		"app.exe  -v \"x y\" z   --token=abc\t\"a b\"  "
+	);
+	assert_eq!(
+		splice_str(
+			cmdline,
+			&[(2, Edit::Replace(vec![units("--token"), units("")]))]
+		),
+		"app.exe  -v   --token \"\"\t\"a b\"  "
+	);
+	// Raw text is used as it is, and only the first edit for an index is used.
+	assert_eq!(
+		splice_str(
+			cmdline,
+			&[(2, Edit::ReplaceRaw(units("--token=***"))), (2, Edit::Drop)]
+		),
+		"app.exe  -v   --token=***\t\"a b\"  "
+	);
+	assert_eq!(
+		splice_str(cmdline, &[(2, Edit::ReplaceRaw(Vec::new()))]),
+		splice_str(cmdline, &[(2, Edit::Drop)])
+	);
+
+	// Leaving out every argument leaves nothing, not an empty program name.
+	assert_eq!(splice_str(" a ", &[(0, Edit::Drop), (1, Edit::Drop)]), "");
+	assert_eq!(splice_str("a ", &[(0, Edit::Drop)]), "");
+	// But an empty program name on its own is kept.
+	assert_eq!(splice_str("  a", &[(1, Edit::Drop)]), " ");
+	assert_eq!(splice_str("  a  b", &[(1, Edit::Drop)]), "  b");
+	// The command line ends at a null.
+	assert_eq!(splice_str("a b\0c", &[]), "a b");
+
+	// `rebuild` uses raw text as it is too.
+	let (rebuilt, args) = rebuild_with("app.exe a  b", |_, index| {
+		if index == 1 {
+			Edit::ReplaceRaw(units(r#""x y" z"#))
+		} else {
+			Edit::Keep
+		}
+	});
+	assert_eq!(rebuilt, r#"app.exe "x y" z b"#);
+	assert_eq!(args, ["app.exe", "x y", "z", "b"]);
+}
+
+// Append `extra` to `cmdline`, optionally with a new program name, returning
+// the new command line and its arguments.
+fn append_with(cmdline: &str, program: Option<&str>, extra: &[&str]) -> (String, Vec<String>) {
+	let appended = with_command_line(cmdline, || match program {
+		Some(program) => with_program_and_appended(program, extra),
+		None => with_appended(extra),
+	});
+	assert_eq!(
+		appended.iter().position(|&u| u == 0),
+		Some(appended.len() - 1)
+	);
+	let args = with_command_line_wide(&appended, || {
+		args_native()
+			.map(|arg| String::from_utf16(&arg.utf16_units().collect::<Vec<u16>>()).unwrap())
+			.collect()
+	});
+	let appended = String::from_utf16(&appended[..appended.len() - 1]).unwrap();
+	(appended, args)
+}
+
+#[test]
+fn appended() {
+	// The original arguments are copied exactly.
+	let cmdline = r#""C:\Program Files\app.exe"  a"b c"d	\\"x y" "" \a\\"#;
+	let (appended, args) = append_with(cmdline, None, &["--extra1", "a b"]);
+	assert_eq!(appended, [cmdline, r#" --extra1 "a b""#].concat());
+	assert_eq!(
+		args,
+		[
+			r"C:\Program Files\app.exe",
+			"ab cd",
+			r#"\x y"#,
+			"",
+			r"\a\\",
+			"--extra1",
+			"a b"
+		]
+	);
+
+	// Whitespace at the end isn't doubled.
+	let (appended, args) = append_with("app.exe a \t ", None, &["--extra1"]);
+	assert_eq!(appended, "app.exe a --extra1");
+	assert_eq!(args, ["app.exe", "a", "--extra1"]);
+
+	// No arguments after the program name.
+	for &cmdline in &["app.exe", "app.exe  "] {
+		let (appended, _) = append_with(cmdline, None, &["--extra1", "--extra2"]);
+		assert_eq!(appended, "app.exe --extra1 --extra2");
+	}
+	let (appended, _) = append_with("app.exe", None, &[] as &[&str]);
+	assert_eq!(appended, "app.exe");
+
+	// Extra arguments are quoted if they need to be.
+	let (appended, args) = append_with("app.exe", None, &["", r#"a"b"#, r"c d\"]);
+	assert_eq!(appended, r#"app.exe "" a\"b "c d\\""#);
+	assert_eq!(args, ["app.exe", "", r#"a"b"#, r"c d\"]);
+
+	// A quote left open is closed before anything is added.
+	let (appended, args) = append_with(r#"app.exe "a b\"#, None, &["--extra1"]);
+	assert_eq!(appended, r#"app.exe "a b\\" --extra1"#);
+	assert_eq!(args, ["app.exe", r"a b\", "--extra1"]);
+	let (appended, args) = append_with(r#""C:\app "#, None, &["--extra1"]);
+	assert_eq!(appended, r#""C:\app " --extra1"#);
+	assert_eq!(args, [r"C:\app ", "--extra1"]);
+
+	// An empty program name is kept.
+	let (appended, args) = append_with(" a", None, &["--extra1"]);
+	assert_eq!(appended, " a --extra1");
+	assert_eq!(args, ["", "a", "--extra1"]);
+	let (appended, args) = append_with("", None, &["--extra1"]);
+	assert_eq!(appended, r#""" --extra1"#);
+	assert_eq!(args, ["", "--extra1"]);
+}
+
+#[test]
+fn appended_with_program() {
+	let cmdline = r#"wrapper.exe  "a b"  c\"d "#;
+	let (appended, args) = append_with(cmdline, Some(r"C:\Program Files\tool.exe"), &["--extra1"]);
+	assert_eq!(
+		appended,
+		r#""C:\Program Files\tool.exe"  "a b"  c\"d --extra1"#
+	);
+	assert_eq!(
+		args,
+		[r"C:\Program Files\tool.exe", "a b", r#"c"d"#, "--extra1"]
+	);
+
+	// Only the program name.
+	for &cmdline in &["", "wrapper.exe", "\"wrapper.exe", " "] {
+		let (appended, args) = append_with(cmdline, Some("tool.exe"), &["--extra1"]);
+		assert_eq!(appended, "tool.exe --extra1", "{:?}", cmdline);
+		assert_eq!(args, ["tool.exe", "--extra1"]);
+	}
+}
@@ -0,0 +1,395 @@
+use super::with_command_line;
+use crate::{
+	ArgsNative, FallbackEncoding, ResponseFileArgs, ResponseFileEncoding, ResponseFileError,
+	ResponseFileRules,
+};
+use alloc::{format, string::String, vec::Vec};
+use std::{
+	env, fs,
+	path::PathBuf,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+// A response file in the temp directory that's removed when dropped.
+struct TempFile {
+	path: PathBuf,
+}
+impl TempFile {
+	fn new(contents: &[u8]) -> Self {
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+		let name = format!(
+			"winarg-{}-{}.rsp",
+			std::process::id(),
+			COUNTER.fetch_add(1, Ordering::Relaxed)
+		);
+		let path = env::temp_dir().join(name);
+		fs::write(&path, contents).unwrap();
+		Self { path }
+	}
+	fn write(&self, contents: &[u8]) {
+		fs::write(&self.path, contents).unwrap();
+	}
+	// The path as an `@file` argument.
+	fn arg(&self) -> String {
+		format!(r#""@{}""#, self.path.display())
+	}
+}
+impl Drop for TempFile {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+fn expand(cmdline: &str) -> Vec<Result<String, ResponseFileError>> {
+	expand_with(cmdline, ResponseFileRules::default())
+}
+fn expand_with(cmdline: &str, rules: ResponseFileRules) -> Vec<Result<String, ResponseFileError>> {
+	with_command_line(cmdline, || {
+		ArgsNative::from_env()
+			.expand_response_files_with(rules)
+			.map(|arg| arg.map(|arg| arg.into_string().unwrap()))
+			.collect()
+	})
+}
+fn chk(cmdline: &str, expected: &[&str]) {
+	chk_with(cmdline, ResponseFileRules::default(), expected)
+}
+fn chk_with(cmdline: &str, rules: ResponseFileRules, expected: &[&str]) {
+	let args: Vec<String> = expand_with(cmdline, rules)
+		.into_iter()
+		.map(Result::unwrap)
+		.collect();
+	assert_eq!(args, expected);
+}
+
+#[test]
+fn empty_files() {
+	let empty = TempFile::new(b"");
+	chk(&format!("EXE a {} b", empty.arg()), &["EXE", "a", "b"]);
+	let whitespace = TempFile::new(b" \t\r\n  \n\t");
+	chk(&format!("EXE {} b", whitespace.arg()), &["EXE", "b"]);
+}
+
+#[test]
+fn mixed_arguments() {
+	let file = TempFile::new(b"one \"two three\"\r\nfour\\\"five\n\n  six");
+	chk(
+		&format!("EXE a {} b {}", file.arg(), file.arg()),
+		&[
+			"EXE",
+			"a",
+			"one",
+			"two three",
+			r#"four"five"#,
+			"six",
+			"b",
+			"one",
+			"two three",
+			r#"four"five"#,
+			"six",
+		],
+	);
+}
+
+#[test]
+fn unclosed_quote_ends_with_line() {
+	let file = TempFile::new(b"\"a b\nc");
+	chk(&format!("EXE {}", file.arg()), &["EXE", "a b", "c"]);
+}
+
+#[test]
+fn literal_at() {
+	chk("EXE @@a @ @@ b", &["EXE", "@a", "@", "@", "b"]);
+	// The program name is never expanded.
+	chk("@EXE a", &["@EXE", "a"]);
+	// `@@` is also unescaped in files.
+	let file = TempFile::new(b"@@b @");
+	chk(&format!("EXE {}", file.arg()), &["EXE", "@b", "@"]);
+}
+
+#[test]
+fn encodings() {
+	let text = "a --name=Zürich \"😅 b\"";
+	let expected = ["EXE", "a", "--name=Zürich", "😅 b"];
+
+	let utf8 = TempFile::new(text.as_bytes());
+	chk(&format!("EXE {}", utf8.arg()), &expected);
+
+	let utf8_bom = TempFile::new(format!("\u{FEFF}{}", text).as_bytes());
+	chk(&format!("EXE {}", utf8_bom.arg()), &expected);
+
+	let utf16: Vec<u8> = "\u{FEFF}"
+		.encode_utf16()
+		.chain(text.encode_utf16())
+		.flat_map(u16::to_le_bytes)
+		.collect();
+	let utf16_le = TempFile::new(&utf16);
+	chk(&format!("EXE {}", utf16_le.arg()), &expected);
+
+	let utf16: Vec<u8> = "\u{FEFF}"
+		.encode_utf16()
+		.chain(text.encode_utf16())
+		.flat_map(u16::to_be_bytes)
+		.collect();
+	let utf16_be = TempFile::new(&utf16);
+	chk(&format!("EXE {}", utf16_be.arg()), &expected);
+
+	// Strict decoding gives the same result for valid UTF-8.
+	for file in [&utf8, &utf8_bom, &utf16_le, &utf16_be] {
+		let args: Vec<String> = with_command_line(&format!("EXE {}", file.arg()), || {
+			ArgsNative::from_env()
+				.expand_response_files()
+				.fallback_encoding(FallbackEncoding::Utf8Strict)
+				.map(|arg| arg.unwrap().into_string().unwrap())
+				.collect()
+		});
+		assert_eq!(args, expected);
+	}
+}
+
+#[test]
+fn ansi_encoding() {
+	extern "system" {
+		fn GetACP() -> u32;
+	}
+	// The ANSI code page is a system setting so this only tests Windows-1252.
+	if unsafe { GetACP() } != 1252 {
+		return;
+	}
+	let file = TempFile::new(b"a --name=Z\xFCrich");
+	let args: Vec<String> = with_command_line(&format!("EXE {}", file.arg()), || {
+		ArgsNative::from_env()
+			.expand_response_files()
+			.fallback_encoding(FallbackEncoding::Ansi)
+			.map(|arg| arg.unwrap().into_string().unwrap())
+			.collect()
+	});
+	assert_eq!(args, ["EXE", "a", "--name=Zürich"]);
+}
+
+#[test]
+fn decode_errors() {
+	// A truncated UTF-16 file.
+	let mut utf16: Vec<u8> = "\u{FEFF}a b"
+		.encode_utf16()
+		.flat_map(u16::to_le_bytes)
+		.collect();
+	utf16.pop();
+	let file = TempFile::new(&utf16);
+	let args = expand(&format!("EXE {} c", file.arg()));
+	assert_eq!(args.len(), 3);
+	let error = args[1].as_ref().unwrap_err();
+	assert_eq!(error.encoding(), Some(ResponseFileEncoding::Utf16Le));
+	assert!(error.io_error().is_none());
+	assert!(format!("{}", error).contains("UTF-16LE"));
+	assert_eq!(args[2].as_ref().unwrap(), "c");
+
+	// Invalid UTF-8 is replaced by default.
+	let file = TempFile::new(b"a --name=Z\xFCrich");
+	chk(
+		&format!("EXE {}", file.arg()),
+		&["EXE", "a", "--name=Z�rich"],
+	);
+	// But is an error when strict.
+	let args: Vec<_> = with_command_line(&format!("EXE {}", file.arg()), || {
+		ArgsNative::from_env()
+			.expand_response_files()
+			.fallback_encoding(FallbackEncoding::Utf8Strict)
+			.collect()
+	});
+	let error = args[1].as_ref().unwrap_err();
+	assert_eq!(error.encoding(), Some(ResponseFileEncoding::Utf8));
+	assert!(format!("{}", error).contains("byte 10"));
+
+	// The offset includes the BOM.
+	let file = TempFile::new(b"\xEF\xBB\xBFa --name=Z\xFCrich");
+	let args: Vec<_> = with_command_line(&format!("EXE {}", file.arg()), || {
+		ArgsNative::from_env()
+			.expand_response_files()
+			.fallback_encoding(FallbackEncoding::Utf8Strict)
+			.collect()
+	});
+	let error = args[1].as_ref().unwrap_err();
+	assert_eq!(error.encoding(), Some(ResponseFileEncoding::Utf8Bom));
+	assert!(format!("{}", error).contains("byte 13"));
+
+	// The offset is kept when converted to `winarg::Error`.
+	let error = crate::Error::from(args.into_iter().nth(1).unwrap().unwrap_err());
+	assert_eq!(error.offset(), Some(13));
+	assert_eq!(error.index(), None);
+	assert!(format!("{}", error).contains("byte 13"));
+}
+
+#[test]
+fn missing_file() {
+	let path = env::temp_dir().join("winarg-does-not-exist.rsp");
+	let args = expand(&format!(r#"EXE a "@{}" b"#, path.display()));
+	assert_eq!(args.len(), 4);
+	let error = args[2].as_ref().unwrap_err();
+	assert_eq!(error.path(), path);
+	assert_eq!(
+		error.io_error().unwrap().kind(),
+		std::io::ErrorKind::NotFound
+	);
+	assert!(!error.is_cycle() && !error.is_too_deep());
+	assert!(format!("{}", error).contains("winarg-does-not-exist.rsp"));
+	assert_eq!(args[3].as_ref().unwrap(), "b");
+}
+
+#[test]
+fn windows_rules() {
+	let file = TempFile::new(
+		br#"C:\dir\file.obj "C:\dir name\\" \\server\share\a.obj
+"C:\dir name\" \\server\share\a.obj
+'single quotes' "" a""b"#,
+	);
+	chk_with(
+		&format!("EXE {}", file.arg()),
+		ResponseFileRules::WindowsCommandLine,
+		&[
+			"EXE",
+			r"C:\dir\file.obj",
+			r"C:\dir name\",
+			r"\\server\share\a.obj",
+			// An escaped quote doesn't close the quoted string.
+			r#"C:\dir name" \\server\share\a.obj"#,
+			"'single",
+			"quotes'",
+			"",
+			"ab",
+		],
+	);
+}
+
+#[test]
+fn gnu_rules() {
+	let file = TempFile::new(
+		br#"C:\dir\file.obj 'C:\dir name\' "C:\dir name\file.obj" \\server\share\a.obj
+it\'s \"quoted\" "multi
+line" '' a"b c"d 'say "hi"'"#,
+	);
+	chk_with(
+		&format!("EXE {}", file.arg()),
+		ResponseFileRules::Gnu,
+		&[
+			"EXE",
+			r"C:\dir\file.obj",
+			r"C:\dir name\",
+			r"C:\dir name\file.obj",
+			r"\\server\share\a.obj",
+			"it's",
+			r#""quoted""#,
+			"multi\nline",
+			"",
+			"ab cd",
+			r#"say "hi""#,
+		],
+	);
+	let empty = TempFile::new(b" \r\n\t ");
+	chk_with(
+		&format!("EXE {} a", empty.arg()),
+		ResponseFileRules::Gnu,
+		&["EXE", "a"],
+	);
+}
+
+#[test]
+fn nested() {
+	let inner = TempFile::new(b"c d");
+	let outer = TempFile::new(format!("a {} b", inner.arg()).as_bytes());
+	chk(
+		&format!("EXE {} {}", outer.arg(), inner.arg()),
+		&["EXE", "a", "c", "d", "b", "c", "d"],
+	);
+}
+
+#[test]
+fn self_inclusion() {
+	let file = TempFile::new(b"");
+	file.write(format!("a {} b", file.arg()).as_bytes());
+	let args = expand(&format!("EXE {} c", file.arg()));
+	assert_eq!(args.len(), 5);
+	assert_eq!(args[1].as_ref().unwrap(), "a");
+	let error = args[2].as_ref().unwrap_err();
+	assert!(error.is_cycle());
+	assert_eq!(error.chain(), [file.path.clone(), file.path.clone()]);
+	// The rest of the file is still read.
+	assert_eq!(args[3].as_ref().unwrap(), "b");
+	assert_eq!(args[4].as_ref().unwrap(), "c");
+}
+
+#[test]
+fn cycle() {
+	let a = TempFile::new(b"");
+	let b = TempFile::new(format!("b {}", a.arg()).as_bytes());
+	a.write(format!("a {}", b.arg()).as_bytes());
+	let args = expand(&format!("EXE {}", a.arg()));
+	assert_eq!(args.len(), 4);
+	let error = args[3].as_ref().unwrap_err();
+	assert!(error.is_cycle());
+	assert_eq!(
+		error.chain(),
+		[a.path.clone(), b.path.clone(), a.path.clone()]
+	);
+	let message = format!("{}", error);
+	assert!(message.contains(&format!(
+		"`{}` -> `{}` -> `{}`",
+		a.path.display(),
+		b.path.display(),
+		a.path.display()
+	)));
+}
+
+// Creates a chain of `depth` response files that each include the next.
+fn nested_files(depth: usize) -> Vec<TempFile> {
+	let mut files: Vec<TempFile> = Vec::new();
+	for i in 0..depth {
+		let contents = match files.last() {
+			Some(next) => format!("{} {}", i, next.arg()),
+			None => format!("{}", i),
+		};
+		files.push(TempFile::new(contents.as_bytes()));
+	}
+	files.reverse();
+	files
+}
+
+#[test]
+fn depth_limit() {
+	let depth = ResponseFileArgs::DEFAULT_MAX_DEPTH;
+	let files = nested_files(depth);
+	let args = expand(&format!("EXE {}", files[0].arg()));
+	assert_eq!(args.len(), depth + 1);
+	assert!(args.iter().all(Result::is_ok));
+
+	let files = nested_files(depth + 1);
+	let args = expand(&format!("EXE {} a", files[0].arg()));
+	assert_eq!(args.len(), depth + 3);
+	let error = args[depth + 1].as_ref().unwrap_err();
+	assert!(error.is_too_deep());
+	assert_eq!(error.chain().len(), depth + 1);
+	assert_eq!(error.path(), files[depth].path);
+	assert_eq!(args[depth + 2].as_ref().unwrap(), "a");
+}
+
+#[test]
+fn max_depth() {
+	let files = nested_files(2);
+	let args: Vec<_> = with_command_line(&format!("EXE {}", files[0].arg()), || {
+		ArgsNative::from_env()
+			.expand_response_files()
+			.max_depth(1)
+			.collect()
+	});
+	assert_eq!(args.len(), 3);
+	assert!(args[2].as_ref().unwrap_err().is_too_deep());
+
+	let args: Vec<_> = with_command_line(&format!("EXE {}", files[0].arg()), || {
+		ArgsNative::from_env()
+			.expand_response_files()
+			.max_depth(0)
+			.collect()
+	});
+	assert_eq!(args.len(), 2);
+	assert!(args[1].as_ref().unwrap_err().is_too_deep());
+}
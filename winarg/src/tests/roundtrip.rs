@@ -0,0 +1,210 @@
+// Quick round trip property tests that don't need to start processes.
+//
+// Each run uses a new seed, which is printed on failure along with the
+// smallest input found that still fails. Set `WINARG_SEED` to run with a
+// particular seed. Miri always uses the same seed and fewer cases.
+
+use super::{std, String, Vec};
+use crate::{
+	arg_spans_of, is_representable_with, quote::quote, quoted_len, unquote_one_with, ParserRules,
+	QuotingRules,
+};
+
+// Miri is much slower so it only tries a few.
+const CASES: usize = if cfg!(miri) { 20 } else { 2000 };
+
+// The code units that matter to the parser, with a few that don't.
+const UNITS: &[u16] = &[
+	b' ' as u16,
+	b'\t' as u16,
+	b'"' as u16,
+	b'"' as u16,
+	b'\\' as u16,
+	b'\\' as u16,
+	b'a' as u16,
+	b'b' as u16,
+	b'\n' as u16,
+	0xE9,
+	0xD800,
+	0xDC00,
+];
+
+// SplitMix64.
+pub(super) struct Rng(pub(super) u64);
+impl Rng {
+	fn next(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+	pub(super) fn below(&mut self, n: usize) -> usize {
+		(self.next() % n as u64) as usize
+	}
+	fn units(&mut self, max_len: usize) -> Vec<u16> {
+		let len = self.below(max_len + 1);
+		(0..len).map(|_| UNITS[self.below(UNITS.len())]).collect()
+	}
+}
+
+pub(super) fn seed() -> u64 {
+	match std::env::var("WINARG_SEED") {
+		Ok(seed) => seed.parse().expect("WINARG_SEED must be a u64"),
+		// Miri can't read the clock.
+		Err(_) if cfg!(miri) => 0,
+		Err(_) => std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.map_or(0, |d| d.as_nanos() as u64),
+	}
+}
+
+// Remove and simplify code units for as long as `fails` still fails.
+fn shrink(mut args: Vec<Vec<u16>>, fails: impl Fn(&[Vec<u16>]) -> bool) -> Vec<Vec<u16>> {
+	let mut changed = true;
+	while changed {
+		changed = false;
+		let mut candidates = Vec::new();
+		for i in 0..args.len() {
+			let mut fewer = args.clone();
+			fewer.remove(i);
+			candidates.push(fewer);
+			for j in 0..args[i].len() {
+				let mut shorter = args.clone();
+				shorter[i].remove(j);
+				candidates.push(shorter);
+				if args[i][j] != b'a' as u16 {
+					let mut simpler = args.clone();
+					simpler[i][j] = b'a' as u16;
+					candidates.push(simpler);
+				}
+			}
+		}
+		if let Some(smaller) = candidates.into_iter().find(|c| fails(c)) {
+			args = smaller;
+			changed = true;
+		}
+	}
+	args
+}
+
+fn check(seed: u64, input: Vec<Vec<u16>>, fails: impl Fn(&[Vec<u16>]) -> bool) {
+	if fails(&input) {
+		let input = shrink(input, &fails);
+		let lossy: Vec<String> = input.iter().map(|u| String::from_utf16_lossy(u)).collect();
+		panic!(
+			"failed with seed {}; smallest input: {:?} ({:x?})",
+			seed, lossy, input
+		);
+	}
+}
+
+fn join(args: &[Vec<u16>]) -> Vec<u16> {
+	let mut cmdline = Vec::new();
+	quote(args, |u| cmdline.push(u));
+	cmdline
+}
+
+fn parse(cmdline: &[u16], rules: ParserRules) -> Vec<Vec<u16>> {
+	arg_spans_of(cmdline)
+		.enumerate()
+		.map(|(index, span)| {
+			let (arg, used) = unquote_one_with(&cmdline[span.clone()], index == 0, rules);
+			assert_eq!(used, span.len());
+			arg.collect()
+		})
+		.collect()
+}
+
+// Arguments that can be written to a command line are parsed back unchanged,
+// with either version of the parsing rules.
+fn join_fails(args: &[Vec<u16>]) -> bool {
+	for &quoting in &[QuotingRules::Modern, QuotingRules::Legacy] {
+		if is_representable_with(args, quoting).is_err() {
+			continue;
+		}
+		let cmdline = join(args);
+		if quoted_len(args) != cmdline.len() {
+			return true;
+		}
+		#[cfg(feature = "alloc")]
+		if let Some((program, rest)) = args.split_first() {
+			let mut builder = crate::CommandLineBuilder::new(program).unwrap();
+			builder.args(rest).unwrap();
+			if builder.as_slice() != cmdline.as_slice() {
+				return true;
+			}
+		}
+		for &rules in &[ParserRules::Modern, ParserRules::Pre2008] {
+			if parse(&cmdline, rules) != args {
+				return true;
+			}
+		}
+	}
+	false
+}
+
+// Parsing any command line and joining the arguments gives a command line
+// that's parsed into the same arguments, and joined the same way again.
+fn rejoin_fails(cmdline: &[u16]) -> bool {
+	let args = parse(cmdline, ParserRules::Modern);
+	let joined = join(&args);
+	let reparsed = parse(&joined, ParserRules::Modern);
+	#[cfg(feature = "alloc")]
+	if crate::canonicalize(cmdline).as_ref() != Ok(&joined) {
+		return true;
+	}
+	reparsed != args || join(&reparsed) != joined
+}
+
+#[test]
+fn join_then_parse() {
+	let seed = seed();
+	let mut rng = Rng(seed);
+	for _ in 0..CASES {
+		let count = 1 + rng.below(5);
+		let mut args: Vec<Vec<u16>> = (0..count).map(|_| rng.units(6)).collect();
+		// Sometimes include an argument that can't be represented.
+		if rng.below(20) == 0 {
+			let arg = rng.below(count);
+			let at = rng.below(args[arg].len() + 1);
+			args[arg].insert(at, 0);
+		}
+		check(seed, args, join_fails);
+	}
+}
+
+#[test]
+fn parse_then_join() {
+	let seed = seed();
+	let mut rng = Rng(seed);
+	for _ in 0..CASES {
+		let cmdline = rng.units(24);
+		check(seed, [cmdline].into(), |input| match input {
+			[cmdline] => rejoin_fails(cmdline),
+			// Shrinking may remove the command line.
+			_ => false,
+		});
+	}
+}
+
+#[test]
+fn shrinks_to_minimal_input() {
+	// A deliberately wrong property shows the shrinking works.
+	let fails = |args: &[Vec<u16>]| args.iter().any(|arg| arg.contains(&(b'"' as u16)));
+	let args = [
+		"EXE".encode_utf16().collect(),
+		"ab \"cd\\".encode_utf16().collect(),
+	]
+	.into();
+	let shrunk = shrink(args, fails);
+	assert_eq!(shrunk, [[b'"' as u16]]);
+	let message = std::panic::catch_unwind(|| check(7, shrunk, fails))
+		.unwrap_err()
+		.downcast::<String>()
+		.unwrap();
+	assert_eq!(
+		*message,
+		r#"failed with seed 7; smallest input: ["\""] ([[22]])"#
+	);
+}
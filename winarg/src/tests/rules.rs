@@ -0,0 +1,268 @@
+// Tests for how `ParserRules` relate to each other, and for
+// `LeadingWhitespace`.
+//
+// The rules only differ in how they read quotes. `TABLE` pins down how each of
+// them parses inputs that are known to differ, along with some that look
+// like they might but don't. The rows come from
+// https://daviddeley.com/autohotkey/parameters/parameters.htm#WINCRULESEX and
+// from comparing with `crt::split_pre_2008` in the testing crate. To add a
+// newly found divergence, add a row.
+
+use super::{chk, units, vec, with_command_line, String, Vec};
+use crate::{arg_spans_of, unquote_one_with, GroupedToken, LeadingWhitespace, ParserRules, Token};
+
+// A command line and the arguments each of the rules parses it into.
+struct Case {
+	cmdline: &'static str,
+	modern: &'static [&'static str],
+	pre_2008: &'static [&'static str],
+}
+
+const TABLE: &[Case] = &[
+	// `""` in quotes is removed instead of being a literal quote.
+	Case {
+		cmdline: r#"EXE "a b c"""#,
+		modern: &["EXE", r#"a b c""#],
+		pre_2008: &["EXE", "a b c"],
+	},
+	Case {
+		cmdline: r#"EXE """CallMeIshmael"""  b  c"#,
+		modern: &["EXE", r#""CallMeIshmael""#, "b", "c"],
+		pre_2008: &["EXE", "CallMeIshmael", "b", "c"],
+	},
+	Case {
+		cmdline: r#"EXE """"Call Me Ishmael"" b c"#,
+		modern: &["EXE", r#""Call"#, "Me", "Ishmael", "b", "c"],
+		pre_2008: &["EXE", "Call", "Me", "Ishmael", "b", "c"],
+	},
+	Case {
+		cmdline: r#"EXE "this is """all""" in the same argument""#,
+		modern: &["EXE", r#"this is "all" in the same argument"#],
+		pre_2008: &["EXE", "this is all in the same argument"],
+	},
+	Case {
+		cmdline: r#"EXE "a""b""#,
+		modern: &["EXE", r#"a"b"#],
+		pre_2008: &["EXE", "ab"],
+	},
+	Case {
+		cmdline: r#"EXE "" """"#,
+		modern: &["EXE", "", r#"""#],
+		pre_2008: &["EXE", "", ""],
+	},
+	Case {
+		cmdline: r#"EXE """\"#,
+		modern: &["EXE", r#""\"#],
+		pre_2008: &["EXE", r"\"],
+	},
+	Case {
+		cmdline: r#"EXE a""" b"#,
+		modern: &["EXE", r#"a" b"#],
+		pre_2008: &["EXE", "a b"],
+	},
+	Case {
+		cmdline: r#"EXE " "" c"#,
+		modern: &["EXE", r#" " c"#],
+		pre_2008: &["EXE", "  c"],
+	},
+	// Outside quotes, `""` is an empty pair of quotes under both.
+	Case {
+		cmdline: r#"EXE a""b"#,
+		modern: &["EXE", "ab"],
+		pre_2008: &["EXE", "ab"],
+	},
+	// Escaped quotes aren't affected.
+	Case {
+		cmdline: r#"EXE "a\"b" c\\"d e""#,
+		modern: &["EXE", r#"a"b"#, r"c\d e"],
+		pre_2008: &["EXE", r#"a"b"#, r"c\d e"],
+	},
+	Case {
+		cmdline: r#"EXE "a\"" b"#,
+		modern: &["EXE", r#"a""#, "b"],
+		pre_2008: &["EXE", r#"a""#, "b"],
+	},
+	// The program name has no special cases for quotes.
+	Case {
+		cmdline: r#""EXE """for""" check"#,
+		modern: &["EXE for check"],
+		pre_2008: &["EXE for check"],
+	},
+	Case {
+		cmdline: "EXE \"a\tb\" c",
+		modern: &["EXE", "a\tb", "c"],
+		pre_2008: &["EXE", "a\tb", "c"],
+	},
+];
+
+#[test]
+fn table() {
+	for case in TABLE {
+		for &(rules, parts) in &[
+			(ParserRules::Modern, case.modern),
+			(ParserRules::Pre2008, case.pre_2008),
+		] {
+			let mut args = vec![String::new()];
+			with_command_line(case.cmdline, || {
+				for token in crate::Parser().with_rules(rules) {
+					match token {
+						Token::NextArg => args.push(String::new()),
+						token => args.last_mut().unwrap().extend(token.as_char()),
+					}
+				}
+			});
+			assert_eq!(args, parts, "{:?} under {:?}", case.cmdline, rules);
+			assert_eq!(
+				parse(&units(case.cmdline), rules),
+				parts,
+				"{:?} under {:?}",
+				case.cmdline,
+				rules
+			);
+		}
+		// The default is the modern rules.
+		chk(case.cmdline, case.modern);
+	}
+}
+
+// Every command line up to 7 code units long (4 under Miri) made from these. The rules can
+// only differ where there's a `""`, so every other command line must be parsed
+// the same way by all of them. That includes every command line without quotes.
+#[test]
+fn only_double_quotes_differ() {
+	const UNITS: [u16; 5] = [
+		b'"' as u16,
+		b'\\' as u16,
+		b' ' as u16,
+		b'\t' as u16,
+		b'a' as u16,
+	];
+	let mut cmdline = Vec::new();
+	let mut differ = 0;
+	let max_len = if cfg!(miri) { 4 } else { 7 };
+	for len in 0..=max_len {
+		for n in 0..UNITS.len().pow(len) {
+			cmdline.clear();
+			cmdline.extend((0..len).map(|i| UNITS[n / UNITS.len().pow(i) % UNITS.len()]));
+			let modern = parse(&cmdline, ParserRules::Modern);
+			if parse(&cmdline, ParserRules::Pre2008) != modern {
+				assert!(
+					cmdline.windows(2).any(|w| w == [b'"' as u16; 2]),
+					"{:?}",
+					String::from_utf16_lossy(&cmdline)
+				);
+				differ += 1;
+			}
+		}
+	}
+	assert!(differ > 0);
+}
+
+#[test]
+fn leading_whitespace() {
+	let cases: &[(&str, &[&str], &[&str])] = &[
+		(" test", &["", "test"], &["test"]),
+		("\t\t", &[""], &[]),
+		(" \t\"a b\" c", &["", "a b", "c"], &["a b", "c"]),
+		// The first word is read as the program name, without escapes.
+		(r#"  C:\dir\" a"#, &["", r#"C:\dir""#, "a"], &[r"C:\dir\ a"]),
+		// Command lines without leading whitespace are the same either way.
+		("", &[], &[]),
+		(r#"EXE "a b" c"#, &["EXE", "a b", "c"], &["EXE", "a b", "c"]),
+		("EXE  ", &["EXE"], &["EXE"]),
+	];
+	for &(cmdline, empty_arg0, skip) in cases {
+		for &(leading, parts) in &[
+			(LeadingWhitespace::EmptyArg0, empty_arg0),
+			(LeadingWhitespace::SkipToFirstToken, skip),
+		] {
+			let mut args = Vec::new();
+			with_command_line(cmdline, || {
+				let parser = crate::Parser().with_leading_whitespace(leading);
+				for token in parser.grouped() {
+					match token {
+						GroupedToken::ArgStart { .. } => args.push(String::new()),
+						GroupedToken::Unit(unit) => args
+							.last_mut()
+							.unwrap()
+							.push(char::from_u32(unit.get().into()).unwrap()),
+					}
+				}
+			});
+			assert_eq!(args, parts, "{:?} with {:?}", cmdline, leading);
+
+			let wide = units(cmdline);
+			let spanned: Vec<String> = arg_spans_of(&wide)
+				.with_leading_whitespace(leading)
+				.enumerate()
+				.map(|(index, span)| {
+					let (arg, _) = unquote_one_with(&wide[span], index == 0, ParserRules::Modern);
+					String::from_utf16_lossy(&arg.collect::<Vec<u16>>())
+				})
+				.collect();
+			assert_eq!(spanned, parts, "{:?} with {:?}", cmdline, leading);
+		}
+		// The default is the C runtime's behaviour.
+		chk(cmdline, empty_arg0);
+	}
+}
+
+fn parse(cmdline: &[u16], rules: ParserRules) -> Vec<String> {
+	arg_spans_of(cmdline)
+		.enumerate()
+		.map(|(index, span)| {
+			let (arg, _) = unquote_one_with(&cmdline[span], index == 0, rules);
+			String::from_utf16_lossy(&arg.collect::<Vec<u16>>())
+		})
+		.collect()
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn check_portability() {
+	use crate::{check_portability, PortabilityWarning};
+
+	// Every row of the table that differs is reported, at the arguments that
+	// differ.
+	for case in TABLE {
+		let cmdline = units(case.cmdline);
+		let mut differ = Vec::new();
+		for (index, span) in arg_spans_of(&cmdline).enumerate() {
+			let (modern, pre_2008) = (case.modern.get(index), case.pre_2008.get(index));
+			if modern != pre_2008 {
+				differ.push(PortabilityWarning::Argument {
+					index,
+					span,
+					modern: units(modern.unwrap()),
+					pre_2008: units(pre_2008.unwrap()),
+				});
+			}
+		}
+		assert_eq!(check_portability(&cmdline), differ, "{:?}", case.cmdline);
+		assert_eq!(differ.is_empty(), case.modern == case.pre_2008);
+	}
+
+	let warnings = check_portability(&units(r#""C:\a b\app.exe" x "a""b" """#));
+	assert_eq!(
+		warnings,
+		[PortabilityWarning::Argument {
+			index: 2,
+			span: 19..25,
+			modern: units(r#"a"b"#),
+			pre_2008: units("ab"),
+		}]
+	);
+	// `""` outside quotes is an empty argument under both rules, and the
+	// program name has no literal quotes.
+	assert!(check_portability(&units(r#""a""b" "" c"" a""b"#)).is_empty());
+
+	// An empty command line, including one that starts with a null.
+	for cmdline in [&[][..], &[0, b'a' as u16]] {
+		assert_eq!(
+			check_portability(cmdline),
+			[PortabilityWarning::EmptyCommandLine]
+		);
+	}
+	// Whitespace isn't empty.
+	assert!(check_portability(&units(" ")).is_empty());
+}
@@ -0,0 +1,101 @@
+// Tests for service command lines.
+
+use super::{units, Vec};
+use crate::{check_image_path, DiagnosticKind};
+
+fn kinds(image_path: &str) -> Vec<DiagnosticKind> {
+	check_image_path(&units(image_path))
+		.into_iter()
+		.map(|d| d.kind)
+		.collect()
+}
+
+#[test]
+fn unquoted_path_with_spaces() {
+	let image_path = units(r"C:\Program Files\My Service\svc.exe -k netsvcs");
+	let diagnostics = check_image_path(&image_path);
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].kind, DiagnosticKind::UnquotedPathWithSpaces);
+	assert_eq!(diagnostics[0].span, 0..35);
+
+	let vulnerable = [
+		r"C:\Program Files\svc.exe",
+		"C:\\Program Files\\svc.EXE\t-k",
+		r"C:\Program Files\svc.Exe --config C:\svc.exe",
+		r"C:\My Files\a.exe\svc.exe",
+	];
+	for image_path in vulnerable {
+		assert_eq!(
+			kinds(image_path),
+			[DiagnosticKind::UnquotedPathWithSpaces],
+			"{:?}",
+			image_path
+		);
+	}
+	// The span ends at the first `.exe` that's followed by whitespace.
+	let diagnostics = check_image_path(&units(r"C:\My Files\a.exe\svc.exe"));
+	assert_eq!(diagnostics[0].span, 0..25);
+	let diagnostics = check_image_path(&units(r"C:\My Files\a.exe b\svc.exe"));
+	assert_eq!(diagnostics[0].span, 0..17);
+}
+
+#[test]
+fn safe_image_paths() {
+	let safe = [
+		"",
+		"svc.exe",
+		r"C:\Windows\System32\svchost.exe -k netsvcs",
+		r"%SystemRoot%\System32\svchost.exe -k LocalService -p",
+		r#""C:\Program Files\My Service\svc.exe" -k"#,
+		r#""C:\Program Files\My Service\svc.exe""#,
+		r"C:\Program Files\svc",
+		"C:\\svc.exe \"C:\\Program Files\\data.exe\"",
+		r"C:\svc.exe C:\Program Files\data.exe",
+		"\"C:\\Program Files\\svc.exe\"\0C:\\Program Files\\svc.exe",
+	];
+	for image_path in safe {
+		assert_eq!(kinds(image_path), [], "{:?}", image_path);
+	}
+}
+
+#[test]
+fn other_diagnostics() {
+	// Already quoted, but the quote is never closed.
+	assert_eq!(
+		kinds(r#""C:\Program Files\svc.exe -k"#),
+		[DiagnosticKind::UnterminatedQuote]
+	);
+	assert_eq!(
+		kinds(r#"C:\Program Files\svc.exe "C:\dir\""#),
+		[
+			DiagnosticKind::UnquotedPathWithSpaces,
+			DiagnosticKind::TrailingBackslashesBeforeEnd,
+			DiagnosticKind::UnterminatedQuote
+		]
+	);
+}
+
+#[cfg(all(windows, feature = "std"))]
+#[test]
+fn service_image_path() {
+	use crate::{service_image_path, Error};
+	use std::path::Path;
+
+	let image_path = service_image_path(Path::new(r"C:\Program Files\svc.exe"), ["-k", "a b", ""]);
+	let image_path = image_path.unwrap();
+	assert_eq!(
+		image_path,
+		units("\"C:\\Program Files\\svc.exe\" -k \"a b\" \"\"\0")
+	);
+	assert_eq!(check_image_path(&image_path), []);
+
+	// Paths without spaces don't need quotes.
+	let image_path = service_image_path(Path::new(r"C:\svc\svc.exe"), None::<&str>).unwrap();
+	assert_eq!(image_path, units("C:\\svc\\svc.exe\0"));
+
+	let error = service_image_path(Path::new(r#"C:\Program "Files"\svc.exe"#), ["a"]).unwrap_err();
+	assert!(matches!(error, Error::Unrepresentable(_)));
+	assert_eq!((error.index(), error.offset()), (Some(0), Some(11)));
+	let error = service_image_path(Path::new(r"C:\svc.exe"), ["a", "b\0c"]).unwrap_err();
+	assert_eq!((error.index(), error.unit()), (Some(2), Some(0)));
+}
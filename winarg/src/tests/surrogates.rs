@@ -0,0 +1,180 @@
+// Isolated surrogates are valid in Windows command lines but not in Unicode.
+// These check each API handles them the way it's documented to.
+
+use super::{with_command_line_wide, String, Vec};
+use crate::{ArgsNative, Argument};
+
+const HIGH: u16 = 0xD800;
+const LOW: u16 = 0xDC00;
+const QUOTE: u16 = b'"' as u16;
+const A: u16 = b'a' as u16;
+
+// Parses `EXE ` followed by `rest` and returns the first argument after `EXE`.
+fn arg1(rest: &[u16]) -> Argument {
+	let cmdline: Vec<u16> = "EXE ".encode_utf16().chain(rest.iter().copied()).collect();
+	with_command_line_wide(&cmdline, || {
+		let mut args = ArgsNative::from_env();
+		assert!(args.next().unwrap() == "EXE");
+		args.next().unwrap()
+	})
+}
+
+// Checks every way of reading `arg`.
+fn chk(arg: &Argument, units: &[u16], scalars: &str, code_points: &[u32]) {
+	assert_eq!(arg.utf16_units().collect::<Vec<u16>>(), units);
+	assert_eq!(arg.scalars().collect::<String>(), scalars);
+	assert_eq!(arg.code_points().collect::<Vec<u32>>(), code_points);
+	// Comparing with code units is exact.
+	assert!(*arg == units);
+	assert!(units == *arg);
+	// A `&str` can't contain isolated surrogates so it never matches, not even
+	// the lossy version.
+	assert!(*arg != scalars);
+	assert!(scalars != *arg);
+	// WTF-8 is lossless, the same as `OsStr` uses.
+	#[cfg(all(windows, feature = "std"))]
+	{
+		use super::std::ffi::OsString;
+		use std::os::windows::ffi::OsStringExt;
+		let os = OsString::from_wide(units);
+		assert_eq!(arg.wtf8_units().collect::<Vec<u8>>(), os.as_encoded_bytes());
+	}
+}
+
+#[test]
+fn lone_high_surrogate() {
+	let arg = arg1(&[A, HIGH, A]);
+	chk(&arg, &[A, HIGH, A], "a\u{FFFD}a", &[0x61, 0xD800, 0x61]);
+}
+
+#[test]
+fn lone_low_surrogate() {
+	let arg = arg1(&[A, LOW, A]);
+	chk(&arg, &[A, LOW, A], "a\u{FFFD}a", &[0x61, 0xDC00, 0x61]);
+}
+
+#[test]
+fn reversed_pair() {
+	// A low surrogate followed by a high surrogate isn't a pair.
+	let arg = arg1(&[LOW, HIGH]);
+	chk(&arg, &[LOW, HIGH], "\u{FFFD}\u{FFFD}", &[0xDC00, 0xD800]);
+}
+
+#[test]
+fn next_to_quotes() {
+	let arg = arg1(&[QUOTE, HIGH, QUOTE, A]);
+	chk(&arg, &[HIGH, A], "\u{FFFD}a", &[0xD800, 0x61]);
+
+	// A quoted space is kept and doesn't end the argument.
+	let arg = arg1(&[LOW, QUOTE, b' ' as u16, LOW, QUOTE]);
+	chk(
+		&arg,
+		&[LOW, b' ' as u16, LOW],
+		"\u{FFFD} \u{FFFD}",
+		&[0xDC00, 0x20, 0xDC00],
+	);
+}
+
+#[test]
+fn final_unit() {
+	let arg = arg1(&[A, HIGH]);
+	chk(&arg, &[A, HIGH], "a\u{FFFD}", &[0x61, 0xD800]);
+	let arg = arg1(&[HIGH]);
+	chk(&arg, &[HIGH], "\u{FFFD}", &[0xD800]);
+}
+
+#[test]
+fn valid_pair() {
+	// For comparison, a valid pair is a single scalar value.
+	let arg = arg1(&[0xD83D, 0xDE05]);
+	assert_eq!(arg.scalars().collect::<String>(), "😅");
+	assert_eq!(arg.code_points().collect::<Vec<u32>>(), [0x1F605]);
+	assert!(arg == "😅");
+}
+
+#[test]
+fn replacement() {
+	let arg = arg1(&[A, HIGH, 0xD83D, 0xDE05, LOW, A]);
+	let scalars = |replacement| arg.scalars_with(replacement).collect::<String>();
+	assert_eq!(scalars(Some('\u{2370}')), "a\u{2370}😅\u{2370}a");
+	// Or dropped entirely.
+	assert_eq!(scalars(None), "a😅a");
+	// The default is unchanged.
+	assert_eq!(scalars(Some('\u{FFFD}')), arg.scalars().collect::<String>());
+	assert_eq!(arg.scalars().collect::<String>(), "a\u{FFFD}😅\u{FFFD}a");
+	#[cfg(feature = "alloc")]
+	{
+		assert_eq!(arg.to_string_lossy_with(Some('?')), "a?😅?a");
+		assert_eq!(arg.to_string_lossy_with(None), "a😅a");
+	}
+
+	let cmdline = [HIGH, b' ' as u16, A, LOW];
+	with_command_line_wide(&cmdline, || {
+		let list = |replacement| crate::null_separated_list_with(replacement).collect::<String>();
+		assert_eq!(list(Some('\u{2370}')), "\u{2370}\0a\u{2370}");
+		assert_eq!(list(None), "\0a");
+		assert_eq!(
+			list(Some('\u{FFFD}')),
+			crate::null_separated_list().collect::<String>()
+		);
+	});
+}
+
+#[test]
+fn wtf8() {
+	let wtf8 = |rest: &[u16]| arg1(rest).wtf8_units().collect::<Vec<u8>>();
+	// Lone surrogates are encoded as if they were scalar values.
+	assert_eq!(wtf8(&[HIGH]), [0xED, 0xA0, 0x80]);
+	assert_eq!(wtf8(&[A, LOW]), [0x61, 0xED, 0xB0, 0x80]);
+	assert_eq!(wtf8(&[0xDBFF, A]), [0xED, 0xAF, 0xBF, 0x61]);
+	// A low surrogate then a high surrogate isn't a pair.
+	assert_eq!(wtf8(&[LOW, HIGH]), [0xED, 0xB0, 0x80, 0xED, 0xA0, 0x80]);
+	// A pair is the scalar value it encodes, so valid text is UTF-8.
+	assert_eq!(wtf8(&[0xD83D, 0xDE05]), "😅".as_bytes());
+	let text = "a\u{7F}\u{80}é\u{7FF}\u{800}€\u{FFFF}\u{10000}\u{10FFFF}";
+	let units: Vec<u16> = text.encode_utf16().collect();
+	assert_eq!(wtf8(&units), text.as_bytes());
+	// An empty argument has no bytes.
+	assert_eq!(wtf8(&[QUOTE, QUOTE]), [0_u8; 0]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn from_wtf8() {
+	use crate::from_wtf8;
+
+	// Every argument is decoded back to the code units it had.
+	let text: Vec<u16> = "a\u{7F}\u{80}é\u{7FF}\u{800}€\u{FFFF}\u{10000}\u{10FFFF}"
+		.encode_utf16()
+		.collect();
+	let cases: [&[u16]; 6] = [
+		&[HIGH],
+		&[A, LOW],
+		&[0xDBFF, A],
+		&[LOW, HIGH],
+		&[0xD83D, 0xDE05],
+		&text,
+	];
+	for &units in &cases {
+		let wtf8 = arg1(units).wtf8_units().collect::<Vec<u8>>();
+		assert_eq!(from_wtf8(&wtf8).as_deref(), Some(units));
+	}
+	assert_eq!(from_wtf8(&[]), Some(Vec::new()));
+
+	// A surrogate pair must be encoded as one code point.
+	assert_eq!(from_wtf8(&[0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x85]), None);
+	// Anything else that isn't UTF-8 is invalid.
+	let invalid: [&[u8]; 8] = [
+		&[0x80],
+		&[0xC0, 0x80],
+		&[0xC2],
+		&[0xE0, 0x9F, 0xBF],
+		&[0xE2, 0x82, 0x61],
+		&[0xF0, 0x8F, 0xBF, 0xBF],
+		&[0xF4, 0x90, 0x80, 0x80],
+		&[0xFF],
+	];
+	for &bytes in &invalid {
+		assert_eq!(from_wtf8(bytes), None, "{:x?}", bytes);
+	}
+}
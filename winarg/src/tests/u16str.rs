@@ -0,0 +1,52 @@
+// Tests for the `widestring` conversions.
+
+use super::{with_command_line_wide, Vec};
+use crate::{is_representable, quote_one, quoted_len, Argument};
+use widestring::{u16cstr, u16str, U16CString, U16Str, U16String};
+
+fn args(cmdline: &[u16]) -> Vec<Argument> {
+	with_command_line_wide(cmdline, || crate::args_native().collect())
+}
+
+#[test]
+fn eq() {
+	let args = args(u16str!(r#"EXE a "" "b c" ab"#).as_slice());
+	assert!(args[1] == *u16str!("a"));
+	assert!(*u16str!("a") == args[1]);
+	assert!(args[1] == *u16cstr!("a"));
+	assert!(*u16cstr!("a") == args[1]);
+	assert!(args[3] == *u16str!("b c"));
+	assert!(*u16cstr!("b c") == args[3]);
+	// Empty strings.
+	assert!(args[2] == *u16str!(""));
+	assert!(*u16str!("") == args[2]);
+	assert!(args[2] == *u16cstr!(""));
+	assert!(*u16cstr!("") == args[2]);
+	// Different lengths never match.
+	assert!(args[1] != *u16str!(""));
+	assert!(*u16str!("") != args[1]);
+	assert!(args[2] != *u16cstr!("a"));
+	assert!(*u16cstr!("a") != args[4]);
+	assert!(args[4] != *u16str!("a"));
+}
+
+#[test]
+fn to_u16cstring() {
+	let cmdline = [0x45, 0x20, 0x22, 0x61, 0x20, 0xD800, 0x22, 0x20, 0x22, 0x22];
+	let args = args(&cmdline);
+	let arg = args[1].to_u16cstring();
+	assert_eq!(arg.as_slice_with_nul(), [0x61, 0x20, 0xD800, 0]);
+	assert!(args[1] == *arg);
+	assert_eq!(args[2].to_u16cstring(), U16CString::new());
+}
+
+#[test]
+fn quoting() {
+	let owned = U16String::from_str("a b");
+	let args: [&U16Str; 3] = [u16str!("app.exe"), &owned, u16str!("")];
+	assert_eq!(quoted_len(&args), r#"app.exe "a b" """#.len());
+	assert!(is_representable(&args).is_ok());
+	let mut quoted = U16String::new();
+	quote_one(owned.as_slice(), false, |u| quoted.push_slice([u]));
+	assert_eq!(quoted, *u16str!(r#""a b""#));
+}
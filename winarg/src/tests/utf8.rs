@@ -0,0 +1,81 @@
+// Tests for parsing UTF-8 command lines.
+
+use super::{String, Vec};
+use crate::{cow_args_of, utf8::parse_str};
+use alloc::borrow::Cow;
+
+// Check that `parse_str` gives the same arguments as the UTF-16 parser, and
+// that borrowed arguments are part of `cmdline`.
+fn check(cmdline: &str) -> Vec<Cow<'_, str>> {
+	let wide: Vec<u16> = cmdline.encode_utf16().collect();
+	let expected: Vec<String> = cow_args_of(&wide)
+		.map(|arg| String::from_utf16(&arg).unwrap())
+		.collect();
+	let args: Vec<Cow<str>> = parse_str(cmdline).collect();
+	assert_eq!(args, expected, "{:?}", cmdline);
+	let range = cmdline.as_bytes().as_ptr_range();
+	for arg in &args {
+		if let Cow::Borrowed(arg) = arg {
+			assert!(range.start <= arg.as_ptr(), "{:?}", cmdline);
+			assert!(
+				arg.as_bytes().as_ptr_range().end <= range.end,
+				"{:?}",
+				cmdline
+			);
+		}
+	}
+	args
+}
+
+#[test]
+fn same_as_wide() {
+	let cases = [
+		"",
+		" ",
+		"\t\t",
+		" app.exe a",
+		"app.exe",
+		"app.exe   ",
+		r#"app.exe "a b"  c\"d "" e"#,
+		r#""C:\Program Files\app.exe" a\\\"b "c"#,
+		r#""C:\app\"x y"#,
+		r#"a"b"c" d"e"f"#,
+		r#"app.exe "a""b" "" """ """" a\\\\"b c"\\"#,
+		r"app.exe a\\ b\ \\\\",
+		"app.exe \"\\",
+		"app.exe\ta\tb\t",
+		"héllo wörld \"日本 語\" 🦀\\\"🦀 \"\u{A0}\"",
+		"\u{3000}app.exe\u{3000}a",
+		"app.exe a\0b c",
+		"\0app.exe",
+	];
+	for cmdline in cases {
+		check(cmdline);
+	}
+}
+
+#[test]
+fn borrowed() {
+	let args = check(r#""C:\My Documents\app.exe" C:\file "a b" a\"b "c\\" é "d\"#);
+	let borrowed: Vec<bool> = args
+		.iter()
+		.map(|arg| matches!(arg, Cow::Borrowed(_)))
+		.collect();
+	assert_eq!(borrowed, [true, true, true, false, false, true, false]);
+	// The program name never has escapes.
+	let args = check(r#""C:\dir\" a"#);
+	assert!(matches!(args[0], Cow::Borrowed(r"C:\dir\")));
+}
+
+#[test]
+fn as_str() {
+	let mut args = parse_str("app.exe  \"a b\"\tc\0d");
+	assert_eq!(args.as_str(), "app.exe  \"a b\"\tc");
+	args.next();
+	assert_eq!(args.as_str(), "\"a b\"\tc");
+	args.next();
+	args.next();
+	assert_eq!(args.as_str(), "");
+	assert_eq!(args.next(), None);
+	assert_eq!(args.next(), None);
+}
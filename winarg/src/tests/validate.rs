@@ -0,0 +1,114 @@
+// Tests for `validate`.
+
+use super::Vec;
+use crate::{validate, Diagnostic, DiagnosticKind, DiagnosticKind::*};
+use core::ops::Range;
+
+fn diagnostics(cmdline: &str) -> Vec<Diagnostic> {
+	let cmdline: Vec<u16> = cmdline.encode_utf16().collect();
+	validate(&cmdline).collect()
+}
+
+fn d(kind: DiagnosticKind, span: Range<usize>) -> Diagnostic {
+	Diagnostic { kind, span }
+}
+
+#[test]
+fn clean() {
+	assert_eq!(diagnostics(""), []);
+	assert_eq!(diagnostics("EXE"), []);
+	assert_eq!(diagnostics(r#""C:\Program Files\app.exe" a"#), []);
+	assert_eq!(diagnostics(r#"EXE "a b" c\"d "e\\" "f""g" h\" i\\\"j"#), []);
+}
+
+#[test]
+fn unterminated_quote() {
+	assert_eq!(diagnostics(r#"EXE a "b c"#), [d(UnterminatedQuote, 6..7)]);
+	assert_eq!(diagnostics(r#"EXE "a" "b"#), [d(UnterminatedQuote, 8..9)]);
+	// `""` in quotes doesn't end them.
+	assert_eq!(diagnostics(r#"EXE "a"""#), [d(UnterminatedQuote, 4..5)]);
+	assert_eq!(diagnostics(r#""EXE a"#), [d(UnterminatedQuote, 0..1)]);
+}
+
+#[test]
+fn quote_in_program_name() {
+	assert_eq!(
+		diagnostics(r#"E"X"E a"#),
+		[d(QuoteInProgramName, 1..2), d(QuoteInProgramName, 3..4)]
+	);
+	assert_eq!(diagnostics(r#""E"XE a"#), [d(QuoteInProgramName, 2..3)]);
+	assert_eq!(diagnostics(r#""" a"#), []);
+	assert_eq!(
+		diagnostics(r#"""" a"#),
+		[
+			d(QuoteInProgramName, 1..2),
+			d(QuoteInProgramName, 2..3),
+			d(UnterminatedQuote, 2..3)
+		]
+	);
+	// Backslashes don't escape quotes in the program name.
+	assert_eq!(diagnostics(r#""C:\dir\" a"#), []);
+	assert_eq!(
+		diagnostics(r#"EXE\" a"#),
+		[d(QuoteInProgramName, 4..5), d(UnterminatedQuote, 4..5)]
+	);
+}
+
+#[test]
+fn trailing_backslashes_before_end() {
+	assert_eq!(
+		diagnostics(r#"EXE "C:\dir\" a"#),
+		[
+			d(TrailingBackslashesBeforeEnd, 11..12),
+			d(UnterminatedQuote, 4..5)
+		]
+	);
+	assert_eq!(
+		diagnostics(r#"EXE "C:\dir\\\" "b"#),
+		[d(TrailingBackslashesBeforeEnd, 11..14)]
+	);
+	assert_eq!(
+		diagnostics(r#"EXE "a\""#),
+		[
+			d(TrailingBackslashesBeforeEnd, 6..7),
+			d(UnterminatedQuote, 4..5)
+		]
+	);
+	// An even number of backslashes is fine.
+	assert_eq!(diagnostics(r#"EXE "C:\dir\\" a"#), []);
+	// So is an escaped quote outside quotes.
+	assert_eq!(diagnostics(r#"EXE a\" b"#), []);
+}
+
+#[test]
+fn embedded_nul() {
+	assert_eq!(diagnostics("EXE a\0b"), [d(EmbeddedNul, 5..6)]);
+	// The command line ends at the null.
+	assert_eq!(
+		diagnostics("EXE \"a\0\"b"),
+		[d(UnterminatedQuote, 4..5), d(EmbeddedNul, 6..7)]
+	);
+	assert_eq!(diagnostics("\0\0"), [d(EmbeddedNul, 0..1)]);
+}
+
+#[test]
+fn too_long() {
+	let mut cmdline: Vec<u16> = Vec::new();
+	cmdline.resize(32766, u16::from(b'a'));
+	assert!(validate(&cmdline).next().is_none());
+	cmdline.extend([u16::from(b'"'), u16::from(b'a')].iter());
+	assert_eq!(
+		validate(&cmdline).collect::<Vec<_>>(),
+		[
+			d(TooLong, 32766..32768),
+			d(QuoteInProgramName, 32766..32767),
+			d(UnterminatedQuote, 32766..32767)
+		]
+	);
+	// Only the command line before a null counts.
+	cmdline[10] = 0;
+	assert_eq!(
+		validate(&cmdline).collect::<Vec<_>>(),
+		[d(EmbeddedNul, 10..11)]
+	);
+}
@@ -0,0 +1,175 @@
+//! Trimming the start and end of an argument.
+
+use crate::{code_points, scalars, Argument, FlagValue};
+use core::char::decode_utf16;
+use core::fmt;
+
+impl Argument {
+	/// The argument without leading and trailing ASCII whitespace.
+	///
+	/// This trims the argument after quotes have been removed, so `" C:\path "`
+	/// becomes `C:\path`. Whitespace is the same as [`char::is_ascii_whitespace`].
+	/// Nothing is allocated.
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     let arg = arg.trim();
+	///     println!("{}", arg.scalars().collect::<String>());
+	/// }
+	/// ```
+	pub fn trim(&self) -> Trimmed {
+		trim_by(self, 0, true, true, is_whitespace)
+	}
+	/// The argument without leading ASCII whitespace. See [`trim`](Self::trim).
+	pub fn trim_start(&self) -> Trimmed {
+		trim_by(self, 0, true, false, is_whitespace)
+	}
+	/// The argument without trailing ASCII whitespace. See [`trim`](Self::trim).
+	pub fn trim_end(&self) -> Trimmed {
+		trim_by(self, 0, false, true, is_whitespace)
+	}
+	/// The argument without any leading and trailing `c`.
+	///
+	/// `c` can be any `char`, including one outside the Basic Multilingual
+	/// Plane. Isolated surrogates are never trimmed.
+	///
+	/// ```
+	/// for arg in winarg::args_native().skip(1) {
+	///     let name = arg.trim_matches('-');
+	///     println!("{}", name.scalars().collect::<String>());
+	/// }
+	/// ```
+	pub fn trim_matches(&self, c: char) -> Trimmed {
+		trim_by(self, 0, true, true, |x| x == c)
+	}
+}
+
+impl FlagValue {
+	/// The value without leading and trailing ASCII whitespace.
+	///
+	/// Only the value is trimmed, so `--path=" C:\path "` becomes `C:\path`.
+	/// See [`Argument::trim`].
+	///
+	/// ```
+	/// if let Some(path) = winarg::value_of("--path") {
+	///     let path = path.trim();
+	///     println!("{}", path.scalars().collect::<String>());
+	/// }
+	/// ```
+	pub fn trim(&self) -> Trimmed {
+		trim_by(&self.arg, self.start, true, true, is_whitespace)
+	}
+	/// The value without leading ASCII whitespace. See [`trim`](Self::trim).
+	pub fn trim_start(&self) -> Trimmed {
+		trim_by(&self.arg, self.start, true, false, is_whitespace)
+	}
+	/// The value without trailing ASCII whitespace. See [`trim`](Self::trim).
+	pub fn trim_end(&self) -> Trimmed {
+		trim_by(&self.arg, self.start, false, true, is_whitespace)
+	}
+	/// The value without any leading and trailing `c`. See
+	/// [`Argument::trim_matches`].
+	pub fn trim_matches(&self, c: char) -> Trimmed {
+		trim_by(&self.arg, self.start, true, true, |x| x == c)
+	}
+}
+
+fn is_whitespace(c: char) -> bool {
+	c.is_ascii_whitespace()
+}
+
+// Trim the part of `arg` after the first `from` code units.
+fn trim_by<F: Fn(char) -> bool>(
+	arg: &Argument,
+	from: usize,
+	start: bool,
+	end: bool,
+	trim: F,
+) -> Trimmed {
+	// Find the first and last characters that are kept in a single pass.
+	let mut offset = from;
+	let mut kept: Option<(usize, usize)> = None;
+	for c in decode_utf16(arg.utf16_units().skip(from)) {
+		// An isolated surrogate is `None`.
+		let c = c.ok();
+		let len = c.map_or(1, char::len_utf16);
+		if !c.is_some_and(&trim) {
+			let first = kept.map_or(offset, |(first, _)| first);
+			kept = Some((first, offset + len));
+		}
+		offset += len;
+	}
+	// If everything is trimmed, trimming either end leaves nothing.
+	let (first, last) = kept.unwrap_or((offset, 0));
+	let first = if start { first } else { from };
+	let last = if end { last } else { offset };
+	Trimmed {
+		arg: arg.clone(),
+		start: first,
+		len: last.saturating_sub(first),
+	}
+}
+
+/// An argument with its start or end trimmed.
+///
+/// Returned by [`Argument::trim`], [`FlagValue::trim`] and the other trim
+/// methods. It can be
+/// compared to `&str` or `&[u16]`, either way round, like an `Argument`.
+#[derive(Clone, Debug)]
+pub struct Trimmed {
+	arg: Argument,
+	// The number of code units before and in the trimmed argument.
+	start: usize,
+	len: usize,
+}
+impl Trimmed {
+	/// Iterates scalar values. Isolated surrogates are replaced with `�`.
+	///
+	/// See [`Argument::scalars`].
+	pub fn scalars(&self) -> impl Iterator<Item = char> + fmt::Debug + Clone {
+		scalars(self.utf16_units())
+	}
+	/// Iterates code points, which may include isolated surrogates.
+	///
+	/// See [`Argument::code_points`].
+	pub fn code_points(&self) -> impl Iterator<Item = u32> + fmt::Debug + Clone {
+		code_points(self.utf16_units())
+	}
+	/// Iterates UTF-16 code units. This is lossless.
+	pub fn utf16_units(&self) -> impl Iterator<Item = u16> + fmt::Debug + Clone {
+		self.arg.utf16_units().skip(self.start).take(self.len)
+	}
+	/// The length in UTF-16 code units.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+	/// Returns `true` if everything was trimmed.
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+	/// The whole argument, before it was trimmed. For a trimmed
+	/// [`FlagValue`] this includes the flag, as [`FlagValue::argument`] does.
+	pub fn argument(&self) -> &Argument {
+		&self.arg
+	}
+}
+impl PartialEq<&str> for Trimmed {
+	fn eq(&self, other: &&str) -> bool {
+		self.utf16_units().eq(other.encode_utf16())
+	}
+}
+impl PartialEq<&[u16]> for Trimmed {
+	fn eq(&self, other: &&[u16]) -> bool {
+		self.utf16_units().eq(other.iter().copied())
+	}
+}
+impl PartialEq<Trimmed> for &str {
+	fn eq(&self, other: &Trimmed) -> bool {
+		other == self
+	}
+}
+impl PartialEq<Trimmed> for &[u16] {
+	fn eq(&self, other: &Trimmed) -> bool {
+		other == self
+	}
+}
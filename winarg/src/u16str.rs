@@ -0,0 +1,74 @@
+//! Conversions to and from `widestring` string types.
+//!
+//! The quoting functions and `WideBuf::join` already take anything that
+//! implements `AsRef<[u16]>`, which includes `&U16Str` and `U16String`.
+
+use crate::Argument;
+use alloc::vec::Vec;
+use widestring::{U16CStr, U16CString, U16Str};
+
+impl Argument {
+	/// The argument as a null terminated `U16CString`, for passing to Windows
+	/// functions.
+	///
+	/// This is lossless. An argument can't contain a null because a null ends
+	/// the command line, so this can't fail.
+	///
+	/// ```
+	/// use std::ptr;
+	/// use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+	/// use windows_sys::Win32::Storage::FileSystem::{
+	///     CreateFileW, FILE_ATTRIBUTE_NORMAL, FILE_GENERIC_READ, FILE_SHARE_READ, OPEN_EXISTING,
+	/// };
+	///
+	/// for arg in winarg::args_native().skip(1) {
+	///     let path = arg.to_u16cstring();
+	///     // SAFETY: `path` is null terminated.
+	///     let file = unsafe {
+	///         CreateFileW(
+	///             path.as_ptr(),
+	///             FILE_GENERIC_READ,
+	///             FILE_SHARE_READ,
+	///             ptr::null(),
+	///             OPEN_EXISTING,
+	///             FILE_ATTRIBUTE_NORMAL,
+	///             ptr::null_mut(),
+	///         )
+	///     };
+	///     if file != INVALID_HANDLE_VALUE {
+	///         println!("opened {}", arg);
+	///         // SAFETY: `file` is an open handle.
+	///         unsafe { CloseHandle(file) };
+	///     }
+	/// }
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "widestring")))]
+	pub fn to_u16cstring(&self) -> U16CString {
+		U16CString::from_vec_truncate(self.utf16_units().collect::<Vec<u16>>())
+	}
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "widestring")))]
+impl PartialEq<U16Str> for Argument {
+	fn eq(&self, other: &U16Str) -> bool {
+		self.eq(other.as_slice().iter().copied())
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "widestring")))]
+impl PartialEq<Argument> for U16Str {
+	fn eq(&self, other: &Argument) -> bool {
+		other.eq(self.as_slice().iter().copied())
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "widestring")))]
+impl PartialEq<U16CStr> for Argument {
+	fn eq(&self, other: &U16CStr) -> bool {
+		self.eq(other.as_slice().iter().copied())
+	}
+}
+#[cfg_attr(docsrs, doc(cfg(feature = "widestring")))]
+impl PartialEq<Argument> for U16CStr {
+	fn eq(&self, other: &Argument) -> bool {
+		other.eq(self.as_slice().iter().copied())
+	}
+}
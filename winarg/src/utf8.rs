@@ -0,0 +1,174 @@
+//! Parsing a command line that's stored as UTF-8.
+//!
+//! A command line read from a config file, a JSON document or a database is
+//! usually a `&str`. [`parse_str`] splits it into arguments without converting
+//! it to UTF-16 and back. Arguments without quotes or escapes, which are most
+//! of them, are borrowed from the string.
+//!
+//! The rules are the same as the rest of the crate uses for UTF-16 command
+//! lines, including for the program name. Quotes, backslashes, spaces and tabs
+//! are all ASCII so they're found in the UTF-8 bytes directly.
+//!
+//! This module is only available with the `alloc` feature.
+
+use alloc::{borrow::Cow, string::String};
+use core::iter::FusedIterator;
+
+/// Split a UTF-8 command line into arguments, starting with the program name.
+///
+/// Each argument is borrowed from `cmdline` if it's part of it without any
+/// quotes or escapes to remove, and otherwise copied into a `String`. Either
+/// way it's the same as the argument [`cow_args_of`](crate::cow_args_of) would
+/// give for the command line encoded as UTF-16. The command line ends at the
+/// first null, if any.
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// let args: Vec<Cow<str>> =
+///     winarg::utf8::parse_str(r#"app.exe C:\dir\file.txt "C:\My Documents" a\"b"#).collect();
+/// assert!(matches!(args[1], Cow::Borrowed(r"C:\dir\file.txt")));
+/// assert!(matches!(args[2], Cow::Borrowed(r"C:\My Documents")));
+/// assert!(matches!(args[3], Cow::Owned(_)));
+/// assert_eq!(args[3], r#"a"b"#);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn parse_str(cmdline: &str) -> StrArgs<'_> {
+	StrArgs {
+		rest: cmdline.split('\0').next().unwrap_or(cmdline),
+		is_program: true,
+	}
+}
+
+/// An iterator over the arguments of a UTF-8 command line.
+///
+/// Created by [`parse_str`].
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Debug)]
+pub struct StrArgs<'a> {
+	rest: &'a str,
+	is_program: bool,
+}
+impl<'a> StrArgs<'a> {
+	/// The part of the command line that hasn't been parsed yet.
+	///
+	/// ```
+	/// let mut args = winarg::utf8::parse_str("app.exe  a b");
+	/// args.next();
+	/// assert_eq!(args.as_str(), "a b");
+	/// ```
+	pub fn as_str(&self) -> &'a str {
+		self.rest
+	}
+}
+impl<'a> Iterator for StrArgs<'a> {
+	type Item = Cow<'a, str>;
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.rest.is_empty() {
+			return None;
+		}
+		let is_program = core::mem::replace(&mut self.is_program, false);
+		let end = arg_len(self.rest.as_bytes(), is_program);
+		let (raw, rest) = self.rest.split_at(end);
+		self.rest = rest.trim_start_matches(is_separator);
+		Some(match as_unquoted(raw, is_program) {
+			Some(arg) => Cow::Borrowed(arg),
+			None => Cow::Owned(unquote(raw, is_program)),
+		})
+	}
+}
+impl FusedIterator for StrArgs<'_> {}
+
+fn is_separator(c: char) -> bool {
+	c == ' ' || c == '\t'
+}
+
+// The number of bytes at the start of `cmdline` that are part of the first
+// argument, including its quotes and escapes. This always ends at a
+// character boundary because it stops before an ASCII separator or at the
+// end.
+fn arg_len(cmdline: &[u8], is_program: bool) -> usize {
+	let mut quote_mode = false;
+	let mut i = 0;
+	while let Some(&byte) = cmdline.get(i) {
+		match byte {
+			b' ' | b'\t' if !quote_mode => break,
+			b'\\' if !is_program => {
+				let mut slashes = 0_usize;
+				while cmdline.get(i) == Some(&b'\\') {
+					i += 1;
+					slashes += 1;
+				}
+				// An odd number of slashes escapes a quote.
+				if slashes % 2 == 1 && cmdline.get(i) == Some(&b'"') {
+					i += 1;
+				}
+			}
+			b'"' => {
+				i += 1;
+				// `""` in quotes is a literal quote.
+				if !is_program && quote_mode && cmdline.get(i) == Some(&b'"') {
+					i += 1;
+				} else {
+					quote_mode = !quote_mode;
+				}
+			}
+			_ => i += 1,
+		}
+	}
+	i
+}
+
+// The argument in `raw`, as found by `arg_len`, if it's part of `raw`
+// without any quotes or escapes to remove.
+fn as_unquoted(raw: &str, is_program: bool) -> Option<&str> {
+	// Without quotes, backslashes don't escape anything.
+	if !raw.contains('"') {
+		return Some(raw);
+	}
+	// Quotes around the whole argument, without a backslash before the
+	// closing quote, are the only ones.
+	let inner = raw.strip_prefix('"')?.strip_suffix('"')?;
+	if !inner.contains('"') && (is_program || !inner.ends_with('\\')) {
+		Some(inner)
+	} else {
+		None
+	}
+}
+
+// Remove the quotes and escapes from the argument in `raw`, as found by
+// `arg_len`.
+fn unquote(raw: &str, is_program: bool) -> String {
+	let mut arg = String::with_capacity(raw.len());
+	let mut quote_mode = false;
+	let mut chars = raw.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' if !is_program => {
+				let mut slashes = 1;
+				while chars.next_if_eq(&'\\').is_some() {
+					slashes += 1;
+				}
+				if chars.peek() == Some(&'"') {
+					// Half the slashes are kept. An odd number escapes the quote.
+					arg.extend(core::iter::repeat_n('\\', slashes / 2));
+					if slashes % 2 == 1 {
+						chars.next();
+						arg.push('"');
+					}
+				} else {
+					arg.extend(core::iter::repeat_n('\\', slashes));
+				}
+			}
+			'"' => {
+				if !is_program && quote_mode && chars.next_if_eq(&'"').is_some() {
+					arg.push('"');
+				} else {
+					quote_mode = !quote_mode;
+				}
+			}
+			c => arg.push(c),
+		}
+	}
+	arg
+}
@@ -0,0 +1,235 @@
+//! Finding likely mistakes in a command line.
+
+use crate::{MAX_COMMAND_LINE, QUOTE, SLASH, SPACE, TAB};
+use core::{iter::FusedIterator, ops::Range};
+
+/// Check a command line for things that are probably mistakes.
+///
+/// This doesn't change how a command line is parsed. Every command line is
+/// valid; these are only warnings. Each diagnostic has a span pointing at the
+/// part of `cmdline` that caused it.
+///
+/// The diagnostics aren't sorted by their spans. A command line that's too
+/// long is reported first, then diagnostics are returned as the command line is
+/// scanned. An unterminated quote is only known at the end so it comes last,
+/// followed by an embedded null.
+///
+/// ```
+/// use winarg::{validate, DiagnosticKind};
+///
+/// let cmdline: Vec<u16> = r#"app.exe "C:\My Files\" --verbose"#.encode_utf16().collect();
+/// let kinds: Vec<DiagnosticKind> = validate(&cmdline).map(|d| d.kind).collect();
+/// assert_eq!(
+///     kinds,
+///     [DiagnosticKind::TrailingBackslashesBeforeEnd, DiagnosticKind::UnterminatedQuote]
+/// );
+/// ```
+pub fn validate(cmdline: &[u16]) -> Diagnostics<'_> {
+	let end = cmdline
+		.iter()
+		.position(|&u| u == 0)
+		.unwrap_or(cmdline.len());
+	Diagnostics {
+		cmdline,
+		end,
+		pos: 0,
+		in_program_name: true,
+		open_quote: None,
+		stage: Stage::Start,
+	}
+}
+
+/// A possible mistake found by [`validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+	/// What was found.
+	pub kind: DiagnosticKind,
+	/// The code units, as indexes into the command line, that caused it.
+	pub span: Range<usize>,
+}
+
+/// The kinds of [`Diagnostic`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+	/// A quote that's never closed, so the quoted text runs to the end of
+	/// the command line. The span is the opening quote.
+	UnterminatedQuote,
+	/// A quote in the program name other than one surrounding the whole name.
+	/// Quotes are removed from the program name and can't be escaped. The span
+	/// is the quote.
+	QuoteInProgramName,
+	/// Backslashes that escape what looks like the closing quote of an
+	/// argument, such as in `"C:\My Files\"`. The quote becomes part of the
+	/// argument instead. The span is the backslashes.
+	TrailingBackslashesBeforeEnd,
+	/// A null, which ends the command line. Anything after it is ignored. The
+	/// span is the null.
+	EmbeddedNul,
+	/// The command line is longer than `CreateProcessW` allows. The span is
+	/// everything past the limit.
+	TooLong,
+	/// An executable path with spaces that isn't quoted, such as
+	/// `C:\Program Files\app.exe`, which Windows may read as `C:\Program.exe`.
+	/// The span is the path. This is only reported by
+	/// [`check_image_path`](crate::check_image_path).
+	UnquotedPathWithSpaces,
+}
+
+/// An iterator over the diagnostics for a command line.
+///
+/// Created by [`validate`].
+#[derive(Clone, Debug)]
+pub struct Diagnostics<'a> {
+	cmdline: &'a [u16],
+	// The length up to the first null, if any.
+	end: usize,
+	pos: usize,
+	in_program_name: bool,
+	// Where the current quoted section started, if in one.
+	open_quote: Option<usize>,
+	stage: Stage,
+}
+
+// The diagnostics are found in this order.
+#[derive(Clone, Copy, Debug)]
+enum Stage {
+	Start,
+	Scan,
+	Quote,
+	Nul,
+	Done,
+}
+
+impl Diagnostics<'_> {
+	fn get(&self, pos: usize) -> Option<u16> {
+		if pos < self.end {
+			self.cmdline.get(pos).copied()
+		} else {
+			None
+		}
+	}
+	// Whitespace or the end of the command line.
+	fn is_boundary(&self, pos: usize) -> bool {
+		matches!(self.get(pos), None | Some(SPACE) | Some(TAB))
+	}
+
+	// Scan the program name up to the next diagnostic.
+	fn program_name(&mut self) -> Option<Diagnostic> {
+		while let Some(unit) = self.get(self.pos) {
+			let pos = self.pos;
+			self.pos += 1;
+			match unit {
+				QUOTE => match self.open_quote.take() {
+					// A quote at the start and one at the end is fine.
+					Some(0) if self.is_boundary(self.pos) => {}
+					Some(_) => return Some(diagnostic(DiagnosticKind::QuoteInProgramName, pos)),
+					None => {
+						self.open_quote = Some(pos);
+						if pos != 0 {
+							return Some(diagnostic(DiagnosticKind::QuoteInProgramName, pos));
+						}
+					}
+				},
+				SPACE | TAB if self.open_quote.is_none() => {
+					self.in_program_name = false;
+					return None;
+				}
+				_ => {}
+			}
+		}
+		None
+	}
+
+	// Scan the arguments up to the next diagnostic.
+	fn arguments(&mut self) -> Option<Diagnostic> {
+		while let Some(unit) = self.get(self.pos) {
+			match unit {
+				SLASH => {
+					let start = self.pos;
+					while self.get(self.pos) == Some(SLASH) {
+						self.pos += 1;
+					}
+					if self.get(self.pos) != Some(QUOTE) {
+						continue;
+					}
+					if (self.pos - start) % 2 == 1 {
+						// The quote is escaped.
+						self.pos += 1;
+						if self.open_quote.is_some() && self.is_boundary(self.pos) {
+							return Some(Diagnostic {
+								kind: DiagnosticKind::TrailingBackslashesBeforeEnd,
+								span: start..self.pos - 1,
+							});
+						}
+					}
+				}
+				QUOTE => {
+					if self.open_quote.is_none() {
+						self.open_quote = Some(self.pos);
+					} else if self.get(self.pos + 1) == Some(QUOTE) {
+						// `""` in quotes is a literal quote.
+						self.pos += 1;
+					} else {
+						self.open_quote = None;
+					}
+					self.pos += 1;
+				}
+				_ => self.pos += 1,
+			}
+		}
+		None
+	}
+}
+impl Iterator for Diagnostics<'_> {
+	type Item = Diagnostic;
+	fn next(&mut self) -> Option<Diagnostic> {
+		loop {
+			match self.stage {
+				Stage::Start => {
+					self.stage = Stage::Scan;
+					if self.end > MAX_COMMAND_LINE {
+						return Some(Diagnostic {
+							kind: DiagnosticKind::TooLong,
+							span: MAX_COMMAND_LINE..self.end,
+						});
+					}
+				}
+				Stage::Scan => {
+					let found = if self.in_program_name {
+						self.program_name()
+					} else {
+						self.arguments()
+					};
+					if found.is_some() {
+						return found;
+					}
+					if self.pos >= self.end {
+						self.stage = Stage::Quote;
+					}
+				}
+				Stage::Quote => {
+					self.stage = Stage::Nul;
+					if let Some(pos) = self.open_quote {
+						return Some(diagnostic(DiagnosticKind::UnterminatedQuote, pos));
+					}
+				}
+				Stage::Nul => {
+					self.stage = Stage::Done;
+					if self.end < self.cmdline.len() {
+						return Some(diagnostic(DiagnosticKind::EmbeddedNul, self.end));
+					}
+				}
+				Stage::Done => return None,
+			}
+		}
+	}
+}
+impl FusedIterator for Diagnostics<'_> {}
+
+fn diagnostic(kind: DiagnosticKind, pos: usize) -> Diagnostic {
+	Diagnostic {
+		kind,
+		span: pos..pos + 1,
+	}
+}
@@ -0,0 +1,27 @@
+//! Removing the verbatim prefix from paths.
+
+use crate::{to_ascii_uppercase, COLON, SLASH};
+use alloc::vec::Vec;
+
+const QUESTION: u16 = b'?' as _;
+
+// Turn `\\?\C:\path` into `C:\path` and `\\?\UNC\server\path` into
+// `\\server\path`. Other verbatim paths, such as `\\?\Volume{...}\path`, can't
+// be written without the prefix so they're left alone.
+pub(crate) fn strip_verbatim_prefix(path: &mut Vec<u16>) {
+	let upper = |unit: u16, letter: u8| to_ascii_uppercase(unit) == u16::from(letter);
+	match path[..] {
+		[SLASH, SLASH, QUESTION, SLASH, drive, COLON, ..]
+			if (u16::from(b'A')..=u16::from(b'Z')).contains(&to_ascii_uppercase(drive)) =>
+		{
+			path.drain(..4);
+		}
+		[SLASH, SLASH, QUESTION, SLASH, u, n, c, SLASH, ..]
+			if upper(u, b'U') && upper(n, b'N') && upper(c, b'C') =>
+		{
+			// Keep two of the backslashes: `\\?\UNC\server` becomes `\\server`.
+			path.drain(2..8);
+		}
+		_ => {}
+	}
+}
@@ -0,0 +1,145 @@
+//! Encoding arguments as WTF-8.
+
+use crate::{is_high_surrogate, is_low_surrogate, Argument};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+
+impl Argument {
+	/// Iterates the bytes of the argument encoded as [WTF-8].
+	///
+	/// WTF-8 is UTF-8 extended to allow isolated surrogates, which are
+	/// encoded as if they were scalar values. This is lossless. Surrogate
+	/// pairs are encoded as the scalar value they make, the same as UTF-8, so
+	/// an argument that's valid Unicode gives exactly its UTF-8 bytes. A low
+	/// surrogate followed by a high surrogate isn't a pair, so each is
+	/// encoded separately.
+	///
+	/// On Windows, `OsStr` stores its text as WTF-8, so these are the same
+	/// bytes as [`OsStr::as_encoded_bytes`] for the argument. Use
+	/// [`from_wtf8`](crate::from_wtf8) to turn them back into UTF-16, which
+	/// `OsString::from_wide` takes. Stored bytes must not be given to
+	/// `OsString::from_encoded_bytes_unchecked`, which is only sound for bytes
+	/// from `as_encoded_bytes` in the same build of the program.
+	///
+	/// ```
+	/// let mut store: Vec<u8> = Vec::new();
+	/// for arg in winarg::args_native() {
+	///     store.extend(arg.wtf8_units());
+	///     store.push(0);
+	/// }
+	/// ```
+	///
+	/// [WTF-8]: https://simonsapin.github.io/wtf-8/
+	/// [`OsStr::as_encoded_bytes`]: https://doc.rust-lang.org/std/ffi/struct.OsStr.html#method.as_encoded_bytes
+	pub fn wtf8_units(&self) -> impl Iterator<Item = u8> + fmt::Debug + Clone {
+		self.code_points()
+			.flat_map(Wtf8Bytes::new as fn(u32) -> Wtf8Bytes)
+	}
+}
+
+/// Decode [WTF-8], such as the bytes from [`Argument::wtf8_units`], into
+/// UTF-16 code units.
+///
+/// Returns `None` if the bytes aren't valid WTF-8. That's any invalid UTF-8
+/// other than an encoded surrogate, and a high surrogate followed by a low
+/// surrogate, which must be encoded as the scalar value they make. So each
+/// argument has only one encoding and is decoded back to exactly the code
+/// units it had.
+///
+/// ```
+/// use std::ffi::OsString;
+/// use std::os::windows::ffi::OsStringExt;
+///
+/// let arg = winarg::args_native().next().unwrap();
+/// let stored: Vec<u8> = arg.wtf8_units().collect();
+/// let wide = winarg::from_wtf8(&stored).unwrap();
+/// assert!(arg == &wide[..]);
+/// let program = OsString::from_wide(&wide);
+/// ```
+///
+/// [WTF-8]: https://simonsapin.github.io/wtf-8/
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn from_wtf8(bytes: &[u8]) -> Option<Vec<u16>> {
+	let mut wide = Vec::with_capacity(bytes.len());
+	let mut bytes = bytes.iter().copied();
+	while let Some(first) = bytes.next() {
+		// The number of continuation bytes, the smallest code point that needs
+		// them, and the bits from the first byte.
+		let (len, min, bits) = match first {
+			0x00..=0x7F => {
+				wide.push(u16::from(first));
+				continue;
+			}
+			0xC2..=0xDF => (1, 0x80, first & 0x1F),
+			0xE0..=0xEF => (2, 0x800, first & 0x0F),
+			0xF0..=0xF4 => (3, 0x10000, first & 0x07),
+			_ => return None,
+		};
+		let mut code_point = u32::from(bits);
+		for _ in 0..len {
+			match bytes.next() {
+				Some(byte @ 0x80..=0xBF) => code_point = code_point << 6 | u32::from(byte & 0x3F),
+				_ => return None,
+			}
+		}
+		if code_point < min || code_point > 0x10FFFF {
+			return None;
+		}
+		if let Some(c) = code_point.checked_sub(0x10000) {
+			wide.push(0xD800 | (c >> 10) as u16);
+			wide.push(0xDC00 | (c & 0x3FF) as u16);
+		} else {
+			let unit = code_point as u16;
+			if is_low_surrogate(unit) && matches!(wide.last(), Some(&u) if is_high_surrogate(u)) {
+				return None;
+			}
+			wide.push(unit);
+		}
+	}
+	Some(wide)
+}
+
+// The bytes of one code point.
+#[derive(Clone, Debug)]
+struct Wtf8Bytes {
+	bytes: [u8; 4],
+	start: usize,
+	end: usize,
+}
+impl Wtf8Bytes {
+	fn new(code_point: u32) -> Self {
+		// Continuation bytes hold six bits each.
+		let cont = |shift: u32| 0x80 | (code_point >> shift & 0x3F) as u8;
+		let (bytes, end) = match code_point {
+			0..=0x7F => ([code_point as u8, 0, 0, 0], 1),
+			0x80..=0x7FF => ([0xC0 | (code_point >> 6) as u8, cont(0), 0, 0], 2),
+			0x800..=0xFFFF => ([0xE0 | (code_point >> 12) as u8, cont(6), cont(0), 0], 3),
+			_ => (
+				[0xF0 | (code_point >> 18) as u8, cont(12), cont(6), cont(0)],
+				4,
+			),
+		};
+		Self {
+			bytes,
+			start: 0,
+			end,
+		}
+	}
+}
+impl Iterator for Wtf8Bytes {
+	type Item = u8;
+	fn next(&mut self) -> Option<u8> {
+		if self.start == self.end {
+			return None;
+		}
+		let byte = self.bytes.get(self.start).copied();
+		self.start += 1;
+		byte
+	}
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let len = self.end - self.start;
+		(len, Some(len))
+	}
+}